@@ -0,0 +1,1264 @@
+use crate::status::{self, Level};
+use crate::theme;
+use eframe::{egui, App, Frame};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tnsm_installer_core::action_queue::ActionQueue;
+use tnsm_installer_core::arch::{self, Arch};
+use tnsm_installer_core::cancel::{self, CancellationToken};
+use tnsm_installer_core::compat::{self, CompatDb};
+use tnsm_installer_core::config::Config;
+use tnsm_installer_core::download::{self, DEFAULT_DLL_URL};
+use tnsm_installer_core::game_data;
+use tnsm_installer_core::game_files::{self, FileState, GameFiles};
+use tnsm_installer_core::events::{Event, EventBus, EventSender, Task, TransferProgress};
+use tnsm_installer_core::i18n::{self, tr, trf};
+use tnsm_installer_core::storage;
+use tnsm_installer_core::status_pipe::{PipeEvent, StatusPipe};
+use tnsm_installer_core::journal;
+use tnsm_installer_core::jumplist::JumpTask;
+use tnsm_installer_core::machine::{self, InstallRecord};
+use tnsm_installer_core::manifest::Problem;
+use tnsm_installer_core::nightly;
+use tnsm_installer_core::policy::{Feature, Policy};
+use tnsm_installer_core::preview::FileChange;
+use tnsm_installer_core::restore_point;
+use tnsm_installer_core::progress::{self, Estimator};
+use tnsm_installer_core::tasks::TaskManager;
+use tnsm_installer_core::undo::{UndoEntry, UndoStack};
+use tnsm_installer_core::version_cache;
+use tnsm_installer_core::watchdog::Watchdog;
+use tnsm_installer_core::{detect, elevated, instance, mod_state, pack, resources, self_update, shell, timestamp};
+
+mod diagnostics_panel;
+mod install_panel;
+mod resources_panel;
+mod settings_panel;
+mod updates_panel;
+
+use diagnostics_panel::{CachesState, GameCheckState, LiveLogState, OrphansState, VerifyState};
+use install_panel::{BundleState, InstallState, ReleasesState, RestoreState};
+use resources_panel::{ConfigBackupsState, GalleryState, GameDataState, ImportState, PluginListState, ProfilesState};
+use settings_panel::{DownloadsState, LanguageState, StorageState};
+use updates_panel::{AppUpdateState, QueueState, TrayState};
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+const GAME_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    Installer,
+    Gallery,
+    Log,
+}
+
+// An action held back by the Preview setting until Apply is clicked
+#[derive(Clone)]
+enum PreviewAction {
+    Install,
+    InstallLocal(PathBuf),
+    Import(PathBuf),
+    Uninstall,
+}
+
+struct Preview {
+    action: PreviewAction,
+    // None while the files are still being looked at
+    changes: Option<Vec<FileChange>>,
+    cancel: CancellationToken,
+}
+
+struct ModInstallerApp {
+    dll_url: String,
+    status_message: String,
+    install: InstallState,
+    steam_path: Option<PathBuf>,
+    game_path: Option<PathBuf>,
+    tasks: TaskManager,
+    events: EventBus,
+    show_manual_input: bool,
+    import: ImportState,
+    is_mod_installed: bool,
+    // A check is running, and whether something changed since it started
+    is_checking_mod: bool,
+    check_mod_again: bool,
+    status_pipe: StatusPipe,
+    config: Config,
+    bundle: BundleState,
+    pending_operations: Vec<journal::Pending>,
+    host_arch: Arch,
+    watchdog: Watchdog,
+    // Started again once its cancellation finishes, see show_stall_notice
+    retry_after_cancel: Option<Task>,
+    game_data: GameDataState,
+    restore: RestoreState,
+    storage: StorageState,
+    caches: CachesState,
+    view: View,
+    gallery: GalleryState,
+    live_log: LiveLogState,
+    language: LanguageState,
+    // Latest install or uninstall by any account on this PC
+    install_record: Option<InstallRecord>,
+    game_path_writable: bool,
+    config_backups: ConfigBackupsState,
+    policy: Policy,
+    // The installed DLL vanished or changed since it was written, usually antivirus
+    dll_problem: Option<Problem>,
+    tray: TrayState,
+    queue: QueueState,
+    undo_stack: UndoStack,
+    game_running: bool,
+    releases: ReleasesState,
+    latest_release: Option<String>,
+    app_update: AppUpdateState,
+    compat_db: CompatDb,
+    // The known-good hashes, the check is only offered for builds listed in it
+    game_files: GameFiles,
+    game_build_id: Option<u64>,
+    verify: VerifyState,
+    orphans: OrphansState,
+    profiles: ProfilesState,
+    // Least severe level shown in the Logs panel
+    log_level: tracing::Level,
+    diagnostics_status: String,
+    plugins: PluginListState,
+    downloads: DownloadsState,
+    settings_status: String,
+    game_check: GameCheckState,
+    audit_status: String,
+}
+
+impl ModInstallerApp {
+    fn new(ctx: &egui::Context, request: Option<instance::Request>) -> Self {
+        let waker = ctx.clone();
+        let events = EventBus::new(move || waker.request_repaint());
+
+        let tasks = TaskManager::default();
+        let status_pipe = StatusPipe::start(tasks.handle());
+
+        // Later launches hand their requests to this window
+        let handoff = events.sender();
+        instance::listen(tasks.handle(), move |request| handoff.send(Event::Handoff(request)));
+        if let Some(request) = request {
+            events.sender().send(Event::Handoff(request));
+        }
+
+        let mut app = Self {
+            dll_url: DEFAULT_DLL_URL.to_string(),
+            status_message: tr("Ready to install").to_string(),
+            install: InstallState::default(),
+            steam_path: None,
+            game_path: None,
+            tasks,
+            events,
+            show_manual_input: false,
+            import: ImportState::default(),
+            is_mod_installed: false,
+            is_checking_mod: false,
+            check_mod_again: false,
+            status_pipe,
+            config: Config::load(),
+            bundle: BundleState::default(),
+            pending_operations: journal::pending(),
+            host_arch: arch::host_arch(),
+            watchdog: Watchdog::default(),
+            retry_after_cancel: None,
+            game_data: GameDataState {
+                backups: game_data::list_backups(),
+                ..Default::default()
+            },
+            restore: RestoreState {
+                points: restore_point::list(),
+                ..Default::default()
+            },
+            storage: StorageState::default(),
+            caches: CachesState::default(),
+            view: View::Installer,
+            gallery: GalleryState::default(),
+            live_log: LiveLogState::default(),
+            language: LanguageState {
+                problems: i18n::load_packs(),
+                ..Default::default()
+            },
+            install_record: None,
+            game_path_writable: true,
+            config_backups: ConfigBackupsState::default(),
+            policy: Policy::load().unwrap_or_default(),
+            dll_problem: None,
+            tray: TrayState::default(),
+            queue: QueueState {
+                actions: ActionQueue::load(),
+                ..Default::default()
+            },
+            undo_stack: UndoStack::load(),
+            game_running: false,
+            releases: ReleasesState {
+                cached: version_cache::list(),
+                ..Default::default()
+            },
+            latest_release: None,
+            app_update: AppUpdateState::default(),
+            compat_db: CompatDb::load(),
+            game_files: GameFiles::load(),
+            game_build_id: None,
+            verify: VerifyState::default(),
+            orphans: OrphansState::default(),
+            profiles: ProfilesState::default(),
+            log_level: tracing::Level::INFO,
+            diagnostics_status: String::new(),
+            plugins: PluginListState::default(),
+            downloads: DownloadsState::default(),
+            settings_status: String::new(),
+            game_check: GameCheckState::default(),
+            audit_status: String::new(),
+        };
+        if let Some(code) = &app.config.language {
+            i18n::set_language(code);
+        }
+        status::set_colorblind(app.config.colorblind);
+        theme::set(app.config.theme, app.config.accent_color);
+        app.detect_steam_path();
+        app.apply_policy();
+        app.remember_game_path();
+        app.check_mod_installed();
+        app.refresh_steam_accounts();
+        app.refresh_compat_db();
+        app.refresh_game_files();
+        app.check_for_update();
+        if app.config.shell_integration {
+            app.refresh_shell_integration();
+        }
+        if app.config.tray_mode
+            && let Err(e) = app.start_tray(ctx)
+        {
+            app.status_message = trf("❌ {}", &[&e]);
+        }
+
+        // An interrupted import may still need its unpacked pack
+        if app.pending_operations.is_empty() {
+            app.clean_up_storage();
+        } else {
+            app.refresh_storage_usage();
+        }
+        app
+    }
+
+    fn detect_steam_path(&mut self) {
+        // A saved game folder that's still there skips searching the libraries
+        let saved = self.config.game_path.clone().filter(|path| detect::is_game_directory(path));
+
+        if let Some(path) = detect::detect_steam_path() {
+            self.steam_path = Some(path.clone());
+            if saved.is_none() {
+                self.find_game_directory(&path);
+            }
+        }
+
+        if let Some(game_path) = saved {
+            self.status_message = trf("✓ Game found: {}", &[&game_path.display()]);
+            self.game_path = Some(game_path);
+            self.show_manual_input = false;
+            return;
+        }
+
+        // Another account on this PC may have found it already
+        if self.game_path.is_none()
+            && let Some(record) = machine::latest()
+            && detect::is_game_directory(&record.game_path)
+        {
+            self.status_message = trf("✓ Game found: {}", &[&record.game_path.display()]);
+            self.game_path = Some(record.game_path);
+            self.show_manual_input = false;
+            return;
+        }
+
+        if self.steam_path.is_none() {
+            self.status_message = tr("⚠ Steam directory not found. Please browse for path.").to_string();
+            self.show_manual_input = true;
+        }
+    }
+
+    fn find_game_directory(&mut self, steam_path: &Path) {
+        if let Some(folder) = detect::find_game_directory(steam_path) {
+            self.status_message = trf("✓ Game found: {}", &[&folder.display()]);
+            self.game_path = Some(folder);
+            self.show_manual_input = false;
+            return;
+        }
+
+        self.status_message = tr("⚠ Sky Children of the Light not found in Steam directories").to_string();
+        self.show_manual_input = true;
+    }
+
+    fn apply_policy(&mut self) {
+        self.apply_pinned_version();
+
+        if let Some(game_path) = self.policy.game_path.clone() {
+            self.status_message = if detect::is_game_directory(&game_path) {
+                trf("✓ Game path set by policy: {}", &[&game_path.display()])
+            } else {
+                trf("❌ The game path set by policy was not found: {}", &[&game_path.display()])
+            };
+            self.game_path = Some(game_path);
+            self.show_manual_input = false;
+        }
+    }
+
+    // The policy's pin wins over the user's
+    fn apply_pinned_version(&mut self) {
+        self.dll_url = self.policy.pinned_dll_url()
+            .or_else(|| self.config.pinned_version.as_deref().map(download::release_dll_url))
+            .or_else(|| self.config.dll_url.clone())
+            .unwrap_or_else(|| DEFAULT_DLL_URL.to_string());
+    }
+
+    fn remember_game_path(&mut self) {
+        self.profiles.list = None;
+        if self.game_path != self.config.game_path && self.policy.game_path.is_none() {
+            self.config.game_path = self.game_path.clone();
+            let _ = self.config.save();
+        }
+    }
+
+    fn is_game_path_locked(&self) -> bool {
+        self.policy.game_path.is_some() || !self.policy.allows(Feature::ChangeGamePath)
+    }
+
+    // What's shown stays until the check is back. Asked for again while one
+    // runs, it checks once more afterwards rather than running two at once.
+    fn check_mod_installed(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        if self.is_checking_mod {
+            self.check_mod_again = true;
+            return;
+        }
+
+        self.is_checking_mod = true;
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let plugins = self.config.plugins.clone();
+
+        self.tasks.spawn(async move {
+            events.send(Event::ModChecked(mod_state::check(game_path, proxy, plugins).await));
+        });
+    }
+
+    // Picks up new known issues, the cached copy is shown until then
+    fn refresh_compat_db(&mut self) {
+        if !self.policy.allows(Feature::UpdateCheck) {
+            return;
+        }
+
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            if let Ok(db) = compat::refresh(&cancel).await {
+                events.send(Event::CompatDb(db));
+            }
+        });
+    }
+
+    // Picks up hashes for new game builds, the cached copy is used until then
+    fn refresh_game_files(&mut self) {
+        if !self.policy.allows(Feature::UpdateCheck) {
+            return;
+        }
+
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            if let Ok(game_files) = game_files::refresh(&cancel).await {
+                events.send(Event::GameFiles(game_files));
+            }
+        });
+    }
+
+    fn browse_for_path(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title(tr("Select Steam or Game Directory"))
+            .pick_folder()
+        {
+            // Check if it's a Steam directory
+            if detect::is_steam_directory(&path) {
+                self.steam_path = Some(path.clone());
+                self.find_game_directory(&path);
+                self.refresh_steam_accounts();
+            }
+            // Check if it's directly the game directory
+            else if detect::is_game_directory(&path) {
+                self.game_path = Some(path.clone());
+                self.status_message = trf("✓ Game path set: {}", &[&path.display()]);
+                self.show_manual_input = false;
+            }
+            else {
+                self.status_message = tr("❌ Invalid path. Please select Steam folder or game folder.").to_string();
+            }
+            self.remember_game_path();
+            self.check_mod_installed();
+        }
+    }
+
+    fn launch_game(&mut self) {
+        match shell::launch_game(self.game_path.as_deref()) {
+            Ok(()) => self.status_message = tr("✅ Starting Sky...").to_string(),
+            Err(e) => self.status_message = trf("❌ Failed to launch the game: {}", &[&e]),
+        }
+    }
+
+    fn run_task(&mut self, task: JumpTask) {
+        let result = match task {
+            JumpTask::Install => {
+                if !self.install.running {
+                    self.install_mod();
+                }
+                Ok(())
+            }
+            JumpTask::LaunchGame => shell::launch_game(self.game_path.as_deref()),
+            JumpTask::OpenLogs => crate::open_log_dir(),
+            JumpTask::OpenResources => match &self.game_path {
+                Some(game_path) => shell::open_folder(&resources::resources_path(game_path)),
+                None => Err(tr("Game directory not set").to_string()),
+            },
+        };
+
+        if let Err(e) = result {
+            self.status_message = trf("❌ {}", &[&e]);
+        }
+    }
+
+    // Goes where the matching Browse button would: folders and packs are
+    // imported as resources, DLLs installed as the mod
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect()
+        });
+        // Imports and installs run one at a time, so only the first is used
+        let Some(path) = dropped.into_iter().next() else {
+            return;
+        };
+
+        if path.is_dir() {
+            self.import_from_shell(path);
+        } else if pack::is_archive(&path) {
+            self.open_pack(path);
+        } else if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dll")) {
+            self.install_local_dll(path);
+        } else {
+            self.status_message = trf(
+                "❌ {} isn't a folder, resource pack or DLL",
+                &[&path.file_name().unwrap_or_default().to_string_lossy()]
+            );
+        }
+    }
+
+    fn show_drop_hint(&self, ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_hint")));
+        let rect = painter.clip_rect();
+        painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(180));
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            tr("Drop a resources folder or pack to import it, or a DLL to install it"),
+            egui::FontId::proportional(18.0),
+            egui::Color32::WHITE,
+        );
+    }
+
+    // Servers that don't send a Content-Length only get the amount so far
+    fn show_download_progress(&self, ui: &mut egui::Ui, progress: &TransferProgress) {
+        let estimate = self.install.estimator.estimate();
+        let mut text = if progress.bytes_total > 0 {
+            format!(
+                "{} / {} · {:.0}%",
+                storage::format_size(progress.bytes_done),
+                storage::format_size(progress.bytes_total),
+                progress.bytes_done as f64 * 100.0 / progress.bytes_total as f64
+            )
+        } else {
+            trf("{} downloaded", &[&storage::format_size(progress.bytes_done)])
+        };
+        if let Some(rate) = estimate.bytes_per_second {
+            text.push_str(&format!(" · {}", progress::format_rate(rate)));
+        }
+        if let Some(eta) = estimate.eta.filter(|_| progress.bytes_total > 0) {
+            text.push_str(&format!(" · {}", progress::format_eta(eta)));
+        }
+
+        let fraction = if progress.bytes_total > 0 {
+            (progress.bytes_done as f64 / progress.bytes_total as f64).min(1.0) as f32
+        } else {
+            0.0
+        };
+        ui.add(egui::ProgressBar::new(fraction)
+            .fill(status::color(Level::Working))
+            .desired_width(300.0)
+            .animate(progress.bytes_total == 0)
+            .text(text));
+    }
+
+    // Changes to the mod DLL itself can go through the elevated helper
+    fn can_change_mod(&self) -> bool {
+        self.game_path_writable || elevated::AVAILABLE
+    }
+
+    fn is_busy(&self) -> bool {
+        self.install.running || self.restore.is_reverting || self.import.progress.is_some()
+    }
+
+    fn push_undo(&mut self, entry: UndoEntry) {
+        self.undo_stack.push(entry);
+        if let Err(e) = self.undo_stack.save() {
+            self.restore.status = trf("❌ {}", &[&e]);
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.is_busy() || self.game_running {
+            return;
+        }
+        let Some(entry) = self.undo_stack.pop() else {
+            return;
+        };
+        let _ = self.undo_stack.save();
+
+        match restore_point::list().into_iter().find(|point| point.dir == entry.restore_point) {
+            Some(point) => self.start_revert(point, false),
+            None => self.restore.status = trf("⚠ Can't undo {}, its restore point no longer exists", &[&entry.label]),
+        }
+    }
+
+    // Someone else on this PC owns the install; this account may only be able to look
+    fn show_other_account_notice(&mut self, ui: &mut egui::Ui) {
+        let other = self.install_record.clone()
+            .filter(|record| record.mod_installed && record.is_other_user());
+
+        if other.is_none() && self.game_path_writable {
+            return;
+        }
+
+        ui.vertical_centered(|ui| {
+            if let Some(record) = other {
+                let version = record.installed_version.as_deref().unwrap_or(tr("unknown version"));
+                ui.label(egui::RichText::new(trf("ℹ Installed by {} ({}) on {}", &[&record.user, &version, &timestamp::display(&record.timestamp)]))
+                    .size(12.0)
+                    .color(status::color(Level::Working)));
+            }
+            if !self.game_path_writable && elevated::AVAILABLE {
+                ui.label(egui::RichText::new(tr("🛡 This account can't change the game folder. Installing, updating or uninstalling the mod and importing resources ask for administrator approval for just those files."))
+                    .size(12.0)
+                    .color(status::color(Level::Warning)));
+            } else if !self.game_path_writable {
+                ui.label(egui::RichText::new(tr("🔒 This account can't change the game folder. Ask whoever installed the mod, or an administrator, to install or update it."))
+                    .size(12.0)
+                    .color(status::color(Level::Warning)));
+            }
+        });
+        ui.add_space(10.0);
+    }
+
+    fn show_stall_notice(&mut self, ui: &mut egui::Ui, task: Task) {
+        let Some(idle) = self.watchdog.stalled_for(task) else {
+            return;
+        };
+
+        ui.label(status::text(&trf("⚠ No progress for {}s, it may still be working or it may be stuck", &[&idle.as_secs()])));
+
+        let cancel = match task {
+            Task::Install => &self.install.cancel,
+            Task::Import => &self.import.cancel,
+            Task::Bundle => &self.bundle.cancel,
+            Task::GameData => &self.game_data.cancel,
+            Task::Restore | Task::Storage | Task::Cache | Task::Cleanup | Task::Plugins | Task::Diagnostics | Task::Repair => &None,
+        };
+        if let Some(cancel) = cancel
+            && ui.button(tr("🔄 Cancel and Retry")).clicked()
+        {
+            cancel.cancel();
+            self.retry_after_cancel = Some(task);
+        }
+    }
+
+    fn handle_events(&mut self, ctx: &egui::Context) {
+        while let Some(event) = self.events.try_recv() {
+            match &event {
+                Event::Progress(task, message) => {
+                    self.watchdog.touch(*task);
+                    tracing::debug!("{:?}: {}", task, message);
+                }
+                Event::Transfer(task, _) => self.watchdog.touch(*task),
+                Event::Finished(task, result) => {
+                    self.watchdog.stop(*task);
+                    match result {
+                        Ok(message) => tracing::info!("{:?}: {}", task, message),
+                        Err(e) => tracing::error!("{:?}: {}", task, e),
+                    }
+                }
+                _ => {}
+            }
+
+            match event {
+                Event::Handoff(request) => {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+
+                    match request {
+                        instance::Request::OpenPack(path) => self.open_pack(path),
+                        instance::Request::ImportFolder(path) => self.import_from_shell(path),
+                        instance::Request::RunTask(task) => self.run_task(task),
+                        instance::Request::Focus => {}
+                    }
+                }
+                Event::PackExtracted(Ok(source)) => {
+                    self.import_folder(source);
+                }
+                Event::PackExtracted(Err(e)) => {
+                    self.import.status = task_error("Unpacking", e);
+                    self.import.progress = None;
+                    self.import.cancel = None;
+                }
+                Event::Progress(Task::Install, msg) => {
+                    self.status_message = msg;
+                }
+                Event::InstallVerified(result) => match result {
+                    Ok(verification) => {
+                        let mut notes = Vec::new();
+                        if verification.unknown_dll {
+                            notes.push(tr("reinstall the mod to record its DLL"));
+                        }
+                        if verification.unknown_resources {
+                            notes.push(tr("TNSM Resources wasn't imported by this installer"));
+                        }
+                        self.verify.status = match (verification.problems.len(), notes.is_empty()) {
+                            (0, true) => trf("✅ All {} installed files are intact", &[&verification.checked]),
+                            (0, false) => trf("⚠ {} files intact, but {}", &[&verification.checked, &notes.join(tr(" and "))]),
+                            (count, _) => trf("❌ {} problem(s) found, reinstall the mod or import the resources again", &[&count]),
+                        };
+                        self.verify.problems = verification.problems;
+                    }
+                    Err(e) => self.verify.status = trf("❌ {}", &[&e]),
+                },
+                Event::GameFilesChecked(result) => {
+                    let build_id = self.game_build_id.unwrap_or_default();
+                    match result {
+                        Ok(None) => {
+                            self.game_check.status = trf("⚠ No known-good hashes for game build {} yet", &[&build_id]);
+                        }
+                        Ok(Some(checks)) => {
+                            let total = checks.len();
+                            self.game_check.problems = checks.into_iter()
+                                .filter(|check| check.state != FileState::Intact)
+                                .collect();
+                            self.game_check.status = if self.game_check.problems.is_empty() {
+                                trf("✅ All {} key files match game build {}", &[&total, &build_id])
+                            } else {
+                                trf(
+                                    "❌ {} of {} key files don't match game build {}, verify the game files in Steam",
+                                    &[&self.game_check.problems.len(), &total, &build_id]
+                                )
+                            };
+                        }
+                        Err(e) => self.game_check.status = trf("❌ {}", &[&e]),
+                    }
+                }
+                Event::OrphansScanned(result) => {
+                    self.orphans.is_cleaning = false;
+                    match result {
+                        Ok(orphans) => {
+                            self.orphans.status = if orphans.is_empty() {
+                                tr("✓ No leftover files found").to_string()
+                            } else {
+                                String::new()
+                            };
+                            self.orphans.list = orphans.into_iter()
+                                .map(|orphan| {
+                                    let selected = orphan.kind.selected_by_default();
+                                    (orphan, selected)
+                                })
+                                .collect();
+                        }
+                        Err(e) => self.orphans.status = trf("❌ {}", &[&e]),
+                    }
+                }
+                Event::Progress(Task::Cleanup, msg) => {
+                    self.orphans.status = msg;
+                }
+                Event::Progress(Task::Plugins, msg) => {
+                    self.plugins.status = msg;
+                }
+                Event::Progress(Task::Diagnostics, msg) => {
+                    self.diagnostics_status = msg;
+                }
+                Event::Progress(Task::Repair, msg) => {
+                    self.verify.status = msg;
+                }
+                Event::Finished(Task::Repair, result) => {
+                    let repaired = result.is_ok();
+                    self.verify.status = result.unwrap_or_else(|e| e);
+                    self.verify.problems.clear();
+                    self.restore.points = restore_point::list();
+                    self.releases.cached = version_cache::list();
+                    self.check_mod_installed();
+                    if repaired {
+                        self.status_pipe.send(PipeEvent::ResourcesChanged);
+                    }
+                }
+                Event::Finished(Task::Diagnostics, result) => {
+                    self.diagnostics_status = result.unwrap_or_else(|e| e);
+                }
+                Event::Finished(Task::Plugins, result) => {
+                    self.plugins.status = result.unwrap_or_else(|e| e);
+                    self.plugins.is_installing = false;
+                    self.plugins.progress = None;
+                }
+                Event::Finished(Task::Cleanup, result) => {
+                    self.orphans.status = result.unwrap_or_else(|e| e);
+                    self.orphans.is_cleaning = false;
+                    self.orphans.list.clear();
+                    self.restore.points = restore_point::list();
+                }
+                Event::Uninstalled(result) => {
+                    self.install.running = false;
+                    match (result, self.game_path.clone()) {
+                        (Ok(_), Some(game_path)) => self.finish_uninstall(&game_path, tr("✅ Mod uninstalled successfully!").to_string()),
+                        (Err(e), _) => self.status_message = trf("❌ Uninstallation failed: {}", &[&e]),
+                        (Ok(_), None) => {}
+                    }
+                }
+                Event::Undoable(entry) => {
+                    self.push_undo(entry);
+                }
+                Event::Previewed(result) => match result {
+                    Ok(changes) => {
+                        if let Some(preview) = &mut self.install.preview {
+                            preview.changes = Some(changes);
+                        }
+                    }
+                    Err(e) => {
+                        let action = self.install.preview.take().map(|preview| preview.action);
+                        if !cancel::is_cancelled(&e) {
+                            let message = trf("❌ Couldn't preview the changes: {}", &[&e]);
+                            match action {
+                                Some(PreviewAction::Import(_)) => self.import.status = message,
+                                _ => self.status_message = message,
+                            }
+                        }
+                    }
+                },
+                Event::CompatDb(db) => {
+                    self.compat_db = db;
+                }
+                Event::GameFiles(game_files) => {
+                    self.game_files = game_files;
+                }
+                Event::AppUpdate(update) => {
+                    self.app_update.available = Some(update);
+                }
+                Event::AppUpdated(result) => {
+                    self.app_update.is_updating = false;
+                    match result.and_then(|exe| self_update::restart(&exe)) {
+                        Ok(()) => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+                        Err(e) => self.status_message = trf("❌ Updating the installer failed: {}", &[&e]),
+                    }
+                }
+                Event::LatestRelease(tag) => {
+                    self.latest_release = Some(tag);
+                    self.notify_update();
+                    // For the notes of the update
+                    if self.available_update().is_some() && self.releases.list.is_empty() && self.releases.status.is_empty() {
+                        self.check_releases();
+                    }
+                }
+                Event::Releases(result) => {
+                    match result {
+                        Ok(releases) => {
+                            self.releases.status = if releases.is_empty() {
+                                tr("❌ No releases found").to_string()
+                            } else {
+                                String::new()
+                            };
+                            self.releases.list = releases;
+                        }
+                        Err(e) => self.releases.status = trf("❌ {}", &[&e]),
+                    }
+                }
+                Event::NightlyBuilds(result) => {
+                    match result {
+                        Ok(builds) => {
+                            self.releases.nightly_status = if builds.is_empty() {
+                                tr("❌ No nightly builds available right now").to_string()
+                            } else {
+                                String::new()
+                            };
+                            self.releases.nightly = builds;
+                        }
+                        Err(e) => self.releases.nightly_status = trf("❌ {}", &[&e]),
+                    }
+                }
+                Event::CacheScanned(locations) => {
+                    self.caches.status = if locations.is_empty() {
+                        tr("✓ No caches found").to_string()
+                    } else {
+                        String::new()
+                    };
+                    // Shared driver caches affect other games, so they have to be picked by hand
+                    self.caches.locations = locations.into_iter()
+                        .map(|location| {
+                            let selected = !location.shared;
+                            (location, selected)
+                        })
+                        .collect();
+                }
+                Event::Progress(Task::Cache, msg) => {
+                    self.caches.status = msg;
+                }
+                Event::Finished(Task::Cache, result) => {
+                    self.caches.status = result.unwrap_or_else(|e| e);
+                    self.caches.is_clearing = false;
+                    self.caches.locations.clear();
+                }
+                Event::StorageUsage(usage) => {
+                    self.storage.usage = usage;
+                }
+                Event::Progress(Task::Storage, msg) => {
+                    self.storage.status = msg;
+                }
+                Event::Progress(Task::Restore, msg) => {
+                    self.restore.status = msg;
+                }
+                Event::Progress(Task::GameData, msg) => {
+                    self.game_data.status = msg;
+                }
+                Event::Progress(Task::Bundle, msg) => {
+                    self.bundle.status = msg;
+                }
+                Event::Progress(Task::Import, msg) => {
+                    self.import.status = msg;
+                }
+                Event::Transfer(Task::Import, progress) => {
+                    self.import.progress = Some(progress);
+                    self.import.estimator.update(progress);
+                }
+                Event::Transfer(Task::Install, progress) => {
+                    self.install.progress = Some(progress);
+                    self.install.estimator.update(progress);
+                }
+                Event::Transfer(Task::Plugins, progress) => {
+                    self.plugins.progress = Some(progress);
+                }
+                Event::Transfer(_, _) => {}
+                Event::Finished(Task::Install, result) => {
+                    self.status_message = result.unwrap_or_else(|e| e);
+                    self.releases.cached = version_cache::list();
+                    self.install.running = false;
+                    self.install.cancel = None;
+                    self.install.progress = None;
+                    self.install.estimator = Estimator::default();
+                    self.game_data.backups = game_data::list_backups();
+                    self.restore.points = restore_point::list();
+
+                    if self.retry_after_cancel.take_if(|task| *task == Task::Install).is_some() {
+                        self.install_mod();
+                    }
+                }
+                Event::Finished(Task::Storage, result) => {
+                    self.storage.status = result.unwrap_or_else(|e| e);
+                    self.storage.is_cleaning_up = false;
+                    self.restore.points = restore_point::list();
+                    self.restore.selected = 0;
+                    self.game_data.backups = game_data::list_backups();
+                    self.game_data.selected = 0;
+                }
+                Event::Finished(Task::Restore, result) => {
+                    self.restore.status = result.unwrap_or_else(|e| e);
+                    self.restore.is_reverting = false;
+                    self.restore.points = restore_point::list();
+                    self.restore.selected = 0;
+                    self.check_mod_installed();
+                }
+                Event::Finished(Task::GameData, result) => {
+                    self.game_data.status = result.unwrap_or_else(|e| e);
+                    self.game_data.cancel = None;
+                    self.game_data.backups = game_data::list_backups();
+                    self.game_data.selected = 0;
+                }
+                Event::Finished(Task::Bundle, result) => {
+                    self.bundle.status = result.unwrap_or_else(|e| e);
+                    self.bundle.cancel = None;
+                }
+                Event::Finished(Task::Import, result) => {
+                    if result.is_ok() {
+                        self.status_pipe.send(PipeEvent::ResourcesChanged);
+                    }
+                    let cancelled = self.import.cancel.as_ref().is_some_and(|cancel| cancel.is_cancelled());
+                    let was_elevated = std::mem::take(&mut self.import.is_elevated);
+                    let retry_elevated = result.is_err() && !cancelled && !was_elevated
+                        && !self.game_path_writable && elevated::AVAILABLE;
+                    self.import.status = result.unwrap_or_else(|e| e);
+                    self.import.progress = None;
+                    self.import.cancel = None;
+                    self.restore.points = restore_point::list();
+
+                    if retry_elevated && let Some(source) = self.import.source.clone() {
+                        self.import_elevated(source);
+                    }
+
+                    if self.retry_after_cancel.take_if(|task| *task == Task::Import).is_some()
+                        && let Some(source) = self.import.source.clone()
+                    {
+                        self.import_folder(source);
+                    }
+                }
+                Event::ModInstalled(version) => {
+                    self.is_mod_installed = true;
+                    self.status_pipe.send(PipeEvent::InstallComplete);
+                    if let Some(game_path) = &self.game_path {
+                        let _ = machine::record(game_path, true, version.clone());
+                        self.install_record = machine::latest();
+                    }
+                    self.config.installed_version = version;
+                    let _ = self.config.save();
+                    if let Some(tray) = &self.tray.icon {
+                        tray.set_update(self.available_update());
+                    }
+                    self.dll_problem = None;
+                    self.check_for_quarantine();
+                }
+                Event::ModChecked(result) => {
+                    self.is_checking_mod = false;
+                    if let Ok(state) = result
+                        && self.game_path.as_ref() == Some(&state.game_path)
+                    {
+                        self.is_mod_installed = state.is_mod_installed;
+                        self.game_path_writable = state.game_path_writable;
+                        self.install_record = state.install_record;
+                        self.install.pending_removal = state.pending_removal;
+                        self.game_build_id = state.game_build_id;
+                        self.dll_problem = state.dll_problem;
+                        self.install.conflicts = state.conflicts;
+                        self.import.link = state.resources_link;
+                    }
+                    if std::mem::take(&mut self.check_mod_again) {
+                        self.check_mod_installed();
+                    }
+                }
+                Event::DllChecked(problem) => {
+                    if let Some(problem) = problem {
+                        tracing::warn!("Installed DLL is {} after installing, likely quarantined by antivirus", problem.label());
+                    }
+                    self.dll_problem = problem;
+                }
+            }
+        }
+    }
+}
+
+fn task_error(operation: &'static str, error: String) -> String {
+    if cancel::is_cancelled(&error) {
+        trf("⚠ {} cancelled", &[&tr(operation)])
+    } else {
+        trf("❌ {} failed: {}", &[&tr(operation), &error])
+    }
+}
+
+fn rate_limit_reporter(events: &EventSender, task: Task) -> impl Fn(u64) + Sync + use<> {
+    let events = events.clone();
+    move |seconds| events.progress(task, trf("⏳ GitHub rate limit reached, retrying in {}s", &[&seconds]))
+}
+
+impl App for ModInstallerApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Stop background work and let it clean up its temp files and journals
+        self.tasks.shutdown(SHUTDOWN_TIMEOUT);
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+        // Apply whatever the background tasks reported since the last frame
+        self.handle_events(ctx);
+        self.handle_tray_commands(ctx);
+        self.run_action_queue(ctx);
+        self.handle_dropped_files(ctx);
+        if let Some(after) = self.watchdog.next_check() {
+            ctx.request_repaint_after(after);
+        }
+
+        theme::apply_theme(ctx);
+        self.show_drop_hint(ctx);
+
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.view, View::Installer, tr("⚡ Installer"));
+                if self.policy.allows(Feature::Gallery) {
+                    ui.selectable_value(&mut self.view, View::Gallery, tr("🖼 Gallery"));
+                }
+                if self.policy.allows(Feature::Log) {
+                    ui.selectable_value(&mut self.view, View::Log, tr("📜 Live Log"));
+                }
+
+                if let Some(label) = self.undo_stack.peek().map(|entry| entry.label.clone()) {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let enabled = !self.is_busy() && !self.game_running;
+                        if ui.add_enabled(enabled, egui::Button::new(tr("↶ Undo")))
+                            .on_hover_text(format!("{} (Ctrl+Z)", label))
+                            .clicked()
+                        {
+                            self.undo();
+                        }
+                    });
+                }
+            });
+        });
+
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z))
+        {
+            self.undo();
+        }
+
+        match self.view {
+            View::Gallery => {
+                egui::CentralPanel::default().show(ctx, |ui| self.show_gallery(ui));
+                return;
+            }
+            View::Log => {
+                egui::CentralPanel::default().show(ctx, |ui| self.show_live_log(ui));
+                return;
+            }
+            View::Installer => {}
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // The window has a fixed size, longer sections scroll
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(20.0);
+
+                    // Title
+                    ui.heading(egui::RichText::new(tr("🌟 ThatNoobSkyApp"))
+                        .size(28.0)
+                        .color(theme::accent()));
+
+                    ui.add_space(10.0);
+                    ui.label(egui::RichText::new(tr("Installer for ThatNoobSkyMod"))
+                        .size(14.0)
+                        .color(egui::Color32::GRAY));
+
+                    if let Some(version) = self.config.installed_version.as_deref()
+                        && self.is_mod_installed
+                        && nightly::is_nightly(version)
+                    {
+                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new(trf("🌙 Unstable nightly build {} installed, install a release to go back", &[&version]))
+                            .size(12.0)
+                            .color(status::color(Level::Warning)));
+                    }
+
+                    if let Some(message) = &self.policy.message {
+                        ui.add_space(10.0);
+                        ui.label(egui::RichText::new(format!("🔐 {}", message))
+                            .size(12.0)
+                            .color(status::color(Level::Warning)));
+                    }
+
+                    ui.add_space(30.0);
+                });
+
+                self.show_pending_operations(ui);
+
+                self.show_install_status(ui);
+
+                ui.add_space(20.0);
+
+                // Browse for Path button
+                if self.is_game_path_locked() {
+                    // Set by the policy, nothing to browse for
+                } else if self.show_manual_input || self.game_path.is_none() {
+                    ui.vertical_centered(|ui| {
+                        if ui.button(tr("📁 Browse for Game Directory")).clicked() {
+                            self.browse_for_path();
+                        }
+                    });
+                    ui.add_space(10.0);
+                } else if self.game_path.is_some() {
+                    // Show option to change path
+                    ui.vertical_centered(|ui| {
+                        if ui.button(tr("📝 Change Path")).clicked() {
+                            self.browse_for_path();
+                        }
+                    });
+                    ui.add_space(10.0);
+                }
+
+                self.show_app_update_notice(ui);
+                self.show_other_account_notice(ui);
+                self.show_quarantine_notice(ui);
+                self.show_conflict_notice(ui);
+                self.show_action_queue(ui);
+                self.show_preview(ui);
+
+                self.show_install_buttons(ui);
+
+                ui.add_space(20.0);
+
+                if self.policy.allows(Feature::Uninstall) {
+                    self.show_uninstall_button(ui);
+
+                    ui.add_space(20.0);
+                }
+
+                if self.policy.allows(Feature::Import) {
+                    self.show_import(ui);
+
+                    ui.add_space(15.0);
+                }
+
+                if self.policy.allows(Feature::Bundles) {
+                    self.show_bundles(ui);
+
+                    ui.add_space(15.0);
+                }
+
+                // Restore Points
+                if !self.restore.points.is_empty() && self.policy.allows(Feature::RestorePoints) {
+                    self.show_restore_points(ui);
+
+                    ui.add_space(15.0);
+                }
+
+                if self.policy.allows(Feature::GameDataBackups) {
+                    self.show_game_data_backup(ui);
+
+                    ui.add_space(15.0);
+                }
+
+                self.show_settings(ui);
+
+                ui.add_space(15.0);
+
+                // A version pinned by the policy is the only one allowed
+                if self.policy.pinned_dll_url().is_none() {
+                    egui::CollapsingHeader::new(tr("🔀 A/B Versions"))
+                        .id_salt("ab_versions")
+                        .show(ui, |ui| self.show_version_switcher(ui));
+                }
+
+                if self.policy.allows(Feature::Nightly) && self.policy.pinned_dll_url().is_none() {
+                    egui::CollapsingHeader::new(tr("🌙 Nightly Builds"))
+                        .id_salt("nightly")
+                        .show(ui, |ui| self.show_nightly_builds(ui));
+                }
+
+                if self.policy.allows(Feature::Plugins) {
+                    egui::CollapsingHeader::new(tr("🧩 Plugins"))
+                        .id_salt("plugins")
+                        .show(ui, |ui| self.show_plugins(ui));
+                }
+
+                if self.policy.allows(Feature::Import) {
+                    egui::CollapsingHeader::new(tr("🗂 Resource Profiles"))
+                        .id_salt("resource_profiles")
+                        .show(ui, |ui| self.show_resource_profiles(ui));
+                }
+
+                if self.policy.allows(Feature::ConfigBackups) {
+                    egui::CollapsingHeader::new(tr("👤 Mod Config Backups"))
+                        .id_salt("config_backups")
+                        .show(ui, |ui| self.show_config_backups(ui));
+                }
+
+                if self.policy.allows(Feature::GameCaches) {
+                    egui::CollapsingHeader::new(tr("🧽 Game Caches"))
+                        .id_salt("game_caches")
+                        .show(ui, |ui| self.show_game_caches(ui));
+                }
+
+                if self.policy.allows(Feature::Verify) {
+                    egui::CollapsingHeader::new(tr("🔎 Verify Installation"))
+                        .id_salt("verify_installation")
+                        .show(ui, |ui| self.show_install_verification(ui));
+                }
+
+                // Hidden until game_files.json has hashes for this build
+                if self.game_build_id.is_some_and(|build_id| self.game_files.hashes(build_id).is_some()) {
+                    egui::CollapsingHeader::new(tr("🩺 Game Files"))
+                        .id_salt("game_files")
+                        .show(ui, |ui| self.show_game_file_check(ui));
+                }
+
+                if self.policy.allows(Feature::LeftoverFiles) {
+                    egui::CollapsingHeader::new(tr("🧹 Leftover Files"))
+                        .id_salt("leftover_files")
+                        .show(ui, |ui| self.show_orphans(ui));
+                }
+
+                if self.policy.allows(Feature::AuditLog) {
+                    egui::CollapsingHeader::new(tr("📋 Audit Log"))
+                        .id_salt("audit_log")
+                        .show(ui, |ui| self.show_audit_log(ui));
+                }
+
+                if self.policy.allows(Feature::InstallerLog) {
+                    egui::CollapsingHeader::new(tr("🧾 Logs"))
+                        .id_salt("installer_log")
+                        .show(ui, |ui| self.show_installer_log(ui));
+                }
+
+                if self.policy.allows(Feature::Downloads) {
+                    egui::CollapsingHeader::new(tr("📡 Downloads"))
+                        .id_salt("downloads")
+                        .show(ui, |ui| self.show_download_settings(ui));
+                }
+
+                if self.policy.allows(Feature::SettingsTransfer) {
+                    egui::CollapsingHeader::new(tr("💾 Export and Import Settings"))
+                        .id_salt("settings_export")
+                        .show(ui, |ui| self.show_settings_export(ui));
+                }
+
+                if self.policy.allows(Feature::Storage) {
+                    egui::CollapsingHeader::new(tr("💽 Storage"))
+                        .id_salt("storage")
+                        .show(ui, |ui| self.show_storage(ui));
+                }
+
+                ui.add_space(15.0);
+
+                ui.vertical_centered(|ui| {
+                    ui.label(egui::RichText::new(tr("⚠ Note: Run the game as usual to activate the mod"))
+                        .size(12.0)
+                        .color(status::color(Level::Warning)));
+                });
+            });
+        });
+    }
+}
+
+pub fn run(request: Option<instance::Request>, minimized: bool) -> eframe::Result<()> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([500.0, 720.0])
+            .with_resizable(false),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "ThatNoobSkyApp",
+        options,
+        Box::new(move |cc| {
+            if minimized {
+                cc.egui_ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+            // Gallery thumbnails are loaded straight from file:// URIs
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+            Ok(Box::new(ModInstallerApp::new(&cc.egui_ctx, request)))
+        }),
+    )
+}
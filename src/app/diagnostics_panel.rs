@@ -0,0 +1,655 @@
+// Checking and repairing an install: verification, game files, leftover
+// files, game caches, the audit log and the installer and mod logs.
+
+use super::{ModInstallerApp, task_error, rate_limit_reporter};
+use crate::logging;
+use crate::status::{self, Level};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tnsm_installer_core::game_cache::{self, CacheLocation};
+use tnsm_installer_core::game_files::{self, FileCheck, FileState, GameFiles};
+use tnsm_installer_core::events::{Event, Task};
+use tnsm_installer_core::i18n::{tr, trf};
+use tnsm_installer_core::install::InstallSettings;
+use tnsm_installer_core::log_tail::{self, LogTail};
+use tnsm_installer_core::manifest::{self, FileProblem, Problem};
+use tnsm_installer_core::orphans::{self, Orphan};
+use tnsm_installer_core::pipeline::InstallStep;
+use tnsm_installer_core::undo::UndoEntry;
+use tnsm_installer_core::{audit, config, diagnostics, download, install, resources, storage, timestamp, version_cache};
+
+// Game caches found to clear
+#[derive(Default)]
+pub(super) struct CachesState {
+    pub(super) status: String,
+    // Scanned locations and whether each is selected for clearing
+    pub(super) locations: Vec<(CacheLocation, bool)>,
+    pub(super) is_clearing: bool,
+}
+
+// The Live Log tab following the mod's log
+pub(super) struct LiveLogState {
+    pub(super) tail: Option<LogTail>,
+    pub(super) status: String,
+    pub(super) follow: bool,
+    pub(super) filter: String,
+    pub(super) problems_only: bool,
+}
+
+impl Default for LiveLogState {
+    fn default() -> Self {
+        Self {
+            tail: None,
+            status: String::new(),
+            follow: true,
+            filter: String::new(),
+            problems_only: false,
+        }
+    }
+}
+
+// Verify Installation
+#[derive(Default)]
+pub(super) struct VerifyState {
+    pub(super) status: String,
+    pub(super) problems: Vec<FileProblem>,
+}
+
+// Checking the game files against known-good hashes
+#[derive(Default)]
+pub(super) struct GameCheckState {
+    pub(super) status: String,
+    pub(super) problems: Vec<FileCheck>,
+}
+
+// Leftover files in the game folder
+#[derive(Default)]
+pub(super) struct OrphansState {
+    pub(super) list: Vec<(Orphan, bool)>,
+    pub(super) status: String,
+    pub(super) is_cleaning: bool,
+}
+
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl ModInstallerApp {
+    fn scan_game_caches(&mut self) {
+        self.caches.status = tr("⏳ Looking for caches...").to_string();
+        let steam_path = self.steam_path.clone();
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            if let Ok(locations) = game_cache::scan(steam_path).await {
+                events.send(Event::CacheScanned(locations));
+            }
+        });
+    }
+
+    fn clear_game_caches(&mut self) {
+        let selected: Vec<CacheLocation> = self.caches.locations.iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(location, _)| location.clone())
+            .collect();
+        if selected.is_empty() {
+            return;
+        }
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Clear Game Caches"))
+            .set_description(trf(
+                "Delete {} cached files ({})?\n\nClose Sky first. The caches are rebuilt the next time the game starts, which may take a little longer.",
+                &[&selected.iter().map(|location| location.files).sum::<usize>(), &storage::format_size(selected.iter().map(|location| location.bytes).sum())]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.caches.is_clearing = true;
+        self.caches.status = tr("⏳ Clearing caches...").to_string();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let result = match game_cache::clear(selected, cancel).await {
+                Ok(report) if report.skipped > 0 => Ok(trf(
+                    "✅ {} freed, {} files in use were skipped",
+                    &[&storage::format_size(report.bytes), &report.skipped]
+                )),
+                Ok(report) => Ok(trf("✅ {} freed", &[&storage::format_size(report.bytes)])),
+                Err(e) => Err(task_error("Cache cleanup", e)),
+            };
+            events.finished(Task::Cache, result);
+        });
+    }
+
+    fn check_game_files(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        let Some(build_id) = self.game_build_id else {
+            self.game_check.status = tr("⚠ The game build couldn't be read, only Steam installs can be checked").to_string();
+            return;
+        };
+
+        self.game_check.status = tr("⏳ Checking game files...").to_string();
+        self.game_check.problems.clear();
+        let events = self.events.sender();
+        let game_files = self.game_files.clone();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let result = game_files::check(game_path, game_files, build_id, cancel).await;
+            events.send(Event::GameFilesChecked(result));
+        });
+    }
+
+    pub(super) fn verify_installation(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+
+        self.verify.status = tr("⏳ Verifying installed files...").to_string();
+        self.verify.problems.clear();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let result = manifest::verify(game_path, proxy, cancel).await;
+            events.send(Event::InstallVerified(result));
+        });
+    }
+
+    // Reinstalls the DLL, from the version cache when it holds the same one,
+    // and copies broken resource files again from where they were imported
+    fn repair_installation(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        if self.game_running {
+            self.verify.status = tr("⚠ Close Sky before repairing").to_string();
+            return;
+        }
+
+        let broken: Vec<&FileProblem> = self.verify.problems.iter()
+            .filter(|problem| problem.problem != Problem::Extra)
+            .collect();
+        let repair_dll = broken.iter().any(|problem| problem.relative == Path::new(self.config.proxy_dll.file_name()));
+        let resource_files: Vec<PathBuf> = broken.iter()
+            .filter_map(|problem| problem.relative.strip_prefix(resources::RESOURCES_DIR_NAME).ok())
+            .map(Path::to_path_buf)
+            .collect();
+        let dll_url = self.config.installed_version.as_deref()
+            .map(download::release_dll_url)
+            .unwrap_or_else(|| self.dll_url.clone());
+
+        self.verify.status = tr("⏳ Repairing...").to_string();
+        let events = self.events.sender();
+        let settings = InstallSettings::from_config(&self.config);
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let mut repaired = Vec::new();
+
+            if repair_dll {
+                let step_events = events.clone();
+                let on_step = move |step: &dyn InstallStep| {
+                    step_events.progress(Task::Repair, trf("⏳ {}...", &[&tr(step.description())]));
+                };
+                let cached = manifest::recorded_dll_sha256(&game_path)
+                    .and_then(|sha256| version_cache::find_by_sha256(&sha256));
+                let result = match cached {
+                    Some(version) => install::install_cached(&version, &game_path, &settings, &cancel, on_step).await,
+                    None => {
+                        let on_rate_limited = rate_limit_reporter(&events, Task::Repair);
+                        install::download_and_install(&dll_url, &game_path, &settings, &cancel, &on_rate_limited, &|_| {}, on_step).await
+                    }
+                };
+                if let Err(e) = result {
+                    events.finished(Task::Repair, Err(task_error("Repair", e)));
+                    return;
+                }
+                repaired.push(tr("mod DLL reinstalled").to_string());
+            }
+
+            if !resource_files.is_empty() {
+                events.progress(Task::Repair, trf("⏳ Repairing {} resource files...", &[&resource_files.len()]));
+                let result = resources::repair(game_path.clone(), resource_files, cancel.clone()).await;
+                match result {
+                    Ok((count, unrepairable, restore_point)) => {
+                        if let Some(restore_point) = restore_point {
+                            events.send(Event::Undoable(UndoEntry { restore_point, label: tr("Resource repair").to_string() }));
+                        }
+                        repaired.push(trf("{} resource files copied again", &[&count]));
+                        if !unrepairable.is_empty() {
+                            events.finished(Task::Repair, Ok(trf(
+                                "⚠ {}, but {} files are no longer where they were imported from, import them again",
+                                &[&repaired.join(", "), &unrepairable.len()]
+                            )));
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        events.finished(Task::Repair, Err(task_error("Repair", e)));
+                        return;
+                    }
+                }
+            }
+
+            events.finished(Task::Repair, Ok(trf("✅ Repaired: {}", &[&repaired.join(", ")])));
+        });
+    }
+
+    pub(super) fn show_install_verification(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Checks the mod DLL and TNSM Resources against what was installed and imported, e.g. after antivirus removed something."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        ui.add_enabled_ui(!self.verify.status.starts_with('⏳') && self.game_path.is_some(), |ui| {
+            if ui.button(tr("🔎 Verify")).clicked() {
+                self.verify_installation();
+            }
+        });
+
+        let repairable = self.verify.problems.iter().any(|problem| problem.problem != Problem::Extra);
+        if repairable
+            && !self.verify.status.starts_with('⏳')
+            && ui.button(tr("🔧 Repair"))
+                .on_hover_text(tr("Fetches only the missing and broken files again"))
+                .clicked()
+        {
+            self.repair_installation();
+        }
+
+        if !self.verify.status.is_empty() {
+            ui.label(status::text(&self.verify.status));
+        }
+
+        for problem in &self.verify.problems {
+            ui.label(egui::RichText::new(format!("• {} ({})", problem.relative.display(), tr(problem.problem.label())))
+                .size(12.0)
+                .color(status::color(Level::Error)));
+        }
+    }
+
+    fn export_audit_log(&mut self, extension: &str) {
+        let Some(dest) = rfd::FileDialog::new()
+            .set_title(tr("Export Audit Log"))
+            .add_filter(extension.to_uppercase(), &[extension])
+            .set_file_name(format!("tnsm-audit-{}.{}", timestamp::now(), extension))
+            .save_file()
+        else {
+            return;
+        };
+
+        self.audit_status = match audit::export(&dest) {
+            Ok(count) => trf("✅ Exported {} entries to {}", &[&count, &dest.display()]),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+    }
+
+    pub(super) fn show_audit_log(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Every change the installer made on this PC, with who made it, when, how it ended and the mod DLL's hash before and after."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("📄 Export CSV")).clicked() {
+                self.export_audit_log("csv");
+            }
+            if ui.button(tr("📄 Export JSON")).clicked() {
+                self.export_audit_log("json");
+            }
+        });
+
+        if !self.audit_status.is_empty() {
+            ui.label(status::text(&self.audit_status));
+        }
+    }
+
+    fn collect_diagnostics(&mut self) {
+        let Some(dest) = rfd::FileDialog::new()
+            .set_title(tr("Save Diagnostics"))
+            .add_filter("ZIP", &["zip"])
+            .set_file_name(format!("tnsm-diagnostics-{}.zip", timestamp::now()))
+            .save_file()
+        else {
+            return;
+        };
+
+        self.diagnostics_status = tr("⏳ Collecting diagnostics...").to_string();
+        let game_path = self.game_path.clone();
+        let events = self.events.sender();
+        let config = self.config.clone();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let result = diagnostics::collect(dest.clone(), game_path, config, env!("CARGO_PKG_VERSION"), cancel)
+                .await
+                .map(|_| trf("✅ Saved to {}, attach it to your issue", &[&dest.display()]))
+                .map_err(|e| trf("❌ {}", &[&e]));
+            events.finished(Task::Diagnostics, result);
+        });
+    }
+
+    pub(super) fn show_installer_log(&mut self, ui: &mut egui::Ui) {
+        let lines = logging::lines(self.log_level);
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(tr("Verbosity"))
+                .selected_text(self.log_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [tracing::Level::ERROR, tracing::Level::WARN, tracing::Level::INFO, tracing::Level::DEBUG] {
+                        ui.selectable_value(&mut self.log_level, level, level.as_str());
+                    }
+                });
+
+            if ui.add_enabled(!lines.is_empty(), egui::Button::new(tr("📋 Copy logs"))).clicked() {
+                let text: Vec<_> = lines.iter().map(|line| line.to_text()).collect();
+                ui.ctx().copy_text(text.join("\n"));
+            }
+
+            if ui.add_enabled(!self.diagnostics_status.starts_with('⏳'), egui::Button::new(tr("🩺 Collect Diagnostics")))
+                .on_hover_text(tr("Saves logs, versions and file hashes into one zip to attach to a bug report"))
+                .clicked()
+            {
+                self.collect_diagnostics();
+            }
+        });
+
+        if !self.diagnostics_status.is_empty() {
+            status::show(ui, &self.diagnostics_status, 12.0);
+        }
+
+        if let Some(path) = config::installer_log_path() {
+            ui.label(egui::RichText::new(format!("{} {}", tr("Also saved to"), path.display()))
+                .size(12.0)
+                .color(egui::Color32::GRAY));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("installer_log")
+            .max_height(200.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &lines {
+                    let color = match line.level {
+                        tracing::Level::ERROR => status::color(Level::Error),
+                        tracing::Level::WARN => status::color(Level::Warning),
+                        tracing::Level::INFO => ui.visuals().text_color(),
+                        _ => egui::Color32::GRAY,
+                    };
+                    ui.label(egui::RichText::new(line.to_text()).monospace().size(11.0).color(color));
+                }
+            });
+    }
+
+    pub(super) fn show_game_file_check(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Checks the game's key files against known-good copies, to tell a broken game install apart from a mod problem."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        ui.add_enabled_ui(!self.game_check.status.starts_with('⏳') && self.game_path.is_some(), |ui| {
+            if ui.button(tr("🩺 Check Game Files")).clicked() {
+                self.check_game_files();
+            }
+        });
+
+        if !self.game_check.status.is_empty() {
+            ui.label(status::text(&self.game_check.status));
+        }
+
+        for problem in &self.game_check.problems {
+            let state = match problem.state {
+                FileState::Missing => tr("missing"),
+                _ => tr("modified"),
+            };
+            ui.label(egui::RichText::new(format!("• {} ({})", problem.relative, state))
+                .size(12.0)
+                .color(status::color(Level::Error)));
+        }
+    }
+
+    fn scan_orphans(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+
+        self.orphans.is_cleaning = true;
+        self.orphans.status = tr("⏳ Scanning the game folder...").to_string();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let plugins = self.config.plugins.clone();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let game_files = match game_files::refresh(&cancel).await {
+                Ok(game_files) => {
+                    events.send(Event::GameFiles(game_files.clone()));
+                    game_files
+                }
+                // Offline, go by the last copy
+                Err(_) => GameFiles::load(),
+            };
+            let result = orphans::scan(game_path, proxy, plugins, game_files, cancel).await;
+            events.send(Event::OrphansScanned(result));
+        });
+    }
+
+    fn clean_up_orphans(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        let selected: Vec<Orphan> = self.orphans.list.iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(orphan, _)| orphan.clone())
+            .collect();
+        if selected.is_empty() {
+            return;
+        }
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Clean Up Leftover Files"))
+            .set_description(trf(
+                "Remove {} files ({}) from the game folder?\n\nThey are saved to a restore point first, so this can be undone.",
+                &[&selected.len(), &storage::format_size(selected.iter().map(|orphan| orphan.size).sum())]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.orphans.is_cleaning = true;
+        self.orphans.status = tr("⏳ Cleaning up...").to_string();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let result = match orphans::clean_up(game_path, selected, cancel).await {
+                Ok((count, restore_point)) => {
+                    if let Some(restore_point) = restore_point {
+                        events.send(Event::Undoable(UndoEntry { restore_point, label: tr("Leftover file cleanup").to_string() }));
+                    }
+                    Ok(trf("✅ Removed {} files, undo or revert the restore point to bring them back", &[&count]))
+                }
+                Err(e) => Err(task_error("Cleanup", e)),
+            };
+            events.finished(Task::Cleanup, result);
+        });
+    }
+
+    pub(super) fn show_orphans(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Finds files in the game folder left behind by other mods, failed installs or manual changes."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        for (orphan, selected) in &mut self.orphans.list {
+            ui.checkbox(selected, format!(
+                "{} - {} ({})",
+                orphan.relative.display(),
+                tr(orphan.kind.describe()),
+                storage::format_size(orphan.size)
+            ));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!self.orphans.is_cleaning && self.game_path.is_some(), |ui| {
+                if ui.button(tr("🔍 Scan Game Folder")).clicked() {
+                    self.scan_orphans();
+                }
+
+                let selected = self.orphans.list.iter().filter(|(_, selected)| *selected).count();
+                ui.add_enabled_ui(selected > 0 && self.game_path_writable && !self.game_running, |ui| {
+                    if ui.button(trf("🗑 Clean Up Selected ({})", &[&selected])).clicked() {
+                        self.clean_up_orphans();
+                    }
+                });
+            });
+        });
+
+        if !self.orphans.status.is_empty() {
+            status::show(ui, &self.orphans.status, 12.0);
+        }
+    }
+
+    fn choose_mod_log(&mut self) {
+        let mut dialog = rfd::FileDialog::new()
+            .set_title(tr("Select the Mod's Log File"))
+            .add_filter(tr("Log"), &["log", "txt"]);
+        if let Some(game_path) = &self.game_path {
+            dialog = dialog.set_directory(game_path);
+        }
+
+        if let Some(path) = dialog.pick_file() {
+            self.config.mod_log_path = Some(path);
+            let _ = self.config.save();
+        }
+    }
+
+    pub(super) fn show_live_log(&mut self, ui: &mut egui::Ui) {
+        // Where the mod logs to isn't known up front, so it's picked once
+        let Some(log_path) = self.config.mod_log_path.clone() else {
+            ui.label(tr("Choose the log file the mod writes to, it's shown here as the game runs."));
+            if ui.button(tr("📂 Choose log file")).clicked() {
+                self.choose_mod_log();
+            }
+            return;
+        };
+
+        if self.live_log.tail.as_ref().is_none_or(|tail| tail.path() != log_path) {
+            self.live_log.tail = Some(LogTail::new(log_path));
+        }
+        let Some(tail) = &mut self.live_log.tail else {
+            return;
+        };
+
+        // Only polled while the tab is open
+        match tail.poll() {
+            Ok(_) => self.live_log.status.clear(),
+            Err(e) => self.live_log.status = trf("❌ {}", &[&e]),
+        }
+        ui.ctx().request_repaint_after(LOG_POLL_INTERVAL);
+
+        ui.horizontal(|ui| {
+            ui.label(tr("🔍"));
+            ui.add(egui::TextEdit::singleline(&mut self.live_log.filter)
+                .hint_text(tr("Filter"))
+                .desired_width(150.0));
+            ui.checkbox(&mut self.live_log.problems_only, tr("Warnings and errors only"));
+            ui.checkbox(&mut self.live_log.follow, tr("Follow"));
+        });
+
+        let filter = self.live_log.filter.to_lowercase();
+        let visible: Vec<&str> = tail.lines()
+            .filter(|line| !self.live_log.problems_only || log_tail::level(line) != log_tail::Level::Info)
+            .filter(|line| filter.is_empty() || line.to_lowercase().contains(&filter))
+            .collect();
+
+        let mut clear = false;
+        let mut choose = false;
+        ui.horizontal(|ui| {
+            if ui.button(tr("📋 Copy")).clicked() {
+                ui.ctx().copy_text(visible.join("\n"));
+            }
+            clear = ui.button(tr("🧹 Clear")).clicked();
+            choose = ui.button(tr("📂 Choose log file")).clicked();
+            ui.label(egui::RichText::new(tail.path().display().to_string())
+                .size(11.0)
+                .color(egui::Color32::GRAY));
+        });
+
+        if !self.live_log.status.is_empty() {
+            status::show(ui, &self.live_log.status, 12.0);
+        }
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(self.live_log.follow)
+            .show_rows(ui, row_height, visible.len(), |ui, rows| {
+                for line in &visible[rows] {
+                    let color = match log_tail::level(line) {
+                        log_tail::Level::Error => status::color(Level::Error),
+                        log_tail::Level::Warning => status::color(Level::Warning),
+                        log_tail::Level::Info => ui.visuals().text_color(),
+                    };
+                    ui.label(egui::RichText::new(*line).monospace().color(color));
+                }
+            });
+
+        if clear {
+            tail.clear();
+        }
+        if choose {
+            self.choose_mod_log();
+        }
+    }
+
+    pub(super) fn show_game_caches(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Clearing shader caches can fix graphical glitches after a mod update."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        for (location, selected) in &mut self.caches.locations {
+            let mut text = trf(
+                "{} - {} ({} files)",
+                &[&tr(location.name), &storage::format_size(location.bytes), &location.files]
+            );
+            if location.shared {
+                text.push_str(tr(" · shared with other games"));
+            }
+            ui.checkbox(selected, text)
+                .on_hover_text(location.path.display().to_string());
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!self.caches.is_clearing, |ui| {
+                if ui.button(tr("🔍 Scan")).clicked() {
+                    self.scan_game_caches();
+                }
+
+                let selected_bytes: u64 = self.caches.locations.iter()
+                    .filter(|(_, selected)| *selected)
+                    .map(|(location, _)| location.bytes)
+                    .sum();
+                let any_selected = self.caches.locations.iter().any(|(_, selected)| *selected);
+                ui.add_enabled_ui(any_selected, |ui| {
+                    if ui.button(trf("🗑 Clear Selected ({})", &[&storage::format_size(selected_bytes)])).clicked() {
+                        self.clear_game_caches();
+                    }
+                });
+            });
+        });
+
+        if !self.caches.status.is_empty() {
+            status::show(ui, &self.caches.status, 12.0);
+        }
+    }
+}
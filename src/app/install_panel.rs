@@ -0,0 +1,1406 @@
+// The install panel: installing, switching and uninstalling the mod, offline
+// bundles, restore points and the notices that go with them.
+
+use super::{ModInstallerApp, PreviewAction, Preview, task_error, rate_limit_reporter};
+use crate::status::{self, Level};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tnsm_installer_core::action_queue::QueuedAction;
+use tnsm_installer_core::arch::Arch;
+use tnsm_installer_core::cancel::CancellationToken;
+use tnsm_installer_core::conflicts::Conflict;
+use tnsm_installer_core::download::{self, DEFAULT_DLL_URL};
+use tnsm_installer_core::github::Release;
+use tnsm_installer_core::events::{Event, Task, TransferProgress};
+use tnsm_installer_core::i18n::{tr, trf};
+use tnsm_installer_core::status_pipe::PipeEvent;
+use tnsm_installer_core::install::{InstallSettings, ProxyDll};
+use tnsm_installer_core::journal::{self, Operation};
+use tnsm_installer_core::manifest::{self, Problem};
+use tnsm_installer_core::nightly::{self, NightlyBuild};
+use tnsm_installer_core::pipeline::InstallStep;
+use tnsm_installer_core::progress::Estimator;
+use tnsm_installer_core::preview::{self, ChangeKind};
+use tnsm_installer_core::restore_point::{self, RestorePointInfo};
+use tnsm_installer_core::undo::UndoEntry;
+use tnsm_installer_core::version_cache::CachedVersion;
+use tnsm_installer_core::{audit, bundle, conflicts, elevated, game_data, github, install, locked_file, machine, resources, shell, storage, workers};
+
+// The running install or uninstall and what it found in the way
+#[derive(Default)]
+pub(super) struct InstallState {
+    pub(super) running: bool,
+    pub(super) cancel: Option<CancellationToken>,
+    // Download progress of the install running now
+    pub(super) progress: Option<TransferProgress>,
+    pub(super) estimator: Estimator,
+    // Also delete TNSM Resources when uninstalling
+    pub(super) uninstall_resources: bool,
+    // Old DLLs moved aside by an uninstall, deleted after a restart
+    pub(super) pending_removal: Vec<PathBuf>,
+    // Other mods' proxy DLLs and injector files, found when the folder is picked
+    pub(super) conflicts: Vec<Conflict>,
+    // Installing anyway was chosen for the conflicts found
+    pub(super) conflicts_acknowledged: bool,
+    pub(super) preview: Option<Preview>,
+    // Apply was clicked, so the action runs instead of being previewed again
+    pub(super) preview_approved: bool,
+}
+
+// Creating and installing offline bundles
+#[derive(Default)]
+pub(super) struct BundleState {
+    pub(super) status: String,
+    pub(super) cancel: Option<CancellationToken>,
+}
+
+// Restore points of the game folder
+#[derive(Default)]
+pub(super) struct RestoreState {
+    pub(super) status: String,
+    pub(super) is_reverting: bool,
+    pub(super) points: Vec<RestorePointInfo>,
+    pub(super) selected: usize,
+}
+
+// Releases, cached versions and nightly builds to pick from
+#[derive(Default)]
+pub(super) struct ReleasesState {
+    pub(super) list: Vec<Release>,
+    pub(super) status: String,
+    pub(super) cached: Vec<CachedVersion>,
+    pub(super) nightly: Vec<NightlyBuild>,
+    pub(super) nightly_status: String,
+    // Token being typed in, saved to the config with Save
+    pub(super) github_token: String,
+}
+
+// How long after installing the DLL is checked for antivirus quarantine
+const QUARANTINE_CHECK_DELAY: Duration = Duration::from_secs(5);
+
+impl ModInstallerApp {
+    fn resume_pending_operation(&mut self, index: usize) {
+        if index >= self.pending_operations.len() {
+            return;
+        }
+        // The resumed operation journals itself from here on
+        let pending = self.pending_operations.remove(index);
+        let operation = pending.operation.clone();
+        pending.discard();
+
+        match operation {
+            Operation::Install { game_path, dll_url, .. } => {
+                self.game_path = Some(game_path);
+                self.dll_url = dll_url;
+                self.install_mod();
+            }
+            operation @ Operation::Import { .. } => {
+                self.import.status = tr("⏳ Resuming resource import...").to_string();
+                self.start_import(operation, None);
+            }
+        }
+    }
+
+    fn roll_back_pending_operation(&mut self, index: usize) {
+        if index >= self.pending_operations.len() {
+            return;
+        }
+
+        match self.pending_operations.remove(index).roll_back() {
+            Ok(summary) => {
+                self.status_message = trf("✅ Rolled back: {}", &[&summary]);
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Rollback failed: {}", &[&e]);
+            }
+        }
+        self.check_mod_installed();
+    }
+
+    pub(super) fn install_mod(&mut self) {
+        if self.dll_url.is_empty() {
+            self.status_message = tr("❌ Please enter a DLL URL first").to_string();
+            return;
+        }
+
+        if self.game_path.is_none() {
+            self.status_message = tr("❌ Game directory not found. Cannot install.").to_string();
+            return;
+        }
+
+        if !self.resolve_conflicts() {
+            return;
+        }
+
+        if self.wait_for_game(QueuedAction::UpdateMod { dll_url: self.dll_url.clone() }) {
+            return;
+        }
+
+        if self.preview_first(PreviewAction::Install) {
+            return;
+        }
+
+        self.install.running = true;
+        self.status_message = tr("⏳ Downloading and installing...").to_string();
+        self.watchdog.start(Task::Install);
+
+        let game_path = self.game_path.as_ref().unwrap().clone();
+        let dll_url = self.dll_url.clone();
+        let beta = self.config.beta_channel;
+        let events = self.events.sender();
+        let settings = InstallSettings::from_config(&self.config);
+        let cancel = self.tasks.child_token();
+        self.install.cancel = Some(cancel.clone());
+
+        // A safety net before the first install touches anything
+        let back_up_first = self.game_data.backups.is_empty() && game_data::data_dir().is_some();
+
+        self.tasks.spawn(async move {
+            if back_up_first {
+                events.progress(Task::Install, tr("⏳ Backing up game data...").to_string());
+                // A failed backup shouldn't stand in the way of installing, it can be retried by hand
+                let _ = game_data::back_up(cancel.clone()).await;
+            }
+
+            let on_rate_limited = rate_limit_reporter(&events, Task::Install);
+            let on_progress = |progress| events.send(Event::Transfer(Task::Install, progress));
+
+            let step_events = events.clone();
+            let on_step = move |step: &dyn InstallStep| {
+                step_events.progress(Task::Install, trf("⏳ {}...", &[&tr(step.description())]));
+            };
+
+            let result = match download::channel_dll_url(&dll_url, beta, &cancel, &on_rate_limited).await {
+                Ok(dll_url) => install::download_and_install(&dll_url, &game_path, &settings, &cancel, &on_rate_limited, &on_progress, on_step).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(version) => {
+                    events.send(Event::ModInstalled(version));
+                    events.finished(Task::Install, Ok(tr("✅ Mod installed successfully! Launch the game to use it.").to_string()));
+                }
+                Err(e) => events.finished(Task::Install, Err(task_error("Installation", e))),
+            }
+        });
+    }
+
+    // Installs a version from the version cache, no download needed
+    fn switch_version(&mut self, version: String) {
+        let Some(game_path) = self.game_path.clone() else {
+            self.status_message = tr("❌ Game directory not found. Cannot install.").to_string();
+            return;
+        };
+
+        self.install.running = true;
+        self.status_message = trf("⏳ Switching to {}...", &[&version]);
+        self.watchdog.start(Task::Install);
+
+        let events = self.events.sender();
+        let settings = InstallSettings::from_config(&self.config);
+        let cancel = self.tasks.child_token();
+        self.install.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let step_events = events.clone();
+            let on_step = move |step: &dyn InstallStep| {
+                step_events.progress(Task::Install, trf("⏳ {}...", &[&tr(step.description())]));
+            };
+
+            match install::install_cached(&version, &game_path, &settings, &cancel, on_step).await {
+                Ok(installed) => {
+                    events.send(Event::ModInstalled(installed));
+                    events.finished(Task::Install, Ok(trf("✅ Switched to {}, restart the game to use it", &[&version])));
+                }
+                Err(e) => events.finished(Task::Install, Err(task_error("Switching versions", e))),
+            }
+        });
+    }
+
+    pub(super) fn check_releases(&mut self) {
+        self.releases.status = tr("⏳ Loading releases...").to_string();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            events.send(Event::Releases(github::fetch_releases(&cancel, &|_| {}).await));
+        });
+    }
+
+    // Which release Install downloads, e.g. an older one when the newest
+    // breaks after a game patch
+    fn show_version_picker(&mut self, ui: &mut egui::Ui) {
+        let selected = download::release_tag(&self.dll_url).map(str::to_string);
+        // A custom DLL URL isn't a release to pick
+        if selected.is_none() && self.dll_url != DEFAULT_DLL_URL {
+            return;
+        }
+
+        let mut choice = selected.clone();
+        let mut retry = false;
+        ui.add_enabled_ui(!self.install.running && self.config.pinned_version.is_none(), |ui| {
+            let combo = egui::ComboBox::from_id_salt("release")
+                .selected_text(choice.as_deref().unwrap_or(tr("Latest release")))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut choice, None, tr("Latest release"));
+                    for release in &self.releases.list {
+                        let label = if release.prerelease {
+                            trf("{} (pre-release)", &[&release.tag_name])
+                        } else {
+                            release.tag_name.clone()
+                        };
+                        ui.selectable_value(&mut choice, Some(release.tag_name.clone()), label);
+                    }
+                    if !self.releases.status.is_empty() {
+                        ui.label(status::text(&self.releases.status));
+                    }
+                    if self.releases.status.starts_with('❌') && ui.small_button(tr("🔄 Retry")).clicked() {
+                        retry = true;
+                    }
+                });
+
+            // Listed once the picker is first opened, not on every start
+            if combo.inner.is_some() && self.releases.list.is_empty() && self.releases.status.is_empty() {
+                retry = true;
+            }
+            combo.response.on_disabled_hover_text(tr("Unpin the version in Settings to pick another one"));
+        });
+
+        if retry {
+            self.check_releases();
+        }
+        if choice != selected {
+            self.set_dll_url(choice.as_deref().map_or_else(|| DEFAULT_DLL_URL.to_string(), download::release_dll_url));
+        }
+
+        let release = match &choice {
+            Some(tag) => self.releases.list.iter().find(|release| &release.tag_name == tag),
+            None => self.newest_release(),
+        };
+        if let Some(release) = release {
+            show_release_notes(ui, release);
+        }
+    }
+
+    // What "latest" installs on the current channel
+    fn newest_release(&self) -> Option<&Release> {
+        self.releases.list.iter().find(|release| self.config.beta_channel || !release.prerelease)
+    }
+
+    fn check_nightly_builds(&mut self) {
+        let Some(token) = self.config.github_token.clone() else {
+            return;
+        };
+
+        self.releases.nightly_status = tr("⏳ Looking for nightly builds...").to_string();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            events.send(Event::NightlyBuilds(nightly::list(&token, &cancel).await));
+        });
+    }
+
+    fn install_nightly(&mut self, build: NightlyBuild) {
+        let (Some(game_path), Some(token)) = (self.game_path.clone(), self.config.github_token.clone()) else {
+            return;
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Install Nightly Build"))
+            .set_description(trf(
+                "Nightly builds are untested and may crash the game or break your mod settings.\n\nInstall {}?",
+                &[&build.label()]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.install.running = true;
+        self.status_message = trf("⏳ Installing nightly build #{}...", &[&build.run_number]);
+        self.watchdog.start(Task::Install);
+
+        let events = self.events.sender();
+        let settings = InstallSettings::from_config(&self.config);
+        let cancel = self.tasks.child_token();
+        self.install.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let on_progress = |progress| events.send(Event::Transfer(Task::Install, progress));
+
+            let step_events = events.clone();
+            let on_step = move |step: &dyn InstallStep| {
+                step_events.progress(Task::Install, trf("⏳ {}...", &[&tr(step.description())]));
+            };
+
+            match install::install_nightly(&build, &token, &game_path, &settings, &cancel, &on_progress, on_step).await {
+                Ok(version) => {
+                    events.send(Event::ModInstalled(version));
+                    events.finished(Task::Install, Ok(tr("✅ Nightly build installed. Launch the game to test it.").to_string()));
+                }
+                Err(e) => events.finished(Task::Install, Err(task_error("Installing the nightly build", e))),
+            }
+        });
+    }
+
+    // A DLL dropped onto the window, e.g. a local build of the mod
+    pub(super) fn install_local_dll(&mut self, path: PathBuf) {
+        let Some(game_path) = self.game_path.clone() else {
+            self.status_message = tr("❌ Game directory not found. Cannot install.").to_string();
+            return;
+        };
+        if self.policy.pinned_dll_url().is_some() {
+            self.status_message = tr("❌ The mod version is set by policy on this PC").to_string();
+            return;
+        }
+        if self.is_busy() {
+            self.status_message = tr("⚠ Wait for the current task to finish before installing").to_string();
+            return;
+        }
+
+        if self.preview_first(PreviewAction::InstallLocal(path.clone())) {
+            return;
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Install DLL"))
+            .set_description(trf(
+                "Install {} as the mod?\n\nOnly install DLLs you trust, such as your own build of TNSM. The current DLL is saved to a restore point first.",
+                &[&name]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes || !self.resolve_conflicts() {
+            return;
+        }
+
+        self.install.running = true;
+        self.status_message = trf("⏳ Installing {}...", &[&name]);
+        self.watchdog.start(Task::Install);
+
+        let events = self.events.sender();
+        let settings = InstallSettings::from_config(&self.config);
+        let cancel = self.tasks.child_token();
+        self.install.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let step_events = events.clone();
+            let on_step = move |step: &dyn InstallStep| {
+                step_events.progress(Task::Install, trf("⏳ {}...", &[&tr(step.description())]));
+            };
+
+            match install::install_local(&path, &game_path, &settings, &cancel, on_step).await {
+                Ok(version) => {
+                    events.send(Event::ModInstalled(version));
+                    events.finished(Task::Install, Ok(trf("✅ {} installed. Launch the game to use it.", &[&name])));
+                }
+                Err(e) => events.finished(Task::Install, Err(task_error("Installation", e))),
+            }
+        });
+    }
+
+    pub(super) fn show_nightly_builds(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Untested builds straight from CI, for testers. Downloading them needs a GitHub personal access token."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Token:"));
+            ui.add(egui::TextEdit::singleline(&mut self.releases.github_token)
+                .password(true)
+                .hint_text(match self.config.github_token {
+                    Some(_) => tr("saved"),
+                    None => "github_pat_...",
+                })
+                .desired_width(220.0));
+
+            if ui.add_enabled(!self.releases.github_token.trim().is_empty(), egui::Button::new(tr("💾 Save"))).clicked() {
+                self.config.github_token = Some(self.releases.github_token.trim().to_string());
+                self.releases.github_token.clear();
+                let _ = self.config.save();
+                self.check_nightly_builds();
+            }
+            if self.config.github_token.is_some() && ui.button(tr("🗑 Forget")).clicked() {
+                self.config.github_token = None;
+                self.releases.nightly.clear();
+                self.releases.nightly_status.clear();
+                let _ = self.config.save();
+            }
+        });
+
+        if self.config.github_token.is_none() {
+            return;
+        }
+
+        if ui.button(tr("🔄 Check for Builds")).clicked() {
+            self.check_nightly_builds();
+        }
+
+        if !self.releases.nightly_status.is_empty() {
+            ui.label(status::text(&self.releases.nightly_status));
+        }
+
+        let mut install = None;
+        ui.add_enabled_ui(!self.install.running && !self.game_running && self.can_change_mod(), |ui| {
+            for build in &self.releases.nightly {
+                ui.horizontal(|ui| {
+                    if ui.small_button(tr("📥 Install")).clicked() {
+                        install = Some(build.clone());
+                    }
+                    ui.label(build.label());
+                });
+            }
+        });
+        if let Some(build) = install {
+            self.install_nightly(build);
+        }
+    }
+
+    pub(super) fn show_version_switcher(&mut self, ui: &mut egui::Ui) {
+        if self.releases.cached.len() < 2 {
+            ui.label(tr("Install two different releases to switch between them without downloading again."));
+            return;
+        }
+
+        ui.label(egui::RichText::new(tr("Swap between two versions in seconds, e.g. to check whether a problem started with a new release."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        let (mut a, mut b) = self.config.ab_versions.clone().unwrap_or_else(|| {
+            (self.releases.cached[0].version.clone(), self.releases.cached[1].version.clone())
+        });
+
+        egui::Grid::new("ab_versions").num_columns(2).show(ui, |ui| {
+            for (label, selected) in [("A", &mut a), ("B", &mut b)] {
+                ui.label(label);
+                egui::ComboBox::from_id_salt(("ab_version", label))
+                    .selected_text(selected.as_str())
+                    .show_ui(ui, |ui| {
+                        for cached in &self.releases.cached {
+                            ui.selectable_value(selected, cached.version.clone(), &cached.version);
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+
+        if self.config.ab_versions.as_ref() != Some(&(a.clone(), b.clone())) {
+            self.config.ab_versions = Some((a.clone(), b.clone()));
+            let _ = self.config.save();
+        }
+
+        let active = self.config.installed_version.clone().filter(|_| self.is_mod_installed);
+        let target = if active.as_deref() == Some(a.as_str()) { b } else { a };
+
+        ui.add_enabled_ui(!self.install.running && !self.game_running && self.can_change_mod(), |ui| {
+            let text = match &active {
+                Some(active) => trf("🔀 Switch from {} to {}", &[&active, &target]),
+                None => trf("🔀 Install {}", &[&target]),
+            };
+            if ui.button(text).clicked() {
+                self.switch_version(target);
+            }
+        });
+    }
+
+    fn create_offline_bundle(&mut self) {
+        // Resource packs are optional, cancelling the picker bundles just the DLL
+        let resource_packs = rfd::FileDialog::new()
+            .set_title(tr("Select Resource Packs to Include (Cancel to skip)"))
+            .pick_folders()
+            .unwrap_or_default();
+
+        let Some(dest) = rfd::FileDialog::new()
+            .set_title(tr("Save Offline Bundle"))
+            .add_filter(tr("TNSM Bundle"), &[bundle::BUNDLE_EXTENSION])
+            .set_file_name(format!("ThatNoobSkyMod.{}", bundle::BUNDLE_EXTENSION))
+            .save_file()
+        else {
+            return;
+        };
+
+        self.bundle.status = tr("⏳ Creating offline bundle...").to_string();
+
+        let dll_url = self.dll_url.clone();
+        let events = self.events.sender();
+        let mirrors = self.config.download_mirrors.clone();
+        let cancel = self.tasks.child_token();
+        self.bundle.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let on_rate_limited = rate_limit_reporter(&events, Task::Bundle);
+
+            let result = match bundle::create_bundle(&dll_url, &mirrors, dest, resource_packs, &cancel, &on_rate_limited).await {
+                Ok(_) => Ok(tr("✅ Offline bundle created!").to_string()),
+                Err(e) => Err(task_error("Bundle creation", e)),
+            };
+            events.finished(Task::Bundle, result);
+        });
+    }
+
+    fn install_from_bundle(&mut self) {
+        if self.game_path.is_none() {
+            self.status_message = tr("❌ Game directory not found. Cannot install.").to_string();
+            return;
+        }
+
+        let Some(bundle_path) = rfd::FileDialog::new()
+            .set_title(tr("Select Offline Bundle"))
+            .add_filter(tr("TNSM Bundle"), &[bundle::BUNDLE_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+
+        self.install.running = true;
+        self.status_message = tr("⏳ Verifying and installing bundle...").to_string();
+
+        let game_path = self.game_path.as_ref().unwrap().clone();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let cancel = self.tasks.child_token();
+        self.install.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let result = workers::run(move || bundle::install_bundle(&bundle_path, &game_path, proxy, &cancel))
+                .await
+                .and_then(|result| result);
+
+            match result {
+                Ok(manifest) => {
+                    events.send(Event::ModInstalled(manifest.mod_version));
+                    events.finished(Task::Install, Ok(tr("✅ Mod installed from offline bundle! Launch the game to use it.").to_string()));
+                }
+                Err(e) => events.finished(Task::Install, Err(task_error("Bundle installation", e))),
+            }
+        });
+    }
+
+    fn revert_to_restore_point(&mut self) {
+        let Some(point) = self.restore.points.get(self.restore.selected) else {
+            return;
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Revert Game Files"))
+            .set_description(trf(
+                "Put back the {} game files changed after this restore point?\n\n{}",
+                &[&point.files, &point.label()]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.start_revert(point.clone(), true);
+    }
+
+    // Undoing a change reverts its restore point without making the revert
+    // itself undoable, so pressing Undo again goes further back
+    pub(super) fn start_revert(&mut self, point: RestorePointInfo, undoable: bool) {
+        self.restore.is_reverting = true;
+        self.restore.status = tr("⏳ Reverting game files...").to_string();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+
+        self.tasks.spawn(async move {
+            let label = trf("Revert of {}", &[&point.label()]);
+            let result = match restore_point::revert(point, proxy).await {
+                Ok((summary, before)) => {
+                    if let Some(restore_point) = before.filter(|_| undoable) {
+                        events.send(Event::Undoable(UndoEntry { restore_point, label }));
+                    }
+                    Ok(trf("✅ Reverted: {}", &[&summary]))
+                }
+                Err(e) => Err(task_error("Revert", e)),
+            };
+            events.finished(Task::Restore, result);
+        });
+    }
+
+    fn restore_pre_install_state(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        let Some(oldest) = self.restore.points.last() else {
+            return;
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Restore Pre-Install State"))
+            .set_description(trf(
+                "Revert every restore point, putting the game folder back the way it was before the oldest one?\n\n{}",
+                &[&oldest.label()]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.restore.is_reverting = true;
+        self.restore.status = tr("⏳ Restoring the pre-install state...").to_string();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+
+        self.tasks.spawn(async move {
+            let result = match restore_point::revert_all(game_path, proxy).await {
+                Ok((summary, before)) => {
+                    if let Some(restore_point) = before {
+                        events.send(Event::Undoable(UndoEntry { restore_point, label: tr("Pre-install state restore").to_string() }));
+                    }
+                    Ok(trf("✅ Pre-install state restored: {}", &[&summary]))
+                }
+                Err(e) => Err(task_error("Restore", e)),
+            };
+            events.finished(Task::Restore, result);
+        });
+    }
+
+    fn delete_restore_point(&mut self) {
+        let Some(point) = self.restore.points.get(self.restore.selected) else {
+            return;
+        };
+
+        if let Err(e) = restore_point::delete(point) {
+            self.restore.status = trf("❌ {}", &[&e]);
+        }
+        let dir = point.dir.clone();
+        self.undo_stack.entries.retain(|entry| entry.restore_point != dir);
+        let _ = self.undo_stack.save();
+        self.restore.points = restore_point::list();
+        self.restore.selected = 0;
+    }
+
+    fn confirm_uninstall(&mut self) {
+        // The preview lists what goes and asks to apply, no need to ask twice
+        if self.preview_first(PreviewAction::Uninstall) {
+            return;
+        }
+
+        let mut removed = vec![self.config.proxy_dll.file_name().to_string()];
+        if self.install.uninstall_resources {
+            removed.push(trf("the {} folder and every imported resource in it, which can't be undone", &[&resources::RESOURCES_DIR_NAME]));
+        }
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Uninstall Mod"))
+            .set_description(trf("Remove {} from the game folder?", &[&removed.join(tr(" and "))]))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer == rfd::MessageDialogResult::Yes {
+            self.uninstall_mod();
+        }
+    }
+
+    pub(super) fn uninstall_mod(&mut self) {
+        if self.game_path.is_none() {
+            self.status_message = tr("❌ Game directory not found. Cannot uninstall.").to_string();
+            return;
+        }
+
+        let game_path = &self.game_path.clone().unwrap();
+
+        if !install::is_mod_installed(game_path, self.config.proxy_dll) {
+            self.status_message = tr("⚠ Mod is not installed.").to_string();
+            return;
+        }
+
+        if self.wait_for_game(QueuedAction::Uninstall) {
+            return;
+        }
+
+        if !self.game_path_writable {
+            self.uninstall_elevated(game_path.clone());
+            return;
+        }
+
+        let proxy = self.config.proxy_dll;
+        let result = install::uninstall(game_path, proxy)
+            .map(|_| tr("✅ Mod uninstalled successfully!").to_string())
+            .or_else(|e| remove_locked_dll(game_path, proxy, e));
+
+        match result {
+            Ok(message) => {
+                self.finish_uninstall(game_path, message);
+                if self.install.uninstall_resources && resources::resources_path(game_path).exists() {
+                    self.remove_resources(game_path.clone());
+                }
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Uninstallation failed: {}", &[&e]);
+            }
+        }
+    }
+
+    // Can be a lot of files, so it runs after the DLL is already gone
+    fn remove_resources(&mut self, game_path: PathBuf) {
+        self.install.running = true;
+        self.status_message = trf("⏳ Mod uninstalled, removing {}...", &[&resources::RESOURCES_DIR_NAME]);
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+
+        self.tasks.spawn(async move {
+            let result = match resources::remove_all(game_path).await {
+                Ok((files, bytes)) => Ok(trf(
+                    "✅ Mod uninstalled: removed {} and {} ({} files, {})",
+                    &[&proxy.file_name(), &resources::RESOURCES_DIR_NAME, &files, &storage::format_size(bytes)]
+                )),
+                Err(e) => Err(trf("⚠ Mod uninstalled, but {} couldn't be removed: {}", &[&resources::RESOURCES_DIR_NAME, &e])),
+            };
+            events.finished(Task::Install, result);
+        });
+    }
+
+    // Only the DLL is removed with administrator approval, everything else
+    // stays with this account
+    fn uninstall_elevated(&mut self, game_path: PathBuf) {
+        self.install.running = true;
+        self.status_message = tr("⏳ Waiting for administrator approval...").to_string();
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+
+        self.tasks.spawn(async move {
+            let audit = audit::begin("uninstall", Some(&game_path)).dll(proxy).detail("with administrator approval");
+            let result = elevated::remove_file(&game_path, Path::new(proxy.file_name())).await;
+            audit.finish(&result);
+            events.send(Event::Uninstalled(result));
+        });
+    }
+
+    pub(super) fn finish_uninstall(&mut self, game_path: &Path, message: String) {
+        self.status_message = message;
+        self.is_mod_installed = false;
+        self.status_pipe.send(PipeEvent::Uninstalled);
+        self.config.installed_version = None;
+        let _ = self.config.save();
+        let _ = machine::record(game_path, false, None);
+        manifest::forget_dll(game_path);
+        self.install_record = machine::latest();
+        self.install.pending_removal = locked_file::pending(game_path);
+    }
+
+    // Asked once before installing while other mods are found. False when the
+    // install shouldn't go ahead.
+    fn resolve_conflicts(&mut self) -> bool {
+        if self.install.conflicts.is_empty() || self.install.conflicts_acknowledged {
+            return true;
+        }
+
+        let list = self.install.conflicts.iter()
+            .map(|conflict| format!("{}: {}", conflict.file_name, conflict.description))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Other Mods Found"))
+            .set_description(trf(
+                "These files belong to other mods that load the same way TNSM does. With both installed, one of them usually doesn't load:\n\n{}\n\nBack them up and disable them before installing? They're saved to a restore point, so this can be undone.",
+                &[&list]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNoCancel)
+            .show();
+
+        match answer {
+            rfd::MessageDialogResult::Yes => self.disable_conflicts(),
+            rfd::MessageDialogResult::No => {
+                self.install.conflicts_acknowledged = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // A handful of small files, quick enough to move without a background task
+    fn disable_conflicts(&mut self) -> bool {
+        let Some(game_path) = self.game_path.clone() else {
+            return false;
+        };
+
+        match conflicts::disable(&game_path, &self.install.conflicts) {
+            Ok((count, restore_point)) => {
+                if let Some(restore_point) = restore_point {
+                    self.push_undo(UndoEntry { restore_point, label: tr("Disabling other mods").to_string() });
+                }
+                self.status_message = trf("✅ Disabled {} files of other mods, undo to bring them back", &[&count]);
+                self.install.conflicts = conflicts::scan(&game_path, self.config.proxy_dll, &self.config.plugins);
+                self.restore.points = restore_point::list();
+                true
+            }
+            Err(e) => {
+                self.status_message = trf("❌ {}", &[&e]);
+                false
+            }
+        }
+    }
+
+    pub(super) fn show_conflict_notice(&mut self, ui: &mut egui::Ui) {
+        if self.install.conflicts.is_empty() || self.install.conflicts_acknowledged {
+            return;
+        }
+
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(tr("⚠ Other mods found in the game folder. They load the same way TNSM does, so one of them may not load:"))
+                .size(12.0)
+                .color(status::color(Level::Warning)));
+            for conflict in &self.install.conflicts {
+                ui.label(egui::RichText::new(format!("{}: {}", conflict.file_name, conflict.description))
+                    .size(12.0)
+                    .color(egui::Color32::GRAY));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("📦 Back Up and Disable")).clicked() {
+                    self.disable_conflicts();
+                }
+                if ui.button(tr("Keep Them")).clicked() {
+                    self.install.conflicts_acknowledged = true;
+                }
+            });
+        });
+        ui.add_space(10.0);
+    }
+
+    // With Preview on, works out what the action would change and lists it
+    // instead of running it. True when the action was held back for that.
+    pub(super) fn preview_first(&mut self, action: PreviewAction) -> bool {
+        if !self.config.preview_changes || std::mem::take(&mut self.install.preview_approved) {
+            return false;
+        }
+        let Some(game_path) = self.game_path.clone() else {
+            return false;
+        };
+        if self.install.preview.is_some() {
+            self.status_message = tr("⚠ Apply or discard the preview first").to_string();
+            return true;
+        }
+
+        let cancel = self.tasks.child_token();
+        let remove_resources = self.install.uninstall_resources;
+        let remove_missing = self.config.import_remove_missing;
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let job_action = action.clone();
+        let job_cancel = cancel.clone();
+        self.install.preview = Some(Preview { action, changes: None, cancel });
+
+        self.tasks.spawn(async move {
+            let result = match job_action {
+                PreviewAction::Install => preview::install(&game_path, proxy, None),
+                PreviewAction::InstallLocal(path) => preview::install(&game_path, proxy, Some(&path)),
+                PreviewAction::Import(source) => preview::import(source, game_path, remove_missing, job_cancel).await,
+                PreviewAction::Uninstall => preview::uninstall(game_path, proxy, remove_resources, job_cancel).await,
+            };
+            events.send(Event::Previewed(result));
+        });
+        true
+    }
+
+    fn apply_preview(&mut self) {
+        let Some(preview) = self.install.preview.take() else {
+            return;
+        };
+
+        self.install.preview_approved = true;
+        match preview.action {
+            PreviewAction::Install => self.install_mod(),
+            PreviewAction::InstallLocal(path) => self.install_local_dll(path),
+            PreviewAction::Import(source) => self.import_folder(source),
+            PreviewAction::Uninstall => self.uninstall_mod(),
+        }
+        // Still set when the action stopped before getting to its preview
+        self.install.preview_approved = false;
+    }
+
+    pub(super) fn show_preview(&mut self, ui: &mut egui::Ui) {
+        let Some(preview) = &self.install.preview else {
+            return;
+        };
+
+        let title = match &preview.action {
+            PreviewAction::Install => tr("🔍 Installing the mod would change:").to_string(),
+            PreviewAction::InstallLocal(path) => trf(
+                "🔍 Installing {} would change:",
+                &[&path.file_name().unwrap_or_default().to_string_lossy()]
+            ),
+            PreviewAction::Import(source) => trf(
+                "🔍 Importing {} would change:",
+                &[&source.file_name().unwrap_or_default().to_string_lossy()]
+            ),
+            PreviewAction::Uninstall => tr("🔍 Uninstalling the mod would change:").to_string(),
+        };
+
+        let mut apply = false;
+        let mut discard = false;
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(title).strong());
+
+            let Some(changes) = &preview.changes else {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr("Looking at the files..."));
+                    if ui.button(tr("✖ Cancel")).clicked() {
+                        preview.cancel.cancel();
+                    }
+                });
+                return;
+            };
+
+            if changes.is_empty() {
+                ui.label(egui::RichText::new(tr("Nothing in the game folder, every file is already up to date"))
+                    .size(12.0)
+                    .color(egui::Color32::GRAY));
+            } else {
+                let (written, deleted) = preview::totals(changes);
+                ui.label(egui::RichText::new(trf(
+                    "{} files: {} written, {} deleted",
+                    &[&changes.len(), &storage::format_size(written), &storage::format_size(deleted)]
+                ))
+                    .size(12.0)
+                    .color(egui::Color32::GRAY));
+
+                // Imports can list thousands of files, so only the visible rows are laid out
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                egui::ScrollArea::vertical()
+                    .id_salt("preview")
+                    .max_height(200.0)
+                    .show_rows(ui, row_height, changes.len(), |ui, rows| {
+                        for change in &changes[rows] {
+                            let level = match change.kind {
+                                ChangeKind::Create => Level::Success,
+                                ChangeKind::Overwrite => Level::Warning,
+                                ChangeKind::Delete => Level::Error,
+                            };
+                            let size = match change.size {
+                                Some(size) => storage::format_size(size),
+                                None => tr("size known after downloading").to_string(),
+                            };
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(tr(change.kind.label())).color(status::color(level)));
+                                ui.label(change.path.display().to_string());
+                                ui.label(egui::RichText::new(size).color(egui::Color32::GRAY));
+                            });
+                        }
+                    });
+            }
+
+            ui.horizontal(|ui| {
+                apply = ui.button(tr("✅ Apply")).clicked();
+                discard = ui.button(tr("Discard")).clicked();
+            });
+        });
+        ui.add_space(10.0);
+
+        if apply {
+            self.apply_preview();
+        } else if discard {
+            self.install.preview = None;
+        }
+    }
+
+    pub(super) fn check_for_quarantine(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        let events = self.events.sender();
+        let proxy = self.config.proxy_dll;
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(QUARANTINE_CHECK_DELAY) => {}
+                _ = cancel.cancelled() => return,
+            }
+            if let Ok(problem) = workers::run(move || manifest::check_dll(&game_path, proxy)).await {
+                events.send(Event::DllChecked(problem));
+            }
+        });
+    }
+
+    pub(super) fn show_quarantine_notice(&mut self, ui: &mut egui::Ui) {
+        let Some(problem) = self.dll_problem else {
+            return;
+        };
+
+        let what = match problem {
+            Problem::Missing => tr("was removed"),
+            _ => tr("was changed"),
+        };
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(trf(
+                "🛡 The mod DLL {} after it was installed. Windows Defender most likely quarantined it. Allow it in Protection history, or add the game folder as an exclusion, then install again.",
+                &[&what]
+            ))
+                .size(12.0)
+                .color(status::color(Level::Warning)));
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("🛡 Open Protection History")).clicked()
+                    && let Err(e) = shell::open_protection_history()
+                {
+                    self.status_message = trf("❌ {}", &[&e]);
+                }
+                if ui.button(tr("🔄 Check Again")).clicked() {
+                    self.check_mod_installed();
+                }
+                if self.can_change_mod() && !self.install.running && ui.button(tr("📥 Install Again")).clicked() {
+                    self.install_mod();
+                }
+            });
+        });
+        ui.add_space(10.0);
+    }
+
+    // Interrupted operations from a previous run
+    pub(super) fn show_pending_operations(&mut self, ui: &mut egui::Ui) {
+        let descriptions: Vec<String> = self.pending_operations.iter().map(journal::Pending::describe).collect();
+        for (index, description) in descriptions.iter().enumerate() {
+            ui.group(|ui| {
+                ui.set_width(470.0);
+                ui.vertical(|ui| {
+                    ui.label(status::text(&trf("⚠ {}", &[&description])));
+                    ui.add_space(5.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("▶ Resume")).clicked() {
+                            self.resume_pending_operation(index);
+                        }
+                        if ui.button(tr("↩ Roll Back")).clicked() {
+                            self.roll_back_pending_operation(index);
+                        }
+                    });
+                });
+            });
+
+            ui.add_space(10.0);
+        }
+    }
+
+    // Status Information
+    pub(super) fn show_install_status(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.set_width(470.0);
+            ui.vertical(|ui| {
+                ui.label(egui::RichText::new(tr("📍 Installation Status:")).strong());
+                ui.add_space(5.0);
+
+                ui.label(status::text(&self.status_message));
+
+                if let Some(game_path) = &self.game_path {
+                    ui.add_space(5.0);
+                    ui.label(egui::RichText::new(format!("📂 {}", game_path.display()))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY));
+                }
+
+                if self.host_arch == Arch::Arm64 {
+                    ui.add_space(5.0);
+                    ui.label(egui::RichText::new(tr("💻 ARM64 PC: an x64 Sky runs under emulation and gets the x64 mod"))
+                        .size(11.0)
+                        .color(egui::Color32::GRAY));
+                }
+
+                if self.is_mod_installed {
+                    ui.add_space(5.0);
+                    let installed = match &self.config.installed_version {
+                        Some(version) => trf("🔧 Mod {} is currently installed", &[&version]),
+                        None => tr("🔧 Mod is currently installed").to_string(),
+                    };
+                    ui.label(egui::RichText::new(installed)
+                        .size(11.0)
+                        .color(status::color(Level::Success)));
+                }
+
+                if let Some(latest) = self.available_update().map(str::to_string) {
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        status::show(ui, &trf("⬆ Update available: {}", &[&latest]), 11.0);
+                        ui.add_enabled_ui(!self.install.running && !self.game_running && self.can_change_mod(), |ui| {
+                            if ui.small_button(tr("⬆ Update")).clicked() {
+                                self.update_mod();
+                            }
+                        });
+                    });
+                    if let Some(release) = self.releases.list.iter().find(|release| release.tag_name == latest) {
+                        show_release_notes(ui, release);
+                    }
+                }
+
+                if let Some(build_id) = self.game_build_id {
+                    let installed = self.config.installed_version.as_deref().filter(|_| self.is_mod_installed);
+                    for issue in self.compat_db.known_issues(build_id, installed) {
+                        ui.add_space(5.0);
+                        status::show(ui, &trf("⚠ Known issue with game build {}: {}", &[&build_id, &issue.description]), 11.0);
+                    }
+                }
+
+                if !self.install.pending_removal.is_empty() {
+                    ui.add_space(5.0);
+                    status::show(ui, tr("⏳ The old mod DLL is deleted after the next restart"), 11.0);
+                }
+            });
+        });
+    }
+
+    // Install/Uninstall Buttons
+    pub(super) fn show_install_buttons(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            // Another release than the installed one goes in over it
+            let installed = self.config.installed_version.as_deref().filter(|_| self.is_mod_installed);
+            let other_release = download::release_tag(&self.dll_url)
+                .filter(|tag| self.is_mod_installed && installed != Some(*tag))
+                .map(str::to_string);
+
+            let install_button = egui::Button::new(
+                egui::RichText::new(match &other_release {
+                    Some(tag) => trf("⚡ Install {}", &[&tag]),
+                    None => tr("⚡ Install Mod").to_string(),
+                })
+                    .size(18.0)
+                    .strong()
+            ).min_size(egui::vec2(180.0, 45.0));
+
+            let can_install = !self.is_mod_installed || other_release.is_some();
+            ui.add_enabled_ui(!self.install.running && can_install && self.can_change_mod(), |ui| {
+                if ui.add(install_button).clicked() {
+                    self.install_mod();
+                }
+            });
+
+            if self.policy.pinned_dll_url().is_none() {
+                ui.add_space(5.0);
+                self.show_version_picker(ui);
+            }
+
+            if self.install.running {
+                ui.add_space(10.0);
+                match &self.install.progress {
+                    Some(progress) => self.show_download_progress(ui, progress),
+                    // The spinner schedules its own repaints while visible
+                    None => {
+                        ui.spinner();
+                    }
+                }
+
+                if let Some(cancel) = &self.install.cancel
+                    && ui.button(tr("✖ Cancel"))
+                        .on_hover_text(tr("Stops the install and leaves the game folder as it was"))
+                        .clicked()
+                {
+                    cancel.cancel();
+                }
+
+                self.show_stall_notice(ui, Task::Install);
+            }
+
+            // Closes the loop after an install: start Sky and see the mod load
+            if self.is_mod_installed && !self.install.running {
+                ui.add_space(10.0);
+                ui.add_enabled_ui(!self.game_running, |ui| {
+                    if ui.button(tr("▶ Launch Game"))
+                        .on_disabled_hover_text(tr("Sky is already running"))
+                        .clicked()
+                    {
+                        self.launch_game();
+                    }
+                });
+            }
+        });
+    }
+
+    pub(super) fn show_uninstall_button(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            let uninstall_button = egui::Button::new(
+                egui::RichText::new(tr("🗑 Uninstall Mod"))
+                    .size(18.0)
+                    .strong()
+            ).min_size(egui::vec2(180.0, 45.0));
+
+            ui.add_enabled_ui(!self.install.running && self.is_mod_installed && self.can_change_mod(), |ui| {
+                if ui.add(uninstall_button).clicked() {
+                    self.confirm_uninstall();
+                }
+                // Resources stay with this account, the elevated helper only removes the DLL
+                ui.add_enabled_ui(self.game_path_writable, |ui| {
+                    ui.checkbox(&mut self.install.uninstall_resources, tr("Also remove TNSM Resources"));
+                });
+            });
+        });
+    }
+
+    // Offline Bundle Button
+    pub(super) fn show_bundles(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            let bundle_button = egui::Button::new(
+                egui::RichText::new(tr("💾 Create Offline Bundle"))
+                    .size(16.0)
+            ).min_size(egui::vec2(200.0, 40.0));
+
+            let install_bundle_button = egui::Button::new(
+                egui::RichText::new(tr("📂 Install from Bundle"))
+                    .size(16.0)
+            ).min_size(egui::vec2(200.0, 40.0));
+
+            ui.add_enabled_ui(self.bundle.cancel.is_none(), |ui| {
+                if ui.add(bundle_button).clicked() {
+                    self.create_offline_bundle();
+                }
+            });
+
+            ui.add_space(5.0);
+
+            ui.add_enabled_ui(!self.install.running, |ui| {
+                if ui.add(install_bundle_button).clicked() {
+                    self.install_from_bundle();
+                }
+            });
+
+            if let Some(cancel) = &self.bundle.cancel {
+                ui.add_space(5.0);
+                if ui.button(tr("✖ Cancel Bundle")).clicked() {
+                    cancel.cancel();
+                }
+            }
+
+            if !self.bundle.status.is_empty() {
+                ui.add_space(5.0);
+
+                status::show(ui, &self.bundle.status, 12.0);
+            }
+        });
+    }
+
+    pub(super) fn show_restore_points(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_enabled_ui(!self.restore.is_reverting && !self.install.running && self.import.progress.is_none(), |ui| {
+                ui.horizontal(|ui| {
+                    let selected = self.restore.points
+                        .get(self.restore.selected)
+                        .map(RestorePointInfo::label)
+                        .unwrap_or_default();
+                    egui::ComboBox::from_id_salt("restore_point")
+                        .selected_text(selected)
+                        .width(280.0)
+                        .show_ui(ui, |ui| {
+                            for (index, point) in self.restore.points.iter().enumerate() {
+                                ui.selectable_value(&mut self.restore.selected, index, point.label());
+                            }
+                        });
+                    if self.game_running {
+                        if ui.button(tr("⏱ Revert After Sky Closes")).clicked()
+                            && let Some(point) = self.restore.points.get(self.restore.selected)
+                        {
+                            let action = QueuedAction::RemovePack { restore_point: point.dir.clone(), label: point.label() };
+                            self.queue_action(action);
+                        }
+                    } else {
+                        if ui.button(tr("⏪ Revert")).clicked() {
+                            self.revert_to_restore_point();
+                        }
+                        if ui.button(tr("⏮ Pre-Install"))
+                            .on_hover_text(tr("Revert every restore point, back to before the mod was installed"))
+                            .clicked()
+                        {
+                            self.restore_pre_install_state();
+                        }
+                    }
+                    if ui.button("🗑").on_hover_text(tr("Delete restore point")).clicked() {
+                        self.delete_restore_point();
+                    }
+                });
+            });
+
+            if !self.restore.status.is_empty() {
+                ui.add_space(5.0);
+
+                status::show(ui, &self.restore.status, 12.0);
+            }
+        });
+    }
+}
+
+// The DLL is usually held by a running game. Offers to close whatever holds
+// it, then to move it aside and delete it after a restart.
+fn remove_locked_dll(game_path: &Path, proxy: ProxyDll, error: String) -> Result<String, String> {
+    let dll = install::dll_path(game_path, proxy);
+    let holders = locked_file::holders(&dll).unwrap_or_default();
+
+    if !holders.is_empty() {
+        let names = holders.iter()
+            .map(|holder| format!("{} (PID {})", holder.name, holder.pid))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Mod In Use"))
+            .set_description(trf(
+                "The mod can't be removed while these programs use it:\n\n{}\n\nClose them and uninstall? Unsaved progress in them is lost.",
+                &[&names]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+
+        if answer == rfd::MessageDialogResult::Yes {
+            locked_file::close_holders(&dll)?;
+            if install::uninstall(game_path, proxy).is_ok() {
+                return Ok(tr("✅ Mod uninstalled successfully!").to_string());
+            }
+        }
+    }
+
+    let answer = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title(tr("Remove After Restart"))
+        .set_description(trf(
+            "The mod DLL can't be deleted right now ({}).\n\nMove it aside and delete it after the next restart? The game won't load the mod from its next launch.",
+            &[&error]
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    if answer != rfd::MessageDialogResult::Yes {
+        return Err(error);
+    }
+
+    locked_file::schedule_delete(&dll)?;
+    Ok(tr("✅ Mod uninstalled, the old DLL is deleted after the next restart").to_string())
+}
+
+// Release notes are Markdown; headings, lists and emphasis markers are
+// enough to read them without a full renderer
+fn show_release_notes(ui: &mut egui::Ui, release: &Release) {
+    let Some(body) = release.body.as_deref().map(str::trim).filter(|body| !body.is_empty()) else {
+        return;
+    };
+
+    egui::CollapsingHeader::new(trf("📝 What's new in {}", &[&release.tag_name]))
+        .id_salt(("release_notes", &release.tag_name))
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt(("release_notes_scroll", &release.tag_name))
+                .max_height(180.0)
+                .show(ui, |ui| {
+                    for line in body.lines() {
+                        let line = line.trim_end();
+                        let plain = line.replace("**", "").replace('`', "");
+                        if let Some(heading) = plain.trim_start().strip_prefix('#') {
+                            ui.label(egui::RichText::new(heading.trim_start_matches('#').trim()).strong());
+                        } else if let Some(item) = plain.trim_start().strip_prefix("- ").or_else(|| plain.trim_start().strip_prefix("* ")) {
+                            ui.label(format!("• {}", item));
+                        } else if plain.is_empty() {
+                            ui.add_space(4.0);
+                        } else {
+                            ui.label(plain);
+                        }
+                    }
+                });
+        });
+}
@@ -0,0 +1,1070 @@
+// Importing and linking resources, plugins, resource profiles, backups of
+// the mod config and game data, and the screenshot gallery.
+
+use super::{ModInstallerApp, PreviewAction, task_error};
+use crate::status::{self, Level};
+use eframe::egui;
+use std::path::{Path, PathBuf};
+use tnsm_installer_core::cancel::CancellationToken;
+use tnsm_installer_core::config_backups::{self, ConfigBackup};
+use tnsm_installer_core::game_data::{self, GameDataBackup};
+use tnsm_installer_core::events::{Event, Task, TransferProgress};
+use tnsm_installer_core::i18n::{tr, trf};
+use tnsm_installer_core::status_pipe::PipeEvent;
+use tnsm_installer_core::steam_accounts::{self, SteamAccount};
+use tnsm_installer_core::journal::Operation;
+use tnsm_installer_core::plugins::{self, Plugin, PluginState};
+use tnsm_installer_core::policy::Feature;
+use tnsm_installer_core::profiles::Profiles;
+use tnsm_installer_core::progress::{self, Estimator};
+use tnsm_installer_core::screenshots::Screenshot;
+use tnsm_installer_core::script::{self, PostInstallScript};
+use tnsm_installer_core::undo::UndoEntry;
+use tnsm_installer_core::{pack, profiles, resource_link, resources, restore_point, screenshots, shell, storage};
+
+// Importing or linking TNSM Resources
+#[derive(Default)]
+pub(super) struct ImportState {
+    pub(super) status: String,
+    pub(super) progress: Option<TransferProgress>,
+    pub(super) estimator: Estimator,
+    pub(super) cancel: Option<CancellationToken>,
+    pub(super) source: Option<PathBuf>,
+    // The running import goes through the elevated helper
+    pub(super) is_elevated: bool,
+    pub(super) pack_url: String,
+    // Folder TNSM Resources links to instead of holding imported files
+    pub(super) link: Option<PathBuf>,
+}
+
+// Backups of the game data folder
+#[derive(Default)]
+pub(super) struct GameDataState {
+    pub(super) status: String,
+    pub(super) cancel: Option<CancellationToken>,
+    pub(super) backups: Vec<GameDataBackup>,
+    pub(super) selected: usize,
+}
+
+// The screenshot gallery tab
+#[derive(Default)]
+pub(super) struct GalleryState {
+    // None until the gallery is first opened
+    pub(super) screenshots: Option<Result<Vec<Screenshot>, String>>,
+    pub(super) dir: Option<PathBuf>,
+    pub(super) status: String,
+}
+
+// Backups of the mod config, per Steam account
+#[derive(Default)]
+pub(super) struct ConfigBackupsState {
+    pub(super) accounts: Vec<SteamAccount>,
+    pub(super) backups: Vec<ConfigBackup>,
+    pub(super) selected: usize,
+    pub(super) status: String,
+}
+
+// The plugin table
+pub(super) struct PluginListState {
+    // Filled in by the add row
+    pub(super) new_plugin: Plugin,
+    pub(super) is_installing: bool,
+    pub(super) progress: Option<TransferProgress>,
+    pub(super) status: String,
+}
+
+impl Default for PluginListState {
+    fn default() -> Self {
+        Self {
+            new_plugin: Plugin { enabled: true, ..Default::default() },
+            is_installing: false,
+            progress: None,
+            status: String::new(),
+        }
+    }
+}
+
+// Resource profiles
+#[derive(Default)]
+pub(super) struct ProfilesState {
+    // Loaded when the profiles section is first shown
+    pub(super) list: Option<Profiles>,
+    pub(super) name: String,
+    pub(super) status: String,
+}
+
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(140.0, 80.0);
+
+impl ModInstallerApp {
+    fn browse_and_import_resources(&mut self) {
+        if self.game_path.is_none() {
+            self.import.status = tr("❌ Game directory not set. Cannot import resources.").to_string();
+            return;
+        }
+
+        if let Some(source_folder) = rfd::FileDialog::new()
+            .set_title(tr("Select TSM Resources Folder"))
+            .pick_folder()
+        {
+            self.import_folder(source_folder);
+        }
+    }
+
+    fn link_resources(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            self.import.status = tr("❌ Game directory not set. Cannot link resources.").to_string();
+            return;
+        };
+        let Some(folder) = rfd::FileDialog::new()
+            .set_title(tr("Select the Folder to Link TNSM Resources To"))
+            .pick_folder()
+        else {
+            return;
+        };
+
+        self.import.status = match resource_link::link(&folder, &game_path) {
+            Ok(()) => trf("✅ TNSM Resources now links to {}, changes there show up without importing", &[&folder.display()]),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+        self.profiles.list = None;
+        self.import.link = resource_link::target(&game_path);
+    }
+
+    fn unlink_resources(&mut self) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+
+        self.import.status = match resource_link::unlink(&game_path) {
+            Ok(()) => tr("✅ TNSM Resources unlinked, the linked folder was left as it is").to_string(),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+        self.import.link = resource_link::target(&game_path);
+    }
+
+    fn browse_and_import_archive(&mut self) {
+        if self.game_path.is_none() {
+            self.import.status = tr("❌ Game directory not set. Cannot import resources.").to_string();
+            return;
+        }
+
+        if let Some(archive) = rfd::FileDialog::new()
+            .set_title(tr("Select Resource Pack"))
+            .add_filter(tr("Resource pack"), &["zip", pack::PACK_EXTENSION])
+            .pick_file()
+        {
+            self.extract_pack(archive);
+        }
+    }
+
+    fn import_from_url(&mut self) {
+        if self.game_path.is_none() {
+            self.import.status = tr("❌ Game directory not set. Cannot import resources.").to_string();
+            return;
+        }
+
+        let url = self.import.pack_url.trim().to_string();
+        self.import.status = tr("⏳ Downloading resource pack...").to_string();
+        let cancel = self.tasks.child_token();
+        self.import.cancel = Some(cancel.clone());
+        self.import.progress = Some(TransferProgress::default());
+        self.import.estimator = Estimator::default();
+
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            let on_progress = |progress| events.send(Event::Transfer(Task::Import, progress));
+            let result = pack::download(&url, &cancel, &on_progress).await;
+            events.send(Event::PackExtracted(result));
+        });
+    }
+
+    pub(super) fn import_folder(&mut self, source_folder: PathBuf) {
+        let Some(game_path) = self.game_path.clone() else {
+            self.import.status = tr("❌ Game directory not set. Cannot import resources.").to_string();
+            return;
+        };
+
+        if self.preview_first(PreviewAction::Import(source_folder.clone())) {
+            return;
+        }
+
+        let post_install = match script::load_post_install(&source_folder) {
+            Ok(script) => script.filter(confirm_post_install),
+            Err(e) => {
+                self.import.status = trf("❌ {}", &[&e]);
+                return;
+            }
+        };
+
+        self.import.status = tr("⏳ Importing resources...").to_string();
+
+        // A fresh import is a resume with nothing copied yet
+        self.start_import(Operation::Import {
+            game_path,
+            source: source_folder,
+            copied: Vec::new(),
+            created: Vec::new(),
+            remove_missing: self.config.import_remove_missing,
+        }, post_install);
+    }
+
+    pub(super) fn import_from_shell(&mut self, source_folder: PathBuf) {
+        if !self.policy.allows(Feature::Import) {
+            self.import.status = tr("❌ Importing resources is disabled on this PC").to_string();
+            return;
+        }
+        if self.import.progress.is_some() {
+            self.import.status = tr("⚠ Wait for the current import to finish before starting another").to_string();
+            return;
+        }
+        self.import_folder(source_folder);
+    }
+
+    pub(super) fn open_pack(&mut self, pack_path: PathBuf) {
+        if !self.policy.allows(Feature::Import) {
+            self.import.status = tr("❌ Importing resources is disabled on this PC").to_string();
+            return;
+        }
+        let Some(game_path) = &self.game_path else {
+            self.import.status = tr("❌ Game directory not set. Cannot import resources.").to_string();
+            return;
+        };
+
+        if self.import.progress.is_some() {
+            self.import.status = tr("⚠ Wait for the current import to finish before opening another pack").to_string();
+            return;
+        }
+
+        let name = pack_path.file_name().unwrap_or_default().to_string_lossy();
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Info)
+            .set_title(tr("Import resource pack?"))
+            .set_description(trf("Import {} into {}?", &[&name, &game_path.display()]))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.extract_pack(pack_path);
+    }
+
+    fn extract_pack(&mut self, pack_path: PathBuf) {
+        self.import.status = tr("⏳ Unpacking resource pack...").to_string();
+        let cancel = self.tasks.child_token();
+        self.import.cancel = Some(cancel.clone());
+        self.import.progress = Some(TransferProgress::default());
+
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            let result = pack::extract(pack_path, cancel).await;
+            events.send(Event::PackExtracted(result));
+        });
+    }
+
+    pub(super) fn start_import(&mut self, operation: Operation, post_install: Option<PostInstallScript>) {
+        let cancel = self.tasks.child_token();
+        self.import.cancel = Some(cancel.clone());
+        self.import.progress = Some(TransferProgress::default());
+        self.import.estimator = Estimator::default();
+        self.watchdog.start(Task::Import);
+        if let Operation::Import { source, .. } = &operation {
+            self.import.source = Some(source.clone());
+        }
+
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            let on_progress = |progress| events.send(Event::Transfer(Task::Import, progress));
+            let scope = match &operation {
+                Operation::Import { game_path, .. } => resources::resources_path(game_path),
+                _ => PathBuf::new(),
+            };
+
+            let source = match &operation {
+                Operation::Import { source, .. } => source.clone(),
+                _ => PathBuf::new(),
+            };
+
+            let result = match resources::resume_import(operation, &cancel, &on_progress).await {
+                Ok(summary) => {
+                    if let Some(restore_point) = summary.restore_point.clone() {
+                        let name = source.file_name().unwrap_or_default().to_string_lossy();
+                        events.send(Event::Undoable(UndoEntry { restore_point, label: trf("Import of {}", &[&name]) }));
+                    }
+                    match post_install {
+                    Some(script) => {
+                        events.progress(Task::Import, tr("⏳ Running post-install script...").to_string());
+                        match script::run_post_install(script, scope).await {
+                            Ok(_) => Ok(trf("✅ Resources imported ({}) and post-install script ran!", &[&summary.describe()])),
+                            Err(e) => Err(trf("⚠ Resources imported ({}), but the post-install script failed: {}", &[&summary.describe(), &e])),
+                        }
+                    }
+                    None => Ok(trf("✅ Resources imported: {}", &[&summary.describe()])),
+                    }
+                }
+                Err(e) => Err(task_error("Import", e)),
+            };
+            // Unpacked .tnsmpack copies are only kept around for resuming
+            if result.is_ok() {
+                pack::remove_extracted(&source);
+            }
+            events.finished(Task::Import, result);
+        });
+    }
+
+    fn back_up_game_data(&mut self) {
+        self.game_data.status = tr("⏳ Backing up game data...").to_string();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+        self.game_data.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let result = match game_data::back_up(cancel).await {
+                Ok(path) => Ok(trf("✅ Game data backed up to {}", &[&path.display()])),
+                Err(e) => Err(task_error("Backup", e)),
+            };
+            events.finished(Task::GameData, result);
+        });
+    }
+
+    fn restore_game_data(&mut self) {
+        let Some(backup) = self.game_data.backups.get(self.game_data.selected) else {
+            return;
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Restore Game Data"))
+            .set_description(trf(
+                "Restore the game data backed up on {}?\n\nClose Sky first. Current settings will be overwritten.",
+                &[&backup.label()]
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.game_data.status = tr("⏳ Restoring game data...").to_string();
+        let backup_path = backup.path.clone();
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+        self.game_data.cancel = Some(cancel.clone());
+
+        self.tasks.spawn(async move {
+            let result = match game_data::restore(backup_path, cancel).await {
+                Ok(_) => Ok(tr("✅ Game data restored").to_string()),
+                Err(e) => Err(task_error("Restore", e)),
+            };
+            events.finished(Task::GameData, result);
+        });
+    }
+
+    fn install_plugin(&mut self, plugin: Plugin) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+
+        self.plugins.is_installing = true;
+        self.plugins.progress = None;
+        self.plugins.status = trf("⏳ Downloading {}...", &[&plugin.name]);
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            let on_progress = |progress| events.send(Event::Transfer(Task::Plugins, progress));
+            let result = plugins::install(&game_path, &plugin, &cancel, &on_progress).await
+                .map(|_| trf("✅ {} installed as {}", &[&plugin.name, &plugin.file_name]))
+                .map_err(|e| trf("❌ {}", &[&e]));
+            events.finished(Task::Plugins, result);
+        });
+    }
+
+    fn add_plugin(&mut self) {
+        let plugin = Plugin {
+            name: self.plugins.new_plugin.name.trim().to_string(),
+            url: self.plugins.new_plugin.url.trim().to_string(),
+            file_name: self.plugins.new_plugin.file_name.trim().to_string(),
+            enabled: true,
+        };
+        if let Err(e) = plugins::check(&plugin, &self.config.plugins, self.config.proxy_dll) {
+            self.plugins.status = trf("❌ {}", &[&e]);
+            return;
+        }
+
+        self.config.plugins.push(plugin.clone());
+        let _ = self.config.save();
+        self.plugins.new_plugin = Plugin { enabled: true, ..Default::default() };
+        self.install_plugin(plugin);
+    }
+
+    fn remove_plugin(&mut self, game_path: &Path, index: usize) {
+        let Some(plugin) = self.config.plugins.get(index).cloned() else {
+            return;
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Remove Plugin"))
+            .set_description(trf("Remove {} and delete {} from the game folder?", &[&plugin.name, &plugin.file_name]))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.plugins.status = match plugins::remove(game_path, &plugin) {
+            Ok(()) => {
+                self.config.plugins.remove(index);
+                let _ = self.config.save();
+                trf("✅ Removed {}", &[&plugin.name])
+            }
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+    }
+
+    fn set_plugin_enabled(&mut self, game_path: &Path, index: usize, enabled: bool) {
+        let Some(plugin) = self.config.plugins.get(index).cloned() else {
+            return;
+        };
+
+        match plugins::set_enabled(game_path, &plugin, enabled) {
+            Ok(()) => {
+                self.config.plugins[index].enabled = enabled;
+                let _ = self.config.save();
+                self.plugins.status.clear();
+            }
+            Err(e) => self.plugins.status = trf("❌ {}", &[&e]),
+        }
+    }
+
+    pub(super) fn show_plugins(&mut self, ui: &mut egui::Ui) {
+        let Some(game_path) = self.game_path.clone() else {
+            ui.label(tr("Set the game directory first."));
+            return;
+        };
+
+        ui.label(egui::RichText::new(tr("Companion DLL mods installed next to TNSM. Disabled plugins stay downloaded but aren't loaded by the game."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        let can_change = !self.plugins.is_installing && !self.game_running && self.game_path_writable;
+        let mut install = None;
+        let mut remove = None;
+        let mut toggle = None;
+
+        egui::Grid::new("plugins").striped(true).show(ui, |ui| {
+            ui.label(egui::RichText::new(tr("Name")).strong());
+            ui.label(egui::RichText::new(tr("File")).strong());
+            ui.label(egui::RichText::new(tr("Enabled")).strong());
+            ui.label(egui::RichText::new(tr("State")).strong());
+            ui.end_row();
+
+            ui.label("ThatNoobSkyMod");
+            ui.label(self.config.proxy_dll.file_name());
+            ui.label("");
+            ui.label(if self.is_mod_installed { tr("Installed") } else { tr("Not installed") });
+            ui.label(egui::RichText::new(tr("Installed above")).size(12.0).color(egui::Color32::GRAY));
+            ui.end_row();
+
+            for (index, plugin) in self.config.plugins.iter().enumerate() {
+                let state = plugin.state(&game_path);
+                ui.label(&plugin.name).on_hover_text(&plugin.url);
+                ui.label(&plugin.file_name);
+
+                let mut enabled = plugin.enabled;
+                if ui.add_enabled(can_change, egui::Checkbox::without_text(&mut enabled)).changed() {
+                    toggle = Some((index, enabled));
+                }
+
+                ui.label(match state {
+                    PluginState::NotInstalled => tr("Not installed"),
+                    PluginState::Enabled => tr("Installed"),
+                    PluginState::Disabled => tr("Disabled"),
+                });
+
+                ui.add_enabled_ui(can_change, |ui| {
+                    ui.horizontal(|ui| {
+                        let label = if state == PluginState::NotInstalled { tr("⬇ Install") } else { tr("🔄 Update") };
+                        if ui.button(label).clicked() {
+                            install = Some(plugin.clone());
+                        }
+                        if ui.button("🗑").on_hover_text(tr("Remove plugin")).clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                });
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.plugins.new_plugin.name)
+                .hint_text(tr("Name"))
+                .desired_width(100.0));
+            ui.add(egui::TextEdit::singleline(&mut self.plugins.new_plugin.url)
+                .hint_text("https://.../plugin.dll")
+                .desired_width(180.0));
+            ui.add(egui::TextEdit::singleline(&mut self.plugins.new_plugin.file_name)
+                .hint_text("version.dll")
+                .desired_width(90.0));
+            if ui.add_enabled(can_change, egui::Button::new(tr("➕ Add"))).clicked() {
+                self.add_plugin();
+            }
+        });
+
+        if let Some((index, enabled)) = toggle {
+            self.set_plugin_enabled(&game_path, index, enabled);
+        }
+        if let Some(plugin) = install {
+            self.install_plugin(plugin);
+        }
+        if let Some(index) = remove {
+            self.remove_plugin(&game_path, index);
+        }
+
+        if let Some(progress) = &self.plugins.progress {
+            ui.add(egui::ProgressBar::new(progress.fraction())
+                .fill(status::color(Level::Working))
+                .desired_width(300.0));
+        }
+        if !self.plugins.status.is_empty() {
+            status::show(ui, &self.plugins.status, 12.0);
+        }
+    }
+
+    fn switch_profile(&mut self, game_path: &Path, name: &str) {
+        self.profiles.status = match profiles::switch(game_path, name) {
+            Ok(()) => {
+                self.status_pipe.send(PipeEvent::ResourcesChanged);
+                trf("✅ Switched to {}", &[&name])
+            }
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+        self.profiles.list = None;
+    }
+
+    fn delete_profile(&mut self, game_path: &Path, name: &str) {
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Delete Profile"))
+            .set_description(trf("Delete the profile \"{}\" and every resource in it?", &[&name]))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        self.profiles.status = match profiles::delete(game_path, name) {
+            Ok(()) => trf("✅ Deleted {}", &[&name]),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+        self.profiles.list = None;
+    }
+
+    pub(super) fn show_resource_profiles(&mut self, ui: &mut egui::Ui) {
+        let Some(game_path) = self.game_path.clone() else {
+            ui.label(tr("Set the game directory first."));
+            return;
+        };
+        let current = self.profiles.list.get_or_insert_with(|| profiles::list(&game_path));
+        let names = current.names.clone();
+        let active = current.active.clone();
+
+        ui.label(egui::RichText::new(tr("Keep several resource packs side by side and switch which one is in TNSM Resources."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        // Renaming folders Sky has open would fail halfway
+        let can_switch = self.import.progress.is_none() && !self.game_running && self.game_path_writable;
+        let mut switch_to = None;
+        let mut delete = None;
+
+        for name in &names {
+            ui.horizontal(|ui| {
+                if active.as_ref() == Some(name) {
+                    ui.label(egui::RichText::new(trf("✅ {}", &[&name])).strong());
+                    ui.label(egui::RichText::new(tr("in use")).size(12.0).color(egui::Color32::GRAY));
+                } else {
+                    ui.label(name);
+                    ui.add_enabled_ui(can_switch, |ui| {
+                        if ui.button(tr("🔀 Switch"))
+                            .on_disabled_hover_text(tr("Close Sky and wait for imports to finish first"))
+                            .clicked()
+                        {
+                            switch_to = Some(name.clone());
+                        }
+                        if ui.button("🗑").on_hover_text(tr("Delete profile")).clicked() {
+                            delete = Some(name.clone());
+                        }
+                    });
+                }
+            });
+        }
+
+        if active.is_none() && resources::resources_path(&game_path).exists() {
+            ui.label(egui::RichText::new(tr("Name the resources in TNSM Resources to keep them when switching."))
+                .size(12.0)
+                .color(status::color(Level::Warning)));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.profiles.name)
+                .hint_text(tr("Profile name"))
+                .desired_width(160.0));
+
+            ui.add_enabled_ui(!self.profiles.name.trim().is_empty() && self.game_path_writable, |ui| {
+                if ui.button(tr("➕ New Empty Profile")).clicked() {
+                    self.profiles.status = match profiles::create(&game_path, &self.profiles.name) {
+                        Ok(name) => trf("✅ Created {}, switch to it and import resources to fill it", &[&name]),
+                        Err(e) => trf("❌ {}", &[&e]),
+                    };
+                    self.profiles.name.clear();
+                    self.profiles.list = None;
+                }
+
+                let label = if active.is_some() { tr("✏ Rename Current") } else { tr("💾 Name Current Resources") };
+                if ui.button(label).clicked() {
+                    self.profiles.status = match profiles::name_current(&game_path, &self.profiles.name) {
+                        Ok(name) => trf("✅ TNSM Resources is now the {} profile", &[&name]),
+                        Err(e) => trf("❌ {}", &[&e]),
+                    };
+                    self.profiles.name.clear();
+                    self.profiles.list = None;
+                }
+            });
+        });
+
+        if let Some(name) = switch_to {
+            self.switch_profile(&game_path, &name);
+        }
+        if let Some(name) = delete {
+            self.delete_profile(&game_path, &name);
+        }
+
+        if !self.profiles.status.is_empty() {
+            status::show(ui, &self.profiles.status, 12.0);
+        }
+    }
+
+    pub(super) fn refresh_steam_accounts(&mut self) {
+        self.config_backups.accounts = self.steam_path.as_deref().map(steam_accounts::list).unwrap_or_default();
+        self.refresh_config_backups();
+    }
+
+    // Picked by hand, otherwise whoever signed in to Steam last
+    fn steam_account(&self) -> Option<&SteamAccount> {
+        self.config.steam_account
+            .and_then(|id| self.config_backups.accounts.iter().find(|account| account.id == id))
+            .or_else(|| self.config_backups.accounts.first())
+    }
+
+    fn refresh_config_backups(&mut self) {
+        self.config_backups.backups = self.steam_account().map(|account| config_backups::list(account.id)).unwrap_or_default();
+        self.config_backups.selected = 0;
+    }
+
+    pub(super) fn show_config_backups(&mut self, ui: &mut egui::Ui) {
+        let Some(game_path) = self.game_path.clone() else {
+            ui.label(tr("Set the game directory first."));
+            return;
+        };
+        let Some(account) = self.steam_account().cloned() else {
+            ui.label(tr("No Steam accounts found on this PC."));
+            return;
+        };
+
+        ui.label(egui::RichText::new(tr("Keybinds and settings are backed up per Steam account, so everyone sharing this PC keeps their own."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        ui.horizontal(|ui| {
+            let mut selected = account.id;
+            egui::ComboBox::from_label(tr("Steam account"))
+                .selected_text(&account.name)
+                .show_ui(ui, |ui| {
+                    for option in &self.config_backups.accounts {
+                        ui.selectable_value(&mut selected, option.id, &option.name);
+                    }
+                });
+            if selected != account.id {
+                self.config.steam_account = Some(selected);
+                let _ = self.config.save();
+                self.refresh_config_backups();
+                self.config_backups.status.clear();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("💾 Back Up Mod Config")).clicked() {
+                self.config_backups.status = match config_backups::back_up(&game_path, account.id) {
+                    Ok(_) => trf("✅ Backed up for {}", &[&account.name]),
+                    Err(e) => trf("❌ {}", &[&e]),
+                };
+                self.refresh_config_backups();
+            }
+
+            if !self.config_backups.backups.is_empty() {
+                let selected_label = self.config_backups.backups
+                    .get(self.config_backups.selected)
+                    .map(ConfigBackup::label)
+                    .unwrap_or_default();
+                egui::ComboBox::from_id_salt("config_backup")
+                    .selected_text(selected_label)
+                    .show_ui(ui, |ui| {
+                        for (index, backup) in self.config_backups.backups.iter().enumerate() {
+                            ui.selectable_value(&mut self.config_backups.selected, index, backup.label());
+                        }
+                    });
+
+                if ui.button(tr("↩ Restore")).clicked()
+                    && let Some(backup) = self.config_backups.backups.get(self.config_backups.selected)
+                {
+                    self.config_backups.status = match config_backups::restore(&game_path, backup) {
+                        Ok(restore_point) => {
+                            if let Some(restore_point) = restore_point {
+                                self.push_undo(UndoEntry { restore_point, label: tr("Mod config restore").to_string() });
+                            }
+                            tr("✅ Mod config restored, restart the game to apply it").to_string()
+                        }
+                        Err(e) => trf("❌ {}", &[&e]),
+                    };
+                    self.restore.points = restore_point::list();
+                }
+            }
+        });
+
+        if !self.config_backups.status.is_empty() {
+            status::show(ui, &self.config_backups.status, 12.0);
+        }
+    }
+
+    fn load_screenshots(&mut self) {
+        self.gallery.dir = self.config.screenshot_dir.clone()
+            .filter(|dir| dir.is_dir())
+            .or_else(|| screenshots::find_dir(self.game_path.as_deref()));
+
+        self.gallery.screenshots = Some(match &self.gallery.dir {
+            Some(dir) => screenshots::list(dir),
+            None => Err(tr("Screenshot folder not found. Take a screenshot in game or choose the folder.").to_string()),
+        });
+    }
+
+    fn choose_screenshot_dir(&mut self) {
+        if let Some(dir) = rfd::FileDialog::new()
+            .set_title(tr("Select Screenshot Folder"))
+            .pick_folder()
+        {
+            self.config.screenshot_dir = Some(dir);
+            let _ = self.config.save();
+            self.load_screenshots();
+        }
+    }
+
+    fn copy_screenshot(&mut self, ctx: &egui::Context, path: &Path) {
+        match image::open(path) {
+            Ok(image) => {
+                let image = image.to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                ctx.copy_image(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()));
+                self.gallery.status = tr("✓ Copied to clipboard").to_string();
+            }
+            Err(e) => self.gallery.status = trf("❌ Failed to read {}: {}", &[&path.display(), &e]),
+        }
+    }
+
+    fn export_screenshots(&mut self, paths: Vec<PathBuf>) {
+        let Some(dest) = rfd::FileDialog::new()
+            .set_title(tr("Export Screenshots To"))
+            .pick_folder()
+        else {
+            return;
+        };
+
+        self.gallery.status = match screenshots::export(&paths, &dest) {
+            Ok(count) => trf("✅ Exported {} screenshots to {}", &[&count, &dest.display()]),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+    }
+
+    pub(super) fn show_gallery(&mut self, ui: &mut egui::Ui) {
+        if self.gallery.screenshots.is_none() {
+            self.load_screenshots();
+        }
+
+        ui.horizontal(|ui| {
+            if let Some(dir) = self.gallery.dir.clone()
+                && ui.button(tr("📂 Open Folder")).on_hover_text(dir.display().to_string()).clicked()
+                && let Err(e) = shell::open_folder(&dir)
+            {
+                self.gallery.status = trf("❌ {}", &[&e]);
+            }
+            if ui.button(tr("📁 Choose Folder")).clicked() {
+                self.choose_screenshot_dir();
+            }
+            if ui.button(tr("🔄 Refresh")).clicked() {
+                self.load_screenshots();
+            }
+            if let Some(Ok(all)) = &self.gallery.screenshots
+                && !all.is_empty()
+                && ui.button(tr("📤 Export All")).clicked()
+            {
+                let paths = all.iter().map(|screenshot| screenshot.path.clone()).collect();
+                self.export_screenshots(paths);
+            }
+        });
+
+        if !self.gallery.status.is_empty() {
+            status::show(ui, &self.gallery.status, 12.0);
+        }
+        ui.add_space(10.0);
+
+        let screenshots = match &self.gallery.screenshots {
+            Some(Ok(screenshots)) if screenshots.is_empty() => {
+                ui.label(tr("No screenshots yet."));
+                return;
+            }
+            Some(Ok(screenshots)) => screenshots,
+            Some(Err(e)) => {
+                ui.label(status::text(&trf("⚠ {}", &[&e])));
+                return;
+            }
+            None => return,
+        };
+
+        // Actions are collected and run after the loop, which borrows the list
+        let mut open = None;
+        let mut copy = None;
+        let mut export = None;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for screenshot in screenshots {
+                    ui.group(|ui| {
+                        ui.set_width(THUMBNAIL_SIZE.x);
+                        ui.vertical(|ui| {
+                            let uri = format!("file://{}", screenshot.path.display());
+                            let thumbnail = ui.add(egui::Image::new(uri)
+                                .fit_to_exact_size(THUMBNAIL_SIZE)
+                                .sense(egui::Sense::click()))
+                                .on_hover_text(format!("{} · {}", screenshot.name(), storage::format_size(screenshot.bytes)));
+                            if thumbnail.double_clicked() {
+                                open = Some(screenshot.path.clone());
+                            }
+
+                            ui.horizontal(|ui| {
+                                if ui.small_button("🔍").on_hover_text(tr("Open")).clicked() {
+                                    open = Some(screenshot.path.clone());
+                                }
+                                if ui.small_button("📋").on_hover_text(tr("Copy")).clicked() {
+                                    copy = Some(screenshot.path.clone());
+                                }
+                                if ui.small_button("📤").on_hover_text(tr("Export")).clicked() {
+                                    export = Some(screenshot.path.clone());
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+        });
+
+        if let Some(path) = open
+            && let Err(e) = shell::open(&path)
+        {
+            self.gallery.status = trf("❌ {}", &[&e]);
+        }
+        if let Some(path) = copy {
+            self.copy_screenshot(ui.ctx(), &path);
+        }
+        if let Some(path) = export {
+            self.export_screenshots(vec![path]);
+        }
+    }
+
+    // After an import this account couldn't write: copies the same files
+    // again through the elevated helper, once the user agrees
+    pub(super) fn import_elevated(&mut self, source: PathBuf) {
+        let Some(game_path) = self.game_path.clone() else {
+            return;
+        };
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Administrator Approval Needed"))
+            .set_description(tr("This account can't change the game folder, so the resources couldn't be copied.\n\nCopy them with administrator approval?"))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        let cancel = self.tasks.child_token();
+        self.import.cancel = Some(cancel.clone());
+        self.import.progress = Some(TransferProgress::default());
+        self.import.estimator = Estimator::default();
+        self.import.is_elevated = true;
+        self.import.status = tr("⏳ Waiting for administrator approval...").to_string();
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            let on_progress = |progress| events.send(Event::Transfer(Task::Import, progress));
+            let result = match resources::import_elevated(&source, &game_path, &cancel, &on_progress).await {
+                Ok(count) => Ok(trf("✅ Resources imported with administrator approval: {} files copied", &[&count])),
+                Err(e) => Err(task_error("Import", e)),
+            };
+            if result.is_ok() {
+                pack::remove_extracted(&source);
+            }
+            events.finished(Task::Import, result);
+        });
+    }
+
+    // Import TSM Resources Button
+    pub(super) fn show_import(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            let import_button = egui::Button::new(
+                egui::RichText::new(tr("📦 Import TSM Resources"))
+                    .size(16.0)
+            ).min_size(egui::vec2(200.0, 40.0));
+
+            // Imports would write into the linked folder
+            let linked = self.import.link.is_some();
+            ui.add_enabled_ui(self.import.progress.is_none() && self.game_path_writable && !linked, |ui| {
+                if ui.add(import_button).clicked() {
+                    self.browse_and_import_resources();
+                }
+                if ui.button(tr("🗜 Import from ZIP...")).clicked() {
+                    self.browse_and_import_archive();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.import.pack_url)
+                        .hint_text("https://.../pack.zip")
+                        .desired_width(220.0));
+                    if ui.add_enabled(!self.import.pack_url.trim().is_empty(), egui::Button::new(tr("🌐 Import from URL"))).clicked() {
+                        self.import_from_url();
+                    }
+                });
+
+                let mut remove_missing = self.config.import_remove_missing;
+                if ui.checkbox(&mut remove_missing, tr("🧹 Remove files the source no longer has"))
+                    .on_hover_text(tr("Importing a folder again also deletes what an earlier import of it wrote that it no longer has. They're saved to a restore point first."))
+                    .changed()
+                {
+                    self.config.import_remove_missing = remove_missing;
+                    let _ = self.config.save();
+                }
+            });
+
+            ui.add_enabled_ui(self.import.progress.is_none() && self.game_path_writable && !self.game_running, |ui| {
+                match &self.import.link {
+                    Some(target) => {
+                        ui.label(egui::RichText::new(trf("🔗 TNSM Resources is linked to {}", &[&target.display()]))
+                            .size(12.0)
+                            .color(egui::Color32::GRAY));
+                        if ui.button(tr("Unlink")).on_hover_text(tr("Removes only the link, the folder keeps its files")).clicked() {
+                            self.unlink_resources();
+                        }
+                    }
+                    None => {
+                        if ui.button(tr("🔗 Link a Folder..."))
+                            .on_hover_text(tr("Points TNSM Resources at a folder of yours instead of copying it, so edits there show up without importing again"))
+                            .on_disabled_hover_text(tr("Close Sky and wait for imports to finish first"))
+                            .clicked()
+                        {
+                            self.link_resources();
+                        }
+                    }
+                }
+            });
+
+            if let Some(progress) = &self.import.progress {
+                ui.add_space(5.0);
+                let estimate = self.import.estimator.estimate();
+                let mut text = trf("{} / {} files", &[&progress.files_done, &progress.files_total]);
+                if let Some(rate) = estimate.bytes_per_second {
+                    text.push_str(&trf(" · {}", &[&progress::format_rate(rate)]));
+                }
+                if let Some(eta) = estimate.eta {
+                    text.push_str(&trf(" · {}", &[&progress::format_eta(eta)]));
+                }
+
+                ui.add(egui::ProgressBar::new(estimate.fraction)
+                    .fill(status::color(Level::Working))
+                    .desired_width(300.0)
+                    .text(text));
+
+                if let Some(cancel) = &self.import.cancel
+                    && ui.button(tr("✖ Cancel Import"))
+                        .on_hover_text(tr("Stops the import and puts the resources folder back as it was"))
+                        .clicked()
+                {
+                    cancel.cancel();
+                }
+
+                self.show_stall_notice(ui, Task::Import);
+            }
+
+            if !self.import.status.is_empty() {
+                ui.add_space(5.0);
+
+                status::show(ui, &self.import.status, 12.0);
+            }
+        });
+    }
+
+    // Game Data Backup
+    pub(super) fn show_game_data_backup(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_enabled_ui(self.game_data.cancel.is_none(), |ui| {
+                if ui.button(tr("🗄 Back Up Game Data")).clicked() {
+                    self.back_up_game_data();
+                }
+
+                if !self.game_data.backups.is_empty() {
+                    ui.horizontal(|ui| {
+                        let selected = self.game_data.backups
+                            .get(self.game_data.selected)
+                            .map(GameDataBackup::label)
+                            .unwrap_or_default();
+                        egui::ComboBox::from_id_salt("game_data_backup")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (index, backup) in self.game_data.backups.iter().enumerate() {
+                                    ui.selectable_value(&mut self.game_data.selected, index, backup.label());
+                                }
+                            });
+                        if ui.button(tr("↩ Restore")).clicked() {
+                            self.restore_game_data();
+                        }
+                    });
+                }
+            });
+
+            if !self.game_data.status.is_empty() {
+                ui.add_space(5.0);
+
+                status::show(ui, &self.game_data.status, 12.0);
+            }
+        });
+    }
+}
+
+// Scripts only run with the user's say-so, after they've seen what it does
+fn confirm_post_install(script: &PostInstallScript) -> bool {
+    let answer = rfd::MessageDialog::new()
+        .set_level(rfd::MessageLevel::Warning)
+        .set_title(tr("Run post-install script?"))
+        .set_description(trf(
+            "This resource pack includes a post-install script ({}). It can only rename and write files inside the {} folder.\n\n{}\n\nRun it after importing?",
+            &[&script.name, &resources::RESOURCES_DIR_NAME, &script.source]
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+
+    answer == rfd::MessageDialogResult::Yes
+}
@@ -0,0 +1,681 @@
+// The settings panel, downloads and proxy, exporting settings and storage
+
+use super::{ModInstallerApp, task_error};
+use crate::status::{self, Level};
+use crate::theme;
+use eframe::egui;
+use tnsm_installer_core::config::Theme;
+use tnsm_installer_core::download::DEFAULT_DLL_URL;
+use tnsm_installer_core::events::{Event, Task};
+use tnsm_installer_core::i18n::{self, tr, trf};
+use tnsm_installer_core::storage::{self, Category, Usage};
+use tnsm_installer_core::install::ProxyDll;
+use tnsm_installer_core::proxy::{ProxyMode, ProxySettings};
+use tnsm_installer_core::policy::Feature;
+use tnsm_installer_core::settings_export::{self, ExportedSettings};
+use tnsm_installer_core::{autostart, detect, github, install, lang, scheduler, shell, workers};
+
+// What the installer keeps on disk
+#[derive(Default)]
+pub(super) struct StorageState {
+    pub(super) status: String,
+    pub(super) usage: Vec<Usage>,
+    pub(super) is_cleaning_up: bool,
+}
+
+// Community language files
+#[derive(Default)]
+pub(super) struct LanguageState {
+    pub(super) status: String,
+    // Files that failed to load and translations that were dropped
+    pub(super) problems: Vec<String>,
+}
+
+// Download mirrors and the proxy
+#[derive(Default)]
+pub(super) struct DownloadsState {
+    // Mirror list being edited, one per line, filled when the downloads section is first shown
+    pub(super) mirrors: Option<String>,
+    pub(super) status: String,
+    // Proxy settings being edited, saved as a whole
+    pub(super) proxy: Option<ProxySettings>,
+    pub(super) proxy_status: String,
+}
+
+impl ModInstallerApp {
+    pub(super) fn set_dll_url(&mut self, dll_url: String) {
+        self.config.dll_url = Some(dll_url.clone()).filter(|url| url != DEFAULT_DLL_URL);
+        let _ = self.config.save();
+        self.dll_url = dll_url;
+    }
+
+    // An installed DLL is renamed right away, so uninstalling and verifying
+    // find it under the new name
+    fn set_proxy_dll(&mut self, proxy_dll: ProxyDll) {
+        if let Some(game_path) = &self.game_path
+            && let Err(e) = install::move_dll(game_path, self.config.proxy_dll, proxy_dll)
+        {
+            self.status_message = trf("❌ {}", &[&e]);
+            return;
+        }
+        self.config.proxy_dll = proxy_dll;
+        let _ = self.config.save();
+        self.check_mod_installed();
+    }
+
+    fn set_beta_channel(&mut self, beta: bool) {
+        self.config.beta_channel = beta;
+        let _ = self.config.save();
+        self.latest_release = None;
+        self.check_for_update();
+    }
+
+    fn set_pinned_version(&mut self, version: Option<String>) {
+        self.config.pinned_version = version;
+        let _ = self.config.save();
+        self.apply_pinned_version();
+    }
+
+    pub(super) fn refresh_storage_usage(&mut self) {
+        let events = self.events.sender();
+        self.tasks.spawn(async move {
+            if let Ok(usage) = storage::usage().await {
+                events.send(Event::StorageUsage(usage));
+            }
+        });
+    }
+
+    pub(super) fn clean_up_storage(&mut self) {
+        // Restore points and unpacked packs are in use while these run
+        if self.install.running || self.import.progress.is_some() {
+            self.storage.status = tr("⚠ Wait for the current install or import to finish").to_string();
+            return;
+        }
+
+        self.storage.is_cleaning_up = true;
+        self.storage.status = tr("⏳ Cleaning up...").to_string();
+        let retention = self.config.retention;
+        let events = self.events.sender();
+
+        self.tasks.spawn(async move {
+            let result = match storage::clean_up(retention).await {
+                Ok(report) if report.items == 0 => Ok(tr("✅ Nothing to clean up").to_string()),
+                Ok(report) => Ok(trf(
+                    "✅ Removed {} items, {} freed",
+                    &[&report.items, &storage::format_size(report.bytes)]
+                )),
+                Err(e) => Err(task_error("Cleanup", e)),
+            };
+            if let Ok(usage) = storage::usage().await {
+                events.send(Event::StorageUsage(usage));
+            }
+            events.finished(Task::Storage, result);
+        });
+    }
+
+    fn export_settings(&mut self) {
+        let Some(dest) = rfd::FileDialog::new()
+            .set_title(tr("Export Settings"))
+            .add_filter("JSON", &["json"])
+            .set_file_name("tnsm-settings.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let settings = ExportedSettings::collect(&self.config, self.game_path.as_deref());
+        self.settings_status = match settings_export::export(&dest, &settings) {
+            Ok(()) => trf("✅ Settings exported to {}", &[&dest.display()]),
+            Err(e) => trf("❌ {}", &[&e]),
+        };
+    }
+
+    // Goes through the same setters as changing each setting by hand, so
+    // policy locks still apply and an installed DLL is renamed along
+    fn import_settings(&mut self) {
+        if self.is_busy() {
+            self.settings_status = tr("⚠ Wait for the current task to finish before importing settings").to_string();
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(tr("Import Settings"))
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let settings = match settings_export::load(&path) {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.settings_status = trf("❌ {}", &[&e]);
+                return;
+            }
+        };
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Info)
+            .set_title(tr("Import Settings"))
+            .set_description(tr("Replace the game folder, mod version, update channel, DLL URL and proxy DLL name with the imported ones?"))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            return;
+        }
+
+        let mut problems = Vec::new();
+
+        if let Some(game_path) = settings.game_path.clone()
+            && !self.is_game_path_locked()
+            && self.game_path.as_ref() != Some(&game_path)
+        {
+            if detect::is_game_directory(&game_path) {
+                self.game_path = Some(game_path);
+                self.show_manual_input = false;
+                self.remember_game_path();
+                self.check_mod_installed();
+            } else {
+                problems.push(trf("the game folder {} wasn't found on this PC", &[&game_path.display()]));
+            }
+        }
+
+        if self.policy.pinned_dll_url().is_none() {
+            self.set_dll_url(settings.dll_url.clone().unwrap_or_else(|| DEFAULT_DLL_URL.to_string()));
+            self.set_pinned_version(settings.pinned_version.clone());
+            if settings.beta_channel != self.config.beta_channel {
+                self.set_beta_channel(settings.beta_channel);
+            }
+        }
+
+        if settings.proxy_dll != self.config.proxy_dll {
+            self.set_proxy_dll(settings.proxy_dll);
+            if self.config.proxy_dll != settings.proxy_dll {
+                problems.push(trf("the mod couldn't be renamed to {}", &[&settings.proxy_dll.file_name()]));
+            }
+        }
+
+        if let Some(game_path) = self.game_path.clone() {
+            match settings_export::create_missing_profiles(&settings, &game_path) {
+                Ok(0) => {}
+                Ok(count) => problems.push(trf("{} resource profiles were created empty, import their resources again", &[&count])),
+                Err(e) => problems.push(e),
+            }
+            self.profiles.list = None;
+        }
+
+        // The policy's game folder and pinned version win over the imported ones
+        self.apply_policy();
+
+        self.settings_status = if problems.is_empty() {
+            tr("✅ Settings imported").to_string()
+        } else {
+            trf("⚠ Settings imported, but {}", &[&problems.join("; ")])
+        };
+    }
+
+    pub(super) fn show_download_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Failed downloads are tried again, waiting a little longer each time. When a release can't be downloaded from GitHub, the mirrors are tried in order."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        let mut attempts = self.config.download_attempts;
+        ui.horizontal(|ui| {
+            ui.label(tr("Attempts:"));
+            ui.add(egui::DragValue::new(&mut attempts).range(1..=10));
+        });
+        if attempts != self.config.download_attempts {
+            self.config.download_attempts = attempts;
+            let _ = self.config.save();
+            let _ = github::configure(&self.config.proxy, attempts);
+        }
+
+        let text = self.downloads.mirrors.get_or_insert_with(|| self.config.download_mirrors.join("\n"));
+        ui.label(tr("Mirrors, one per line:"));
+        ui.add(egui::TextEdit::multiline(text)
+            .hint_text("https://mirror.example.com/tnsm")
+            .desired_rows(3)
+            .desired_width(f32::INFINITY));
+
+        let mirrors: Vec<String> = text.lines()
+            .map(|line| line.trim().trim_end_matches('/').to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        if ui.add_enabled(mirrors != self.config.download_mirrors, egui::Button::new(tr("💾 Save"))).clicked() {
+            match mirrors.iter().find(|url| !url.starts_with("https://") && !url.starts_with("http://")) {
+                Some(url) => self.downloads.status = trf("❌ {} isn't a web address", &[&url]),
+                None => {
+                    self.config.download_mirrors = mirrors;
+                    self.downloads.status = match self.config.save() {
+                        Ok(()) => tr("✅ Mirrors saved").to_string(),
+                        Err(e) => trf("❌ {}", &[&e]),
+                    };
+                }
+            }
+        }
+
+        if !self.downloads.status.is_empty() {
+            status::show(ui, &self.downloads.status, 12.0);
+        }
+
+        ui.add_space(10.0);
+        self.show_proxy_settings(ui);
+    }
+
+    fn show_proxy_settings(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("Proxy")).strong());
+        ui.label(egui::RichText::new(tr("System uses the proxy set in Windows. Behind a proxy that needs a username and password, enter it manually."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+
+        let proxy = self.downloads.proxy.get_or_insert_with(|| self.config.proxy.clone());
+        ui.horizontal(|ui| {
+            for mode in ProxyMode::ALL {
+                ui.selectable_value(&mut proxy.mode, mode, tr(mode.name()));
+            }
+        });
+
+        if proxy.mode == ProxyMode::Manual {
+            egui::Grid::new("proxy_settings").num_columns(2).show(ui, |ui| {
+                ui.label(tr("Host:"));
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut proxy.host)
+                        .hint_text("proxy.example.com")
+                        .desired_width(180.0));
+                    ui.label(tr("Port:"));
+                    ui.add(egui::DragValue::new(&mut proxy.port).range(0..=65535));
+                });
+                ui.end_row();
+
+                ui.label(tr("Username:"));
+                ui.add(egui::TextEdit::singleline(&mut proxy.username)
+                    .hint_text(tr("optional"))
+                    .desired_width(180.0));
+                ui.end_row();
+
+                ui.label(tr("Password:"));
+                ui.add(egui::TextEdit::singleline(&mut proxy.password)
+                    .password(true)
+                    .desired_width(180.0));
+                ui.end_row();
+            });
+        }
+
+        let changed = *proxy != self.config.proxy;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(changed, egui::Button::new(tr("💾 Save"))).clicked() {
+                match proxy.check() {
+                    Ok(()) => {
+                        self.config.proxy = proxy.clone();
+                        if let Err(e) = github::configure(proxy, self.config.download_attempts) {
+                            tracing::warn!("{}", e);
+                        }
+                        self.downloads.proxy_status = match self.config.save() {
+                            Ok(()) => tr("✅ Proxy settings saved").to_string(),
+                            Err(e) => trf("❌ {}", &[&e]),
+                        };
+                    }
+                    Err(e) => self.downloads.proxy_status = trf("❌ {}", &[&e]),
+                }
+            }
+            if ui.add_enabled(changed, egui::Button::new(tr("↩ Revert"))).clicked() {
+                *proxy = self.config.proxy.clone();
+                self.downloads.proxy_status.clear();
+            }
+        });
+
+        if !self.downloads.proxy_status.is_empty() {
+            status::show(ui, &self.downloads.proxy_status, 12.0);
+        }
+    }
+
+    fn set_daily_update_check(&mut self, enabled: bool) {
+        let result = if enabled {
+            scheduler::register_daily_check()
+        } else {
+            scheduler::unregister_daily_check()
+        };
+
+        match result {
+            Ok(_) => {
+                self.config.daily_update_check = enabled;
+                self.status_message = if enabled {
+                    tr("✅ Daily update check scheduled").to_string()
+                } else {
+                    tr("✅ Daily update check removed").to_string()
+                };
+                let _ = self.config.save();
+
+                // Starting at login only makes sense with background checks on
+                if !enabled && self.config.launch_at_startup {
+                    self.set_launch_at_startup(false);
+                }
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Failed to update scheduled task: {}", &[&e]);
+            }
+        }
+    }
+
+    fn show_theme_settings(&mut self, ui: &mut egui::Ui) {
+        let mut selected = self.config.theme;
+        let mut custom_accent = self.config.accent_color.is_some();
+        let mut accent = self.config.accent_color.unwrap_or_else(theme::default_accent);
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Theme:"));
+            egui::ComboBox::from_id_salt("theme")
+                .selected_text(tr(selected.label()))
+                .show_ui(ui, |ui| {
+                    for option in Theme::ALL {
+                        ui.selectable_value(&mut selected, option, tr(option.label()));
+                    }
+                });
+
+            ui.checkbox(&mut custom_accent, tr("Custom accent color"));
+            if custom_accent {
+                egui::color_picker::color_edit_button_srgb(ui, &mut accent);
+            }
+        });
+
+        let accent = Some(accent).filter(|_| custom_accent);
+        if selected != self.config.theme || accent != self.config.accent_color {
+            self.config.theme = selected;
+            self.config.accent_color = accent;
+            theme::set(selected, accent);
+            let _ = self.config.save();
+        }
+    }
+
+    pub(super) fn set_language(&mut self, code: &str) {
+        i18n::set_language(code);
+        self.config.language = Some(code.to_string()).filter(|code| code != lang::ENGLISH_CODE);
+        let _ = self.config.save();
+    }
+
+    fn install_language_file(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(tr("Select Language File"))
+            .add_filter(tr("Language File"), &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match i18n::install_pack(&path) {
+            Ok(problems) => {
+                let language = i18n::language();
+                self.set_language(&language.code);
+                self.language.status = trf("✅ Installed {}", &[&language.name]);
+                self.language.problems = problems;
+            }
+            Err(e) => {
+                self.language.status = trf("❌ {}", &[&e]);
+            }
+        }
+    }
+
+    fn reload_languages(&mut self) {
+        self.language.problems = i18n::load_packs();
+        self.language.status = trf("✅ Found {} language(s)", &[&i18n::languages().len()]);
+    }
+
+    fn set_folder_context_menu(&mut self, enabled: bool) {
+        match shell::set_folder_context_menu(enabled) {
+            Ok(_) => {
+                self.config.folder_context_menu = enabled;
+                let _ = self.config.save();
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Failed to update folder context menu: {}", &[&e]);
+            }
+        }
+    }
+
+    fn set_shell_integration(&mut self, enabled: bool) {
+        match shell::set_shell_integration(enabled) {
+            Ok(_) => {
+                self.config.shell_integration = enabled;
+                let _ = self.config.save();
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Failed to update shell integration: {}", &[&e]);
+            }
+        }
+    }
+
+    // Registers again if the installer was moved since it was turned on
+    pub(super) fn refresh_shell_integration(&mut self) {
+        self.tasks.spawn(async {
+            let _ = workers::run(shell::refresh_shell_integration).await;
+        });
+    }
+
+    pub(super) fn set_launch_at_startup(&mut self, enabled: bool) {
+        let result = if enabled {
+            autostart::enable()
+        } else {
+            autostart::disable()
+        };
+
+        match result {
+            Ok(_) => {
+                self.config.launch_at_startup = enabled;
+                let _ = self.config.save();
+            }
+            Err(e) => {
+                self.status_message = trf("❌ Failed to update startup entry: {}", &[&e]);
+            }
+        }
+    }
+
+    pub(super) fn show_settings(&mut self, ui: &mut egui::Ui) {
+        ui.vertical_centered(|ui| {
+            if self.policy.allows(Feature::UpdateCheck) {
+                let mut daily_update_check = self.config.daily_update_check;
+                if ui.checkbox(&mut daily_update_check, tr("🕒 Check for mod updates daily")).changed() {
+                    self.set_daily_update_check(daily_update_check);
+                }
+
+                // Pinned by the policy already, nothing for the user to change
+                let pin_version = self.config.pinned_version.clone().or(self.config.installed_version.clone());
+                if self.policy.pinned_dll_url().is_none()
+                    && let Some(version) = pin_version
+                {
+                    let mut pinned = self.config.pinned_version.is_some();
+                    if ui.checkbox(&mut pinned, trf("📌 Stay on {} and skip updates", &[&version]))
+                        .on_hover_text(tr("Installs and update checks keep this version until it's unpinned"))
+                        .changed()
+                    {
+                        self.set_pinned_version(pinned.then_some(version));
+                    }
+                }
+
+                if self.policy.pinned_dll_url().is_none() {
+                    let mut beta_channel = self.config.beta_channel;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Update channel:"));
+                        ui.selectable_value(&mut beta_channel, false, tr("Stable"));
+                        ui.selectable_value(&mut beta_channel, true, tr("Beta"))
+                            .on_hover_text(tr("Installs and updates include pre-releases, for testers"));
+                    });
+                    if beta_channel != self.config.beta_channel {
+                        self.set_beta_channel(beta_channel);
+                    }
+                }
+
+                let mut tray_mode = self.config.tray_mode;
+                if ui.checkbox(&mut tray_mode, tr("🔔 Keep running in the tray and notify about mod updates"))
+                    .on_hover_text(tr("Closing the window minimizes it instead, quit from the tray icon's menu"))
+                    .changed()
+                {
+                    self.set_tray_mode(ui.ctx(), tray_mode);
+                }
+
+                // Only offered alongside the background update mode
+                ui.add_enabled_ui(self.config.daily_update_check || self.config.tray_mode, |ui| {
+                    let mut launch_at_startup = self.config.launch_at_startup;
+                    if ui.checkbox(&mut launch_at_startup, tr("🚀 Start minimized at login")).changed() {
+                        self.set_launch_at_startup(launch_at_startup);
+                    }
+                });
+            }
+
+            let mut preview_changes = self.config.preview_changes;
+            if ui.checkbox(&mut preview_changes, tr("🔍 Preview file changes before installing, importing or uninstalling"))
+                .on_hover_text(tr("Lists the files that would be created, overwritten or deleted, and waits for Apply"))
+                .changed()
+            {
+                self.config.preview_changes = preview_changes;
+                let _ = self.config.save();
+            }
+
+            ui.add_enabled_ui(!self.install.running, |ui| {
+                let mut proxy_dll = self.config.proxy_dll;
+                ui.horizontal(|ui| {
+                    ui.label(tr("Load the mod as:"));
+                    egui::ComboBox::from_id_salt("proxy_dll")
+                        .selected_text(proxy_dll.file_name())
+                        .show_ui(ui, |ui| {
+                            for option in ProxyDll::ALL {
+                                ui.selectable_value(&mut proxy_dll, option, option.file_name());
+                            }
+                        });
+                })
+                .response
+                .on_hover_text(tr("Pick another name when a different mod or overlay already uses this one"));
+                if proxy_dll != self.config.proxy_dll {
+                    self.set_proxy_dll(proxy_dll);
+                }
+            });
+
+            let mut colorblind = self.config.colorblind;
+            if ui.checkbox(&mut colorblind, tr("👁 Colorblind-friendly status colors")).changed() {
+                status::set_colorblind(colorblind);
+                self.config.colorblind = colorblind;
+                let _ = self.config.save();
+            }
+
+            self.show_theme_settings(ui);
+
+            if self.policy.allows(Feature::Language) {
+                ui.horizontal(|ui| {
+                    let languages = i18n::languages();
+                    let current = i18n::language();
+                    let mut selected = current.code.clone();
+                    egui::ComboBox::from_label("🌐")
+                        .selected_text(&current.name)
+                        .show_ui(ui, |ui| {
+                            for option in &languages {
+                                ui.selectable_value(&mut selected, option.code.clone(), &option.name);
+                            }
+                        });
+                    if selected != current.code {
+                        self.set_language(&selected);
+                    }
+
+                    if ui.button(tr("📥 Install Language File...")).clicked() {
+                        self.install_language_file();
+                    }
+                    if ui.button(tr("🔄 Reload")).on_hover_text(tr("Look for new files in the lang folders")).clicked() {
+                        self.reload_languages();
+                    }
+                });
+
+                if !self.language.status.is_empty() {
+                    status::show(ui, &self.language.status, 12.0);
+                }
+
+                if !self.language.problems.is_empty() {
+                    egui::CollapsingHeader::new(trf("⚠ {} translation problem(s)", &[&self.language.problems.len()]))
+                        .id_salt("language_problems")
+                        .show(ui, |ui| {
+                            for problem in &self.language.problems {
+                                ui.label(egui::RichText::new(problem)
+                                    .size(12.0)
+                                    .color(status::color(Level::Warning)));
+                            }
+                        });
+                }
+            }
+
+            if self.policy.allows(Feature::ContextMenu) {
+                let mut folder_context_menu = self.config.folder_context_menu;
+                if ui.checkbox(&mut folder_context_menu, tr("📂 \"Install as TNSM resources\" in folder right-click menu")).changed() {
+                    self.set_folder_context_menu(folder_context_menu);
+                }
+
+                let mut shell_integration = self.config.shell_integration;
+                if ui.checkbox(&mut shell_integration, tr("📦 Open .tnsmpack files with the installer and add taskbar jump list tasks")).changed() {
+                    self.set_shell_integration(shell_integration);
+                }
+            }
+        });
+    }
+
+    pub(super) fn show_settings_export(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new(tr("The game folder, mod version, update channel, DLL URL, resource profile names and proxy DLL name, in one file to set up another PC the same way."))
+            .size(12.0)
+            .color(egui::Color32::GRAY));
+        ui.horizontal(|ui| {
+            if ui.button(tr("📤 Export Settings...")).clicked() {
+                self.export_settings();
+            }
+            ui.add_enabled_ui(!self.is_busy(), |ui| {
+                if ui.button(tr("📥 Import Settings...")).clicked() {
+                    self.import_settings();
+                }
+            });
+        });
+        if !self.settings_status.is_empty() {
+            status::show(ui, &self.settings_status, 12.0);
+        }
+    }
+
+    pub(super) fn show_storage(&mut self, ui: &mut egui::Ui) {
+            let mut retention_changed = false;
+
+            egui::Grid::new("storage_usage").striped(true).show(ui, |ui| {
+                ui.label(egui::RichText::new(tr("Stored")).strong());
+                ui.label(egui::RichText::new(tr("Usage")).strong());
+                ui.label(egui::RichText::new(tr("Max MB")).strong());
+                ui.label(egui::RichText::new(tr("Max days")).strong());
+                ui.end_row();
+
+                for category in Category::ALL {
+                    let usage = self.storage.usage.iter().find(|usage| usage.category == category);
+                    ui.label(tr(category.name()));
+                    ui.label(usage.map_or(tr("…").to_string(), |usage| {
+                        format!("{} ({})", storage::format_size(usage.bytes), usage.items)
+                    }));
+
+                    let limits = self.config.retention.limits_mut(category);
+                    retention_changed |= ui.add(egui::DragValue::new(&mut limits.max_mb).range(0..=100_000))
+                        .on_hover_text(tr("0 means no limit"))
+                        .changed();
+                    retention_changed |= ui.add(egui::DragValue::new(&mut limits.max_age_days).range(0..=3650))
+                        .on_hover_text(tr("0 means no limit"))
+                        .changed();
+                    ui.end_row();
+                }
+            });
+
+            if retention_changed {
+                let _ = self.config.save();
+            }
+
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.storage.is_cleaning_up, |ui| {
+                    if ui.button(tr("🧹 Clean Up Now")).clicked() {
+                        self.clean_up_storage();
+                    }
+                });
+                if ui.button(tr("🔄 Refresh")).clicked() {
+                    self.refresh_storage_usage();
+                }
+            });
+
+            if !self.storage.status.is_empty() {
+                status::show(ui, &self.storage.status, 12.0);
+            }
+    }
+}
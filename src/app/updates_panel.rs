@@ -0,0 +1,357 @@
+// Installer and mod updates, tray mode and actions queued until the game
+// closes
+
+use super::{ModInstallerApp, GAME_CHECK_INTERVAL};
+use crate::status::{self, Level};
+use crate::tray::{Tray, TrayCommand};
+use eframe::egui;
+use std::time::{Duration, Instant};
+use tnsm_installer_core::action_queue::{ActionQueue, QueuedAction};
+use tnsm_installer_core::cancel::CancellationToken;
+use tnsm_installer_core::config::Config;
+use tnsm_installer_core::download::DEFAULT_DLL_URL;
+use tnsm_installer_core::events::Event;
+use tnsm_installer_core::i18n::{tr, trf};
+use tnsm_installer_core::policy::Feature;
+use tnsm_installer_core::self_update::AppUpdate;
+use tnsm_installer_core::{game_process, github, restore_point, self_update};
+
+// Tray mode
+#[derive(Default)]
+pub(super) struct TrayState {
+    pub(super) icon: Option<Tray>,
+    // Stops the periodic update checks
+    pub(super) check_cancel: Option<CancellationToken>,
+    // Release the last update notification was shown for
+    pub(super) notified_release: Option<String>,
+    // Quit was picked in the tray menu, closing the window really exits
+    pub(super) quitting: bool,
+}
+
+// Changes held back until Sky closes
+#[derive(Default)]
+pub(super) struct QueueState {
+    pub(super) actions: ActionQueue,
+    pub(super) game_checked: Option<Instant>,
+    pub(super) ran: usize,
+    pub(super) status: String,
+}
+
+// Updating the installer itself
+#[derive(Default)]
+pub(super) struct AppUpdateState {
+    pub(super) available: Option<AppUpdate>,
+    pub(super) is_updating: bool,
+}
+
+// How often tray mode looks for a new mod release
+const TRAY_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+impl ModInstallerApp {
+    // Quietly, a failed check just means no update is offered
+    pub(super) fn check_for_update(&mut self) {
+        if !self.policy.allows(Feature::UpdateCheck) {
+            return;
+        }
+
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+        let check_mod = self.config.pinned_version.is_none();
+        let beta = self.config.beta_channel;
+
+        self.tasks.spawn(async move {
+            if let Ok(Some(update)) = self_update::check(env!("CARGO_PKG_VERSION"), &cancel).await {
+                events.send(Event::AppUpdate(update));
+            }
+            if check_mod && let Ok(release) = github::fetch_newest_release(beta, &cancel, &|_| {}).await {
+                events.send(Event::LatestRelease(release.tag_name));
+            }
+        });
+    }
+
+    fn update_app(&mut self) {
+        let Some(update) = self.app_update.available.clone() else {
+            return;
+        };
+
+        self.app_update.is_updating = true;
+        self.status_message = trf("⏳ Updating the installer to {}...", &[&update.version]);
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+
+        self.tasks.spawn(async move {
+            events.send(Event::AppUpdated(self_update::apply(&update, &cancel).await));
+        });
+    }
+
+    pub(super) fn show_app_update_notice(&mut self, ui: &mut egui::Ui) {
+        let Some(update) = &self.app_update.available else {
+            return;
+        };
+
+        let version = update.version.clone();
+        ui.vertical_centered(|ui| {
+            ui.label(egui::RichText::new(trf("⬆ ThatNoobSkyApp {} is available (this is {})", &[&version, &env!("CARGO_PKG_VERSION")]))
+                .size(12.0)
+                .color(status::color(Level::Info)));
+            ui.add_enabled_ui(!self.app_update.is_updating && !self.install.running, |ui| {
+                if ui.button(tr("⬆ Update and Restart")).clicked() {
+                    self.update_app();
+                }
+            });
+        });
+        ui.add_space(10.0);
+    }
+
+    // The newest release when the installed one is older. Only known for
+    // installs made through the installer, which record their version.
+    pub(super) fn available_update(&self) -> Option<&str> {
+        let installed = self.config.installed_version.as_deref().filter(|_| self.is_mod_installed)?;
+        self.latest_release.as_deref()
+            .filter(|latest| *latest != installed && self.config.pinned_version.is_none())
+    }
+
+    pub(super) fn update_mod(&mut self) {
+        self.set_dll_url(DEFAULT_DLL_URL.to_string());
+        self.install_mod();
+    }
+
+    pub(super) fn start_tray(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let tray = Tray::new(ctx)?;
+        tray.set_update(self.available_update());
+        self.tray.icon = Some(tray);
+
+        if !self.policy.allows(Feature::UpdateCheck) {
+            return Ok(());
+        }
+        let events = self.events.sender();
+        let cancel = self.tasks.child_token();
+        self.tray.check_cancel = Some(cancel.clone());
+
+        // The check at startup already covers now
+        self.tasks.spawn_background(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(TRAY_CHECK_INTERVAL) => {}
+                    _ = cancel.cancelled() => return,
+                }
+                // The pin and channel may have changed since the last check
+                let config = Config::load();
+                if config.pinned_version.is_some() {
+                    continue;
+                }
+                if let Ok(release) = github::fetch_newest_release(config.beta_channel, &cancel, &|_| {}).await {
+                    events.send(Event::LatestRelease(release.tag_name));
+                }
+            }
+        });
+        Ok(())
+    }
+
+    pub(super) fn set_tray_mode(&mut self, ctx: &egui::Context, enabled: bool) {
+        if enabled {
+            if let Err(e) = self.start_tray(ctx) {
+                self.status_message = trf("❌ {}", &[&e]);
+                return;
+            }
+        } else {
+            self.tray.icon = None;
+            if let Some(cancel) = self.tray.check_cancel.take() {
+                cancel.cancel();
+            }
+        }
+        self.config.tray_mode = enabled;
+        let _ = self.config.save();
+
+        // Starting at login only makes sense with background checks on
+        if !enabled && !self.config.daily_update_check && self.config.launch_at_startup {
+            self.set_launch_at_startup(false);
+        }
+    }
+
+    // Once per release, the tray menu offers it until it's installed
+    pub(super) fn notify_update(&mut self) {
+        let Some(tray) = &self.tray.icon else {
+            return;
+        };
+        let update = self.available_update().map(str::to_string);
+        tray.set_update(update.as_deref());
+
+        let (Some(latest), Some(installed)) = (update, self.config.installed_version.as_deref()) else {
+            return;
+        };
+        if self.tray.notified_release.as_ref() == Some(&latest) {
+            return;
+        }
+        if let Err(e) = tray.notify_update(&latest, installed) {
+            tracing::warn!("{}", e);
+        }
+        self.tray.notified_release = Some(latest);
+    }
+
+    pub(super) fn handle_tray_commands(&mut self, ctx: &egui::Context) {
+        let Some(tray) = &self.tray.icon else {
+            return;
+        };
+        let commands: Vec<_> = std::iter::from_fn(|| tray.try_recv()).collect();
+
+        for command in commands {
+            if command != TrayCommand::Quit {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            match command {
+                TrayCommand::Show => {}
+                TrayCommand::InstallUpdate => {
+                    if self.available_update().is_some() && !self.is_busy() {
+                        self.update_mod();
+                    }
+                }
+                TrayCommand::Quit => {
+                    self.tray.quitting = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            }
+        }
+
+        // Closing the window keeps the app running in the tray
+        if ctx.input(|i| i.viewport().close_requested()) && !self.tray.quitting {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+    }
+
+    // Sky has the mod DLL open while it runs, so changing it then fails or
+    // breaks the running game. Offers to close Sky and queues the change to
+    // run once it has exited. Returns true when the change has to wait.
+    pub(super) fn wait_for_game(&mut self, action: QueuedAction) -> bool {
+        self.queue.game_checked = Some(Instant::now());
+        self.game_running = game_process::is_running();
+        if !self.game_running {
+            return false;
+        }
+
+        let answer = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title(tr("Sky is running"))
+            .set_description(tr("Sky has the mod open while it runs, so it can't be changed right now.\n\nClose Sky and continue once it has exited?"))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if answer != rfd::MessageDialogResult::Yes {
+            self.status_message = tr("⚠ Close Sky before changing the mod").to_string();
+            return true;
+        }
+
+        match game_process::close() {
+            Ok(()) => {
+                self.queue_action(action);
+                self.status_message = tr("⏳ Waiting for Sky to close...").to_string();
+            }
+            Err(e) => self.status_message = trf("❌ {}", &[&e]),
+        }
+        true
+    }
+
+    pub(super) fn queue_action(&mut self, action: QueuedAction) {
+        self.queue.actions.push(action);
+        if let Err(e) = self.queue.actions.save() {
+            self.queue.status = trf("❌ {}", &[&e]);
+        }
+    }
+
+    // Runs queued changes one at a time once Sky has exited
+    pub(super) fn run_action_queue(&mut self, ctx: &egui::Context) {
+        if self.queue.game_checked.is_none_or(|checked| checked.elapsed() >= GAME_CHECK_INTERVAL) {
+            self.queue.game_checked = Some(Instant::now());
+            self.game_running = game_process::is_running();
+        }
+        if !self.queue.actions.actions.is_empty() || self.queue.ran > 0 {
+            ctx.request_repaint_after(GAME_CHECK_INTERVAL);
+        }
+        if self.game_running || self.is_busy() {
+            return;
+        }
+
+        match self.queue.actions.pop() {
+            Some(action) => {
+                let _ = self.queue.actions.save();
+                self.queue.ran += 1;
+                self.queue.status = trf("⏳ {}...", &[&action.describe()]);
+                self.run_queued_action(action);
+            }
+            None if self.queue.ran > 0 => {
+                self.queue.status = trf("✅ Sky closed, applied {} queued change(s)", &[&self.queue.ran]);
+                self.queue.ran = 0;
+                ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Informational));
+            }
+            None => {}
+        }
+    }
+
+    fn run_queued_action(&mut self, action: QueuedAction) {
+        match action {
+            QueuedAction::UpdateMod { dll_url } => {
+                self.dll_url = dll_url;
+                self.install_mod();
+            }
+            QueuedAction::Uninstall => {
+                if self.is_mod_installed {
+                    self.uninstall_mod();
+                }
+            }
+            QueuedAction::RemovePack { restore_point, label } => {
+                match restore_point::list().into_iter().find(|point| point.dir == restore_point) {
+                    Some(point) => self.start_revert(point, true),
+                    None => self.queue.status = trf("⚠ The restore point for {} no longer exists", &[&label]),
+                }
+            }
+        }
+    }
+
+    pub(super) fn show_action_queue(&mut self, ui: &mut egui::Ui) {
+        if !self.game_running && self.queue.actions.actions.is_empty() && self.queue.status.is_empty() {
+            return;
+        }
+
+        ui.vertical_centered(|ui| {
+            if self.game_running {
+                ui.label(egui::RichText::new(tr("🎮 Sky is running. Changes to the mod can wait until it's closed."))
+                    .size(12.0)
+                    .color(status::color(Level::Warning)));
+
+                ui.horizontal(|ui| {
+                    ui.add_enabled_ui(self.game_path_writable, |ui| {
+                        if ui.button(tr("⏱ Update After Sky Closes")).clicked() {
+                            self.queue_action(QueuedAction::UpdateMod { dll_url: self.dll_url.clone() });
+                        }
+                        if self.is_mod_installed
+                            && self.policy.allows(Feature::Uninstall)
+                            && ui.button(tr("⏱ Uninstall After Sky Closes")).clicked()
+                        {
+                            self.queue_action(QueuedAction::Uninstall);
+                        }
+                    });
+                });
+            }
+
+            let mut removed = None;
+            for (index, action) in self.queue.actions.actions.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(trf("⏱ {}", &[&action.describe()])).size(12.0));
+                    if ui.small_button("✖").on_hover_text(tr("Remove from queue")).clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                self.queue.actions.actions.remove(index);
+                let _ = self.queue.actions.save();
+            }
+
+            if !self.queue.status.is_empty() {
+                status::show(ui, &self.queue.status, 12.0);
+            }
+        });
+        ui.add_space(10.0);
+    }
+}
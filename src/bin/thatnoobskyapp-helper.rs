@@ -0,0 +1,30 @@
+#![windows_subsystem = "windows"]
+
+// Started by the installer with administrator approval to change a game
+// folder the user can't write to, see tnsm_installer_core::elevated. It only
+// serves the pipe it was given and exits when the installer closes it.
+
+use clap::Parser;
+use std::path::PathBuf;
+use tnsm_installer_core::elevated;
+
+#[derive(Parser)]
+#[command(name = "thatnoobskyapp-helper", about = "Elevated file helper for ThatNoobSkyApp")]
+struct Args {
+    /// Pipe created by the installer
+    #[arg(long)]
+    pipe: String,
+
+    /// Game directory the helper may change
+    #[arg(long)]
+    game_path: PathBuf,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(e) = elevated::serve(&args.pipe, &args.game_path) {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,205 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use tnsm_installer_core::download::DEFAULT_DLL_URL;
+use tnsm_installer_core::jumplist::JumpTask;
+
+#[derive(Parser)]
+#[command(name = "ThatNoobSkyApp", about = "Installer for ThatNoobSkyMod")]
+pub struct Cli {
+    /// Check for a newer mod release instead of opening the installer
+    #[arg(long)]
+    pub check_updates: bool,
+
+    /// Only notify when an update is available
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// Start with the window minimized, used when launched at login
+    #[arg(long)]
+    pub minimized: bool,
+
+    /// Jump list task to run: install, launch-game, open-logs or open-resources
+    #[arg(long, value_name = "TASK", value_parser = JumpTask::from_arg)]
+    pub task: Option<JumpTask>,
+
+    /// Resource folder to import, used by the Explorer context menu entry
+    #[arg(long, value_name = "FOLDER")]
+    pub import: Option<PathBuf>,
+
+    /// Install silently as described by a setup.toml answer file and print the result as JSON
+    #[arg(long, value_name = "FILE")]
+    pub answer_file: Option<PathBuf>,
+
+    /// Started by the previous version after updating the installer
+    #[arg(long, hide = true)]
+    pub after_update: bool,
+
+    /// Print the result of install, uninstall or import-resources as JSON, progress goes to stderr
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Resource pack (.tnsmpack) to import, passed when the file is opened from Explorer
+    #[arg(value_name = "PACK")]
+    pub pack: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Download and install the mod without opening the installer window
+    Install(InstallArgs),
+
+    /// Remove the mod DLL without opening the installer window
+    Uninstall(UninstallArgs),
+
+    /// Import a resource folder, .zip or .tnsmpack without opening the installer window
+    ImportResources(ImportResourcesArgs),
+
+    /// Export the log of changes the installer made as CSV or JSON, picked by the file extension
+    ExportAuditLog(ExportAuditLogArgs),
+
+    /// Measure download, disk copy and hashing speed and print a report to share in bug reports
+    Benchmark(BenchmarkArgs),
+
+    /// Upload release assets, checksums and the signed mirror index (maintainers only)
+    #[command(hide = true)]
+    PublishMirror(PublishMirrorArgs),
+
+    /// Write a detached .sig next to each release asset, to attach to the GitHub release (maintainers only)
+    #[command(hide = true)]
+    SignAssets(SignAssetsArgs),
+
+    /// Write winget and Scoop manifests for an installer release (maintainers only)
+    #[command(hide = true)]
+    PackageManifests(PackageManifestsArgs),
+
+    /// Print the game_files.json entry for a verified game install (maintainers only)
+    #[command(hide = true)]
+    HashGameFiles(HashGameFilesArgs),
+}
+
+#[derive(Args)]
+pub struct PublishMirrorArgs {
+    /// Release version the assets belong to, e.g. v1.2.0
+    #[arg(long)]
+    pub version: String,
+
+    /// Base URL of the WebDAV endpoint
+    #[arg(long)]
+    pub endpoint: String,
+
+    #[arg(long)]
+    pub username: Option<String>,
+
+    #[arg(long, env = "TNSM_MIRROR_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+
+    /// File containing the hex-encoded ed25519 signing key
+    #[arg(long)]
+    pub signing_key: Option<PathBuf>,
+
+    /// Release assets to publish
+    #[arg(required = true)]
+    pub assets: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SignAssetsArgs {
+    /// File containing the hex-encoded ed25519 signing key
+    #[arg(long)]
+    pub signing_key: PathBuf,
+
+    /// Release assets to sign
+    #[arg(required = true)]
+    pub assets: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct InstallArgs {
+    /// Game directory, detected from the Steam install when omitted
+    #[arg(long)]
+    pub game_path: Option<PathBuf>,
+
+    /// Where to download the mod DLL from
+    #[arg(long, default_value = DEFAULT_DLL_URL)]
+    pub dll_url: String,
+}
+
+#[derive(Args)]
+pub struct UninstallArgs {
+    /// Game directory, detected from the Steam install when omitted
+    #[arg(long)]
+    pub game_path: Option<PathBuf>,
+
+    /// Also delete the TNSM Resources folder
+    #[arg(long)]
+    pub remove_resources: bool,
+}
+
+#[derive(Args)]
+pub struct ImportResourcesArgs {
+    /// Game directory, detected from the Steam install when omitted
+    #[arg(long)]
+    pub game_path: Option<PathBuf>,
+
+    /// Resource folder, .zip or .tnsmpack file, or a link to a .zip
+    pub source: PathBuf,
+
+    /// Also delete files an earlier import of this source wrote that it no longer has
+    #[arg(long)]
+    pub remove_missing: bool,
+}
+
+#[derive(Args)]
+pub struct PackageManifestsArgs {
+    /// Installer release tag (app-v<version>) to describe, defaults to the newest one
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Directory the winget/ and scoop/ manifests are written to
+    #[arg(long, default_value = "manifests")]
+    pub out: PathBuf,
+}
+
+#[derive(Args)]
+pub struct BenchmarkArgs {
+    /// Game directory to test copying into, detected from the Steam install when omitted
+    #[arg(long)]
+    pub game_path: Option<PathBuf>,
+
+    /// Don't download the mod, e.g. on a metered connection
+    #[arg(long)]
+    pub skip_download: bool,
+}
+
+#[derive(Args)]
+pub struct ExportAuditLogArgs {
+    /// File to write, e.g. audit.csv or audit.json
+    pub path: PathBuf,
+}
+
+#[derive(Args)]
+pub struct HashGameFilesArgs {
+    /// Game directory, freshly verified through Steam
+    #[arg(long)]
+    pub game_path: PathBuf,
+
+    /// Key files to hash, relative to the game directory
+    #[arg(default_value = "Sky.exe")]
+    pub files: Vec<String>,
+}
+
+// The GUI subsystem has no console, so borrow the parent's when run from a terminal
+#[cfg(windows)]
+pub fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn attach_console() {}
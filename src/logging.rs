@@ -0,0 +1,115 @@
+// Installer log: everything traced by the app and the core crate goes to
+// installer.log in the app directory and, for the Logs panel, into a buffer
+// of recent lines. The previous log is kept as installer.log.old once it
+// grows past LOG_ROTATE_SIZE.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use tnsm_installer_core::{config, timestamp};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+const LOG_ROTATE_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_LINES: usize = 2000;
+
+static LINES: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone)]
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+pub struct LogLine {
+    pub level: Level,
+    // e.g. 20261016-142530
+    pub timestamp: String,
+    pub message: String,
+}
+
+impl LogLine {
+    #[cfg(feature = "gui")]
+    pub fn to_text(&self) -> String {
+        format!("{} {:>5} {}", timestamp::display(&self.timestamp), self.level, self.message)
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+struct BufferLayer;
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let mut lines = LINES.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() >= MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(LogLine {
+            level: *event.metadata().level(),
+            timestamp: timestamp::now(),
+            message: visitor.0,
+        });
+    }
+}
+
+fn open_log_file() -> Option<std::fs::File> {
+    let path = config::installer_log_path()?;
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > LOG_ROTATE_SIZE) {
+        let _ = std::fs::rename(&path, path.with_extension("log.old"));
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()
+}
+
+// Logging is best effort, the installer runs the same without a log file
+pub fn init() {
+    let file_layer = open_log_file().map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(Mutex::new(file))
+            .with_ansi(false)
+    });
+
+    // Debug for the installer itself, only problems from the HTTP stack
+    let filter = Targets::new()
+        .with_target("thatnoobskyapp", Level::DEBUG)
+        .with_target("tnsm_installer_core", Level::DEBUG)
+        .with_default(Level::WARN);
+
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(BufferLayer)
+        .try_init();
+
+    tracing::info!("ThatNoobSkyApp {} started", env!("CARGO_PKG_VERSION"));
+}
+
+// Oldest first, only lines at `max_level` or more severe
+#[cfg(feature = "gui")]
+pub fn lines(max_level: Level) -> Vec<LogLine> {
+    LINES.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|line| line.level <= max_level)
+        .cloned()
+        .collect()
+}
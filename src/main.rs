@@ -1,467 +1,379 @@
-#![windows_subsystem = "windows"]
-
-use eframe::{egui, App, Frame};
-use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver};
+#![cfg_attr(feature = "gui", windows_subsystem = "windows")]
+
+#[cfg(feature = "gui")]
+mod app;
+mod cli;
+mod logging;
+#[cfg(feature = "gui")]
+mod status;
+#[cfg(feature = "gui")]
+mod theme;
+#[cfg(feature = "gui")]
+mod tray;
+mod update_check;
+
+use clap::Parser;
+use cli::{Cli, Command};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tnsm_installer_core::answer_file::{AnswerFile, PackResult, Report};
+use tnsm_installer_core::cancel::CancellationToken;
+use tnsm_installer_core::config::{self, Config};
+use tnsm_installer_core::download::{self, DEFAULT_DLL_URL};
+use tnsm_installer_core::install::InstallSettings;
+use tnsm_installer_core::jumplist::JumpTask;
+use tnsm_installer_core::pipeline::InstallStep;
+use tnsm_installer_core::policy::{Feature, Policy};
+use tnsm_installer_core::{audit, benchmark, detect, game_files, game_process, github, install, instance, machine, manifest, mirror, pack, packaging, resources, self_update, shell, storage};
+
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::error!("{}", e);
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    })
+}
 
-enum InstallStatus {
-    Success(String),
-    Error(String),
+fn block_on<T>(future: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+    tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to start async runtime: {}", e))
+        .and_then(|runtime| runtime.block_on(future))
 }
 
-struct ModInstallerApp {
-    dll_url: String,
-    status_message: String,
-    is_installing: bool,
-    steam_path: Option<PathBuf>,
-    game_path: Option<PathBuf>,
-    runtime: tokio::runtime::Runtime,
-    status_rx: Option<Receiver<InstallStatus>>,
-    show_manual_input: bool,
-    import_status: String,
-    is_mod_installed: bool,
+fn detect_game_path() -> Option<PathBuf> {
+    // The folder the installer window found or was pointed at last time
+    Config::load().game_path.filter(|path| detect::is_game_directory(path))
+        .or_else(|| detect::detect_steam_path().and_then(|steam| detect::find_game_directory(&steam)))
+        // Another account on this PC may have found it already
+        .or_else(|| machine::latest().map(|record| record.game_path).filter(|path| detect::is_game_directory(path)))
 }
 
-impl Default for ModInstallerApp {
-    fn default() -> Self {
-        let mut app = Self {
-            dll_url: String::from("https://github.com/alvindimas05/ThatNoobSkyMod/releases/latest/download/TNSM.dll"),
-            status_message: String::from("Ready to install"),
-            is_installing: false,
-            steam_path: None,
-            game_path: None,
-            runtime: tokio::runtime::Runtime::new().unwrap(),
-            status_rx: None,
-            show_manual_input: false,
-            import_status: String::new(),
-            is_mod_installed: false,
-        };
-        app.detect_steam_path();
-        app.check_mod_installed();
-        app
-    }
+pub fn open_log_dir() -> Result<(), String> {
+    let log_dir = config::log_dir().ok_or("Config directory not found")?;
+    shell::open_folder(&log_dir)
 }
 
-impl ModInstallerApp {
-    fn detect_steam_path(&mut self) {
-        // Common Steam installation paths
-        let possible_paths = vec![
-            PathBuf::from("C:\\Program Files (x86)\\Steam"),
-            PathBuf::from("C:\\Program Files\\Steam"),
-        ];
-
-        for path in possible_paths {
-            if path.exists() {
-                self.steam_path = Some(path.clone());
-                self.find_game_directory(&path);
-                break;
-            }
+// Returns false when the task needs the installer window after all
+fn run_task_without_window(task: JumpTask) -> bool {
+    let result = match task {
+        JumpTask::LaunchGame => shell::launch_game(detect_game_path().as_deref()),
+        JumpTask::OpenLogs => open_log_dir(),
+        JumpTask::OpenResources => {
+            let Some(game_path) = detect_game_path() else {
+                return false;
+            };
+            shell::open_folder(&resources::resources_path(&game_path))
         }
+        JumpTask::Install => return false,
+    };
 
-        if self.steam_path.is_none() {
-            self.status_message = "⚠ Steam directory not found. Please browse for path.".to_string();
-            self.show_manual_input = true;
-        }
-    }
+    result.is_ok()
+}
 
-    fn find_game_directory(&mut self, steam_path: &PathBuf) {
-        // Check common Steam library folders
-        let library_folders = vec![
-            steam_path.join("steamapps\\common\\Sky Children of the Light"),
-            PathBuf::from("D:\\SteamLibrary\\steamapps\\common\\Sky Children of the Light"),
-            PathBuf::from("E:\\SteamLibrary\\steamapps\\common\\Sky Children of the Light"),
-        ];
-
-        for folder in library_folders {
-            if folder.exists() {
-                self.game_path = Some(folder);
-                self.status_message = format!("✓ Game found: {}", self.game_path.as_ref().unwrap().display());
-                self.show_manual_input = false;
-                return;
-            }
-        }
+// Installs the DLL and records it the same way the installer window does
+fn install_to(game_path: &Path, dll_url: &str, log: fn(&str)) -> Result<Option<String>, String> {
+    game_process::ensure_not_running()?;
+    let on_step = move |step: &dyn InstallStep| log(&format!("⏳ {}...", step.description()));
+    let on_rate_limited = |seconds| log(&format!("⏳ Rate limited by GitHub, retrying in {}s", seconds));
+    let cancel = CancellationToken::new();
+    let mut config = Config::load();
+
+    let dll_url = block_on(download::channel_dll_url(dll_url, config.beta_channel, &cancel, &on_rate_limited))?;
+    let version = block_on(install::download_and_install(
+        &dll_url,
+        game_path,
+        &InstallSettings::from_config(&config),
+        &cancel,
+        &on_rate_limited,
+        &|_| {},
+        on_step,
+    ))?;
+
+    config.installed_version = version.clone();
+    let _ = config.save();
+    let _ = machine::record(game_path, true, version.clone());
+
+    Ok(version)
+}
 
-        self.status_message = "⚠ Sky Children of the Light not found in Steam directories".to_string();
-        self.show_manual_input = true;
+// An admin policy overrides whatever was passed on the command line
+fn headless_game_path(policy: &Policy, game_path: Option<PathBuf>, report: &mut Report) -> Result<PathBuf, String> {
+    let game_path = policy.game_path.clone()
+        .or(game_path)
+        .or_else(detect_game_path)
+        .ok_or("Game directory not found, pass --game-path")?;
+    report.game_path = Some(game_path.clone());
+    if !detect::is_game_directory(&game_path) {
+        return Err(format!("{} is not the game directory", game_path.display()));
     }
+    Ok(game_path)
+}
 
-    fn check_mod_installed(&mut self) {
-        if let Some(game_path) = &self.game_path {
-            let dll_path = game_path.join("powrprof.dll");
-            self.is_mod_installed = dll_path.exists();
-        }
+fn install_headless(args: cli::InstallArgs, report: &mut Report, log: fn(&str)) -> Result<(), String> {
+    let policy = Policy::load().unwrap_or_default();
+    let game_path = headless_game_path(&policy, args.game_path, report)?;
+
+    // A pinned version only gives way to an explicitly passed URL
+    let config = Config::load();
+    let dll_url = policy.pinned_dll_url()
+        .or_else(|| config.pinned_version.as_deref().filter(|_| args.dll_url == DEFAULT_DLL_URL).map(download::release_dll_url))
+        .unwrap_or(args.dll_url);
+    log(&format!("📂 {}", game_path.display()));
+
+    report.installed_version = install_to(&game_path, &dll_url, log)?;
+    report.mod_installed = true;
+    match &report.installed_version {
+        Some(version) => log(&format!("✅ ThatNoobSkyMod {} installed", version)),
+        None => log("✅ Mod installed"),
     }
+    Ok(())
+}
 
-    fn browse_for_path(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .set_title("Select Steam or Game Directory")
-            .pick_folder()
-        {
-            // Check if it's a Steam directory
-            if path.join("steamapps").exists() {
-                self.steam_path = Some(path.clone());
-                self.find_game_directory(&path);
-            }
-            // Check if it's directly the game directory
-            else if path.exists() && (path.join("Sky.exe").exists() || path.ends_with("Sky Children of the Light")) {
-                self.game_path = Some(path.clone());
-                self.status_message = format!("✓ Game path set: {}", path.display());
-                self.show_manual_input = false;
-            }
-            else {
-                self.status_message = "❌ Invalid path. Please select Steam folder or game folder.".to_string();
-            }
-            self.check_mod_installed();
-        }
+fn uninstall_headless(args: cli::UninstallArgs, report: &mut Report, log: fn(&str)) -> Result<(), String> {
+    let policy = Policy::load().unwrap_or_default();
+    if !policy.allows(Feature::Uninstall) {
+        return Err("Uninstalling the mod is disabled on this PC".to_string());
+    }
+    let game_path = headless_game_path(&policy, args.game_path, report)?;
+    let mut config = Config::load();
+    report.mod_installed = install::is_mod_installed(&game_path, config.proxy_dll);
+    game_process::ensure_not_running()?;
+
+    if report.mod_installed {
+        install::uninstall(&game_path, config.proxy_dll)?;
+        report.mod_installed = false;
+
+        config.installed_version = None;
+        let _ = config.save();
+        let _ = machine::record(&game_path, false, None);
+        manifest::forget_dll(&game_path);
+        log("✅ Mod uninstalled");
+    } else {
+        log("✓ Mod is not installed");
     }
 
-    fn browse_and_import_resources(&mut self) {
-        if self.game_path.is_none() {
-            self.import_status = "❌ Game directory not set. Cannot import resources.".to_string();
-            return;
-        }
+    if args.remove_resources {
+        let (files, bytes) = block_on(resources::remove_all(game_path.clone()))?;
+        log(&format!("✅ Removed {} files ({}) of {}", files, storage::format_size(bytes), resources::RESOURCES_DIR_NAME));
+    }
+    Ok(())
+}
 
-        if let Some(source_folder) = rfd::FileDialog::new()
-            .set_title("Select TSM Resources Folder")
-            .pick_folder()
-        {
-            self.import_status = "⏳ Importing resources...".to_string();
-            
-            let game_path = self.game_path.as_ref().unwrap().clone();
-            let dest_path = game_path.join("TNSM Resources");
-            
-            match self.copy_resources_sync(&source_folder, &dest_path) {
-                Ok(_) => {
-                    self.import_status = "✅ Resources imported successfully!".to_string();
-                }
-                Err(e) => {
-                    self.import_status = format!("❌ Import failed: {}", e);
-                }
-            }
-        }
+fn import_resources_headless(args: cli::ImportResourcesArgs, report: &mut Report, log: fn(&str)) -> Result<(), String> {
+    let policy = Policy::load().unwrap_or_default();
+    if !policy.allows(Feature::Import) {
+        return Err("Importing resources is disabled on this PC".to_string());
     }
+    let game_path = headless_game_path(&policy, args.game_path, report)?;
+    report.mod_installed = install::is_mod_installed(&game_path, Config::load().proxy_dll);
 
-    fn copy_resources_sync(&self, source: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-        // Create destination directory if it doesn't exist
-        std::fs::create_dir_all(dest)
-            .map_err(|e| format!("Failed to create destination directory: {}", e))?;
-
-        // Read source directory
-        let entries = std::fs::read_dir(source)
-            .map_err(|e| format!("Failed to read source directory: {}", e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-            let source_path = entry.path();
-            let file_name = entry.file_name();
-            let dest_path = dest.join(&file_name);
-
-            if source_path.is_dir() {
-                // Recursively copy directories
-                self.copy_dir_recursive(&source_path, &dest_path)?;
-            } else {
-                // Copy files
-                std::fs::copy(&source_path, &dest_path)
-                    .map_err(|e| format!("Failed to copy {}: {}", file_name.to_string_lossy(), e))?;
-            }
-        }
+    let result = import_headless(&args.source, &game_path, args.remove_missing, log);
+    report.packs.push(PackResult { imported: result.is_ok(), error: result.clone().err(), path: args.source });
+    result.map(|_| log("✅ Resources imported"))
+}
 
-        Ok(())
+// Exit code 0 on success and 1 otherwise. With --json the same report as
+// for answer files is the only thing on stdout.
+fn exit_with_report(mut report: Report, result: Result<(), String>, json: bool) -> ! {
+    report.error = result.err();
+    report.success = report.error.is_none() && report.packs.iter().all(|pack| pack.imported);
+
+    if json {
+        println!("{}", report.to_json());
+    } else if let Some(e) = &report.error {
+        eprintln!("❌ {}", e);
     }
+    std::process::exit(if report.success { 0 } else { 1 });
+}
 
-    fn copy_dir_recursive(&self, source: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-        std::fs::create_dir_all(dest)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
-
-        let entries = std::fs::read_dir(source)
-            .map_err(|e| format!("Failed to read directory: {}", e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-            let source_path = entry.path();
-            let file_name = entry.file_name();
-            let dest_path = dest.join(&file_name);
-
-            if source_path.is_dir() {
-                self.copy_dir_recursive(&source_path, &dest_path)?;
-            } else {
-                std::fs::copy(&source_path, &dest_path)
-                    .map_err(|e| format!("Failed to copy file: {}", e))?;
-            }
-        }
+// Post-install scripts are skipped, they only run after the user has seen them
+fn import_headless(source: &Path, game_path: &Path, remove_missing: bool, log: fn(&str)) -> Result<(), String> {
+    log(&format!("⏳ Importing {}...", source.display()));
+
+    let cancel = CancellationToken::new();
+    let link = source.to_str().filter(|source| source.starts_with("https://") || source.starts_with("http://"));
+    let folder = if let Some(url) = link {
+        block_on(pack::download(url, &cancel, &|_| {}))?
+    } else if pack::is_archive(source) {
+        block_on(pack::extract(source.to_path_buf(), cancel.clone()))?
+    } else {
+        source.to_path_buf()
+    };
 
-        Ok(())
-    }
+    let result = block_on(resources::import_resources(&folder, game_path, remove_missing, &cancel, &|_| {}));
+    pack::remove_extracted(&folder);
+    result.map(|summary| log(&format!("{}: {}", source.display(), summary.describe())))
+}
 
-    fn install_mod(&mut self, ctx: egui::Context) {
-        if self.dll_url.is_empty() {
-            self.status_message = "❌ Please enter a DLL URL first".to_string();
-            return;
-        }
+fn apply_answer_file(path: &Path, report: &mut Report) -> Result<(), String> {
+    let answers = AnswerFile::load(path)?;
+    let policy = Policy::load().unwrap_or_default();
+    // stdout is reserved for the JSON report
+    let log: fn(&str) = |line| eprintln!("{}", line);
+
+    let game_path = policy.game_path.clone()
+        .or(answers.game_path.clone())
+        .or_else(detect_game_path)
+        .ok_or("Game directory not found, set game_path in the answer file")?;
+    report.game_path = Some(game_path.clone());
+    if !detect::is_game_directory(&game_path) {
+        return Err(format!("{} is not the game directory", game_path.display()));
+    }
 
-        if self.game_path.is_none() {
-            self.status_message = "❌ Game directory not found. Cannot install.".to_string();
-            return;
-        }
+    if answers.install_mod {
+        let dll_url = policy.pinned_dll_url().unwrap_or_else(|| answers.dll_url());
+        report.installed_version = install_to(&game_path, &dll_url, log)?;
+        report.mod_installed = true;
+    }
 
-        self.is_installing = true;
-        self.status_message = "⏳ Downloading and installing...".to_string();
+    for pack in answers.packs {
+        let result = if policy.allows(Feature::Import) {
+            import_headless(&pack, &game_path, false, log)
+        } else {
+            Err("Importing resources is disabled on this PC".to_string())
+        };
+        report.packs.push(PackResult { imported: result.is_ok(), error: result.err(), path: pack });
+    }
 
-        let game_path = self.game_path.as_ref().unwrap().clone();
-        let dll_url = self.dll_url.clone();
+    Ok(())
+}
 
-        let (tx, rx) = channel();
-        self.status_rx = Some(rx);
+fn run_answer_file(path: &Path) -> Report {
+    let mut report = Report::default();
+    if let Err(e) = apply_answer_file(path, &mut report) {
+        report.error = Some(e);
+    }
+    report.success = report.error.is_none() && report.packs.iter().all(|pack| pack.imported);
+    report
+}
 
-        self.runtime.spawn(async move {
-            let result = download_and_install_async(&dll_url, &game_path).await;
+fn main() {
+    cli::attach_console();
+    let cli = Cli::parse();
+    logging::init();
 
-            let status = match result {
-                Ok(_) => InstallStatus::Success("✅ Mod installed successfully! Launch the game to use it.".to_string()),
-                Err(e) => InstallStatus::Error(format!("❌ Installation failed: {}", e)),
-            };
+    // Requests go through the proxy and retry as set in the installer window
+    let network = Config::load();
+    if let Err(e) = github::configure(&network.proxy, network.download_attempts) {
+        tracing::warn!("{}", e);
+    }
 
-            let _ = tx.send(status);
-            ctx.request_repaint();
-        });
+    if let Some(path) = &cli.answer_file {
+        let report = run_answer_file(path);
+        println!("{}", report.to_json());
+        std::process::exit(if report.success { 0 } else { 1 });
     }
 
-    fn uninstall_mod(&mut self) {
-        if self.game_path.is_none() {
-            self.status_message = "❌ Game directory not found. Cannot uninstall.".to_string();
+    // stdout is reserved for the JSON report with --json
+    let log: fn(&str) = if cli.json {
+        |line| eprintln!("{}", line)
+    } else {
+        |line| println!("{}", line)
+    };
+
+    match cli.command {
+        Some(Command::PublishMirror(args)) => {
+            exit_on_error(mirror::publish(mirror::PublishOptions {
+                version: args.version,
+                assets: args.assets,
+                endpoint: args.endpoint,
+                username: args.username,
+                password: args.password,
+                signing_key: args.signing_key,
+            }, &|message| println!("{}", message)));
             return;
         }
-
-        let dll_path = self.game_path.as_ref().unwrap().join("powrprof.dll");
-        
-        if !dll_path.exists() {
-            self.status_message = "⚠ Mod is not installed.".to_string();
+        Some(Command::SignAssets(args)) => {
+            let paths = exit_on_error(mirror::sign_assets(&args.assets, &args.signing_key));
+            paths.iter().for_each(|path| println!("✓ {}", path.display()));
             return;
         }
-
-        match std::fs::remove_file(&dll_path) {
-            Ok(_) => {
-                self.status_message = "✅ Mod uninstalled successfully!".to_string();
-                self.is_mod_installed = false;
-            }
-            Err(e) => {
-                self.status_message = format!("❌ Uninstallation failed: {}", e);
-            }
+        Some(Command::PackageManifests(args)) => {
+            let paths = exit_on_error(block_on(packaging::write_manifests(
+                args.tag.as_deref(),
+                &args.out,
+                &CancellationToken::new(),
+            )));
+            paths.iter().for_each(|path| println!("✓ {}", path.display()));
+            return;
         }
-    }
-
-    fn check_install_status(&mut self) {
-        if let Some(rx) = &self.status_rx {
-            if let Ok(status) = rx.try_recv() {
-                match status {
-                    InstallStatus::Success(msg) => {
-                        self.status_message = msg;
-                        self.is_installing = false;
-                        self.is_mod_installed = true;
-                        self.status_rx = None;
-                    }
-                    InstallStatus::Error(msg) => {
-                        self.status_message = msg;
-                        self.is_installing = false;
-                        self.status_rx = None;
-                    }
-                }
-            }
+        Some(Command::HashGameFiles(args)) => {
+            let build = exit_on_error(game_files::hash_build(&args.game_path, &args.files));
+            println!("{}", exit_on_error(build.to_json()));
+            return;
         }
+        Some(Command::Benchmark(args)) => {
+            let game_path = exit_on_error(args.game_path
+                .or_else(detect_game_path)
+                .ok_or("Game directory not found, pass --game-path".to_string()));
+            let report = exit_on_error(block_on(benchmark::run(
+                env!("CARGO_PKG_VERSION"),
+                &game_path,
+                args.skip_download,
+                &CancellationToken::new(),
+                &|step| eprintln!("⏳ {}...", step),
+            )));
+            println!("{}", report.to_text());
+            return;
+        }
+        Some(Command::ExportAuditLog(args)) => {
+            let count = exit_on_error(audit::export(&args.path));
+            println!("✓ {} entries written to {}", count, args.path.display());
+            return;
+        }
+        Some(Command::Install(args)) => {
+            let mut report = Report::default();
+            let result = install_headless(args, &mut report, log);
+            exit_with_report(report, result, cli.json);
+        }
+        Some(Command::Uninstall(args)) => {
+            let mut report = Report::default();
+            let result = uninstall_headless(args, &mut report, log);
+            exit_with_report(report, result, cli.json);
+        }
+        Some(Command::ImportResources(args)) => {
+            let mut report = Report::default();
+            let result = import_resources_headless(args, &mut report, log);
+            exit_with_report(report, result, cli.json);
+        }
+        None => {}
     }
-}
 
-async fn download_and_install_async(dll_url: &str, game_path: &PathBuf) -> Result<(), String> {
-    let response = reqwest::get(dll_url)
-        .await
-        .map_err(|e| format!("Download failed: {}", e))?;
+    if cli.check_updates && !update_check::run(cli.quiet) {
+        return;
+    }
 
-    let dll_bytes = response.bytes()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    // The old version is still closing; once it has, its exe can go
+    if cli.after_update {
+        instance::wait_until_closed(Duration::from_secs(10));
+    }
+    self_update::clean_up();
+
+    // Paths are resolved here since the running instance has its own working directory
+    let absolute = |path: PathBuf| std::path::absolute(&path).unwrap_or(path);
+    let request = match (cli.task, cli.import, cli.pack) {
+        (Some(task), _, _) => Some(instance::Request::RunTask(task)),
+        (None, Some(folder), _) => Some(instance::Request::ImportFolder(absolute(folder))),
+        (None, None, Some(pack)) => Some(instance::Request::OpenPack(absolute(pack))),
+        (None, None, None) => None,
+    };
+    if instance::hand_off(request.as_ref().unwrap_or(&instance::Request::Focus)) {
+        return;
+    }
 
-    let dll_path = game_path.join("powrprof.dll");
-    tokio::fs::write(&dll_path, dll_bytes)
-        .await
-        .map_err(|e| format!("Failed to write DLL: {}", e))?;
+    // Quick jump list tasks don't need a window when the installer isn't open
+    if let Some(instance::Request::RunTask(task)) = &request
+        && run_task_without_window(*task)
+    {
+        return;
+    }
 
-    Ok(())
-}
+    #[cfg(feature = "gui")]
+    exit_on_error(app::run(request, cli.minimized).map_err(|e| e.to_string()));
 
-impl App for ModInstallerApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
-        // Check for status updates from async task
-        self.check_install_status();
-
-        let mut style = (*ctx.style()).clone();
-        style.visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(30, 30, 40);
-        style.visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(50, 50, 65);
-        style.visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(70, 70, 90);
-        style.visuals.widgets.active.bg_fill = egui::Color32::from_rgb(90, 90, 120);
-        ctx.set_style(style);
-
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.add_space(20.0);
-
-                // Title
-                ui.heading(egui::RichText::new("🌟 ThatNoobSkyApp")
-                    .size(28.0)
-                    .color(egui::Color32::from_rgb(135, 206, 250)));
-
-                ui.add_space(10.0);
-                ui.label(egui::RichText::new("Installer for ThatNoobSkyMod")
-                    .size(14.0)
-                    .color(egui::Color32::GRAY));
-
-                ui.add_space(30.0);
-            });
-
-            // Status Information
-            ui.group(|ui| {
-                ui.set_width(470.0);
-                ui.vertical(|ui| {
-                    ui.label(egui::RichText::new("📍 Installation Status:").strong());
-                    ui.add_space(5.0);
-
-                    let status_color = if self.status_message.contains("✓") || self.status_message.contains("✅") {
-                        egui::Color32::from_rgb(100, 255, 100)
-                    } else if self.status_message.contains("⚠") {
-                        egui::Color32::from_rgb(255, 200, 100)
-                    } else if self.status_message.contains("❌") {
-                        egui::Color32::from_rgb(255, 100, 100)
-                    } else {
-                        egui::Color32::WHITE
-                    };
-
-                    ui.label(egui::RichText::new(&self.status_message)
-                        .color(status_color));
-
-                    if let Some(game_path) = &self.game_path {
-                        ui.add_space(5.0);
-                        ui.label(egui::RichText::new(format!("📂 {}", game_path.display()))
-                            .size(11.0)
-                            .color(egui::Color32::GRAY));
-                    }
-
-                    if self.is_mod_installed {
-                        ui.add_space(5.0);
-                        ui.label(egui::RichText::new("🔧 Mod is currently installed")
-                            .size(11.0)
-                            .color(egui::Color32::from_rgb(100, 255, 100)));
-                    }
-                });
-            });
-
-            ui.add_space(20.0);
-
-            // Browse for Path button
-            if self.show_manual_input || self.game_path.is_none() {
-                ui.vertical_centered(|ui| {
-                    if ui.button("📁 Browse for Game Directory").clicked() {
-                        self.browse_for_path();
-                    }
-                });
-                ui.add_space(10.0);
-            } else if self.game_path.is_some() {
-                // Show option to change path
-                ui.vertical_centered(|ui| {
-                    if ui.button("📝 Change Path").clicked() {
-                        self.browse_for_path();
-                    }
-                });
-                ui.add_space(10.0);
-            }
-
-            // Install/Uninstall Buttons
-            ui.vertical_centered(|ui| {
-                let install_button = egui::Button::new(
-                    egui::RichText::new("⚡ Install Mod")
-                        .size(18.0)
-                        .strong()
-                ).min_size(egui::vec2(180.0, 45.0));
-
-                ui.add_enabled_ui(!self.is_installing && !self.is_mod_installed, |ui| {
-                    if ui.add(install_button).clicked() {
-                        self.install_mod(ctx.clone());
-                    }
-                });
-                
-                if self.is_installing {
-                    ui.add_space(10.0);
-                    ui.spinner();
-                    ctx.request_repaint();
-                }
-            });
-
-            ui.add_space(20.0);
-
-            ui.vertical_centered(|ui| {
-                let uninstall_button = egui::Button::new(
-                    egui::RichText::new("🗑 Uninstall Mod")
-                        .size(18.0)
-                        .strong()
-                ).min_size(egui::vec2(180.0, 45.0));
-
-                ui.add_enabled_ui(!self.is_installing && self.is_mod_installed, |ui| {
-                    if ui.add(uninstall_button).clicked() {
-                        self.uninstall_mod();
-                    }
-                });
-            });
-
-            ui.add_space(20.0);
-
-            // Import TSM Resources Button
-            ui.vertical_centered(|ui| {
-                let import_button = egui::Button::new(
-                    egui::RichText::new("📦 Import TSM Resources")
-                        .size(16.0)
-                ).min_size(egui::vec2(200.0, 40.0));
-
-                if ui.add(import_button).clicked() {
-                    self.browse_and_import_resources();
-                }
-
-                if !self.import_status.is_empty() {
-                    ui.add_space(5.0);
-                    
-                    let import_color = if self.import_status.contains("✅") {
-                        egui::Color32::from_rgb(100, 255, 100)
-                    } else if self.import_status.contains("⏳") {
-                        egui::Color32::from_rgb(100, 200, 255)
-                    } else {
-                        egui::Color32::from_rgb(255, 100, 100)
-                    };
-                    
-                    ui.label(egui::RichText::new(&self.import_status)
-                        .size(12.0)
-                        .color(import_color));
-                }
-            });
-
-            ui.add_space(20.0);
-
-            ui.vertical_centered(|ui| {
-                ui.label(egui::RichText::new("⚠ Note: Run the game as usual to activate the mod")
-                    .size(12.0)
-                    .color(egui::Color32::from_rgb(255, 200, 100)));
-            });
-        });
+    #[cfg(not(feature = "gui"))]
+    {
+        eprintln!("This build has no installer window, see --help for the available commands");
+        std::process::exit(2);
     }
 }
-
-fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([500.0, 520.0])
-            .with_resizable(false),
-        ..Default::default()
-    };
-
-    eframe::run_native(
-        "ThatNoobSkyApp",
-        options,
-        Box::new(|_| Ok(Box::new(ModInstallerApp::default()))),
-    )
-}
\ No newline at end of file
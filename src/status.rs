@@ -0,0 +1,130 @@
+// How status messages look. Messages carry their kind as a leading marker
+// (✅, ⏳, ⚠, ❌); it's shown as a shape and a word as well as a color, so
+// nothing depends on telling red from green, and the palette can be switched
+// to one that stays distinct with the common kinds of color blindness.
+
+use crate::theme;
+use eframe::egui::{self, Color32, RichText};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tnsm_installer_core::i18n::tr;
+
+static COLORBLIND: AtomicBool = AtomicBool::new(false);
+
+const MARKERS: [&str; 5] = ["✅", "✓", "⏳", "⚠", "❌"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    Success,
+    Working,
+    Warning,
+    Error,
+    Info,
+}
+
+impl Level {
+    pub fn of(message: &str) -> Self {
+        if message.contains('✅') || message.contains('✓') {
+            Level::Success
+        } else if message.contains('⏳') {
+            Level::Working
+        } else if message.contains('⚠') {
+            Level::Warning
+        } else if message.contains('❌') {
+            Level::Error
+        } else {
+            Level::Info
+        }
+    }
+
+    // A different outline for every level, readable without color
+    fn icon(self) -> &'static str {
+        match self {
+            Level::Success => "✔",
+            Level::Working => "⏳",
+            Level::Warning => "⚠",
+            Level::Error => "✖",
+            Level::Info => "ℹ",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Success => tr("OK"),
+            Level::Working => tr("Working"),
+            Level::Warning => tr("Warning"),
+            Level::Error => tr("Error"),
+            Level::Info => tr("Note"),
+        }
+    }
+}
+
+pub fn set_colorblind(colorblind: bool) {
+    COLORBLIND.store(colorblind, Ordering::Relaxed);
+}
+
+pub fn color(level: Level) -> Color32 {
+    if theme::is_light() {
+        return light_color(level);
+    }
+
+    // Okabe-Ito: blue and orange stay apart for red-green color blindness
+    if COLORBLIND.load(Ordering::Relaxed) {
+        return match level {
+            Level::Success => Color32::from_rgb(86, 180, 233),
+            Level::Working => Color32::from_rgb(204, 121, 167),
+            Level::Warning => Color32::from_rgb(240, 228, 66),
+            Level::Error => Color32::from_rgb(230, 159, 0),
+            Level::Info => Color32::WHITE,
+        };
+    }
+
+    match level {
+        Level::Success => Color32::from_rgb(100, 255, 100),
+        Level::Working => Color32::from_rgb(100, 200, 255),
+        Level::Warning => Color32::from_rgb(255, 200, 100),
+        Level::Error => Color32::from_rgb(255, 100, 100),
+        Level::Info => Color32::WHITE,
+    }
+}
+
+// Darker shades that stay readable on the light theme's background
+fn light_color(level: Level) -> Color32 {
+    if COLORBLIND.load(Ordering::Relaxed) {
+        return match level {
+            Level::Success => Color32::from_rgb(0, 114, 178),
+            Level::Working => Color32::from_rgb(150, 70, 120),
+            Level::Warning => Color32::from_rgb(140, 120, 0),
+            Level::Error => Color32::from_rgb(213, 94, 0),
+            Level::Info => Color32::BLACK,
+        };
+    }
+
+    match level {
+        Level::Success => Color32::from_rgb(0, 130, 0),
+        Level::Working => Color32::from_rgb(0, 100, 180),
+        Level::Warning => Color32::from_rgb(170, 100, 0),
+        Level::Error => Color32::from_rgb(190, 30, 30),
+        Level::Info => Color32::BLACK,
+    }
+}
+
+// "✅ Mod installed" becomes "✔ OK: Mod installed"; messages without a
+// marker are shown as they are
+pub fn text(message: &str) -> RichText {
+    let level = Level::of(message);
+    if level == Level::Info {
+        return RichText::new(message);
+    }
+
+    let rest = MARKERS.iter()
+        .find_map(|marker| message.strip_prefix(marker))
+        .unwrap_or(message)
+        .trim_start();
+
+    RichText::new(format!("{} {}: {}", level.icon(), level.label(), rest))
+        .color(color(level))
+}
+
+pub fn show(ui: &mut egui::Ui, message: &str, size: f32) {
+    ui.label(text(message).size(size));
+}
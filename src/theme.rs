@@ -0,0 +1,57 @@
+// Light and dark looks and the accent color, picked in Settings. The choice
+// is kept here rather than passed around so apply_theme() can run at the
+// start of every frame and status colors can tell which background they're
+// drawn on.
+
+use eframe::egui::{self, Color32, Visuals};
+use std::sync::RwLock;
+use tnsm_installer_core::config::Theme;
+
+static CURRENT: RwLock<(Theme, Option<[u8; 3]>)> = RwLock::new((Theme::Dark, None));
+
+pub const DEFAULT_DARK_ACCENT: [u8; 3] = [135, 206, 250];
+// The dark theme's light blue is hard to read on a white background
+pub const DEFAULT_LIGHT_ACCENT: [u8; 3] = [25, 110, 190];
+
+pub fn set(theme: Theme, accent: Option<[u8; 3]>) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = (theme, accent);
+}
+
+pub fn is_light() -> bool {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).0 == Theme::Light
+}
+
+pub fn default_accent() -> [u8; 3] {
+    if is_light() { DEFAULT_LIGHT_ACCENT } else { DEFAULT_DARK_ACCENT }
+}
+
+pub fn accent() -> Color32 {
+    let [r, g, b] = CURRENT.read().unwrap_or_else(|e| e.into_inner()).1.unwrap_or_else(default_accent);
+    Color32::from_rgb(r, g, b)
+}
+
+pub fn apply_theme(ctx: &egui::Context) {
+    let mut visuals = if is_light() {
+        let mut visuals = Visuals::light();
+        visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(235, 235, 242);
+        visuals.widgets.inactive.bg_fill = Color32::from_rgb(215, 215, 226);
+        visuals.widgets.hovered.bg_fill = Color32::from_rgb(195, 195, 212);
+        visuals.widgets.active.bg_fill = Color32::from_rgb(175, 175, 200);
+        visuals
+    } else {
+        let mut visuals = Visuals::dark();
+        visuals.widgets.noninteractive.bg_fill = Color32::from_rgb(30, 30, 40);
+        visuals.widgets.inactive.bg_fill = Color32::from_rgb(50, 50, 65);
+        visuals.widgets.hovered.bg_fill = Color32::from_rgb(70, 70, 90);
+        visuals.widgets.active.bg_fill = Color32::from_rgb(90, 90, 120);
+        visuals
+    };
+
+    let accent = accent();
+    visuals.selection.bg_fill = accent.gamma_multiply(0.6);
+    visuals.hyperlink_color = accent;
+
+    // Otherwise egui follows the system theme and overrides the choice
+    ctx.set_theme(if is_light() { egui::Theme::Light } else { egui::Theme::Dark });
+    ctx.set_visuals(visuals);
+}
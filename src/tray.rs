@@ -0,0 +1,179 @@
+// Tray mode: the app keeps running in the notification area with the window
+// minimized, checks for new mod releases now and then and raises a desktop
+// notification when one is out. Windows only; elsewhere Tray::new fails and
+// the setting can't be turned on.
+//
+// The tray menu and the notification's button run on their own callbacks,
+// so they queue a command and wake the window, and the app picks it up on
+// its next frame.
+
+#[cfg(not(windows))]
+use eframe::egui;
+#[cfg(not(windows))]
+use tnsm_installer_core::i18n::tr;
+
+// Only sent by the Windows tray and notifications
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TrayCommand {
+    Show,
+    InstallUpdate,
+    Quit,
+}
+
+#[cfg(windows)]
+pub use platform::Tray;
+
+#[cfg(windows)]
+mod platform {
+    use super::TrayCommand;
+    use eframe::egui;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use tauri_winrt_notification::Toast;
+    use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+    use tray_icon::{Icon, MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent};
+    use tnsm_installer_core::i18n::{tr, trf};
+
+    const ICON_SIZE: u32 = 32;
+    const SHOW_ID: &str = "show";
+    const INSTALL_ID: &str = "install";
+    const QUIT_ID: &str = "quit";
+    const INSTALL_ACTION: &str = "install";
+
+    pub struct Tray {
+        // Removed from the notification area when dropped
+        _icon: TrayIcon,
+        install_item: MenuItem,
+        commands: Receiver<TrayCommand>,
+        sender: Sender<TrayCommand>,
+        ctx: egui::Context,
+    }
+
+    fn queue(commands: &Sender<TrayCommand>, ctx: &egui::Context, command: TrayCommand) {
+        if commands.send(command).is_ok() {
+            ctx.request_repaint();
+        }
+    }
+
+    // A filled sky-blue circle, there's no icon file to load
+    fn icon() -> Result<Icon, String> {
+        let center = ICON_SIZE as f32 / 2.0;
+        let rgba = (0..ICON_SIZE * ICON_SIZE)
+            .flat_map(|i| {
+                let (x, y) = ((i % ICON_SIZE) as f32 + 0.5, (i / ICON_SIZE) as f32 + 0.5);
+                let inside = (x - center).powi(2) + (y - center).powi(2) <= (center - 1.0).powi(2);
+                if inside { [135, 206, 250, 255] } else { [0, 0, 0, 0] }
+            })
+            .collect();
+        Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE)
+            .map_err(|e| trf("Failed to create tray icon: {}", &[&e]))
+    }
+
+    impl Tray {
+        pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+            let show_item = MenuItem::with_id(SHOW_ID, tr("Open ThatNoobSkyApp"), true, None);
+            let install_item = MenuItem::with_id(INSTALL_ID, tr("Install update"), false, None);
+            let quit_item = MenuItem::with_id(QUIT_ID, tr("Quit"), true, None);
+
+            let menu = Menu::new();
+            menu.append(&show_item)
+                .and_then(|_| menu.append(&install_item))
+                .and_then(|_| menu.append(&PredefinedMenuItem::separator()))
+                .and_then(|_| menu.append(&quit_item))
+                .map_err(|e| trf("Failed to create tray menu: {}", &[&e]))?;
+
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip("ThatNoobSkyApp")
+                .with_icon(icon()?)
+                .build()
+                .map_err(|e| trf("Failed to create tray icon: {}", &[&e]))?;
+
+            let (sender, commands) = mpsc::channel();
+
+            let menu_sender = sender.clone();
+            let menu_ctx = ctx.clone();
+            MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+                let command = if event.id == SHOW_ID {
+                    TrayCommand::Show
+                } else if event.id == INSTALL_ID {
+                    TrayCommand::InstallUpdate
+                } else if event.id == QUIT_ID {
+                    TrayCommand::Quit
+                } else {
+                    return;
+                };
+                queue(&menu_sender, &menu_ctx, command);
+            }));
+
+            let click_sender = sender.clone();
+            let click_ctx = ctx.clone();
+            TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+                if let TrayIconEvent::DoubleClick { button: MouseButton::Left, .. } = event {
+                    queue(&click_sender, &click_ctx, TrayCommand::Show);
+                }
+            }));
+
+            Ok(Self { _icon: icon, install_item, commands, sender, ctx: ctx.clone() })
+        }
+
+        pub fn try_recv(&self) -> Option<TrayCommand> {
+            self.commands.try_recv().ok()
+        }
+
+        // Enables the menu's install item while an update is waiting
+        pub fn set_update(&self, version: Option<&str>) {
+            self.install_item.set_enabled(version.is_some());
+            self.install_item.set_text(match version {
+                Some(version) => trf("Install {}", &[&version]),
+                None => tr("Install update").to_string(),
+            });
+        }
+
+        pub fn notify_update(&self, version: &str, installed: &str) -> Result<(), String> {
+            let commands = self.sender.clone();
+            let ctx = self.ctx.clone();
+
+            // The installer isn't registered as a notification source, so
+            // the notification shows up under PowerShell's
+            Toast::new(Toast::POWERSHELL_APP_ID)
+                .title(&trf("ThatNoobSkyMod {} is available", &[&version]))
+                .text1(&trf("You have {}. Update before playing so the mod keeps working after game patches.", &[&installed]))
+                .add_button(tr("Install now"), INSTALL_ACTION)
+                .on_activated(move |action| {
+                    // Clicking the notification itself only opens the window
+                    let command = match action.as_deref() {
+                        Some(INSTALL_ACTION) => TrayCommand::InstallUpdate,
+                        _ => TrayCommand::Show,
+                    };
+                    queue(&commands, &ctx, command);
+                    Ok(())
+                })
+                .show()
+                .map_err(|e| trf("Failed to show notification: {}", &[&e]))
+        }
+    }
+}
+
+// Can't be created, so none of this runs
+#[cfg(not(windows))]
+pub enum Tray {}
+
+#[cfg(not(windows))]
+impl Tray {
+    pub fn new(_ctx: &egui::Context) -> Result<Self, String> {
+        Err(tr("Tray mode is only available on Windows").to_string())
+    }
+
+    pub fn try_recv(&self) -> Option<TrayCommand> {
+        match *self {}
+    }
+
+    pub fn set_update(&self, _version: Option<&str>) {
+        match *self {}
+    }
+
+    pub fn notify_update(&self, _version: &str, _installed: &str) -> Result<(), String> {
+        match *self {}
+    }
+}
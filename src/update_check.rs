@@ -0,0 +1,104 @@
+#[cfg(feature = "gui")]
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+use tnsm_installer_core::cancel::CancellationToken;
+use tnsm_installer_core::config::Config;
+use tnsm_installer_core::i18n::{self, tr, trf};
+use tnsm_installer_core::{compat, github};
+
+// Runs the `--check-updates` mode. Returns true when the installer window
+// should be opened afterwards.
+pub fn run(quiet: bool) -> bool {
+    let config = Config::load();
+    // The dialogs are in the installer's language
+    i18n::load_packs();
+    if let Some(code) = &config.language {
+        i18n::set_language(code);
+    }
+
+    if let Some(pinned) = &config.pinned_version {
+        if !quiet {
+            show_message(Level::Info, &trf("📌 ThatNoobSkyMod is pinned to {}, unpin it in the installer to get updates.", &[&pinned]));
+        }
+        return false;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(_) => return false,
+    };
+    // Keeps the known issues shown in the installer current, failing is fine
+    let _ = runtime.block_on(compat::refresh(&CancellationToken::new()));
+    let latest = runtime.block_on(github::fetch_newest_release(config.beta_channel, &CancellationToken::new(), &|_| {}));
+
+    match (config.installed_version, latest) {
+        (Some(installed), Ok(release)) if installed != release.tag_name => {
+            ask_to_update(&release.tag_name, &installed)
+        }
+        (_, Err(e)) => {
+            if !quiet {
+                show_message(Level::Error, &trf("❌ {}", &[&e]));
+            }
+            false
+        }
+        (None, Ok(_)) => {
+            if !quiet {
+                show_message(Level::Info, tr("Mod is not installed through this installer."));
+            }
+            false
+        }
+        (Some(installed), Ok(_)) => {
+            if !quiet {
+                show_message(Level::Info, &trf("✅ ThatNoobSkyMod {} is up to date.", &[&installed]));
+            }
+            false
+        }
+    }
+}
+
+enum Level {
+    Info,
+    Error,
+}
+
+#[cfg(feature = "gui")]
+fn ask_to_update(latest: &str, installed: &str) -> bool {
+    let answer = MessageDialog::new()
+        .set_level(MessageLevel::Info)
+        .set_title("ThatNoobSkyApp")
+        .set_description(trf(
+            "ThatNoobSkyMod {} is available (installed: {}).\n\nOpen the installer to update?",
+            &[&latest, &installed],
+        ))
+        .set_buttons(MessageButtons::YesNo)
+        .show();
+    answer == MessageDialogResult::Yes
+}
+
+// Headless builds have no installer to open, so point at the install command instead
+#[cfg(not(feature = "gui"))]
+fn ask_to_update(latest: &str, installed: &str) -> bool {
+    println!("{}", trf("ThatNoobSkyMod {} is available (installed: {}), run `install` to update.", &[&latest, &installed]));
+    false
+}
+
+#[cfg(feature = "gui")]
+fn show_message(level: Level, description: &str) {
+    let level = match level {
+        Level::Info => MessageLevel::Info,
+        Level::Error => MessageLevel::Error,
+    };
+    MessageDialog::new()
+        .set_level(level)
+        .set_title("ThatNoobSkyApp")
+        .set_description(description)
+        .set_buttons(MessageButtons::Ok)
+        .show();
+}
+
+#[cfg(not(feature = "gui"))]
+fn show_message(level: Level, description: &str) {
+    match level {
+        Level::Info => println!("{}", description),
+        Level::Error => eprintln!("{}", description),
+    }
+}
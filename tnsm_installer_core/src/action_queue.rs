@@ -0,0 +1,62 @@
+// Changes queued while Sky is running, applied once it exits. The queue is
+// saved in the app directory so closing the installer doesn't lose it; it
+// runs again the next time the installer sees the game closed.
+
+use crate::i18n::{tr, trf};
+use crate::{app_files, config};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind")]
+pub enum QueuedAction {
+    UpdateMod {
+        dll_url: String,
+    },
+    Uninstall,
+    // Imported packs are removed by reverting the restore point their import made
+    RemovePack {
+        restore_point: PathBuf,
+        label: String,
+    },
+}
+
+impl QueuedAction {
+    pub fn describe(&self) -> String {
+        match self {
+            QueuedAction::UpdateMod { .. } => tr("Update the mod").to_string(),
+            QueuedAction::Uninstall => tr("Uninstall the mod").to_string(),
+            QueuedAction::RemovePack { label, .. } => trf("Revert {}", &[label]),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ActionQueue {
+    pub actions: Vec<QueuedAction>,
+}
+
+fn queue_path() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join("queue.json"))
+}
+
+impl ActionQueue {
+    pub fn load() -> Self {
+        app_files::load_json(queue_path())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        app_files::save_json(queue_path(), self, self.actions.is_empty(), tr("action queue"))
+    }
+
+    // Queuing the same thing twice does nothing
+    pub fn push(&mut self, action: QueuedAction) {
+        if !self.actions.contains(&action) {
+            self.actions.push(action);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<QueuedAction> {
+        (!self.actions.is_empty()).then(|| self.actions.remove(0))
+    }
+}
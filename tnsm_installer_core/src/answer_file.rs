@@ -0,0 +1,89 @@
+// Answer files for unattended installs, so a helper can prepare one file and
+// send it to a less technical player:
+//
+//   game_path = 'D:\Games\Sky Children of the Light'
+//   version = "v1.2.0"
+//   packs = ["Cozy Pack.tnsmpack", 'C:\Users\Me\Downloads\Outfits']
+//
+// Everything is optional: the game is detected, the latest release is
+// installed and no packs are imported. Relative paths are relative to the
+// answer file. The result is reported as JSON for scripts to pick up.
+
+use crate::download;
+use crate::i18n::trf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnswerFile {
+    pub game_path: Option<PathBuf>,
+    // Release tag, the latest release when unset
+    pub version: Option<String>,
+    // Any other DLL URL; version wins if both are set
+    pub dll_url: Option<String>,
+    // Only import the packs when false
+    pub install_mod: bool,
+    // .tnsmpack files or resource folders, imported in order
+    pub packs: Vec<PathBuf>,
+}
+
+impl Default for AnswerFile {
+    fn default() -> Self {
+        Self {
+            game_path: None,
+            version: None,
+            dll_url: None,
+            install_mod: true,
+            packs: Vec::new(),
+        }
+    }
+}
+
+impl AnswerFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| trf("Failed to read answer file: {}", &[&e]))?;
+        let mut answers: Self = toml::from_str(&contents)
+            .map_err(|e| trf("Failed to parse answer file: {}", &[&e]))?;
+
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let base = path.parent().unwrap_or(Path::new("."));
+        answers.game_path = answers.game_path.map(|path| base.join(path));
+        answers.packs = answers.packs.into_iter().map(|pack| base.join(pack)).collect();
+
+        Ok(answers)
+    }
+
+    pub fn dll_url(&self) -> String {
+        self.version.as_deref()
+            .map(download::release_dll_url)
+            .or_else(|| self.dll_url.clone())
+            .unwrap_or_else(|| download::DEFAULT_DLL_URL.to_string())
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct PackResult {
+    pub path: PathBuf,
+    pub imported: bool,
+    pub error: Option<String>,
+}
+
+// Printed to stdout as the only output, progress goes to stderr
+#[derive(Serialize, Default, Debug)]
+pub struct Report {
+    pub success: bool,
+    pub game_path: Option<PathBuf>,
+    pub mod_installed: bool,
+    pub installed_version: Option<String>,
+    pub packs: Vec<PackResult>,
+    pub error: Option<String>,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            .unwrap_or_else(|e| format!("{{\"success\": false, \"error\": \"Failed to serialize report: {}\"}}", e))
+    }
+}
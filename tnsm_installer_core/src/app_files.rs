@@ -0,0 +1,83 @@
+// Files the installer keeps in its app directory: snapshots taken at some
+// point and listed newest first (backups, restore points, cached versions),
+// and small JSON state files that are deleted again once there's nothing in
+// them.
+
+use crate::i18n::{tr, trf};
+use crate::timestamp;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::cmp::Reverse;
+use std::fs::DirEntry;
+use std::path::PathBuf;
+
+// A file or folder named after when it was taken
+pub struct Snapshot {
+    pub path: PathBuf,
+    // timestamp::now() at the time
+    pub timestamp: String,
+}
+
+impl Snapshot {
+    pub fn label(&self) -> String {
+        timestamp::display(&self.timestamp)
+    }
+}
+
+// The entries of `dir` that `parse` accepts, newest first by `key`. Empty
+// when the folder doesn't exist yet.
+pub fn list_newest_first<T, K: Ord>(
+    dir: Option<PathBuf>,
+    parse: impl FnMut(DirEntry) -> Option<T>,
+    mut key: impl FnMut(&T) -> K,
+) -> Vec<T> {
+    let Some(entries) = dir.and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<_> = entries.filter_map(Result::ok).filter_map(parse).collect();
+    items.sort_by_cached_key(|item| Reverse(key(item)));
+    items
+}
+
+// Snapshots in `dir` whose name is `prefix`, the timestamp and `suffix`
+pub fn list_snapshots(dir: Option<PathBuf>, prefix: &str, suffix: &str) -> Vec<Snapshot> {
+    list_newest_first(
+        dir,
+        |entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let timestamp = name.strip_prefix(prefix)?.strip_suffix(suffix)?.to_string();
+            Some(Snapshot { path: entry.path(), timestamp })
+        },
+        |snapshot| snapshot.timestamp.clone(),
+    )
+}
+
+// The default when the file is missing or unreadable
+pub fn load_json<T: DeserializeOwned + Default>(path: Option<PathBuf>) -> T {
+    path.and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// Writes `value` to `path`, or deletes the file when `is_empty`. `what` names
+// it in errors, e.g. "undo history".
+pub fn save_json<T: Serialize>(path: Option<PathBuf>, value: &T, is_empty: bool, what: &str) -> Result<(), String> {
+    let path = path.ok_or(tr("Config directory not found"))?;
+
+    if is_empty {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(trf("Failed to clear {}: {}", &[&what, &e])),
+            _ => Ok(()),
+        };
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create config directory: {}", &[&e]))?;
+    }
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| trf("Failed to serialize {}: {}", &[&what, &e]))?;
+    std::fs::write(&path, contents)
+        .map_err(|e| trf("Failed to write {}: {}", &[&what, &e]))
+}
@@ -0,0 +1,99 @@
+// CPU architecture handling. The mod has to match the architecture of the
+// game executable, not the PC: on Windows on ARM an x64 Sky runs under
+// emulation and needs the x64 DLL, while a native ARM64 Sky needs an ARM64
+// build of the mod.
+
+use crate::i18n::trf;
+use crate::{detect, github};
+use std::io::Read;
+use std::path::Path;
+
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+const IMAGE_FILE_MACHINE_ARM64: u16 = 0xAA64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Arch {
+    X64,
+    Arm64,
+}
+
+impl Arch {
+    pub fn name(self) -> &'static str {
+        match self {
+            Arch::X64 => "x64",
+            Arch::Arm64 => "ARM64",
+        }
+    }
+
+    // Release asset carrying the mod for this architecture
+    pub fn dll_asset_name(self) -> &'static str {
+        match self {
+            Arch::X64 => github::DLL_ASSET_NAME,
+            Arch::Arm64 => github::DLL_ASSET_NAME_ARM64,
+        }
+    }
+
+    fn from_machine(machine: u16) -> Option<Self> {
+        match machine {
+            IMAGE_FILE_MACHINE_AMD64 => Some(Arch::X64),
+            IMAGE_FILE_MACHINE_ARM64 => Some(Arch::Arm64),
+            _ => None,
+        }
+    }
+}
+
+pub fn pe_arch(path: &Path) -> Result<Arch, String> {
+    let mut header = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|file| file.take(4096).read_to_end(&mut header))
+        .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+
+    pe_arch_of(&header, &path.display().to_string())
+}
+
+// Reads the machine field from the COFF header at the start of a PE image
+pub fn pe_arch_of(header: &[u8], name: &str) -> Result<Arch, String> {
+    let invalid = || trf("{} is not a Windows executable", &[&name]);
+
+    if !header.starts_with(b"MZ") {
+        return Err(invalid());
+    }
+    let pe_offset = header.get(0x3C..0x40)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+        .ok_or_else(invalid)?;
+    if header.get(pe_offset..pe_offset + 4) != Some(b"PE\0\0".as_slice()) {
+        return Err(invalid());
+    }
+    let machine = header.get(pe_offset + 4..pe_offset + 6)
+        .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(invalid)?;
+
+    Arch::from_machine(machine)
+        .ok_or_else(|| trf("{} has an unsupported architecture ({})", &[&name, &format!("{:#06x}", machine)]))
+}
+
+// Architecture the mod must be built for, falling back to x64 when Sky.exe can't be read
+pub fn game_arch(game_path: &Path) -> Arch {
+    pe_arch(&game_path.join(detect::GAME_EXE_NAME)).unwrap_or(Arch::X64)
+}
+
+// Native architecture of the PC, even when this app itself runs emulated
+#[cfg(windows)]
+pub fn host_arch() -> Arch {
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    let mut process_machine = 0;
+    let mut native_machine = 0;
+    let ok = unsafe { IsWow64Process2(GetCurrentProcess(), &mut process_machine, &mut native_machine) };
+
+    if ok != 0 {
+        Arch::from_machine(native_machine).unwrap_or(Arch::X64)
+    } else {
+        Arch::X64
+    }
+}
+
+#[cfg(not(windows))]
+pub fn host_arch() -> Arch {
+    if cfg!(target_arch = "aarch64") { Arch::Arm64 } else { Arch::X64 }
+}
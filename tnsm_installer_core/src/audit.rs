@@ -0,0 +1,198 @@
+// Audit log of every change the installer makes: who changed what, when,
+// how it ended and, for changes to the mod DLL, its hash before and after.
+// Entries are appended as JSON lines in the app directory and never
+// rewritten, and can be exported as CSV or JSON, e.g. by event organizers
+// collecting logs from several PCs or when looking into an "it changed by
+// itself" report.
+// Logging is best effort and never fails the change itself.
+
+use crate::i18n::{tr, trf};
+use crate::install::{self, ProxyDll};
+use crate::{cancel, config, machine, timestamp, verify};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Succeeded => "succeeded",
+            Outcome::Failed => "failed",
+            Outcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditEntry {
+    // timestamp::now() when the change started
+    pub timestamp: String,
+    pub user: String,
+    pub computer: String,
+    pub action: String,
+    pub game_path: Option<PathBuf>,
+    pub detail: String,
+    pub outcome: Outcome,
+    pub error: Option<String>,
+    pub dll_sha256_before: Option<String>,
+    pub dll_sha256_after: Option<String>,
+}
+
+fn log_path() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join("audit.jsonl"))
+}
+
+fn computer_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn dll_hash(game_path: Option<&Path>, dll: Option<ProxyDll>) -> Option<String> {
+    verify::sha256_file(&install::dll_path(game_path?, dll?)).ok()
+}
+
+// A change in progress, written to the log once it's finished
+pub struct Recorder {
+    entry: AuditEntry,
+    // The mod DLL the change touches, hashed before and after
+    dll: Option<ProxyDll>,
+}
+
+pub fn begin(action: &str, game_path: Option<&Path>) -> Recorder {
+    tracing::info!("{} started in {}", action, game_path.map_or("-".to_string(), |path| path.display().to_string()));
+    Recorder {
+        entry: AuditEntry {
+            timestamp: timestamp::now(),
+            user: machine::current_user(),
+            computer: computer_name(),
+            action: action.to_string(),
+            game_path: game_path.map(Path::to_path_buf),
+            detail: String::new(),
+            outcome: Outcome::Succeeded,
+            error: None,
+            dll_sha256_before: None,
+            dll_sha256_after: None,
+        },
+        dll: None,
+    }
+}
+
+impl Recorder {
+    pub fn dll(mut self, dll: ProxyDll) -> Self {
+        self.dll = Some(dll);
+        self.entry.dll_sha256_before = dll_hash(self.entry.game_path.as_deref(), self.dll);
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.entry.detail = detail.into();
+        tracing::info!("{}: {}", self.entry.action, self.entry.detail);
+        self
+    }
+
+    pub fn finish<T>(mut self, result: &Result<T, String>) {
+        match result {
+            Ok(_) => tracing::info!("{} succeeded", self.entry.action),
+            Err(e) if cancel::is_cancelled(e) => tracing::warn!("{} cancelled", self.entry.action),
+            Err(e) => tracing::error!("{} failed: {}", self.entry.action, e),
+        }
+        if let Err(e) = result {
+            self.entry.outcome = if cancel::is_cancelled(e) { Outcome::Cancelled } else { Outcome::Failed };
+            self.entry.error = Some(e.clone());
+        }
+        self.entry.dll_sha256_after = dll_hash(self.entry.game_path.as_deref(), self.dll);
+        let _ = append(&self.entry);
+    }
+}
+
+fn append(entry: &AuditEntry) -> Result<(), String> {
+    let path = log_path().ok_or(tr("Config directory not found"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create config directory: {}", &[&e]))?;
+    }
+
+    let line = serde_json::to_string(entry)
+        .map_err(|e| trf("Failed to serialize audit entry: {}", &[&e]))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| trf("Failed to open audit log: {}", &[&e]))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| trf("Failed to write audit log: {}", &[&e]))
+}
+
+// Oldest first. Lines that can't be parsed, e.g. a half-written last line
+// after a crash, are skipped
+pub fn load() -> Vec<AuditEntry> {
+    log_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = "timestamp,user,computer,action,game_path,detail,outcome,error,dll_sha256_before,dll_sha256_after\n".to_string();
+
+    for entry in entries {
+        let game_path = entry.game_path.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+        let fields = [
+            timestamp::display(&entry.timestamp),
+            entry.user.clone(),
+            entry.computer.clone(),
+            entry.action.clone(),
+            game_path,
+            entry.detail.clone(),
+            entry.outcome.as_str().to_string(),
+            entry.error.clone().unwrap_or_default(),
+            entry.dll_sha256_before.clone().unwrap_or_default(),
+            entry.dll_sha256_after.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+// Writes the whole log to `path`, as CSV when it ends in .csv and as a JSON
+// array otherwise. Returns how many entries were exported.
+pub fn export(path: &Path) -> Result<usize, String> {
+    let entries = load();
+    let is_csv = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+
+    let contents = if is_csv {
+        to_csv(&entries)
+    } else {
+        serde_json::to_string_pretty(&entries)
+            .map_err(|e| trf("Failed to serialize audit log: {}", &[&e]))?
+    };
+    std::fs::write(path, contents)
+        .map_err(|e| trf("Failed to export audit log: {}", &[&e]))?;
+
+    Ok(entries.len())
+}
@@ -0,0 +1,69 @@
+// Starting the app minimized at login, for the background update mode.
+// Windows uses the per-user Run key, Linux an XDG autostart entry.
+
+#[cfg(not(windows))]
+use crate::i18n::tr;
+use crate::i18n::trf;
+#[cfg(windows)]
+use crate::registry::reg;
+
+pub const MINIMIZED_FLAG: &str = "--minimized";
+
+#[cfg(windows)]
+const RUN_KEY: &str = "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+#[cfg(windows)]
+const VALUE_NAME: &str = "ThatNoobSkyApp";
+
+fn current_exe() -> Result<std::path::PathBuf, String> {
+    std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))
+}
+
+#[cfg(windows)]
+pub fn enable() -> Result<(), String> {
+    let command = format!("\"{}\" {}", current_exe()?.display(), MINIMIZED_FLAG);
+    reg(&["add", RUN_KEY, "/v", VALUE_NAME, "/t", "REG_SZ", "/d", &command, "/f"])
+}
+
+#[cfg(windows)]
+pub fn disable() -> Result<(), String> {
+    // Already gone is fine, there's nothing to clean up
+    if reg(&["query", RUN_KEY, "/v", VALUE_NAME]).is_err() {
+        return Ok(());
+    }
+    reg(&["delete", RUN_KEY, "/v", VALUE_NAME, "/f"])
+}
+
+#[cfg(not(windows))]
+fn desktop_entry_path() -> Result<std::path::PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join("autostart").join("thatnoobskyapp.desktop"))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+#[cfg(not(windows))]
+pub fn enable() -> Result<(), String> {
+    let path = desktop_entry_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create autostart directory: {}", &[&e]))?;
+    }
+
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=ThatNoobSkyApp\nExec=\"{}\" {}\nX-GNOME-Autostart-enabled=true\n",
+        current_exe()?.display(),
+        MINIMIZED_FLAG
+    );
+    std::fs::write(&path, entry)
+        .map_err(|e| trf("Failed to write autostart entry: {}", &[&e]))
+}
+
+#[cfg(not(windows))]
+pub fn disable() -> Result<(), String> {
+    match std::fs::remove_file(desktop_entry_path()?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(trf("Failed to remove autostart entry: {}", &[&e]))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,275 @@
+// Measures what an install is made of: downloading from GitHub, copying into
+// the game folder (one big file, and many small ones like a resource pack)
+// and hashing. The report is plain text to paste into an issue, and tells a
+// slow network, a slow disk and per-file overhead (usually antivirus
+// scanning every new file) apart. Copies go to a scratch folder inside the
+// game folder, removed again afterwards.
+
+use crate::cancel::{self, CancellationToken};
+use crate::download::{self, DEFAULT_DLL_URL};
+use crate::i18n::trf;
+use crate::{storage, verify, workers};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const LARGE_FILE_SIZE: usize = 64 * 1024 * 1024;
+const SMALL_FILE_SIZE: usize = 32 * 1024;
+const SMALL_FILE_COUNT: usize = 400;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Measurement {
+    pub bytes: u64,
+    pub files: u64,
+    pub elapsed: Duration,
+}
+
+impl Measurement {
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64().max(0.001)
+    }
+
+    pub fn files_per_second(&self) -> f64 {
+        self.files as f64 / self.elapsed.as_secs_f64().max(0.001)
+    }
+
+    fn describe(&self) -> String {
+        let speed = format!("{}/s", storage::format_size(self.bytes_per_second() as u64));
+        if self.files > 1 {
+            format!("{:.0} files/s, {} ({} files in {:.1}s)", self.files_per_second(), speed, self.files, self.elapsed.as_secs_f64())
+        } else {
+            format!("{} ({} in {:.1}s)", speed, storage::format_size(self.bytes), self.elapsed.as_secs_f64())
+        }
+    }
+}
+
+pub struct BenchmarkReport {
+    pub app_version: String,
+    pub threads: usize,
+    pub copy_workers: usize,
+    // None when skipped
+    pub download: Option<Result<Measurement, String>>,
+    pub large_copy: Result<Measurement, String>,
+    pub small_copy_serial: Result<Measurement, String>,
+    pub small_copy_parallel: Result<Measurement, String>,
+    pub hash: Result<Measurement, String>,
+}
+
+impl BenchmarkReport {
+    pub fn to_text(&self) -> String {
+        let line = |name: &str, result: &Result<Measurement, String>| match result {
+            Ok(measurement) => format!("{:<28}{}\n", name, measurement.describe()),
+            Err(e) => format!("{:<28}failed: {}\n", name, e),
+        };
+
+        let mut text = format!(
+            "ThatNoobSkyApp {} benchmark\n{} {}, {} threads, {} copy workers\n\n",
+            self.app_version,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            self.threads,
+            self.copy_workers
+        );
+        match &self.download {
+            Some(result) => text.push_str(&line("Download", result)),
+            None => text.push_str(&format!("{:<28}skipped\n", "Download")),
+        }
+        text.push_str(&line("Copy, one large file", &self.large_copy));
+        text.push_str(&line("Copy, small files serially", &self.small_copy_serial));
+        text.push_str(&line(&format!("Copy, small files x{}", self.copy_workers), &self.small_copy_parallel));
+        text.push_str(&line("SHA-256", &self.hash));
+
+        let findings = self.findings();
+        if !findings.is_empty() {
+            text.push_str("\nFindings:\n");
+            for finding in findings {
+                text.push_str(&format!("- {}\n", finding));
+            }
+        }
+        text
+    }
+
+    pub fn findings(&self) -> Vec<String> {
+        const MB: f64 = 1_048_576.0;
+        let mut findings = Vec::new();
+
+        if let Some(Ok(download)) = &self.download
+            && download.bytes_per_second() < MB
+        {
+            findings.push("The connection to GitHub is slow, downloads will take most of an install".to_string());
+        }
+        if let Ok(large) = &self.large_copy
+            && large.bytes_per_second() < 50.0 * MB
+        {
+            findings.push("Writing to the game folder is slow, the drive may be a hard disk or busy".to_string());
+        }
+        // Small files should get at least a fraction of the large file speed;
+        // much less means every new file costs a lot, typically a scan
+        if let (Ok(large), Ok(small)) = (&self.large_copy, &self.small_copy_serial)
+            && small.bytes_per_second() < large.bytes_per_second() / 10.0
+        {
+            findings.push("Each new file takes long to write, which usually means antivirus scans every file. Excluding the game folder speeds up imports".to_string());
+        }
+        if let (Ok(serial), Ok(parallel)) = (&self.small_copy_serial, &self.small_copy_parallel) {
+            findings.push(format!(
+                "Copying {} files at once is {:.1}x as fast as one at a time",
+                self.copy_workers,
+                parallel.files_per_second() / serial.files_per_second().max(0.001)
+            ));
+        }
+        findings
+    }
+}
+
+// Doesn't compress or deduplicate, so the disk sees the real amount of data
+fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed | 1;
+    let mut bytes = Vec::with_capacity(len + 8);
+    while bytes.len() < len {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        bytes.extend_from_slice(&state.to_le_bytes());
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+fn write_sources(dir: &Path) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| trf("Failed to create benchmark folder: {}", &[&e]))?;
+
+    let large = dir.join("large.bin");
+    std::fs::write(&large, noise(LARGE_FILE_SIZE, 1))
+        .map_err(|e| trf("Failed to write benchmark file: {}", &[&e]))?;
+
+    let small = (0..SMALL_FILE_COUNT)
+        .map(|index| {
+            let path = dir.join(format!("small-{}.bin", index));
+            std::fs::write(&path, noise(SMALL_FILE_SIZE, index as u64 + 2))
+                .map_err(|e| trf("Failed to write benchmark file: {}", &[&e]))?;
+            Ok(path)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok((large, small))
+}
+
+fn copy_files(files: &[PathBuf], dest: &Path, cancel: &CancellationToken) -> Result<Measurement, String> {
+    std::fs::create_dir_all(dest)
+        .map_err(|e| trf("Failed to create benchmark folder: {}", &[&e]))?;
+
+    let start = Instant::now();
+    let mut bytes = 0;
+    for file in files {
+        cancel::checkpoint(cancel)?;
+        bytes += std::fs::copy(file, dest.join(file.file_name().unwrap_or_default()))
+            .map_err(|e| trf("Failed to copy: {}", &[&e]))?;
+    }
+
+    Ok(Measurement { bytes, files: files.len() as u64, elapsed: start.elapsed() })
+}
+
+// The same way imports copy: every file is its own job on the worker pool
+async fn copy_files_parallel(files: &[PathBuf], dest: &Path, cancel: &CancellationToken) -> Result<Measurement, String> {
+    std::fs::create_dir_all(dest)
+        .map_err(|e| trf("Failed to create benchmark folder: {}", &[&e]))?;
+
+    let start = Instant::now();
+    let mut jobs = tokio::task::JoinSet::new();
+    for file in files {
+        let source = file.clone();
+        let target = dest.join(file.file_name().unwrap_or_default());
+        jobs.spawn(workers::run(move || std::fs::copy(source, target)));
+    }
+
+    let mut bytes = 0;
+    while let Some(job) = jobs.join_next().await {
+        cancel::checkpoint(cancel)?;
+        bytes += job
+            .map_err(|e| trf("Copy job failed: {}", &[&e]))??
+            .map_err(|e| trf("Failed to copy: {}", &[&e]))?;
+    }
+
+    Ok(Measurement { bytes, files: files.len() as u64, elapsed: start.elapsed() })
+}
+
+fn hash_file(path: &Path) -> Result<Measurement, String> {
+    let bytes = std::fs::metadata(path)
+        .map_err(|e| trf("Failed to read benchmark file: {}", &[&e]))?
+        .len();
+    let start = Instant::now();
+    verify::sha256_file(path)?;
+
+    Ok(Measurement { bytes, files: 1, elapsed: start.elapsed() })
+}
+
+async fn measure_download(cancel: &CancellationToken) -> Result<Measurement, String> {
+    let resolved = download::resolve(DEFAULT_DLL_URL, crate::arch::Arch::X64, cancel, &|_| {}).await?;
+
+    let start = Instant::now();
+    let dll = download::fetch(&resolved, &[], cancel, &|_| {}, &|_| {}).await?;
+    let elapsed = start.elapsed();
+    let bytes = std::fs::metadata(&dll.path).map_or(0, |metadata| metadata.len());
+
+    Ok(Measurement { bytes, files: 1, elapsed })
+}
+
+pub async fn run(
+    app_version: &str,
+    game_path: &Path,
+    skip_download: bool,
+    cancel: &CancellationToken,
+    on_step: &(dyn Fn(&str) + Sync),
+) -> Result<BenchmarkReport, String> {
+    let download = if skip_download {
+        None
+    } else {
+        on_step("Measuring download speed");
+        Some(measure_download(cancel).await)
+    };
+
+    // Sources live in the temp folder, so reading them isn't what's measured
+    let source_dir = std::env::temp_dir().join(format!("tnsm-benchmark-{}", std::process::id()));
+    let scratch = game_path.join(format!(".tnsm-benchmark-{}", std::process::id()));
+
+    on_step("Preparing test files");
+    let sources = source_dir.clone();
+    let prepared = workers::run(move || write_sources(&sources)).await.and_then(|result| result);
+    let (large, small) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&source_dir);
+            return Err(e);
+        }
+    };
+
+    on_step("Copying a large file into the game folder");
+    let (files, dest, token) = (vec![large], scratch.join("large"), cancel.clone());
+    let large_copy = workers::run(move || copy_files(&files, &dest, &token)).await.and_then(|result| result);
+
+    on_step("Copying small files into the game folder");
+    let (files, dest, token) = (small.clone(), scratch.join("serial"), cancel.clone());
+    let small_copy_serial = workers::run(move || copy_files(&files, &dest, &token)).await.and_then(|result| result);
+
+    on_step("Copying small files in parallel");
+    let small_copy_parallel = copy_files_parallel(&small, &scratch.join("parallel"), cancel).await;
+
+    on_step("Hashing");
+    let copied = scratch.join("large").join("large.bin");
+    let hash = workers::run(move || hash_file(&copied)).await.and_then(|result| result);
+
+    let _ = std::fs::remove_dir_all(&scratch);
+    let _ = std::fs::remove_dir_all(&source_dir);
+    cancel::checkpoint(cancel)?;
+
+    Ok(BenchmarkReport {
+        app_version: app_version.to_string(),
+        threads: std::thread::available_parallelism().map_or(1, |count| count.get()),
+        copy_workers: workers::worker_count(),
+        download,
+        large_copy,
+        small_copy_serial,
+        small_copy_parallel,
+        hash,
+    })
+}
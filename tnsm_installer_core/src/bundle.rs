@@ -0,0 +1,418 @@
+use crate::cancel::{self, CancellationToken};
+use crate::download::{self, DownloadedDll};
+use crate::i18n::{tr, trf};
+use crate::install::{self, ProxyDll};
+use crate::arch::Arch;
+use crate::{arch, audit, manifest, resources, verify, workers};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+pub const BUNDLE_EXTENSION: &str = "tnsmbundle";
+
+// 2 added the DLL's release signature, older bundles are refused
+const BUNDLE_FORMAT: u32 = 2;
+const MANIFEST_NAME: &str = "manifest.json";
+const CHECKSUMS_NAME: &str = "checksums.sha256";
+const DLL_NAME: &str = "TNSM.dll";
+// The detached signature the release shipped for the DLL, see verify::check_signature
+const SIGNATURE_NAME: &str = "TNSM.dll.sig";
+const RESOURCES_DIR: &str = "resources";
+
+#[derive(Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: u32,
+    pub mod_version: Option<String>,
+    pub dll: String,
+    pub resource_packs: Vec<String>,
+}
+
+struct BundleWriter {
+    zip: zip::ZipWriter<std::fs::File>,
+    checksums: String,
+    cancel: CancellationToken,
+}
+
+impl BundleWriter {
+    fn add(&mut self, name: &str, bytes: &[u8]) -> Result<(), String> {
+        self.zip.start_file(name, SimpleFileOptions::default())
+            .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+        self.zip.write_all(bytes)
+            .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+
+        self.checksums.push_str(&format!("{}  {}\n", verify::sha256_hex(bytes), name));
+        Ok(())
+    }
+
+    // Streams a file into the archive, hashing it on the way through
+    fn add_file(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        let mut file = std::fs::File::open(path)
+            .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+
+        self.zip.start_file(name, SimpleFileOptions::default())
+            .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            cancel::checkpoint(&self.cancel)?;
+
+            let read = file.read(&mut buffer)
+                .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+            if read == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..read]);
+            self.zip.write_all(&buffer[..read])
+                .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+        }
+
+        self.checksums.push_str(&format!("{}  {}\n", verify::to_hex(&hasher.finalize()), name));
+        Ok(())
+    }
+
+    fn add_dir(&mut self, source: &Path, prefix: &str) -> Result<(), String> {
+        let entries = std::fs::read_dir(source)
+            .map_err(|e| trf("Failed to read directory: {}", &[&e]))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| trf("Failed to read entry: {}", &[&e]))?;
+            let source_path = entry.path();
+            let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+
+            if source_path.is_dir() {
+                self.add_dir(&source_path, &name)?;
+            } else {
+                self.add_file(&name, &source_path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn write_bundle(
+    dest: &Path,
+    dll: &DownloadedDll,
+    signature: &str,
+    resource_packs: &[PathBuf],
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let result = write_bundle_contents(dest, dll, signature, resource_packs, cancel);
+
+    // Don't leave a half-written bundle behind on failure or cancel
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    result
+}
+
+fn write_bundle_contents(
+    dest: &Path,
+    dll: &DownloadedDll,
+    signature: &str,
+    resource_packs: &[PathBuf],
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let file = std::fs::File::create(dest)
+        .map_err(|e| trf("Failed to create bundle: {}", &[&e]))?;
+
+    let mut writer = BundleWriter {
+        zip: zip::ZipWriter::new(file),
+        checksums: String::new(),
+        cancel: cancel.clone(),
+    };
+
+    writer.add_file(DLL_NAME, &dll.path)?;
+    writer.add(SIGNATURE_NAME, signature.as_bytes())?;
+
+    let mut pack_names = Vec::new();
+    for pack in resource_packs {
+        let name = pack.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| trf("Invalid resource folder: {}", &[&pack.display()]))?;
+
+        writer.add_dir(pack, &format!("{}/{}", RESOURCES_DIR, name))?;
+        pack_names.push(name);
+    }
+
+    let manifest = BundleManifest {
+        format: BUNDLE_FORMAT,
+        mod_version: dll.version.clone(),
+        dll: DLL_NAME.to_string(),
+        resource_packs: pack_names,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| trf("Failed to serialize manifest: {}", &[&e]))?;
+    writer.add(MANIFEST_NAME, &manifest_json)?;
+
+    let checksums = std::mem::take(&mut writer.checksums);
+    writer.zip.start_file(CHECKSUMS_NAME, SimpleFileOptions::default())
+        .map_err(|e| trf("Failed to add checksums: {}", &[&e]))?;
+    writer.zip.write_all(checksums.as_bytes())
+        .map_err(|e| trf("Failed to add checksums: {}", &[&e]))?;
+
+    writer.zip.finish()
+        .map_err(|e| trf("Failed to finish bundle: {}", &[&e]))?;
+
+    Ok(())
+}
+
+pub async fn create_bundle(
+    dll_url: &str,
+    mirrors: &[String],
+    dest: PathBuf,
+    resource_packs: Vec<PathBuf>,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<(), String> {
+    let resolved = download::resolve(dll_url, Arch::X64, cancel, on_rate_limited).await?;
+    let dll = download::fetch(&resolved, mirrors, cancel, on_rate_limited, &|_| {}).await?;
+    let checksum = download::fetch_checksum(&resolved, cancel, on_rate_limited).await?;
+    resolved.verify(&dll, checksum.as_deref())?;
+
+    // Only a signed release can be bundled, since that's what the bundle is
+    // checked against on the PC it's installed on
+    let signature = download::fetch_signature(&resolved, cancel, on_rate_limited).await?;
+    let dll_bytes = std::fs::read(&dll.path)
+        .map_err(|e| trf("Failed to read the download: {}", &[&e]))?;
+    verify::check_signature(&dll_bytes, &signature)?;
+
+    let cancel = cancel.clone();
+    workers::run(move || write_bundle(&dest, &dll, &signature, &resource_packs, &cancel)).await?
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<Vec<u8>, String> {
+    let mut file = archive.by_name(name)
+        .map_err(|e| trf("Bundle is missing {}: {}", &[&name, &e]))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .map_err(|e| trf("Failed to read {}: {}", &[&name, &e]))?;
+    Ok(bytes)
+}
+
+fn parse_checksums(contents: &str) -> Result<HashMap<String, String>, String> {
+    contents.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split_once("  ")
+                .map(|(hash, name)| (name.to_string(), hash.to_string()))
+                .ok_or_else(|| trf("Malformed checksum line: {}", &[&line]))
+        })
+        .collect()
+}
+
+// Checks every file in the bundle before anything is written to the game directory
+pub fn install_bundle(
+    path: &Path,
+    game_path: &Path,
+    proxy: ProxyDll,
+    cancel: &CancellationToken,
+) -> Result<BundleManifest, String> {
+    let audit = audit::begin("install bundle", Some(game_path)).dll(proxy).detail(path.display().to_string());
+    let result = unpack_bundle(path, game_path, proxy, cancel);
+    audit.finish(&result);
+    result
+}
+
+fn unpack_bundle(path: &Path, game_path: &Path, proxy: ProxyDll, cancel: &CancellationToken) -> Result<BundleManifest, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| trf("Failed to open bundle: {}", &[&e]))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| trf("Not a valid bundle: {}", &[&e]))?;
+
+    let checksums = String::from_utf8(read_entry(&mut archive, CHECKSUMS_NAME)?)
+        .map_err(|_| tr("Checksums file is not valid UTF-8").to_string())?;
+    let checksums = parse_checksums(&checksums)?;
+
+    let mut files = Vec::new();
+    let mut resource_files = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)
+            .map_err(|e| trf("Failed to read bundle entry: {}", &[&e]))?;
+        if entry.is_dir() || entry.name() == CHECKSUMS_NAME {
+            continue;
+        }
+
+        // Reject absolute paths and ".." so a bundle can't write outside the game folder
+        if entry.enclosed_name().is_none() {
+            return Err(trf("Unsafe path in bundle: {}", &[&entry.name()]));
+        }
+        if let Some(relative) = resource_relative(entry.name())? {
+            resource_files.push((entry.name().to_string(), relative));
+        }
+        files.push(entry.name().to_string());
+    }
+
+    for name in &files {
+        cancel::checkpoint(cancel)?;
+
+        let expected = checksums.get(name)
+            .ok_or_else(|| trf("{} is not listed in the bundle checksums", &[&name]))?;
+        let bytes = read_entry(&mut archive, name)?;
+
+        if &verify::sha256_hex(&bytes) != expected {
+            return Err(trf("Checksum mismatch for {}", &[&name]));
+        }
+    }
+
+    let manifest: BundleManifest = serde_json::from_slice(&read_entry(&mut archive, MANIFEST_NAME)?)
+        .map_err(|e| trf("Invalid bundle manifest: {}", &[&e]))?;
+    if manifest.format > BUNDLE_FORMAT {
+        return Err(tr("Bundle was created by a newer installer, please update ThatNoobSkyApp").to_string());
+    }
+    if manifest.format < BUNDLE_FORMAT {
+        return Err(tr("Bundle was created by an older installer and isn't signed, create it again").to_string());
+    }
+
+    // The checksums only catch damage, the signature is what shows the DLL
+    // is a real release
+    let dll_bytes = read_entry(&mut archive, &manifest.dll)?;
+    let signature = String::from_utf8(read_entry(&mut archive, SIGNATURE_NAME)?)
+        .map_err(|_| tr("Bundle signature is not valid UTF-8").to_string())?;
+    verify::check_signature(&dll_bytes, &signature)
+        .map_err(|e| trf("Bundle DLL failed the signature check: {}", &[&e]))?;
+    let dll_arch = arch::pe_arch_of(&dll_bytes, &manifest.dll)?;
+    let game_arch = arch::game_arch(game_path);
+    if dll_arch != game_arch {
+        return Err(trf(
+            "This bundle's DLL is built for {}, but Sky is {}",
+            &[&dll_arch.name(), &game_arch.name()]
+        ));
+    }
+
+    let dll_sha256 = verify::sha256_hex(&dll_bytes);
+    install::write_dll(game_path, proxy, &dll_bytes)?;
+    let _ = manifest::record_dll(game_path, &dll_sha256);
+
+    let resources_path = resources::resources_path(game_path);
+    for (name, relative) in &resource_files {
+        let dest_path = resources_path.join(relative);
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| trf("Failed to create directory: {}", &[&e]))?;
+        }
+
+        std::fs::write(&dest_path, read_entry(&mut archive, name)?)
+            .map_err(|e| trf("Failed to write {}: {}", &[&relative.display(), &e]))?;
+    }
+
+    Ok(manifest)
+}
+
+// Where resources/<pack>/<relative path> goes inside TNSM Resources, None for
+// the bundle's other files
+fn resource_relative(name: &str) -> Result<Option<PathBuf>, String> {
+    let Some(relative) = name.strip_prefix(&format!("{}/", RESOURCES_DIR))
+        .and_then(|rest| rest.split_once('/'))
+        .map(|(_, relative)| Path::new(relative))
+    else {
+        return Ok(None);
+    };
+
+    // enclosed_name() lets "p/../x" through since it stays inside the
+    // archive, but once the pack folder is stripped it climbs out of the
+    // resources folder
+    if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+        return Err(trf("Unsafe path in bundle: {}", &[&name]));
+    }
+    // Only TNSM.dll is covered by the release signature, so a program among
+    // the resources would be one nobody signed
+    let is_program = relative.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("dll") || extension.eq_ignore_ascii_case("exe"));
+    if is_program {
+        return Err(trf("The bundle's resources contain a program: {}", &[&name]));
+    }
+    Ok(Some(relative.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A bundle holding `files`, with checksums listed for all of them unless
+    // `checksums` is given
+    fn bundle(dir: &Path, files: &[(&str, &[u8])], checksums: Option<&str>) -> PathBuf {
+        let path = dir.join("test.tnsmbundle");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let mut listed = String::new();
+        for (name, bytes) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+            listed.push_str(&format!("{}  {}\n", verify::sha256_hex(bytes), name));
+        }
+        zip.start_file(CHECKSUMS_NAME, SimpleFileOptions::default()).unwrap();
+        zip.write_all(checksums.unwrap_or(&listed).as_bytes()).unwrap();
+        zip.finish().unwrap();
+        path
+    }
+
+    fn manifest(format: u32) -> Vec<u8> {
+        serde_json::to_vec(&BundleManifest {
+            format,
+            mod_version: None,
+            dll: DLL_NAME.to_string(),
+            resource_packs: Vec::new(),
+        }).unwrap()
+    }
+
+    fn unpack(path: &Path, game: &Path) -> Result<BundleManifest, String> {
+        unpack_bundle(path, game, ProxyDll::default(), &CancellationToken::new())
+    }
+
+    #[test]
+    fn resource_paths_stay_in_the_resources_folder() {
+        assert_eq!(resource_relative("resources/p/a/b.png").unwrap(), Some(PathBuf::from("a/b.png")));
+        assert_eq!(resource_relative(MANIFEST_NAME).unwrap(), None);
+        assert!(resource_relative("resources/p/../version.dll").is_err());
+        assert!(resource_relative("resources/p/a/../../b.png").is_err());
+        assert!(resource_relative("resources/p/tool.EXE").is_err());
+    }
+
+    #[test]
+    fn escaping_resource_is_refused_before_anything_is_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let game = dir.path().join("game");
+        std::fs::create_dir(&game).unwrap();
+        let path = bundle(dir.path(), &[
+            (MANIFEST_NAME, &manifest(BUNDLE_FORMAT)),
+            (DLL_NAME, b"dll"),
+            ("resources/p/../version.dll", b"planted"),
+        ], None);
+
+        let error = unpack(&path, &game).err().unwrap();
+        assert!(error.contains("resources/p/../version.dll"), "{}", error);
+        assert_eq!(std::fs::read_dir(&game).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn damaged_or_unlisted_files_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let listed = format!("{}  {}\n", verify::sha256_hex(b"other"), DLL_NAME);
+        let path = bundle(dir.path(), &[(DLL_NAME, b"dll")], Some(&listed));
+        assert!(unpack(&path, dir.path()).err().unwrap().contains("Checksum mismatch"));
+
+        let path = bundle(dir.path(), &[(DLL_NAME, b"dll")], Some(""));
+        assert!(unpack(&path, dir.path()).err().unwrap().contains("not listed"));
+    }
+
+    #[test]
+    fn unsigned_or_old_bundles_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = bundle(dir.path(), &[(MANIFEST_NAME, &manifest(1)), (DLL_NAME, b"dll")], None);
+        assert!(unpack(&path, dir.path()).err().unwrap().contains("older installer"));
+
+        let path = bundle(dir.path(), &[
+            (MANIFEST_NAME, &manifest(BUNDLE_FORMAT)),
+            (DLL_NAME, b"dll"),
+            (SIGNATURE_NAME, "00".repeat(64).as_bytes()),
+        ], None);
+        assert!(unpack(&path, dir.path()).err().unwrap().contains("signature check"));
+        assert!(!dir.path().join(ProxyDll::default().file_name()).exists());
+    }
+}
@@ -0,0 +1,15 @@
+pub use tokio_util::sync::CancellationToken;
+
+pub const CANCELLED: &str = "Cancelled";
+
+// Cooperative checkpoint for loops that can stop between steps
+pub fn checkpoint(cancel: &CancellationToken) -> Result<(), String> {
+    if cancel.is_cancelled() {
+        return Err(CANCELLED.to_string());
+    }
+    Ok(())
+}
+
+pub fn is_cancelled(error: &str) -> bool {
+    error == CANCELLED
+}
@@ -0,0 +1,139 @@
+// Known issues of mod versions on game builds, from compat.json in the mod's
+// repository so it can be updated without a new installer release:
+//
+//   {
+//     "builds": [
+//       {
+//         "build_id": 19283746,
+//         "issues": [
+//           { "mod_versions": "<v1.3.2", "description": "Outfit previews show the wrong colors" }
+//         ]
+//       }
+//     ]
+//   }
+//
+// A range is a comma separated list of comparisons that all have to hold,
+// e.g. ">=v1.2.0, <v1.3.0". Versions that aren't release tags (nightlies)
+// get every issue of their build. Nothing is refused, the issues are only
+// shown. The last copy fetched is kept in the app directory for when GitHub
+// can't be reached.
+
+use crate::cancel::CancellationToken;
+use crate::i18n::trf;
+use crate::{config, github};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+pub const COMPAT_FILE_NAME: &str = "compat.json";
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct CompatDb {
+    #[serde(default)]
+    pub builds: Vec<BuildEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BuildEntry {
+    pub build_id: u64,
+    #[serde(default)]
+    pub issues: Vec<KnownIssue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KnownIssue {
+    // Unset when it affects every version
+    #[serde(default)]
+    pub mod_versions: Option<String>,
+    pub description: String,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join(COMPAT_FILE_NAME))
+}
+
+// Release tags like "v1.2.3" as numbers; None for anything else
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    // Pre-release and build suffixes compare like the release they belong to
+    let version = version.split(['-', '+']).next()?;
+
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+fn compare(a: &[u64], b: &[u64]) -> Ordering {
+    let length = a.len().max(b.len());
+    let pad = |v: &[u64]| (0..length).map(|i| v.get(i).copied().unwrap_or(0)).collect::<Vec<_>>();
+    pad(a).cmp(&pad(b))
+}
+
+// None when the version or the range can't be understood
+pub fn in_range(version: &str, range: &str) -> Option<bool> {
+    let version = parse_version(version)?;
+
+    for comparison in range.split(',').map(str::trim).filter(|comparison| !comparison.is_empty()) {
+        let (operator, bound) = ["<=", ">=", "<", ">", "="]
+            .iter()
+            .find_map(|operator| comparison.strip_prefix(operator).map(|bound| (*operator, bound)))
+            .unwrap_or(("=", comparison));
+        let ordering = compare(&version, &parse_version(bound)?);
+
+        let holds = match operator {
+            "<=" => ordering != Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "<" => ordering == Ordering::Less,
+            ">" => ordering == Ordering::Greater,
+            _ => ordering == Ordering::Equal,
+        };
+        if !holds {
+            return Some(false);
+        }
+    }
+
+    Some(true)
+}
+
+impl CompatDb {
+    // The last copy fetched, empty when there is none
+    pub fn load() -> Self {
+        cache_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn build(&self, build_id: u64) -> Option<&BuildEntry> {
+        self.builds.iter().find(|entry| entry.build_id == build_id)
+    }
+
+    // Known issues of `version` on this game build, or of every version when unknown
+    pub fn known_issues(&self, build_id: u64, version: Option<&str>) -> Vec<&KnownIssue> {
+        let Some(entry) = self.build(build_id) else {
+            return Vec::new();
+        };
+
+        entry.issues
+            .iter()
+            .filter(|issue| match (&issue.mod_versions, version) {
+                (Some(range), Some(version)) => in_range(version, range) != Some(false),
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+// Downloads the latest database and keeps a copy, see CompatDb::load
+pub async fn refresh(cancel: &CancellationToken) -> Result<CompatDb, String> {
+    let contents = github::fetch_repo_file(COMPAT_FILE_NAME, cancel).await?;
+    let db: CompatDb = serde_json::from_str(&contents)
+        .map_err(|e| trf("Failed to parse compatibility database: {}", &[&e]))?;
+
+    if let Some(path) = cache_path() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, contents);
+    }
+    Ok(db)
+}
@@ -0,0 +1,268 @@
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::plugins::Plugin;
+use crate::proxy::ProxySettings;
+use crate::storage::Retention;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+// Bump this and append to MIGRATIONS whenever a field is renamed, moved or
+// changes meaning. Plain additions don't need a migration, `serde(default)`
+// already fills them in.
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const DEFAULT_DOWNLOAD_ATTEMPTS: u32 = 3;
+
+// MIGRATIONS[n] upgrades a version n config to version n + 1
+type Migration = fn(&mut serde_json::Map<String, Value>) -> Result<(), String>;
+
+const MIGRATIONS: &[Migration] = &[
+    // 0 -> 1: configs from before versioning, same fields
+    |_| Ok(()),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub schema_version: u32,
+    pub installed_version: Option<String>,
+    pub daily_update_check: bool,
+    pub launch_at_startup: bool,
+    pub folder_context_menu: bool,
+    // .tnsmpack files open with the installer, which has taskbar jump list tasks
+    pub shell_integration: bool,
+    // Keep running in the notification area and check for mod updates there
+    pub tray_mode: bool,
+    // List the files an install, import or uninstall would change and wait
+    // for Apply before touching them
+    pub preview_changes: bool,
+    // Importing a folder again also deletes the files an earlier import of it
+    // wrote that it no longer has
+    pub import_remove_missing: bool,
+    pub retention: Retention,
+    // Picked in the gallery when the screenshot folder isn't found on its own
+    pub screenshot_dir: Option<PathBuf>,
+    // The mod's runtime log, picked in the Live log tab
+    pub mod_log_path: Option<PathBuf>,
+    // Code of a community language file, English when unset
+    pub language: Option<String>,
+    // Account ID mod config backups are filed under, the most recent sign-in when unset
+    pub steam_account: Option<u32>,
+    // Release tag installs stay on, with update prompts suppressed, until unpinned
+    pub pinned_version: Option<String>,
+    // The two cached versions the A/B toggle switches between
+    pub ab_versions: Option<(String, String)>,
+    // Personal access token for downloading nightly builds
+    pub github_token: Option<String>,
+    // Status colors that stay apart with red-green color blindness
+    pub colorblind: bool,
+    pub theme: Theme,
+    // RGB color of the title, links and selections, the theme's own when unset
+    pub accent_color: Option<[u8; 3]>,
+    // Game folder found or picked last time, used instead of searching again
+    pub game_path: Option<PathBuf>,
+    // DLL URL installs use, the latest release when unset
+    pub dll_url: Option<String>,
+    // "Latest" includes pre-releases
+    pub beta_channel: bool,
+    // Companion DLL mods, in the order they're listed
+    pub plugins: Vec<Plugin>,
+    // Tries per request before it fails, or a download moves on to a mirror
+    pub download_attempts: u32,
+    // Endpoints filled by `publish-mirror`, tried in order when downloading a
+    // release from GitHub fails
+    pub download_mirrors: Vec<String>,
+    pub proxy: ProxySettings,
+    // System DLL name the mod is installed as
+    pub proxy_dll: ProxyDll,
+    // Fields from a newer installer's schema, kept so saving doesn't drop them
+    #[serde(flatten)]
+    extra: serde_json::Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            installed_version: None,
+            daily_update_check: false,
+            launch_at_startup: false,
+            folder_context_menu: false,
+            shell_integration: false,
+            tray_mode: false,
+            preview_changes: false,
+            import_remove_missing: false,
+            retention: Retention::default(),
+            screenshot_dir: None,
+            mod_log_path: None,
+            language: None,
+            steam_account: None,
+            pinned_version: None,
+            ab_versions: None,
+            github_token: None,
+            colorblind: false,
+            theme: Theme::default(),
+            accent_color: None,
+            game_path: None,
+            dll_url: None,
+            beta_channel: false,
+            plugins: Vec::new(),
+            download_attempts: DEFAULT_DOWNLOAD_ATTEMPTS,
+            download_mirrors: Vec::new(),
+            proxy: ProxySettings::default(),
+            proxy_dll: ProxyDll::default(),
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+// Per-user data directory (%APPDATA%/ThatNoobSkyApp on Windows)
+pub fn app_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ThatNoobSkyApp"))
+}
+
+// Everything the installer did, for pasting into bug reports
+pub fn installer_log_path() -> Option<PathBuf> {
+    app_dir().map(|dir| dir.join("installer.log"))
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    app_dir().map(|dir| dir.join("logs"))
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        app_dir().map(|dir| dir.join("config.json"))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        match Self::parse(&contents) {
+            Ok((config, migrated_from)) => {
+                // Keep the original around in case a migration got something wrong
+                if let Some(version) = migrated_from {
+                    let _ = std::fs::write(path.with_extension(format!("v{}.bak.json", version)), &contents);
+                    let _ = config.save();
+                }
+                config
+            }
+            Err(_) => {
+                // Don't lose an unreadable config to the defaults we're about to save over it
+                let _ = std::fs::write(path.with_extension("corrupt.json"), &contents);
+                Self::default()
+            }
+        }
+    }
+
+    // Returns the config and the schema version it was migrated from, if any
+    fn parse(contents: &str) -> Result<(Self, Option<u32>), String> {
+        let mut value: Value = serde_json::from_str(contents)
+            .map_err(|e| trf("Failed to parse config: {}", &[&e]))?;
+        let fields = value.as_object_mut().ok_or(tr("Config is not a JSON object"))?;
+
+        let version = fields.get("schema_version")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as u32;
+
+        // Up to date, or written by a newer installer, in which case read the fields we know
+        if version >= SCHEMA_VERSION {
+            let config = serde_json::from_value(value)
+                .map_err(|e| trf("Failed to parse config: {}", &[&e]))?;
+            return Ok((config, None));
+        }
+
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(fields)?;
+        }
+        fields.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+
+        let config = serde_json::from_value(value)
+            .map_err(|e| trf("Failed to parse config: {}", &[&e]))?;
+        Ok((config, Some(version)))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or(tr("Config directory not found"))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| trf("Failed to create config directory: {}", &[&e]))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| trf("Failed to serialize config: {}", &[&e]))?;
+
+        // Write then rename so a crash mid-save doesn't leave a corrupt config
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, contents)
+            .and_then(|_| std::fs::rename(&temp_path, &path))
+            .map_err(|e| trf("Failed to write config: {}", &[&e]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unversioned_configs_are_migrated() {
+        let (config, migrated_from) = Config::parse(r#"{"tray_mode": true, "game_path": "C:\\Sky"}"#).unwrap();
+
+        assert_eq!(migrated_from, Some(0));
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+        assert!(config.tray_mode);
+        assert_eq!(config.game_path, Some(PathBuf::from("C:\\Sky")));
+        assert_eq!(config.download_attempts, DEFAULT_DOWNLOAD_ATTEMPTS);
+    }
+
+    #[test]
+    fn current_configs_are_not_migrated() {
+        let contents = serde_json::to_string(&Config::default()).unwrap();
+        let (_, migrated_from) = Config::parse(&contents).unwrap();
+
+        assert_eq!(migrated_from, None);
+    }
+
+    #[test]
+    fn newer_fields_survive_a_save() {
+        let newer = format!(r#"{{"schema_version": {}, "beta_channel": true, "future_field": [1, 2]}}"#, SCHEMA_VERSION + 1);
+        let (config, migrated_from) = Config::parse(&newer).unwrap();
+        assert_eq!(migrated_from, None);
+
+        let saved: Value = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["schema_version"], SCHEMA_VERSION + 1);
+        assert_eq!(saved["beta_channel"], true);
+        assert_eq!(saved["future_field"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn non_objects_are_refused() {
+        assert!(Config::parse("[]").is_err());
+        assert!(Config::parse("{").is_err());
+    }
+}
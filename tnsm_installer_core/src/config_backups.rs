@@ -0,0 +1,58 @@
+// Backups of the mod's config file (keybinds and settings), kept per Steam
+// account so people sharing a PC can each switch back to their own setup.
+//
+//   <app dir>/mod-config/<account ID>/<timestamp>.json
+
+use crate::i18n::{tr, trf};
+use crate::mod_settings::{self, MOD_CONFIG_NAME};
+use crate::restore_point::RestorePoint;
+use crate::app_files::{self, Snapshot};
+use crate::{audit, config, timestamp};
+use std::path::{Path, PathBuf};
+
+pub type ConfigBackup = Snapshot;
+
+fn account_dir(account_id: u32) -> Result<PathBuf, String> {
+    config::app_dir()
+        .map(|dir| dir.join("mod-config").join(account_id.to_string()))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+// Newest first
+pub fn list(account_id: u32) -> Vec<ConfigBackup> {
+    app_files::list_snapshots(account_dir(account_id).ok(), "", ".json")
+}
+
+pub fn back_up(game_path: &Path, account_id: u32) -> Result<PathBuf, String> {
+    let source = mod_settings::config_path(game_path);
+    if !source.exists() {
+        return Err(tr("The mod hasn't written a config file yet").to_string());
+    }
+
+    let dir = account_dir(account_id)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| trf("Failed to create backup directory: {}", &[&e]))?;
+
+    let path = dir.join(format!("{}.json", timestamp::now()));
+    std::fs::copy(&source, &path)
+        .map_err(|e| trf("Failed to back up mod config: {}", &[&e]))?;
+    Ok(path)
+}
+
+// The config being replaced goes into a restore point first, which is returned
+pub fn restore(game_path: &Path, backup: &ConfigBackup) -> Result<Option<PathBuf>, String> {
+    let audit = audit::begin("restore mod config", Some(game_path)).detail(backup.label());
+    let result = replace_config(game_path, backup);
+    audit.finish(&result);
+    result
+}
+
+fn replace_config(game_path: &Path, backup: &ConfigBackup) -> Result<Option<PathBuf>, String> {
+    let restore_point = RestorePoint::begin(game_path, tr("Before restoring a mod config backup").to_string())?;
+    restore_point.preserve(Path::new(MOD_CONFIG_NAME))?;
+    let restore_point = restore_point.finish()?;
+
+    std::fs::copy(&backup.path, mod_settings::config_path(game_path))
+        .map_err(|e| trf("Failed to restore mod config: {}", &[&e]))?;
+    Ok(restore_point)
+}
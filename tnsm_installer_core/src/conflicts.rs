@@ -0,0 +1,86 @@
+// Other mods in the game folder that get into Sky the way TNSM does: proxy
+// DLLs next to Sky.exe and the files injectors leave there. With two of them
+// one usually just doesn't load, without any error. Disabling moves them into
+// a restore point, so it can be undone.
+
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::plugins::Plugin;
+use crate::orphans::PROXY_DLL_NAMES;
+use crate::restore_point::RestorePoint;
+use crate::{audit, install, plugins};
+use std::path::{Path, PathBuf};
+
+// Settings files of injectors, which come with a proxy DLL of their own
+const INJECTOR_FILES: &[(&str, &str)] = &[
+    ("reshade.ini", "ReShade"),
+    ("doorstop_config.ini", "Unity Doorstop"),
+];
+
+#[derive(Clone, Debug)]
+pub struct Conflict {
+    // Directly in the game folder
+    pub file_name: String,
+    pub description: String,
+}
+
+fn describe(name: &str) -> Option<String> {
+    if name == "tnsm.dll" {
+        return Some(tr("An older manual install of TNSM").to_string());
+    }
+    if PROXY_DLL_NAMES.contains(&name) {
+        return Some(tr("Proxy DLL of another mod or overlay").to_string());
+    }
+    if name.ends_with(".asi") {
+        return Some(tr("Plugin for an ASI loader").to_string());
+    }
+    INJECTOR_FILES.iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, injector)| trf("Settings of {}, which loads as a proxy DLL", &[&injector]))
+}
+
+// Only looks at the top of the game folder, quick enough to run whenever the
+// folder is picked
+pub fn scan(game_path: &Path, proxy: ProxyDll, known_plugins: &[Plugin]) -> Vec<Conflict> {
+    let own = install::dll_path(game_path, proxy);
+    let plugin_files = plugins::installed_files(game_path, known_plugins);
+
+    let mut conflicts: Vec<_> = std::fs::read_dir(game_path)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .filter(|entry| entry.path() != own && !plugin_files.contains(&entry.path()))
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let description = describe(&file_name.to_lowercase())?;
+            Some(Conflict { file_name, description })
+        })
+        .collect();
+    conflicts.sort_by_key(|conflict| conflict.file_name.to_lowercase());
+    conflicts
+}
+
+// Returns how many files were disabled and the restore point they were saved to
+pub fn disable(game_path: &Path, conflicts: &[Conflict]) -> Result<(usize, Option<PathBuf>), String> {
+    let audit = audit::begin("disable conflicting mods", Some(game_path)).detail(
+        conflicts.iter().map(|conflict| conflict.file_name.as_str()).collect::<Vec<_>>().join(", ")
+    );
+    let result = move_aside(game_path, conflicts);
+    audit.finish(&result);
+    result
+}
+
+fn move_aside(game_path: &Path, conflicts: &[Conflict]) -> Result<(usize, Option<PathBuf>), String> {
+    let restore_point = RestorePoint::begin(game_path, tr("Before disabling conflicting mods").to_string())?;
+    for conflict in conflicts {
+        restore_point.preserve(Path::new(&conflict.file_name))?;
+    }
+    let restore_point = restore_point.finish()?;
+
+    for conflict in conflicts {
+        std::fs::remove_file(game_path.join(&conflict.file_name))
+            .map_err(|e| trf("Failed to disable {}: {}", &[&conflict.file_name, &e]))?;
+    }
+    Ok((conflicts.len(), restore_point))
+}
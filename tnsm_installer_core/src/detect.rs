@@ -0,0 +1,88 @@
+use crate::vdf;
+use std::path::{Path, PathBuf};
+
+pub const GAME_FOLDER_NAME: &str = "Sky Children of the Light";
+pub const GAME_EXE_NAME: &str = "Sky.exe";
+pub const STEAM_APP_ID: u32 = 2325290;
+
+pub fn detect_steam_path() -> Option<PathBuf> {
+    // Where Steam says it's installed, then the common installation paths
+    let mut possible_paths = registry_steam_paths();
+    possible_paths.push(PathBuf::from("C:\\Program Files (x86)\\Steam"));
+    possible_paths.push(PathBuf::from("C:\\Program Files\\Steam"));
+
+    possible_paths.into_iter().find(|path| is_steam_directory(path))
+}
+
+#[cfg(windows)]
+fn registry_steam_paths() -> Vec<PathBuf> {
+    use crate::registry;
+
+    [
+        (r"HKCU\Software\Valve\Steam", "SteamPath"),
+        (r"HKCU\Software\Valve\Steam", "InstallPath"),
+        (r"HKLM\SOFTWARE\WOW6432Node\Valve\Steam", "InstallPath"),
+        (r"HKLM\SOFTWARE\Valve\Steam", "InstallPath"),
+    ]
+    .into_iter()
+    .filter_map(|(key, name)| registry::query_value(key, name))
+    // SteamPath is written with forward slashes
+    .map(|path| PathBuf::from(path.replace('/', "\\")))
+    .collect()
+}
+
+#[cfg(not(windows))]
+fn registry_steam_paths() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+// Every Steam library on this PC, listed in steamapps\libraryfolders.vdf.
+// The Steam folder itself always comes first.
+pub fn library_folders(steam_path: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_path.to_path_buf()];
+    let Ok(contents) = std::fs::read_to_string(steam_path.join("steamapps").join("libraryfolders.vdf")) else {
+        return libraries;
+    };
+
+    for (depth, key, value) in vdf::values(&contents) {
+        // Current Steam: "libraryfolders" { "1" { "path" "F:\\Games" ... } }
+        // Older Steam:   "libraryfolders" { "1" "F:\\Games" }
+        let is_library = (depth == 2 && key.eq_ignore_ascii_case("path"))
+            || (depth == 1 && key.chars().all(|c| c.is_ascii_digit()));
+        let path = PathBuf::from(value);
+        if is_library && !libraries.contains(&path) {
+            libraries.push(path);
+        }
+    }
+
+    libraries
+}
+
+pub fn find_game_directory(steam_path: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = library_folders(steam_path)
+        .into_iter()
+        .map(|library| library.join("steamapps").join("common").join(GAME_FOLDER_NAME))
+        .collect();
+    // Common library locations, for when libraryfolders.vdf can't be read
+    candidates.push(PathBuf::from("D:\\SteamLibrary\\steamapps\\common").join(GAME_FOLDER_NAME));
+    candidates.push(PathBuf::from("E:\\SteamLibrary\\steamapps\\common").join(GAME_FOLDER_NAME));
+
+    candidates.into_iter().find(|folder| folder.exists())
+}
+
+pub fn is_steam_directory(path: &Path) -> bool {
+    path.join("steamapps").exists()
+}
+
+pub fn is_game_directory(path: &Path) -> bool {
+    path.exists() && (path.join(GAME_EXE_NAME).exists() || path.ends_with(GAME_FOLDER_NAME))
+}
+
+// Steam's build ID of the installed game, from the app manifest in the
+// library's steamapps folder (<library>\steamapps\common\<game>)
+pub fn game_build_id(game_path: &Path) -> Option<u64> {
+    let steamapps = game_path.parent()?.parent()?;
+    let manifest = std::fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", STEAM_APP_ID))).ok()?;
+
+    vdf::find(&manifest, "buildid")?.parse().ok()
+}
@@ -0,0 +1,170 @@
+// One zip with everything needed to look into a "the mod doesn't load"
+// report, for attaching to an issue:
+//
+//   system.txt      installer, OS and game versions and the install state
+//   hashes.txt      SHA-256 of the mod DLL, plugins and Sky.exe
+//   resources.txt   every file in TNSM Resources with its size
+//   installer.log   (and installer.log.old)
+//   mod/            the mod's config from the game folder, and its log when picked
+
+use crate::cancel::{self, CancellationToken};
+use crate::config::{self, Config};
+use crate::i18n::trf;
+use crate::mod_settings::{self, MOD_CONFIG_NAME};
+use crate::resources::{self, RESOURCES_DIR_NAME};
+use crate::{arch, audit, detect, game_process, install, plugins, verify, workers};
+use jwalk::WalkDir;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+#[cfg(windows)]
+fn os_version() -> String {
+    const KEY: &str = r"HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+    let value = |name| crate::registry::query_value(KEY, name).unwrap_or_else(|| "?".to_string());
+    format!("{} {} (build {})", value("ProductName"), value("DisplayVersion"), value("CurrentBuild"))
+}
+
+#[cfg(not(windows))]
+fn os_version() -> String {
+    std::env::consts::OS.to_string()
+}
+
+fn system_info(game_path: Option<&Path>, config: &Config, app_version: &str) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "Installer: {}", app_version);
+    let _ = writeln!(text, "OS: {} ({})", os_version(), arch::host_arch().name());
+    let _ = writeln!(text, "Mod version: {}", config.installed_version.as_deref().unwrap_or("unknown"));
+    let _ = writeln!(text, "Pinned version: {}", config.pinned_version.as_deref().unwrap_or("none"));
+    let _ = writeln!(text, "Beta channel: {}", config.beta_channel);
+    let _ = writeln!(text, "Sky running: {}", game_process::is_running());
+
+    let Some(game_path) = game_path else {
+        let _ = writeln!(text, "Game folder: not found");
+        return text;
+    };
+    let _ = writeln!(text, "Game folder: {}", game_path.display());
+    let _ = writeln!(text, "Game build: {}", detect::game_build_id(game_path).map_or("unknown".to_string(), |id| id.to_string()));
+    let _ = writeln!(text, "Game architecture: {}", arch::game_arch(game_path).name());
+    let _ = writeln!(text, "Mod DLL: {} (present: {})", config.proxy_dll.file_name(), install::dll_path(game_path, config.proxy_dll).exists());
+    for plugin in &config.plugins {
+        let _ = writeln!(text, "Plugin: {} as {} ({:?}, {})", plugin.name, plugin.file_name, plugin.state(game_path), plugin.url);
+    }
+    text
+}
+
+fn file_hashes(game_path: &Path, config: &Config) -> String {
+    let mut files = vec![install::dll_path(game_path, config.proxy_dll), game_path.join(detect::GAME_EXE_NAME)];
+    files.extend(plugins::installed_files(game_path, &config.plugins));
+
+    let mut text = String::new();
+    for path in files.iter().filter(|path| path.exists()) {
+        let hash = verify::sha256_file(path).unwrap_or_else(|e| format!("unreadable: {}", e));
+        let size = std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+        let name = path.strip_prefix(game_path).unwrap_or(path);
+        let _ = writeln!(text, "{}  {}  {} bytes", hash, name.display(), size);
+    }
+    text
+}
+
+fn resource_list(game_path: &Path, cancel: &CancellationToken) -> Result<String, String> {
+    let root = resources::resources_path(game_path);
+    if !root.exists() {
+        return Ok(format!("No {} folder\n", RESOURCES_DIR_NAME));
+    }
+
+    let mut text = String::new();
+    for entry in WalkDir::new(&root).skip_hidden(false).sort(true) {
+        cancel::checkpoint(cancel)?;
+
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        let size = entry.metadata().map_or(0, |metadata| metadata.len());
+        let _ = writeln!(text, "{}  {} bytes", path.strip_prefix(&root).unwrap_or(&path).display(), size);
+    }
+    Ok(text)
+}
+
+fn add(zip: &mut zip::ZipWriter<std::fs::File>, name: &str, bytes: &[u8]) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+    zip.write_all(bytes)
+        .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))
+}
+
+// Missing files are skipped, a diagnostics zip is still useful without them
+fn add_file(zip: &mut zip::ZipWriter<std::fs::File>, name: &str, path: &Path) -> Result<(), String> {
+    match std::fs::read(path) {
+        Ok(bytes) => add(zip, name, &bytes),
+        Err(_) => Ok(()),
+    }
+}
+
+pub async fn collect(
+    dest: PathBuf,
+    game_path: Option<PathBuf>,
+    config: Config,
+    app_version: &'static str,
+    cancel: CancellationToken,
+) -> Result<(), String> {
+    workers::run(move || write_diagnostics(&dest, game_path.as_deref(), &config, app_version, &cancel)).await?
+}
+
+fn write_diagnostics(
+    dest: &Path,
+    game_path: Option<&Path>,
+    config: &Config,
+    app_version: &str,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let audit = audit::begin("collect diagnostics", game_path).detail(dest.display().to_string());
+    let result = write_zip(dest, game_path, config, app_version, cancel);
+
+    // Don't leave a half-written zip behind on failure or cancel
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    audit.finish(&result);
+    result
+}
+
+fn write_zip(
+    dest: &Path,
+    game_path: Option<&Path>,
+    config: &Config,
+    app_version: &str,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    let file = std::fs::File::create(dest)
+        .map_err(|e| trf("Failed to create diagnostics zip: {}", &[&e]))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    add(&mut zip, "system.txt", system_info(game_path, config, app_version).as_bytes())?;
+
+    if let Some(log) = config::installer_log_path() {
+        add_file(&mut zip, "installer.log.old", &log.with_extension("log.old"))?;
+        add_file(&mut zip, "installer.log", &log)?;
+    }
+
+    if let Some(game_path) = game_path {
+        cancel::checkpoint(cancel)?;
+        add(&mut zip, "hashes.txt", file_hashes(game_path, config).as_bytes())?;
+        add(&mut zip, "resources.txt", resource_list(game_path, cancel)?.as_bytes())?;
+        if let Some(mod_log) = &config.mod_log_path
+            && let Some(log_name) = mod_log.file_name()
+        {
+            add_file(&mut zip, &format!("mod/{}", log_name.to_string_lossy()), mod_log)?;
+        }
+        add_file(&mut zip, &format!("mod/{}", MOD_CONFIG_NAME), &mod_settings::config_path(game_path))?;
+    }
+
+    zip.finish()
+        .map_err(|e| trf("Failed to finish diagnostics zip: {}", &[&e]))?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+// Free space checks before writing, so a nearly full drive stops an install or
+// import up front with a clear message instead of halfway through a copy.
+
+use crate::i18n::trf;
+use crate::storage;
+use std::path::Path;
+
+// Room left for filesystem overhead and whatever else writes meanwhile
+const MARGIN: u64 = 16 * 1024 * 1024;
+
+#[cfg(windows)]
+fn available(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut())
+    };
+    (ok != 0).then_some(available)
+}
+
+// Not checked elsewhere, the write itself reports a full disk
+#[cfg(not(windows))]
+fn available(_path: &Path) -> Option<u64> {
+    None
+}
+
+// Bytes free on the drive `path` is on. The path itself doesn't have to exist
+// yet, the nearest folder above it that does is asked instead.
+pub fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|ancestor| ancestor.exists())?;
+    available(existing)
+}
+
+// `what` names the files being written, e.g. "the mod DLL"
+pub fn ensure_free(path: &Path, needed: u64, what: &str) -> Result<(), String> {
+    let Some(free) = free_space(path) else {
+        return Ok(());
+    };
+    if free >= needed.saturating_add(MARGIN) {
+        return Ok(());
+    }
+
+    let drive = path.components().next().map_or(path, |first| Path::new(first.as_os_str()));
+    Err(trf(
+        "Not enough free space on {} for {}: {} needed, {} free",
+        &[
+            &drive.display(),
+            &what,
+            &storage::format_size(needed.saturating_add(MARGIN)),
+            &storage::format_size(free),
+        ],
+    ))
+}
@@ -0,0 +1,428 @@
+use crate::arch::Arch;
+use crate::cancel::{self, CancellationToken};
+use crate::events::TransferProgress;
+use crate::github::{self, HttpClient, Release};
+use crate::i18n::{tr, trf};
+use crate::{disk_space, mirror, verify};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+pub const DEFAULT_DLL_URL: &str = "https://github.com/alvindimas05/ThatNoobSkyMod/releases/latest/download/TNSM.dll";
+
+// The DLL of one release, resolved through the API like "latest" so the
+// version is known and the download is checked against its digest
+pub fn release_dll_url(tag: &str) -> String {
+    format!("https://github.com/{}/releases/download/{}/{}", github::REPO, tag, github::DLL_ASSET_NAME)
+}
+
+// The release a DLL URL from release_dll_url points at
+pub fn release_tag(dll_url: &str) -> Option<&str> {
+    dll_url
+        .strip_prefix(&format!("https://github.com/{}/releases/download/", github::REPO))?
+        .strip_suffix(&format!("/{}", github::DLL_ASSET_NAME))
+        .filter(|tag| !tag.contains('/'))
+}
+
+// A verified download sitting in a temp file, removed again when dropped
+pub struct DownloadedDll {
+    pub path: PathBuf,
+    pub sha256: String,
+    pub version: Option<String>,
+}
+
+impl Drop for DownloadedDll {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+pub fn temp_download_path() -> PathBuf {
+    static NEXT_ID: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    let id = NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+    std::env::temp_dir().join(format!("TNSM-{}-{}.dll.part", std::process::id(), id))
+}
+
+// A DLL already on disk, copied to a temp file so the install pipeline can
+// treat it like a download
+pub fn copy_local(source: &Path, version: Option<String>) -> Result<DownloadedDll, String> {
+    let mut dll = DownloadedDll {
+        path: temp_download_path(),
+        sha256: String::new(),
+        version,
+    };
+    std::fs::copy(source, &dll.path)
+        .map_err(|e| trf("Failed to copy {}: {}", &[&source.display(), &e]))?;
+
+    dll.sha256 = verify::sha256_file(&dll.path)?;
+    Ok(dll)
+}
+
+// Where a DLL URL actually downloads from, plus what's known about it up front
+pub struct ResolvedDownload {
+    pub url: String,
+    pub version: Option<String>,
+    pub digest: Option<String>,
+    // A checksums file published with the release, e.g. TNSM.dll.sha256
+    pub checksum_url: Option<String>,
+    // The detached signature published with the release, "<asset>.sig"
+    pub signature_url: Option<String>,
+    // Sent along with the download, only needed for Actions artifacts
+    pub token: Option<String>,
+}
+
+impl ResolvedDownload {
+    pub fn file_name(&self) -> &str {
+        self.url.rsplit('/').next().unwrap_or(&self.url)
+    }
+
+    // Checks a finished download against GitHub's asset digest and the
+    // release's checksums file, whichever of them there are
+    pub fn verify(&self, dll: &DownloadedDll, checksum: Option<&str>) -> Result<(), String> {
+        if let Some(digest) = &self.digest {
+            verify::check_github_digest(&dll.sha256, digest)?;
+        }
+        if let Some(expected) = checksum
+            && expected != dll.sha256
+        {
+            return Err(trf(
+                "Checksum mismatch with {} (expected {}, got {})",
+                &[&self.checksum_url.as_deref().unwrap_or(tr("the release checksums")), &expected, &dll.sha256]
+            ));
+        }
+        Ok(())
+    }
+}
+
+// The expected hash from the release's checksums file, None when the
+// release doesn't have one. A checksums file that can't be fetched or
+// doesn't list the download fails, rather than installing unchecked.
+pub async fn fetch_checksum(
+    resolved: &ResolvedDownload,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Option<String>, String> {
+    let Some(url) = &resolved.checksum_url else {
+        return Ok(None);
+    };
+
+    let response = github::get_with_retry(&github::client()?, url, cancel, on_rate_limited)
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Failed to fetch checksums: {}", &[&e]) })?;
+    let contents = response.text()
+        .await
+        .map_err(|e| trf("Failed to read checksums: {}", &[&e]))?;
+
+    verify::checksum_for(&contents, resolved.file_name())
+        .map(Some)
+        .ok_or_else(|| trf("{} isn't listed in the release checksums", &[&resolved.file_name()]))
+}
+
+// The release's signature for the download, checked against the release key.
+// Fails when the release wasn't signed.
+pub async fn fetch_signature(
+    resolved: &ResolvedDownload,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<String, String> {
+    let url = resolved.signature_url.as_ref()
+        .ok_or_else(|| trf("{} isn't signed by the ThatNoobSkyMod maintainers", &[&resolved.file_name()]))?;
+
+    let response = github::get_with_retry(&github::client()?, url, cancel, on_rate_limited)
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Failed to fetch signature: {}", &[&e]) })?;
+    response.text()
+        .await
+        .map(|signature| signature.trim().to_string())
+        .map_err(|e| trf("Failed to read signature: {}", &[&e]))
+}
+
+// On the beta channel "latest" becomes the newest release, pre-releases
+// included. Any other URL is left as it is.
+pub async fn channel_dll_url(
+    dll_url: &str,
+    beta: bool,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<String, String> {
+    if !beta || dll_url != DEFAULT_DLL_URL {
+        return Ok(dll_url.to_string());
+    }
+
+    let release = github::fetch_newest_release(true, cancel, on_rate_limited).await?;
+    Ok(release_dll_url(&release.tag_name))
+}
+
+// "<asset>.sha256" next to the asset, or one checksums file for the release
+pub fn checksum_asset(release: &Release, asset_name: &str) -> Option<String> {
+    release.asset(&format!("{}.sha256", asset_name))
+        .or_else(|| release.asset(mirror::CHECKSUMS_NAME))
+        .map(|asset| asset.browser_download_url.clone())
+}
+
+pub async fn resolve(
+    dll_url: &str,
+    arch: Arch,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<ResolvedDownload, String> {
+    // Resolve the release behind "latest" so we know the installed version
+    // and can check the download against GitHub's asset digest
+    let lookup = if dll_url == DEFAULT_DLL_URL {
+        Some(github::fetch_latest_release(cancel, on_rate_limited).await)
+    } else if let Some(tag) = release_tag(dll_url) {
+        Some(github::fetch_release_by_tag(tag, cancel, on_rate_limited).await)
+    } else {
+        None
+    };
+    // Without the release there's nothing to check the download against, so
+    // a failed lookup stops the install instead of skipping the checks
+    let release = match lookup {
+        Some(Ok(release)) => Some(release),
+        Some(Err(e)) if cancel::is_cancelled(&e) => return Err(e),
+        Some(Err(e)) => return Err(trf("Couldn't look up the release to verify the download: {}", &[&e])),
+        None => None,
+    };
+    let asset = release.as_ref().and_then(|release| release.asset(arch.dll_asset_name()));
+
+    // Falling back to the x64 DLL would only fail to load in a native ARM64 game
+    if release.is_some() && asset.is_none() && arch != Arch::X64 {
+        return Err(trf(
+            "ThatNoobSkyMod doesn't ship an {} build yet, and this copy of Sky is {}. Use an x64 copy of the game or check back after the next release.",
+            &[&arch.name(), &arch.name()]
+        ));
+    }
+
+    Ok(ResolvedDownload {
+        url: asset.map_or(dll_url, |asset| asset.browser_download_url.as_str()).to_string(),
+        version: release.as_ref().map(|release| release.tag_name.clone()),
+        digest: asset.and_then(|asset| asset.digest.clone()),
+        checksum_url: asset.and_then(|asset| checksum_asset(release.as_ref()?, &asset.name)),
+        signature_url: asset
+            .and_then(|asset| release.as_ref()?.asset(&format!("{}.sig", asset.name)))
+            .map(|asset| asset.browser_download_url.clone()),
+        token: None,
+    })
+}
+
+// Why a download failed, and whether starting it again could help
+enum FetchError {
+    // Cut off partway
+    Interrupted(String),
+    Failed(String),
+}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> Self {
+        FetchError::Failed(message)
+    }
+}
+
+impl From<FetchError> for String {
+    fn from(error: FetchError) -> Self {
+        match error {
+            FetchError::Interrupted(message) | FetchError::Failed(message) => message,
+        }
+    }
+}
+
+// Downloads without verifying, see ResolvedDownload::verify. When GitHub
+// fails, a release is downloaded from `mirrors` in turn, still checked
+// against GitHub's digest and checksums afterwards.
+pub async fn fetch(
+    resolved: &ResolvedDownload,
+    mirrors: &[String],
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<DownloadedDll, String> {
+    let mut result = fetch_from(&resolved.url, resolved, cancel, on_rate_limited, on_progress).await;
+
+    // Mirrors only have releases, not nightly builds or other URLs
+    let (Some(version), None) = (&resolved.version, &resolved.token) else {
+        return result;
+    };
+    for endpoint in mirrors {
+        let failure = match &result {
+            Err(e) if !cancel::is_cancelled(e) => e.clone(),
+            _ => break,
+        };
+        let url = mirror::asset_url(endpoint, version, resolved.file_name());
+        tracing::warn!("Download failed, trying mirror {}: {}", url, failure);
+        result = fetch_from(&url, resolved, cancel, on_rate_limited, on_progress).await;
+    }
+    result
+}
+
+// A download being streamed into its .part file, kept across attempts so a
+// dropped connection picks up where it left off
+struct PartialDownload {
+    download: DownloadedDll,
+    file: tokio::fs::File,
+    hasher: Sha256,
+    progress: TransferProgress,
+}
+
+impl PartialDownload {
+    async fn create(version: Option<String>) -> Result<Self, String> {
+        // Owning the path from here on means the temp file is cleaned up on every error
+        let download = DownloadedDll {
+            path: temp_download_path(),
+            sha256: String::new(),
+            version,
+        };
+        let file = tokio::fs::File::create(&download.path)
+            .await
+            .map_err(|e| trf("Failed to create temp file: {}", &[&e]))?;
+
+        Ok(Self {
+            download,
+            file,
+            hasher: Sha256::new(),
+            progress: TransferProgress { files_total: 1, ..Default::default() },
+        })
+    }
+
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), String> {
+        self.hasher.update(chunk);
+        self.file.write_all(chunk)
+            .await
+            .map_err(|e| trf("Failed to write temp file: {}", &[&e]))?;
+        self.progress.bytes_done += chunk.len() as u64;
+        Ok(())
+    }
+
+    // For servers that send the whole file again instead of the rest
+    async fn restart(&mut self) -> Result<(), String> {
+        self.file.set_len(0)
+            .await
+            .and(self.file.rewind().await)
+            .map_err(|e| trf("Failed to write temp file: {}", &[&e]))?;
+        self.hasher = Sha256::new();
+        self.progress.bytes_done = 0;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<DownloadedDll, String> {
+        self.file.flush()
+            .await
+            .map_err(|e| trf("Failed to write temp file: {}", &[&e]))?;
+        self.download.sha256 = verify::to_hex(&self.hasher.finalize());
+        Ok(self.download)
+    }
+}
+
+// The first byte of a 206 response, from "Content-Range: bytes 1000-1999/2000"
+fn range_start(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .parse()
+        .ok()
+}
+
+// Resumes with a Range request when the connection drops partway, up to the
+// configured attempts
+async fn fetch_from(
+    url: &str,
+    resolved: &ResolvedDownload,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<DownloadedDll, String> {
+    let client = github::client()?;
+    let attempts = client.attempts;
+    let mut failures = 0;
+    let mut partial = PartialDownload::create(resolved.version.clone()).await?;
+
+    loop {
+        match fetch_into(&client, &mut partial, url, resolved, cancel, on_rate_limited, on_progress).await {
+            Ok(()) => break,
+            Err(FetchError::Interrupted(e)) if failures + 1 < attempts => {
+                failures += 1;
+                tracing::warn!(
+                    "Download of {} was cut off after {} bytes, resuming ({}/{}): {}",
+                    url, partial.progress.bytes_done, failures + 1, attempts, e
+                );
+                github::backoff(failures, cancel).await?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let bytes = partial.progress.bytes_done;
+    let download = partial.finish().await?;
+    tracing::info!("Downloaded {} ({} bytes, SHA-256 {})", url, bytes, download.sha256);
+    Ok(download)
+}
+
+async fn fetch_into(
+    client: &HttpClient,
+    partial: &mut PartialDownload,
+    url: &str,
+    resolved: &ResolvedDownload,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<(), FetchError> {
+    let from = partial.progress.bytes_done;
+    let mut response = github::get_range(client, url, resolved.token.as_deref(), from, cancel, on_rate_limited)
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Download failed: {}", &[&e]) })?;
+
+    if from > 0 {
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            partial.restart().await?;
+        } else if range_start(&response) != Some(from) {
+            // Not the part that's missing, ask for the whole file next time
+            partial.restart().await?;
+            return Err(FetchError::Interrupted(tr("Server resumed at the wrong position").to_string()));
+        }
+    }
+
+    let remaining = response.content_length();
+    if let Some(length) = remaining {
+        disk_space::ensure_free(&std::env::temp_dir(), length, tr("the download"))?;
+    }
+    partial.progress.bytes_total = remaining.map_or(0, |length| partial.progress.bytes_done + length);
+    on_progress(partial.progress);
+
+    // Stream chunks to disk, hashing as we go, so memory stays flat
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk.map_err(|e| FetchError::Interrupted(trf("Failed to read response: {}", &[&e])))?,
+            _ = cancel.cancelled() => return Err(cancel::CANCELLED.to_string().into()),
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+
+        partial.write(&chunk).await?;
+        on_progress(partial.progress);
+    }
+
+    if partial.progress.bytes_total > 0 && partial.progress.bytes_done < partial.progress.bytes_total {
+        return Err(FetchError::Interrupted(tr("Connection closed before the download finished").to_string()));
+    }
+    Ok(())
+}
+
+pub async fn download_dll(
+    dll_url: &str,
+    mirrors: &[String],
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<DownloadedDll, String> {
+    let resolved = resolve(dll_url, Arch::X64, cancel, on_rate_limited).await?;
+    let download = fetch(&resolved, mirrors, cancel, on_rate_limited, on_progress).await?;
+    let checksum = fetch_checksum(&resolved, cancel, on_rate_limited).await?;
+    resolved.verify(&download, checksum.as_deref())?;
+
+    Ok(download)
+}
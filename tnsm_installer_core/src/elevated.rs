@@ -0,0 +1,340 @@
+// Changes to a game folder this account can't write to, e.g. one under
+// Program Files set up by an administrator. Rather than running the whole
+// installer elevated, a small helper is started with administrator approval
+// for just those file operations. The installer keeps downloading, checking
+// and deciding what to do; the helper only takes requests over a pipe the
+// installer created, checks each one is a plain path inside the game folder
+// it was started for, and writes or deletes that one file. File contents
+// travel over the pipe too, so the helper never reads a path the unelevated
+// side picked.
+//
+// One JSON request per line; a write or resource copy is followed by exactly
+// `size` bytes of file contents. Every request is answered with one JSON
+// response line.
+
+use crate::i18n::{tr, trf};
+#[cfg(windows)]
+use crate::{detect, resources};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+#[cfg(windows)]
+use std::time::Duration;
+
+// Installed next to the installer
+pub const HELPER_NAME: &str = "thatnoobskyapp-helper";
+
+pub const AVAILABLE: bool = cfg!(windows);
+
+#[cfg(windows)]
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    Write { relative: PathBuf, size: u64 },
+    Remove { relative: PathBuf },
+    // Relative to TNSM Resources, missing folders on the way are created
+    CopyResource { relative: PathBuf, size: u64 },
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    pub error: Option<String>,
+}
+
+#[cfg(not(windows))]
+fn unsupported() -> String {
+    tr("Changing this folder with administrator approval is only supported on Windows").to_string()
+}
+
+// Only plain names going down from the game folder: no "..", no absolute
+// paths or drive prefixes
+#[cfg(any(windows, test))]
+fn checked_target(game_path: &Path, relative: &Path) -> Result<PathBuf, String> {
+    let plain = relative.components().count() > 0
+        && relative.components().all(|component| matches!(component, std::path::Component::Normal(_)));
+    if !plain {
+        return Err(trf("Refusing to touch {} outside the game folder", &[&relative.display()]));
+    }
+    Ok(game_path.join(relative))
+}
+
+pub fn helper_path() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate the installer: {}", &[&e]))?;
+    let helper = exe.with_file_name(format!("{}{}", HELPER_NAME, std::env::consts::EXE_SUFFIX));
+    if !helper.exists() {
+        return Err(trf("{} is missing next to the installer, reinstall it to change this folder", &[&HELPER_NAME]));
+    }
+    Ok(helper)
+}
+
+// A running helper, approved for one game folder. Dropping it closes the
+// pipe, which ends the helper.
+pub struct Helper {
+    #[cfg(windows)]
+    pipe: tokio::io::BufReader<tokio::net::windows::named_pipe::NamedPipeServer>,
+}
+
+impl Helper {
+    // Shows the Windows administrator prompt
+    #[cfg(windows)]
+    pub async fn start(game_path: &Path) -> Result<Self, String> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let helper = helper_path()?;
+        let name = format!(
+            r"\\.\pipe\ThatNoobSkyApp-elevated-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos())
+        );
+        // Created before the helper starts, so nothing else can own the name
+        let server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(&name)
+            .map_err(|e| trf("Failed to create helper pipe: {}", &[&e]))?;
+
+        let parameters = format!("--pipe {} --game-path {}", quote(&name), quote(&game_path.display().to_string()));
+        run_as_admin(&helper, &parameters)?;
+
+        tokio::time::timeout(CONNECT_TIMEOUT, server.connect())
+            .await
+            .map_err(|_| tr("The elevated helper didn't start").to_string())?
+            .map_err(|e| trf("Failed to connect to the elevated helper: {}", &[&e]))?;
+
+        Ok(Self { pipe: tokio::io::BufReader::new(server) })
+    }
+
+    #[cfg(not(windows))]
+    pub async fn start(_game_path: &Path) -> Result<Self, String> {
+        Err(unsupported())
+    }
+
+    pub async fn write(&mut self, source: &Path, relative: &Path) -> Result<(), String> {
+        let contents = tokio::fs::read(source)
+            .await
+            .map_err(|e| trf("Failed to read {}: {}", &[&source.display(), &e]))?;
+        let request = Request::Write { relative: relative.to_path_buf(), size: contents.len() as u64 };
+        self.send(&request, &contents).await
+    }
+
+    pub async fn remove(&mut self, relative: &Path) -> Result<(), String> {
+        self.send(&Request::Remove { relative: relative.to_path_buf() }, &[]).await
+    }
+
+    pub async fn copy_resource(&mut self, source: &Path, relative: &Path) -> Result<(), String> {
+        let contents = tokio::fs::read(source)
+            .await
+            .map_err(|e| trf("Failed to read {}: {}", &[&source.display(), &e]))?;
+        let request = Request::CopyResource { relative: relative.to_path_buf(), size: contents.len() as u64 };
+        self.send(&request, &contents).await
+    }
+
+    #[cfg(windows)]
+    async fn send(&mut self, request: &Request, body: &[u8]) -> Result<(), String> {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+        let mut line = serde_json::to_string(request)
+            .map_err(|e| trf("Failed to serialize helper request: {}", &[&e]))?;
+        line.push('\n');
+
+        let pipe = self.pipe.get_mut();
+        pipe.write_all(line.as_bytes())
+            .await
+            .and(pipe.write_all(body).await)
+            .and(pipe.flush().await)
+            .map_err(|e| trf("Lost the elevated helper: {}", &[&e]))?;
+
+        let mut answer = String::new();
+        self.pipe.read_line(&mut answer)
+            .await
+            .map_err(|e| trf("Lost the elevated helper: {}", &[&e]))?;
+        let response: Response = serde_json::from_str(&answer)
+            .map_err(|_| tr("The elevated helper stopped unexpectedly").to_string())?;
+
+        match response.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(not(windows))]
+    async fn send(&mut self, _request: &Request, _body: &[u8]) -> Result<(), String> {
+        Err(unsupported())
+    }
+}
+
+// Asks for administrator approval once and writes `source` to `relative`
+// in the game folder
+pub async fn write_file(game_path: &Path, source: &Path, relative: &Path) -> Result<(), String> {
+    Helper::start(game_path).await?.write(source, relative).await
+}
+
+pub async fn remove_file(game_path: &Path, relative: &Path) -> Result<(), String> {
+    Helper::start(game_path).await?.remove(relative).await
+}
+
+// Windows command line quoting: backslashes only need doubling before a quote
+#[cfg(windows)]
+fn quote(argument: &str) -> String {
+    let trailing = argument.len() - argument.trim_end_matches('\\').len();
+    format!("\"{}{}\"", argument, "\\".repeat(trailing))
+}
+
+#[cfg(windows)]
+fn run_as_admin(program: &Path, parameters: &str) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::UI::Shell::{SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW, ShellExecuteExW};
+
+    let wide = |text: &std::ffi::OsStr| text.encode_wide().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let verb = wide("runas".as_ref());
+    let file = wide(program.as_os_str());
+    let parameters = wide(parameters.as_ref());
+
+    let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+    info.fMask = SEE_MASK_NOCLOSEPROCESS;
+    info.lpVerb = verb.as_ptr();
+    info.lpFile = file.as_ptr();
+    info.lpParameters = parameters.as_ptr();
+    // SW_HIDE, the helper has no window anyway
+    info.nShow = 0;
+
+    if unsafe { ShellExecuteExW(&mut info) } == 0 {
+        let e = std::io::Error::last_os_error();
+        // ERROR_CANCELLED, the prompt was declined
+        return Err(if e.raw_os_error() == Some(1223) {
+            tr("Administrator approval was declined").to_string()
+        } else {
+            trf("Failed to start {} as administrator: {}", &[&program.display(), &e])
+        });
+    }
+
+    if !info.hProcess.is_null() {
+        unsafe { CloseHandle(info.hProcess) };
+    }
+    Ok(())
+}
+
+// Runs in the elevated helper. Handles requests until the installer closes
+// the pipe.
+#[cfg(windows)]
+pub fn serve(pipe: &str, game_path: &Path) -> Result<(), String> {
+    use std::io::{BufRead, Write};
+
+    if !detect::is_game_directory(game_path) {
+        return Err(trf("{} is not the game directory", &[&game_path.display()]));
+    }
+
+    let pipe = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(pipe)
+        .map_err(|e| trf("Failed to connect to the installer: {}", &[&e]))?;
+    let mut writer = pipe.try_clone()
+        .map_err(|e| trf("Failed to connect to the installer: {}", &[&e]))?;
+    let mut reader = std::io::BufReader::new(pipe);
+
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {}
+            Err(e) => return Err(trf("Lost the installer: {}", &[&e])),
+        }
+
+        // Anything that can't be parsed ends the session, the body that may
+        // follow it can't be skipped safely
+        let request: Request = serde_json::from_str(&line)
+            .map_err(|e| trf("Invalid request: {}", &[&e]))?;
+        let response = Response { error: handle(game_path, &request, &mut reader).err() };
+
+        let mut answer = serde_json::to_string(&response)
+            .map_err(|e| trf("Failed to serialize response: {}", &[&e]))?;
+        answer.push('\n');
+        writer.write_all(answer.as_bytes())
+            .map_err(|e| trf("Lost the installer: {}", &[&e]))?;
+    }
+}
+
+#[cfg(not(windows))]
+pub fn serve(_pipe: &str, _game_path: &Path) -> Result<(), String> {
+    Err(unsupported())
+}
+
+#[cfg(windows)]
+fn handle(game_path: &Path, request: &Request, body: &mut impl std::io::Read) -> Result<(), String> {
+    match request {
+        Request::Write { relative, size } => {
+            let contents = receive(body, *size, relative)?;
+            write(&checked_target(game_path, relative)?, &contents, relative)
+        }
+        Request::Remove { relative } => {
+            let target = checked_target(game_path, relative)?;
+            std::fs::remove_file(&target)
+                .map_err(|e| trf("Failed to remove {}: {}", &[&relative.display(), &e]))
+        }
+        Request::CopyResource { relative, size } => {
+            let contents = receive(body, *size, relative)?;
+            let target = checked_target(&resources::resources_path(game_path), relative)?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| trf("Failed to create the folder of {}: {}", &[&relative.display(), &e]))?;
+            }
+            write(&target, &contents, relative)
+        }
+    }
+}
+
+// Read even when the path is then refused, to stay in step
+#[cfg(windows)]
+fn receive(body: &mut impl std::io::Read, size: u64, relative: &Path) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut contents = Vec::new();
+    body.by_ref().take(size)
+        .read_to_end(&mut contents)
+        .map_err(|e| trf("Failed to receive {}: {}", &[&relative.display(), &e]))?;
+    Ok(contents)
+}
+
+// Written next to the target first, so a failed write leaves the old file alone
+#[cfg(windows)]
+fn write(target: &Path, contents: &[u8], relative: &Path) -> Result<(), String> {
+    let partial = target.with_extension("tnsm-partial");
+    std::fs::write(&partial, contents)
+        .and_then(|_| std::fs::rename(&partial, target))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&partial);
+            trf("Failed to write {}: {}", &[&relative.display(), &e])
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_stay_inside_the_game_folder() {
+        let game = Path::new("game");
+        assert_eq!(checked_target(game, Path::new("TNSM Resources/a.png")).unwrap(), game.join("TNSM Resources/a.png"));
+        assert!(checked_target(game, Path::new("")).is_err());
+        assert!(checked_target(game, Path::new("../powrprof.dll")).is_err());
+        assert!(checked_target(game, Path::new("a/../../b")).is_err());
+        assert!(checked_target(game, Path::new("./a")).is_err());
+        assert!(checked_target(game, Path::new("/etc/passwd")).is_err());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn drive_and_unc_paths_are_refused() {
+        let game = Path::new(r"C:\Games\Sky");
+        assert!(checked_target(game, Path::new(r"D:\powrprof.dll")).is_err());
+        assert!(checked_target(game, Path::new(r"C:powrprof.dll")).is_err());
+        assert!(checked_target(game, Path::new(r"\\server\share\x.dll")).is_err());
+    }
+}
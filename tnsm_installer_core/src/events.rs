@@ -0,0 +1,140 @@
+use crate::compat::CompatDb;
+use crate::game_cache::CacheLocation;
+use crate::game_files::{FileCheck, GameFiles};
+use crate::instance;
+use crate::manifest::{Problem, Verification};
+use crate::mod_state::ModState;
+use crate::github::Release;
+use crate::nightly::NightlyBuild;
+use crate::self_update::AppUpdate;
+use crate::orphans::Orphan;
+use crate::preview::FileChange;
+use crate::progress;
+use crate::storage::Usage;
+use crate::undo::UndoEntry;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Task {
+    Install,
+    Bundle,
+    Import,
+    GameData,
+    Restore,
+    Storage,
+    Cache,
+    Cleanup,
+    Plugins,
+    Diagnostics,
+    Repair,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransferProgress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+}
+
+impl TransferProgress {
+    pub fn fraction(&self) -> f32 {
+        let total = progress::work_total(self);
+        if total == 0 {
+            return 0.0;
+        }
+        progress::work_done(self) as f32 / total as f32
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Progress(Task, String),
+    Transfer(Task, TransferProgress),
+    Finished(Task, Result<String, String>),
+    // Carries the installed release tag when it could be resolved
+    ModInstalled(Option<String>),
+    // Sent by a later launch of the app, see instance::hand_off
+    Handoff(instance::Request),
+    // A .tnsmpack was unpacked and its folder is ready to import
+    PackExtracted(Result<PathBuf, String>),
+    StorageUsage(Vec<Usage>),
+    CacheScanned(Vec<CacheLocation>),
+    NightlyBuilds(Result<Vec<NightlyBuild>, String>),
+    // Tag of the newest release, from the check at startup
+    LatestRelease(String),
+    // A newer version of the installer itself
+    AppUpdate(AppUpdate),
+    // The installer exe was replaced, restart from this path
+    AppUpdated(Result<PathBuf, String>),
+    // The releases that can be installed, newest first
+    Releases(Result<Vec<Release>, String>),
+    CompatDb(CompatDb),
+    GameFiles(GameFiles),
+    OrphansScanned(Result<Vec<Orphan>, String>),
+    // None when there are no known hashes for the installed game build
+    GameFilesChecked(Result<Option<Vec<FileCheck>>, String>),
+    InstallVerified(Result<Verification, String>),
+    // The installed DLL checked again a few seconds after installing
+    DllChecked(Option<Problem>),
+    ModChecked(Result<ModState, String>),
+    // An uninstall that needed administrator approval finished
+    Uninstalled(Result<(), String>),
+    // A change finished that can be taken back by reverting its restore point
+    Undoable(UndoEntry),
+    // The files an action would change, for the preview shown before it runs
+    Previewed(Result<Vec<FileChange>, String>),
+}
+
+// Cloned into background tasks. Every send wakes the frontend through the
+// waker, so the UI only repaints when something actually happened.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: mpsc::UnboundedSender<Event>,
+    waker: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl EventSender {
+    pub fn send(&self, event: Event) {
+        if self.tx.send(event).is_ok() {
+            (self.waker)();
+        }
+    }
+
+    pub fn progress(&self, task: Task, message: String) {
+        self.send(Event::Progress(task, message));
+    }
+
+    pub fn finished(&self, task: Task, result: Result<String, String>) {
+        self.send(Event::Finished(task, result));
+    }
+}
+
+pub struct EventBus {
+    rx: mpsc::UnboundedReceiver<Event>,
+    sender: EventSender,
+}
+
+impl EventBus {
+    pub fn new(waker: impl Fn() + Send + Sync + 'static) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        Self {
+            rx,
+            sender: EventSender {
+                tx,
+                waker: Arc::new(waker),
+            },
+        }
+    }
+
+    pub fn sender(&self) -> EventSender {
+        self.sender.clone()
+    }
+
+    pub fn try_recv(&mut self) -> Option<Event> {
+        self.rx.try_recv().ok()
+    }
+}
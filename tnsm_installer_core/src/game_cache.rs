@@ -0,0 +1,110 @@
+// Shader and temp caches that are safe to delete: the game and the driver
+// rebuild them on the next launch. Stale shader caches are a common cause of
+// graphical glitches after a mod update. Driver caches are shared with every
+// other game and Steam's with every account on the PC, so they're marked as
+// such and left unselected by default.
+
+use crate::cancel::{self, CancellationToken};
+use crate::{audit, detect, workers};
+use jwalk::WalkDir;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct CacheLocation {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub files: usize,
+    // Used by other games too, clearing it makes them rebuild their shaders as well
+    pub shared: bool,
+}
+
+#[derive(Default)]
+pub struct ClearReport {
+    pub bytes: u64,
+    pub files: usize,
+    // Usually in use by a running game or driver
+    pub skipped: usize,
+}
+
+fn candidates(steam_path: Option<&Path>) -> Vec<(&'static str, PathBuf, bool)> {
+    let mut locations = Vec::new();
+
+    if let Some(steam_path) = steam_path {
+        locations.push((
+            "Steam shader cache for Sky",
+            steam_path.join("steamapps").join("shadercache").join(detect::STEAM_APP_ID.to_string()),
+            // Lives in the Steam folder, so every Windows account on the PC uses it
+            true,
+        ));
+    }
+
+    if let Some(local) = dirs::data_local_dir() {
+        locations.push(("DirectX shader cache", local.join("D3DSCache"), true));
+        locations.push(("NVIDIA DirectX cache", local.join("NVIDIA").join("DXCache"), true));
+        locations.push(("NVIDIA OpenGL cache", local.join("NVIDIA").join("GLCache"), true));
+        locations.push(("AMD DirectX cache", local.join("AMD").join("DxCache"), true));
+        locations.push(("AMD Vulkan cache", local.join("AMD").join("VkCache"), true));
+    }
+
+    locations
+}
+
+fn files_in(path: &Path) -> impl Iterator<Item = (PathBuf, u64)> {
+    WalkDir::new(path)
+        .skip_hidden(false)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| {
+            let bytes = entry.metadata().map_or(0, |metadata| metadata.len());
+            (entry.path(), bytes)
+        })
+}
+
+// Lists the locations that exist with what clearing them would reclaim
+pub async fn scan(steam_path: Option<PathBuf>) -> Result<Vec<CacheLocation>, String> {
+    workers::run(move || existing_locations(steam_path.as_deref())).await
+}
+
+fn existing_locations(steam_path: Option<&Path>) -> Vec<CacheLocation> {
+    candidates(steam_path)
+        .into_iter()
+        .filter(|(_, path, _)| path.is_dir())
+        .map(|(name, path, shared)| {
+            let (bytes, files) = files_in(&path).fold((0, 0), |(bytes, files), (_, size)| (bytes + size, files + 1));
+            CacheLocation { name, path, bytes, files, shared }
+        })
+        .collect()
+}
+
+// Deletes the files inside each location, keeping the folders themselves.
+// Files that can't be deleted are skipped rather than failing the whole
+// cleanup.
+pub async fn clear(locations: Vec<CacheLocation>, cancel: CancellationToken) -> Result<ClearReport, String> {
+    workers::run(move || {
+        let audit = audit::begin("clear game cache", None).detail(format!("{} locations", locations.len()));
+        let result = remove_files(&locations, &cancel);
+        audit.finish(&result);
+        result
+    }).await?
+}
+
+fn remove_files(locations: &[CacheLocation], cancel: &CancellationToken) -> Result<ClearReport, String> {
+    let mut report = ClearReport::default();
+
+    for location in locations {
+        for (path, bytes) in files_in(&location.path) {
+            cancel::checkpoint(cancel)?;
+
+            if std::fs::remove_file(&path).is_ok() {
+                report.bytes += bytes;
+                report.files += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,129 @@
+// Backups of the game's own data: settings, caches and the account files the
+// Steam build keeps in local app data, outside the install folder the mod
+// touches. Each backup is a zip in the app directory named after the time it
+// was taken, so nervous users have something to go back to.
+
+use crate::cancel::{self, CancellationToken};
+use crate::app_files::{self, Snapshot};
+use crate::i18n::{tr, trf};
+use crate::{audit, config, timestamp, workers};
+use jwalk::WalkDir;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+const DATA_DIR_NAME: &str = "Sky";
+const BACKUP_PREFIX: &str = "game-data-";
+
+pub type GameDataBackup = Snapshot;
+
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_local_dir()
+        .map(|dir| dir.join(DATA_DIR_NAME))
+        .filter(|dir| dir.is_dir())
+}
+
+pub fn backups_dir() -> Result<PathBuf, String> {
+    config::app_dir()
+        .map(|dir| dir.join("game-data"))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+// Newest first
+pub fn list_backups() -> Vec<GameDataBackup> {
+    app_files::list_snapshots(backups_dir().ok(), BACKUP_PREFIX, ".zip")
+}
+
+// Returns the new backup's path
+pub async fn back_up(cancel: CancellationToken) -> Result<PathBuf, String> {
+    workers::run(move || write_new_backup(&cancel)).await?
+}
+
+fn write_new_backup(cancel: &CancellationToken) -> Result<PathBuf, String> {
+    let source = data_dir().ok_or(tr("Sky's game data folder was not found"))?;
+    let dir = backups_dir()?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| trf("Failed to create backup directory: {}", &[&e]))?;
+
+    let path = dir.join(format!("{}{}.zip", BACKUP_PREFIX, timestamp::now()));
+    let result = write_backup(&source, &path, cancel);
+
+    // Don't list a half-written zip as a restore option
+    if result.is_err() {
+        let _ = std::fs::remove_file(&path);
+    }
+    result.map(|_| path)
+}
+
+fn write_backup(source: &Path, dest: &Path, cancel: &CancellationToken) -> Result<(), String> {
+    let file = std::fs::File::create(dest)
+        .map_err(|e| trf("Failed to create backup: {}", &[&e]))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    for entry in WalkDir::new(source).skip_hidden(false) {
+        cancel::checkpoint(cancel)?;
+
+        let entry = entry.map_err(|e| trf("Failed to read game data: {}", &[&e]))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        let relative = path.strip_prefix(source)
+            .map_err(|e| trf("Invalid path {}: {}", &[&path.display(), &e]))?;
+        let name = relative.to_string_lossy().replace('\\', "/");
+
+        let mut input = std::fs::File::open(&path)
+            .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+        zip.start_file(name.as_str(), SimpleFileOptions::default())
+            .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+        std::io::copy(&mut input, &mut zip)
+            .map_err(|e| trf("Failed to add {}: {}", &[&name, &e]))?;
+    }
+
+    zip.finish()
+        .map_err(|e| trf("Failed to write backup: {}", &[&e]))?;
+    Ok(())
+}
+
+// Writes the backed up files over the current ones; files created since the
+// backup are left alone
+pub async fn restore(backup: PathBuf, cancel: CancellationToken) -> Result<(), String> {
+    workers::run(move || {
+        let audit = audit::begin("restore game data", None).detail(backup.display().to_string());
+        let result = extract_backup(&backup, &cancel);
+        audit.finish(&result);
+        result
+    }).await?
+}
+
+fn extract_backup(backup: &Path, cancel: &CancellationToken) -> Result<(), String> {
+    let dest = dirs::data_local_dir()
+        .map(|dir| dir.join(DATA_DIR_NAME))
+        .ok_or(tr("Local app data folder not found"))?;
+
+    let file = std::fs::File::open(backup)
+        .map_err(|e| trf("Failed to open backup: {}", &[&e]))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| trf("Not a valid backup: {}", &[&e]))?;
+
+    for index in 0..archive.len() {
+        cancel::checkpoint(cancel)?;
+
+        let mut entry = archive.by_index(index)
+            .map_err(|e| trf("Failed to read backup entry: {}", &[&e]))?;
+        let relative = entry.enclosed_name()
+            .ok_or_else(|| trf("Unsafe path in backup: {}", &[&entry.name()]))?;
+        let out_path = dest.join(relative);
+
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| trf("Failed to create directory: {}", &[&e]))?;
+        }
+        let mut out = std::fs::File::create(&out_path)
+            .map_err(|e| trf("Failed to restore {}: {}", &[&out_path.display(), &e]))?;
+        std::io::copy(&mut entry, &mut out)
+            .map_err(|e| trf("Failed to restore {}: {}", &[&out_path.display(), &e]))?;
+    }
+
+    Ok(())
+}
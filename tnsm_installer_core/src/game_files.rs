@@ -0,0 +1,208 @@
+// The game's own files, from game_files.json in the mod's repository, so
+// scans can tell them apart from files left behind by mods or by hand:
+//
+//   {
+//     "paths": ["Sky.exe", "data/**", "*.dll"],
+//     "builds": [
+//       { "build_id": 19283746, "hashes": { "Sky.exe": "<sha256>" } }
+//     ]
+//   }
+//
+// Paths are relative to the game folder and matched case-insensitively.
+// "dir/**" matches everything under dir, "*" anything within one name. The
+// hashes cover a few key files per Steam build, enough to tell a damaged or
+// tampered game apart from a mod problem without a full Steam verify. The
+// last copy fetched is kept in the app directory.
+
+use crate::cancel::{self, CancellationToken};
+use crate::i18n::trf;
+use crate::{config, detect, github, verify, workers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const GAME_FILES_FILE_NAME: &str = "game_files.json";
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct GameFiles {
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub builds: Vec<BuildHashes>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BuildHashes {
+    pub build_id: u64,
+    // Relative path to lowercase hex SHA-256
+    pub hashes: BTreeMap<String, String>,
+}
+
+impl BuildHashes {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| trf("Failed to serialize game file hashes: {}", &[&e]))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileState {
+    Intact,
+    Modified,
+    Missing,
+}
+
+#[derive(Clone, Debug)]
+pub struct FileCheck {
+    pub relative: String,
+    pub state: FileState,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join(GAME_FILES_FILE_NAME))
+}
+
+// `*` matches any run of characters except a separator
+fn wildcard(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=text.len())
+                .filter(|&i| text.is_char_boundary(i))
+                .take_while(|&i| !text[..i].contains('/'))
+                .any(|i| wildcard(rest, &text[i..]))
+        }
+    }
+}
+
+pub fn normalize(relative: &Path) -> String {
+    relative.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+impl GameFiles {
+    // The last copy fetched, empty when there is none
+    pub fn load() -> Self {
+        cache_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    // Listed by name rather than through a pattern
+    pub fn lists_exactly(&self, relative: &Path) -> bool {
+        let relative = normalize(relative);
+        self.paths.iter().any(|path| path.to_lowercase() == relative)
+    }
+
+    pub fn hashes(&self, build_id: u64) -> Option<&BTreeMap<String, String>> {
+        self.builds.iter()
+            .find(|build| build.build_id == build_id)
+            .map(|build| &build.hashes)
+    }
+
+    pub fn contains(&self, relative: &Path) -> bool {
+        let relative = normalize(relative);
+
+        self.paths.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            match pattern.strip_suffix("/**") {
+                Some(dir) => relative.starts_with(&format!("{}/", dir)),
+                None => wildcard(&pattern, &relative),
+            }
+        })
+    }
+}
+
+// Hashes the key files of this game build; None when the list has no hashes
+// for it yet
+pub async fn check(game_path: PathBuf, game_files: GameFiles, build_id: u64, cancel: CancellationToken) -> Result<Option<Vec<FileCheck>>, String> {
+    workers::run(move || hash_key_files(&game_path, &game_files, build_id, &cancel)).await?
+}
+
+fn hash_key_files(game_path: &Path, game_files: &GameFiles, build_id: u64, cancel: &CancellationToken) -> Result<Option<Vec<FileCheck>>, String> {
+    let Some(hashes) = game_files.hashes(build_id) else {
+        return Ok(None);
+    };
+
+    let mut checks = Vec::new();
+    for (relative, expected) in hashes {
+        cancel::checkpoint(cancel)?;
+
+        let path = game_path.join(relative);
+        let state = if !path.is_file() {
+            FileState::Missing
+        } else if verify::sha256_file(&path)?.eq_ignore_ascii_case(expected) {
+            FileState::Intact
+        } else {
+            FileState::Modified
+        };
+        checks.push(FileCheck { relative: relative.clone(), state });
+    }
+
+    Ok(Some(checks))
+}
+
+// Maintainer tooling: hashes `files` in a freshly verified game install, for
+// adding its build to game_files.json
+pub fn hash_build(game_path: &Path, files: &[String]) -> Result<BuildHashes, String> {
+    let build_id = detect::game_build_id(game_path)
+        .ok_or_else(|| trf("No Steam app manifest found for {}", &[&game_path.display()]))?;
+
+    let hashes = files.iter()
+        .map(|relative| Ok((relative.replace('\\', "/"), verify::sha256_file(&game_path.join(relative))?)))
+        .collect::<Result<_, String>>()?;
+
+    Ok(BuildHashes { build_id, hashes })
+}
+
+// Downloads the latest list and keeps a copy, see GameFiles::load
+pub async fn refresh(cancel: &CancellationToken) -> Result<GameFiles, String> {
+    let contents = github::fetch_repo_file(GAME_FILES_FILE_NAME, cancel).await?;
+    let files: GameFiles = serde_json::from_str(&contents)
+        .map_err(|e| trf("Failed to parse game file list: {}", &[&e]))?;
+
+    if let Some(path) = cache_path() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, contents);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_matches_within_one_name() {
+        assert!(wildcard("*.dll", "steam_api64.dll"));
+        assert!(wildcard("sky.exe", "sky.exe"));
+        assert!(wildcard("data/*.pak", "data/base.pak"));
+        assert!(!wildcard("*.dll", "plugins/extra.dll"));
+        assert!(!wildcard("*.dll", "sky.exe"));
+    }
+
+    #[test]
+    fn wildcard_handles_multibyte_names() {
+        assert!(wildcard("*.pak", "データ.pak"));
+        assert!(wildcard("d*a", "dá·ta"));
+    }
+
+    #[test]
+    fn contains_matches_folders_and_ignores_case() {
+        let files = GameFiles {
+            paths: vec!["Sky.exe".to_string(), "data/**".to_string(), "*.dll".to_string()],
+            builds: Vec::new(),
+        };
+        assert!(files.contains(Path::new("SKY.EXE")));
+        assert!(files.contains(Path::new("Data/Levels/Dawn.level")));
+        assert!(files.contains(Path::new("fmod.dll")));
+        assert!(!files.contains(Path::new("TNSM Resources/a.png")));
+        assert!(files.lists_exactly(Path::new("sky.exe")));
+        assert!(!files.lists_exactly(Path::new("fmod.dll")));
+    }
+}
@@ -0,0 +1,75 @@
+// Whether Sky is running. The mod DLL and resource files are locked while the
+// game has them open, so changes to them wait until it's closed.
+
+use crate::i18n::tr;
+
+#[cfg(windows)]
+pub fn is_running() -> bool {
+    use crate::detect::GAME_EXE_NAME;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW, TH32CS_SNAPPROCESS,
+    };
+
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return false;
+    }
+
+    let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+    let mut found = false;
+    let mut more = unsafe { Process32FirstW(snapshot, &mut entry) } != 0;
+    while more {
+        let length = entry.szExeFile.iter().position(|c| *c == 0).unwrap_or(entry.szExeFile.len());
+        if String::from_utf16_lossy(&entry.szExeFile[..length]).eq_ignore_ascii_case(GAME_EXE_NAME) {
+            found = true;
+            break;
+        }
+        more = unsafe { Process32NextW(snapshot, &mut entry) } != 0;
+    }
+
+    unsafe { CloseHandle(snapshot) };
+    found
+}
+
+#[cfg(not(windows))]
+pub fn is_running() -> bool {
+    false
+}
+
+// Asks Sky to close the way the window's close button does, so it can save
+// first. Doesn't wait for it to exit.
+#[cfg(windows)]
+pub fn close() -> Result<(), String> {
+    use crate::detect::GAME_EXE_NAME;
+    use crate::i18n::trf;
+    use std::os::windows::process::CommandExt;
+
+    // CREATE_NO_WINDOW, otherwise a console flashes up
+    let output = std::process::Command::new("taskkill")
+        .args(["/IM", GAME_EXE_NAME])
+        .creation_flags(0x08000000)
+        .output()
+        .map_err(|e| trf("Failed to run taskkill: {}", &[&e]))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(trf("Failed to close Sky: {}", &[&String::from_utf8_lossy(&output.stderr).trim()]))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn close() -> Result<(), String> {
+    Err(tr("Closing Sky is only supported on Windows").to_string())
+}
+
+// For changes that can't wait, e.g. from the command line
+pub fn ensure_not_running() -> Result<(), String> {
+    if is_running() {
+        return Err(tr("Sky is running, close the game first").to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,361 @@
+use crate::cancel::{self, CancellationToken};
+use crate::config::DEFAULT_DOWNLOAD_ATTEMPTS;
+use crate::i18n::{tr, trf};
+use crate::proxy::{self, ProxySettings};
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const REPO: &str = "alvindimas05/ThatNoobSkyMod";
+pub const DLL_ASSET_NAME: &str = "TNSM.dll";
+pub const DLL_ASSET_NAME_ARM64: &str = "TNSM-arm64.dll";
+// Releases of the installer app share the repository with the mod's. They're
+// tagged app-v<version> and never marked as the repository's latest release,
+// which "latest" DLL URLs go through.
+pub const APP_TAG_PREFIX: &str = "app-v";
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub prerelease: bool,
+    // Release notes in Markdown, null when left empty
+    pub body: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+    // Only present on assets uploaded after GitHub started computing digests
+    pub digest: Option<String>,
+}
+
+impl Release {
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
+    // The installer version of an app release, None for mod releases
+    pub fn app_version(&self) -> Option<&str> {
+        self.tag_name.strip_prefix(APP_TAG_PREFIX)
+    }
+}
+
+const API_URL: &str = "https://api.github.com/";
+// Pinned so a new default API version can't change the responses under us
+const API_VERSION: &str = "2022-11-28";
+
+// Don't sit in the background for an hour waiting on a primary rate limit reset
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+// Waits between tries double from BACKOFF_START up to MAX_BACKOFF
+const BACKOFF_START: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// The client every request goes through, and how many times each is tried
+#[derive(Clone)]
+pub struct HttpClient {
+    pub client: reqwest::Client,
+    pub attempts: u32,
+}
+
+// Shared by every request so connections (and their TLS sessions) get reused
+static CLIENT: Mutex<Option<(ProxySettings, HttpClient)>> = Mutex::new(None);
+
+fn build(settings: &ProxySettings) -> Result<reqwest::Client, String> {
+    // GitHub's API rejects requests without a User-Agent
+    let builder = reqwest::Client::builder()
+        .user_agent("ThatNoobSkyApp")
+        .connect_timeout(Duration::from_secs(15))
+        .pool_idle_timeout(Duration::from_secs(90));
+    proxy::apply(builder, settings)?
+        .build()
+        .map_err(|e| trf("Failed to create HTTP client: {}", &[&e]))
+}
+
+// Called with the config's network settings at startup and whenever they're
+// changed. Building a client loads the system root certificates, which costs
+// tens of milliseconds each time, so it's only built again when the proxy
+// changes.
+pub fn configure(proxy: &ProxySettings, attempts: u32) -> Result<(), String> {
+    let mut cached = CLIENT.lock().unwrap_or_else(|e| e.into_inner());
+    let client = match cached.as_ref() {
+        Some((settings, http)) if settings == proxy => http.client.clone(),
+        _ => build(proxy)?,
+    };
+    *cached = Some((proxy.clone(), HttpClient { client, attempts: attempts.max(1) }));
+    Ok(())
+}
+
+// The configured client, or a direct one with the default attempts for
+// tools that never load a config
+pub fn client() -> Result<HttpClient, String> {
+    let mut cached = CLIENT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, http)) = cached.as_ref() {
+        return Ok(http.clone());
+    }
+
+    let settings = ProxySettings::default();
+    let http = HttpClient { client: build(&settings)?, attempts: DEFAULT_DOWNLOAD_ATTEMPTS };
+    *cached = Some((settings, http.clone()));
+    Ok(http)
+}
+
+fn rate_limit_delay(response: &reqwest::Response) -> Option<Duration> {
+    let status = response.status().as_u16();
+    if status != 403 && status != 429 {
+        return None;
+    }
+
+    let header = |name: &str| {
+        response.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+
+    if let Some(seconds) = header("retry-after") {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    if header("x-ratelimit-remaining") == Some(0) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let reset = header("x-ratelimit-reset")?;
+        return Some(Duration::from_secs(reset.saturating_sub(now) + 1));
+    }
+
+    // Secondary rate limits don't always send headers; GitHub asks for at least a minute
+    if status == 429 {
+        return Some(Duration::from_secs(60));
+    }
+
+    None
+}
+
+// Waits before try `failures + 1`, longer after each failure
+pub async fn backoff(failures: u32, cancel: &CancellationToken) -> Result<(), String> {
+    let delay = BACKOFF_START.saturating_mul(1 << failures.saturating_sub(1).min(16)).min(MAX_BACKOFF);
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => Ok(()),
+        _ = cancel.cancelled() => Err(cancel::CANCELLED.to_string()),
+    }
+}
+
+// Sends a GET request, waiting out GitHub rate limits and trying again after
+// network and server errors instead of failing. `on_rate_limited` is called
+// every second with the remaining wait.
+pub async fn get_with_retry(
+    client: &HttpClient,
+    url: &str,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<reqwest::Response, String> {
+    send_with_retry(client, url, None, 0, cancel, on_rate_limited).await
+}
+
+// Same as get_with_retry, signed in with a personal access token. reqwest
+// drops the header when a download redirects to another host.
+pub async fn get_authorized(
+    client: &HttpClient,
+    url: &str,
+    token: &str,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<reqwest::Response, String> {
+    send_with_retry(client, url, Some(token), 0, cancel, on_rate_limited).await
+}
+
+// The file from byte `from` on, for resuming a download. The response is 206
+// Partial Content if the server went along with it, otherwise the whole file.
+pub async fn get_range(
+    client: &HttpClient,
+    url: &str,
+    token: Option<&str>,
+    from: u64,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<reqwest::Response, String> {
+    send_with_retry(client, url, token, from, cancel, on_rate_limited).await
+}
+
+async fn send_with_retry(
+    client: &HttpClient,
+    url: &str,
+    token: Option<&str>,
+    from: u64,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<reqwest::Response, String> {
+    let attempts = client.attempts;
+    let mut retries = 0;
+    let mut failures = 0;
+
+    loop {
+        tracing::debug!("GET {}", url);
+        let mut request = client.client.get(url);
+        if url.starts_with(API_URL) {
+            request = request
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+                .header("X-GitHub-Api-Version", API_VERSION);
+        }
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        if from > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", from));
+        }
+
+        let sent = tokio::select! {
+            response = request.send() => response.and_then(|response| {
+                if response.status().is_server_error() { response.error_for_status() } else { Ok(response) }
+            }),
+            _ = cancel.cancelled() => return Err(cancel::CANCELLED.to_string()),
+        };
+        let response = match sent {
+            Ok(response) => response,
+            // Dropped connections, timeouts and server errors are often gone a moment later
+            Err(e) if !e.is_builder() && failures + 1 < attempts => {
+                failures += 1;
+                tracing::warn!("Request to {} failed, trying again ({}/{}): {}", url, failures + 1, attempts, e);
+                backoff(failures, cancel).await?;
+                continue;
+            }
+            Err(e) => return Err(trf("Request failed: {}", &[&e])),
+        };
+
+        let Some(wait) = rate_limit_delay(&response) else {
+            return response.error_for_status()
+                .map_err(|e| trf("Request failed: {}", &[&e]));
+        };
+        tracing::warn!("Rate limited by GitHub, waiting {}s", wait.as_secs());
+
+        if retries >= MAX_RATE_LIMIT_RETRIES || wait > MAX_RATE_LIMIT_WAIT {
+            return Err(trf(
+                "GitHub rate limit reached, try again in {} minutes",
+                &[&wait.as_secs().div_ceil(60)]
+            ));
+        }
+        retries += 1;
+
+        for remaining in (1..=wait.as_secs()).rev() {
+            on_rate_limited(remaining);
+
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                _ = cancel.cancelled() => return Err(cancel::CANCELLED.to_string()),
+            }
+        }
+    }
+}
+
+// The latest mod release
+pub async fn fetch_latest_release(
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Release, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", REPO);
+    let release = fetch_release(&url, cancel, on_rate_limited).await?;
+    if release.app_version().is_none() {
+        return Ok(release);
+    }
+
+    // An app release marked as latest by mistake
+    fetch_releases(cancel, on_rate_limited)
+        .await?
+        .into_iter()
+        .find(|release| !release.prerelease)
+        .ok_or_else(|| tr("No releases found").to_string())
+}
+
+pub async fn fetch_release_by_tag(
+    tag: &str,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Release, String> {
+    let url = format!("https://api.github.com/repos/{}/releases/tags/{}", REPO, tag);
+    fetch_release(&url, cancel, on_rate_limited).await
+}
+
+// The release "latest" means: the latest stable one, or on the beta channel
+// the newest one with the mod DLL, pre-releases included
+pub async fn fetch_newest_release(
+    beta: bool,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Release, String> {
+    if !beta {
+        return fetch_latest_release(cancel, on_rate_limited).await;
+    }
+
+    fetch_releases(cancel, on_rate_limited)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| tr("No releases found").to_string())
+}
+
+// Newest first, mod and app releases mixed
+async fn fetch_release_list(
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Vec<Release>, String> {
+    let url = format!("https://api.github.com/repos/{}/releases?per_page=50", REPO);
+    let response = get_with_retry(&client()?, &url, cancel, on_rate_limited)
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Release list failed: {}", &[&e]) })?;
+
+    response.json::<Vec<Release>>()
+        .await
+        .map_err(|e| trf("Failed to parse release list: {}", &[&e]))
+}
+
+// Newest first, only the ones that ship the mod DLL
+pub async fn fetch_releases(
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Vec<Release>, String> {
+    let releases = fetch_release_list(cancel, on_rate_limited).await?;
+    Ok(releases.into_iter()
+        .filter(|release| release.app_version().is_none() && release.asset(DLL_ASSET_NAME).is_some())
+        .collect())
+}
+
+// Newest first, releases of the installer app
+pub async fn fetch_app_releases(
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Vec<Release>, String> {
+    let releases = fetch_release_list(cancel, on_rate_limited).await?;
+    Ok(releases.into_iter().filter(|release| release.app_version().is_some()).collect())
+}
+
+// A file from the repository's main branch, for data the installer keeps
+// up to date without a release (compat.json, game_files.json)
+pub async fn fetch_repo_file(name: &str, cancel: &CancellationToken) -> Result<String, String> {
+    let url = format!("https://raw.githubusercontent.com/{}/main/{}", REPO, name);
+    let response = get_with_retry(&client()?, &url, cancel, &|_| {})
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Failed to download {}: {}", &[&name, &e]) })?;
+
+    response.text()
+        .await
+        .map_err(|e| trf("Failed to read {}: {}", &[&name, &e]))
+}
+
+async fn fetch_release(
+    url: &str,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+) -> Result<Release, String> {
+    let response = get_with_retry(&client()?, url, cancel, on_rate_limited)
+        .await
+        .map_err(|e| if cancel::is_cancelled(&e) { e } else { trf("Release check failed: {}", &[&e]) })?;
+
+    response.json::<Release>()
+        .await
+        .map_err(|e| trf("Failed to parse release info: {}", &[&e]))
+}
@@ -0,0 +1,119 @@
+// Translation layer for UI labels and for the messages and errors the
+// installer logic hands back to be shown. Strings are looked up through tr()
+// when they're shown rather than once at startup, so changing the language
+// shows up on the next repaint without restarting the app. The English text
+// is the lookup key, so a missing translation falls back to English.
+//
+// Languages come from the community language files found in the lang folders
+// (see lang). A lookup walks the selected language, then its fallback chain,
+// then English.
+
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::RwLock;
+use crate::lang::{self, LanguagePack};
+
+// Loaded packs live for the rest of the process so tr() can hand out
+// &'static str; they're only loaded again when the user asks for a rescan
+static PACKS: RwLock<Vec<&'static LanguagePack>> = RwLock::new(Vec::new());
+static CURRENT: RwLock<Option<&'static LanguagePack>> = RwLock::new(None);
+
+// Guards against fallback cycles in hand-written files
+const MAX_FALLBACK_DEPTH: usize = 8;
+
+pub struct Language {
+    pub code: String,
+    // Always shown in its own language
+    pub name: String,
+}
+
+fn english() -> Language {
+    Language { code: lang::ENGLISH_CODE.to_string(), name: "English".to_string() }
+}
+
+pub fn languages() -> Vec<Language> {
+    let packs = PACKS.read().unwrap_or_else(|e| e.into_inner());
+    std::iter::once(english())
+        .chain(packs.iter().map(|pack| Language { code: pack.code.clone(), name: pack.name.clone() }))
+        .collect()
+}
+
+pub fn language() -> Language {
+    match *CURRENT.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(pack) => Language { code: pack.code.clone(), name: pack.name.clone() },
+        None => english(),
+    }
+}
+
+// Takes effect from the next frame on. Unknown codes switch to English.
+pub fn set_language(code: &str) {
+    let pack = PACKS.read().unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|pack| pack.code == code)
+        .copied();
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = pack;
+}
+
+// Rescans the lang folders and keeps the current language if it's still
+// there. Returns what translators should know about: files that failed to
+// load and translations that were dropped.
+pub fn load_packs() -> Vec<String> {
+    let (packs, mut problems) = lang::discover();
+    for pack in &packs {
+        problems.extend(pack.problems.iter().map(|problem| format!("{}: {}", pack.name, problem)));
+    }
+
+    let current = language().code;
+    *PACKS.write().unwrap_or_else(|e| e.into_inner()) = packs
+        .into_iter()
+        .map(|pack| &*Box::leak(Box::new(pack)))
+        .collect();
+    set_language(&current);
+
+    problems
+}
+
+pub fn install_pack(path: &Path) -> Result<Vec<String>, String> {
+    let pack = lang::install(path)?;
+    let problems = load_packs();
+    set_language(&pack.code);
+    Ok(problems)
+}
+
+pub fn tr(text: &'static str) -> &'static str {
+    let Some(mut pack) = *CURRENT.read().unwrap_or_else(|e| e.into_inner()) else {
+        return text;
+    };
+    let packs = PACKS.read().unwrap_or_else(|e| e.into_inner());
+
+    for _ in 0..MAX_FALLBACK_DEPTH {
+        if let Some(translated) = pack.strings.get(text) {
+            return translated;
+        }
+
+        let fallback = pack.fallback.as_deref()
+            .and_then(|code| packs.iter().find(|candidate| candidate.code == code));
+        match fallback {
+            Some(next) => pack = next,
+            None => break,
+        }
+    }
+
+    text
+}
+
+// tr() for messages with {} placeholders, e.g. status messages. The
+// arguments fill the translated text's placeholders in order.
+pub fn trf(text: &'static str, args: &[&dyn Display]) -> String {
+    let mut pieces = tr(text).split("{}");
+    let mut message = pieces.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            message.push_str(&arg.to_string());
+        }
+        message.push_str(piece);
+    }
+    message
+}
@@ -0,0 +1,150 @@
+// Index of the files previous imports wrote into a resources folder, kept in
+// the app directory (one file per destination). Re-importing a pack with tens
+// of thousands of files then only has to compare metadata: a file is skipped
+// when the destination still looks exactly like the last import left it and
+// the source is the same file (same size and mtime) or has the same hash.
+
+use crate::config;
+use crate::i18n::{tr, trf};
+use crate::verify;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    pub size: u64,
+    // Milliseconds since the epoch, of the destination file after copying
+    pub modified: u64,
+    pub sha256: String,
+    // The source file it was copied from, to skip without hashing
+    pub source_modified: u64,
+    // Where it was copied from, to repair it from later. Empty in indexes
+    // written before this was recorded
+    #[serde(default)]
+    pub source: PathBuf,
+}
+
+pub struct ImportIndex {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, IndexEntry>,
+}
+
+pub fn modified_millis(metadata: &Metadata) -> u64 {
+    metadata.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn index_path(dest_root: &Path) -> Option<PathBuf> {
+    let key = verify::sha256_hex(dest_root.to_string_lossy().as_bytes());
+    config::app_dir().map(|dir| dir.join("index").join(format!("{}.json", &key[..16])))
+}
+
+impl ImportIndex {
+    // A missing or unreadable index just means every file gets copied again
+    pub fn load(dest_root: &Path) -> Self {
+        let path = index_path(dest_root);
+        let entries = path.as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        Self { path, entries }
+    }
+
+    pub fn get(&self, relative: &Path) -> Option<&IndexEntry> {
+        self.entries.get(relative)
+    }
+
+    pub fn insert(&mut self, relative: PathBuf, entry: IndexEntry) {
+        self.entries.insert(relative, entry);
+    }
+
+    pub fn remove(&mut self, relative: &Path) {
+        self.entries.remove(relative);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &IndexEntry)> {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = self.path.as_ref().ok_or(tr("Config directory not found"))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| trf("Failed to create index directory: {}", &[&e]))?;
+        }
+
+        let contents = serde_json::to_string(&self.entries)
+            .map_err(|e| trf("Failed to serialize import index: {}", &[&e]))?;
+
+        std::fs::write(path, contents)
+            .map_err(|e| trf("Failed to write import index: {}", &[&e]))
+    }
+}
+
+// Once the folder is gone or replaced, e.g. removed when uninstalling
+pub fn forget(dest_root: &Path) {
+    if let Some(path) = index_path(dest_root) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+// Moves the index into `file`, for a folder moved away to be swapped back
+// in later
+pub fn stash(dest_root: &Path, file: &Path) -> Result<(), String> {
+    let Some(path) = index_path(dest_root).filter(|path| path.exists()) else {
+        return Ok(());
+    };
+    std::fs::copy(&path, file)
+        .map_err(|e| trf("Failed to keep import index: {}", &[&e]))?;
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+// The other way around, for the folder now at `dest_root`
+pub fn unstash(file: &Path, dest_root: &Path) -> Result<(), String> {
+    forget(dest_root);
+    let Some(path) = index_path(dest_root).filter(|_| file.exists()) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create index directory: {}", &[&e]))?;
+    }
+    std::fs::copy(file, &path)
+        .map_err(|e| trf("Failed to restore import index: {}", &[&e]))?;
+    let _ = std::fs::remove_file(file);
+    Ok(())
+}
+
+impl IndexEntry {
+    // Whether copying `source` over `dest` would leave it unchanged
+    pub fn matches(&self, source: &Path, source_metadata: &Metadata, dest: &Path) -> bool {
+        let Ok(dest_metadata) = std::fs::metadata(dest) else {
+            return false;
+        };
+
+        // Touched since the last import, so the recorded hash can't be trusted
+        if dest_metadata.len() != self.size || modified_millis(&dest_metadata) != self.modified {
+            return false;
+        }
+        if source_metadata.len() != self.size {
+            return false;
+        }
+        if modified_millis(source_metadata) == self.source_modified {
+            return true;
+        }
+
+        verify::sha256_file(source).is_ok_and(|sha256| sha256 == self.sha256)
+    }
+}
@@ -0,0 +1,278 @@
+use crate::cancel::CancellationToken;
+use crate::config::Config;
+use crate::i18n::trf;
+use crate::{audit, download, game_process};
+use crate::events::TransferProgress;
+use crate::locked_file::{self, Holder};
+use crate::nightly::NightlyBuild;
+use crate::pipeline::{InstallContext, InstallStep, Pipeline};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// The system DLL the mod is loaded by the game as a proxy for. The mod
+// supports several, for when another mod or overlay already uses one.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ProxyDll {
+    #[default]
+    Powrprof,
+    Version,
+    Dsound,
+}
+
+impl ProxyDll {
+    pub const ALL: [ProxyDll; 3] = [ProxyDll::Powrprof, ProxyDll::Version, ProxyDll::Dsound];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ProxyDll::Powrprof => "powrprof.dll",
+            ProxyDll::Version => "version.dll",
+            ProxyDll::Dsound => "dsound.dll",
+        }
+    }
+}
+
+// The parts of the config an install goes by, handed in by the caller
+#[derive(Clone, Default, Debug)]
+pub struct InstallSettings {
+    pub proxy_dll: ProxyDll,
+    // Release mirrors tried when GitHub fails, see download::fetch
+    pub mirrors: Vec<String>,
+}
+
+impl InstallSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            proxy_dll: config.proxy_dll,
+            mirrors: config.download_mirrors.clone(),
+        }
+    }
+}
+
+// Where the mod is installed in the game folder
+pub fn dll_path(game_path: &Path, proxy: ProxyDll) -> PathBuf {
+    game_path.join(proxy.file_name())
+}
+
+pub fn is_mod_installed(game_path: &Path, proxy: ProxyDll) -> bool {
+    dll_path(game_path, proxy).exists()
+}
+
+// Where a new DLL is written before it's renamed over the installed one. Next
+// to it, so the rename stays on one drive and can't be left half done.
+fn partial_dll_path(game_path: &Path, proxy: ProxyDll) -> PathBuf {
+    dll_path(game_path, proxy).with_extension("tnsm-partial")
+}
+
+// Windows errors for a file another process has open or has loaded
+const ERROR_SHARING_VIOLATION: i32 = 32;
+const ERROR_LOCK_VIOLATION: i32 = 33;
+const ERROR_USER_MAPPED_FILE: i32 = 1224;
+
+// Why writing the mod DLL failed, told apart so the message can say what to do
+#[derive(Debug)]
+pub enum WriteError {
+    // Open in another process, usually the game itself or an antivirus scan
+    Locked { dll: ProxyDll, holders: Vec<Holder> },
+    // The game folder needs administrator rights, or something blocks writing to it
+    AccessDenied { dll: ProxyDll },
+    Io { dll: ProxyDll, error: std::io::Error },
+}
+
+impl WriteError {
+    fn from_io(error: std::io::Error, game_path: &Path, dll: ProxyDll) -> Self {
+        let in_use = matches!(
+            error.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION | ERROR_USER_MAPPED_FILE)
+        );
+        if !in_use && error.kind() != std::io::ErrorKind::PermissionDenied {
+            return WriteError::Io { dll, error };
+        }
+
+        // Replacing a DLL a program has loaded is also refused as access denied
+        let holders = locked_file::holders(&dll_path(game_path, dll)).unwrap_or_default();
+        if in_use || !holders.is_empty() {
+            WriteError::Locked { dll, holders }
+        } else {
+            WriteError::AccessDenied { dll }
+        }
+    }
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::Locked { dll, holders } if holders.is_empty() => f.write_str(&trf(
+                "{} is in use by another program. Close Sky and try again; if it keeps happening, wait for your antivirus to finish scanning the game folder.",
+                &[&dll.file_name()],
+            )),
+            WriteError::Locked { dll, holders } => {
+                let names = holders.iter()
+                    .map(|holder| format!("{} (PID {})", holder.name, holder.pid))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                f.write_str(&trf("{} is in use by {}. Close it and try again.", &[&dll.file_name(), &names]))
+            }
+            WriteError::AccessDenied { dll } => f.write_str(&trf(
+                "Access to {} was denied. Run the installer as administrator, or check that your antivirus isn't blocking the game folder.",
+                &[&dll.file_name()],
+            )),
+            WriteError::Io { dll, error } => f.write_str(&trf(
+                "Failed to write {}: {}. Check that the drive isn't full and that your antivirus isn't blocking the game folder.",
+                &[&dll.file_name(), &error],
+            )),
+        }
+    }
+}
+
+impl From<WriteError> for String {
+    fn from(error: WriteError) -> Self {
+        error.to_string()
+    }
+}
+
+// Replaces the installed DLL with `source`. A failed copy leaves the old DLL
+// alone rather than a truncated one the game crashes on.
+pub async fn copy_dll(game_path: &Path, proxy: ProxyDll, source: &Path) -> Result<(), WriteError> {
+    let partial = partial_dll_path(game_path, proxy);
+    let result = match tokio::fs::copy(source, &partial).await {
+        Ok(_) => tokio::fs::rename(&partial, dll_path(game_path, proxy)).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&partial).await;
+    }
+    result.map_err(|e| WriteError::from_io(e, game_path, proxy))
+}
+
+// Same as copy_dll, for a DLL already in memory
+pub fn write_dll(game_path: &Path, proxy: ProxyDll, contents: &[u8]) -> Result<(), WriteError> {
+    let partial = partial_dll_path(game_path, proxy);
+    let result = std::fs::write(&partial, contents)
+        .and_then(|_| std::fs::rename(&partial, dll_path(game_path, proxy)));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result.map_err(|e| WriteError::from_io(e, game_path, proxy))
+}
+
+// Runs the standard install pipeline, returning the installed release tag
+// when it could be resolved
+pub async fn download_and_install(
+    dll_url: &str,
+    game_path: &Path,
+    settings: &InstallSettings,
+    cancel: &CancellationToken,
+    on_rate_limited: &(dyn Fn(u64) + Sync),
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+    on_step: impl Fn(&dyn InstallStep) + Send + Sync + 'static,
+) -> Result<Option<String>, String> {
+    let mut ctx = InstallContext::new(dll_url, game_path.to_path_buf(), settings.clone(), cancel, on_rate_limited, on_progress);
+
+    let audit = audit::begin("install", Some(game_path)).dll(settings.proxy_dll).detail(dll_url);
+    let result = Pipeline::standard()
+        .before_step(on_step)
+        .run(&mut ctx)
+        .await
+        .map(|_| ctx.version());
+    audit.finish(&result);
+    result
+}
+
+// Installs a version from the version cache without downloading it
+pub async fn install_cached(
+    version: &str,
+    game_path: &Path,
+    settings: &InstallSettings,
+    cancel: &CancellationToken,
+    on_step: impl Fn(&dyn InstallStep) + Send + Sync + 'static,
+) -> Result<Option<String>, String> {
+    // An interrupted switch resumes as a regular install of the same release
+    let dll_url = download::release_dll_url(version);
+    let mut ctx = InstallContext::new(&dll_url, game_path.to_path_buf(), settings.clone(), cancel, &|_| {}, &|_| {});
+
+    let audit = audit::begin("install cached version", Some(game_path)).dll(settings.proxy_dll).detail(version);
+    let result = Pipeline::cached(version)
+        .before_step(on_step)
+        .run(&mut ctx)
+        .await
+        .map(|_| ctx.version());
+    audit.finish(&result);
+    result
+}
+
+// Installs a DLL file from disk, e.g. a local build of the mod
+pub async fn install_local(
+    path: &Path,
+    game_path: &Path,
+    settings: &InstallSettings,
+    cancel: &CancellationToken,
+    on_step: impl Fn(&dyn InstallStep) + Send + Sync + 'static,
+) -> Result<Option<String>, String> {
+    // An interrupted local install resumes as a regular install of the latest release
+    let mut ctx = InstallContext::new(download::DEFAULT_DLL_URL, game_path.to_path_buf(), settings.clone(), cancel, &|_| {}, &|_| {});
+
+    let audit = audit::begin("install local DLL", Some(game_path)).dll(settings.proxy_dll).detail(path.display().to_string());
+    let result = Pipeline::local(path)
+        .before_step(on_step)
+        .run(&mut ctx)
+        .await
+        .map(|_| ctx.version());
+    audit.finish(&result);
+    result
+}
+
+// Installs a nightly build from GitHub Actions, returning its nightly version
+pub async fn install_nightly(
+    build: &NightlyBuild,
+    token: &str,
+    game_path: &Path,
+    settings: &InstallSettings,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+    on_step: impl Fn(&dyn InstallStep) + Send + Sync + 'static,
+) -> Result<Option<String>, String> {
+    // The token isn't written to the journal, so an interrupted nightly
+    // install resumes as a regular install of the latest release
+    let mut ctx = InstallContext::new(download::DEFAULT_DLL_URL, game_path.to_path_buf(), settings.clone(), cancel, &|_| {}, on_progress);
+
+    let audit = audit::begin("install nightly build", Some(game_path)).dll(settings.proxy_dll).detail(build.version());
+    let result = Pipeline::nightly(build, token)
+        .before_step(on_step)
+        .run(&mut ctx)
+        .await
+        .map(|_| ctx.version());
+    audit.finish(&result);
+    result
+}
+
+// Renames an installed DLL when the proxy it's loaded as changes, so it isn't
+// left behind under the old name. Saving the choice is up to the caller.
+pub fn move_dll(game_path: &Path, from: ProxyDll, to: ProxyDll) -> Result<(), String> {
+    let source = game_path.join(from.file_name());
+    let target = game_path.join(to.file_name());
+    if from == to || !source.exists() {
+        return Ok(());
+    }
+    if target.exists() {
+        return Err(trf("{} is already used by another mod, remove it or pick another name", &[&to.file_name()]));
+    }
+
+    let audit = audit::begin("rename mod DLL", Some(game_path))
+        .detail(format!("{} -> {}", from.file_name(), to.file_name()));
+    let result = game_process::ensure_not_running().and_then(|_| {
+        std::fs::rename(&source, &target)
+            .map_err(|e| trf("Failed to rename {}: {}", &[&from.file_name(), &e]))
+    });
+    audit.finish(&result);
+    result
+}
+
+pub fn uninstall(game_path: &Path, proxy: ProxyDll) -> Result<(), String> {
+    let audit = audit::begin("uninstall", Some(game_path)).dll(proxy);
+    // Left behind if the installer was killed mid-write
+    let _ = std::fs::remove_file(partial_dll_path(game_path, proxy));
+    let result = std::fs::remove_file(dll_path(game_path, proxy))
+        .map_err(|e| e.to_string());
+    audit.finish(&result);
+    result
+}
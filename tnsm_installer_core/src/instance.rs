@@ -0,0 +1,143 @@
+// Keeps the installer single-instance. The first instance listens on a local
+// channel (a named pipe on Windows, a Unix socket elsewhere); later launches
+// hand their request to it and exit instead of opening a second window.
+
+use crate::jumplist::JumpTask;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::runtime::Handle;
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\ThatNoobSkyApp";
+
+#[derive(Clone, Debug)]
+pub enum Request {
+    // Bring the running window to the front
+    Focus,
+    // Import this resource pack, after asking the user
+    OpenPack(PathBuf),
+    // Import this resource folder, from the Explorer context menu
+    ImportFolder(PathBuf),
+    // A jump list task
+    RunTask(JumpTask),
+}
+
+impl Request {
+    fn to_line(&self) -> String {
+        match self {
+            Request::Focus => "focus\n".to_string(),
+            Request::OpenPack(path) => format!("open {}\n", path.display()),
+            Request::ImportFolder(path) => format!("import {}\n", path.display()),
+            Request::RunTask(task) => format!("task {}\n", task.arg()),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        match line.split_once(' ') {
+            Some(("open", path)) => Some(Request::OpenPack(PathBuf::from(path))),
+            Some(("import", path)) => Some(Request::ImportFolder(PathBuf::from(path))),
+            Some(("task", task)) => JumpTask::from_arg(task).ok().map(Request::RunTask),
+            None if line == "focus" => Some(Request::Focus),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn socket_path() -> Option<PathBuf> {
+    crate::config::app_dir().map(|dir| dir.join("instance.sock"))
+}
+
+#[cfg(windows)]
+fn connect() -> Option<std::fs::File> {
+    std::fs::OpenOptions::new().write(true).open(PIPE_NAME).ok()
+}
+
+#[cfg(not(windows))]
+fn connect() -> Option<std::os::unix::net::UnixStream> {
+    std::os::unix::net::UnixStream::connect(socket_path()?).ok()
+}
+
+// Returns true when a running instance took the request
+pub fn hand_off(request: &Request) -> bool {
+    match connect() {
+        Some(mut connection) => connection.write_all(request.to_line().as_bytes()).is_ok(),
+        None => false,
+    }
+}
+
+// Waits for the running instance to close, e.g. the one that just replaced
+// this exe with an update. An empty connection is ignored by the listener.
+pub fn wait_until_closed(timeout: Duration) {
+    let start = Instant::now();
+    while connect().is_some() && start.elapsed() < timeout {
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+// Starts accepting requests from later launches. Call after hand_off failed.
+pub fn listen(runtime: &Handle, on_request: impl Fn(Request) + Send + Sync + 'static) {
+    runtime.spawn(serve(on_request));
+}
+
+async fn read_requests(connection: impl tokio::io::AsyncRead + Unpin, on_request: &(impl Fn(Request) + Sync)) {
+    let mut lines = BufReader::new(connection).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Some(request) = Request::parse(line.trim_end()) {
+            on_request(request);
+        }
+    }
+}
+
+#[cfg(windows)]
+async fn serve(on_request: impl Fn(Request) + Send + Sync + 'static) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = match ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)
+    {
+        Ok(server) => server,
+        // Lost the race against another instance starting at the same time
+        Err(_) => return,
+    };
+
+    loop {
+        if server.connect().await.is_err() {
+            return;
+        }
+
+        let client = server;
+        server = match ServerOptions::new().create(PIPE_NAME) {
+            Ok(server) => server,
+            Err(_) => return,
+        };
+
+        // Requests are a single short line, no need to serve clients concurrently
+        read_requests(client, &on_request).await;
+    }
+}
+
+#[cfg(not(windows))]
+async fn serve(on_request: impl Fn(Request) + Send + Sync + 'static) {
+    let Some(path) = socket_path() else {
+        return;
+    };
+
+    // Nobody answered on it, so any socket file left over is from a crashed run
+    let _ = std::fs::remove_file(&path);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let Ok(listener) = tokio::net::UnixListener::bind(&path) else {
+        return;
+    };
+
+    while let Ok((client, _)) = listener.accept().await {
+        read_requests(client, &on_request).await;
+    }
+}
@@ -0,0 +1,409 @@
+// Journals of the operations currently modifying the game directory, one
+// file per operation so an import running next to an install doesn't
+// overwrite its journal. Each is written when its operation starts and
+// removed when it finishes, so a journal found on startup means that
+// operation was interrupted.
+
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::{config, timestamp};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// Saving after every copied file would make big imports quadratic
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind")]
+pub enum Operation {
+    Install {
+        game_path: PathBuf,
+        dll_url: String,
+        // Name the DLL is installed under
+        #[serde(default)]
+        proxy_dll: ProxyDll,
+        // Copy of the DLL that was in place before, restored on rollback
+        backup: Option<PathBuf>,
+    },
+    Import {
+        game_path: PathBuf,
+        source: PathBuf,
+        // Relative paths already copied, skipped when resuming
+        copied: Vec<PathBuf>,
+        // Relative paths that didn't exist before, removed on rollback
+        created: Vec<PathBuf>,
+        // Also delete the files an earlier import of `source` wrote that it
+        // no longer has
+        #[serde(default)]
+        remove_missing: bool,
+    },
+}
+
+impl Operation {
+    pub fn describe(&self) -> String {
+        match self {
+            // Journaled once the download is done, so it was the DLL write
+            Operation::Install { .. } => tr("Mod install interrupted while replacing the DLL").to_string(),
+            Operation::Import { source, copied, .. } => trf(
+                "Resource import from {} interrupted after {} files",
+                &[&source.display(), &copied.len()]
+            ),
+        }
+    }
+}
+
+fn journals_dir() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join("journals"))
+}
+
+fn journal_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn lock_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.lock", id))
+}
+
+fn backup_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}-backup.dll", id))
+}
+
+// Held for as long as the operation runs, or while an interrupted one is
+// offered, so another instance leaves its journal alone. The OS lets go of it
+// if the process dies.
+fn lock(dir: &Path, id: &str) -> Result<Option<File>, String> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(dir, id))
+        .map_err(|e| trf("Failed to open journal lock: {}", &[&e]))?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(file)),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(trf("Failed to lock journal: {}", &[&e])),
+    }
+}
+
+fn write_operation(dir: &Path, id: &str, operation: &Operation) -> Result<(), String> {
+    let contents = serde_json::to_string(operation)
+        .map_err(|e| trf("Failed to serialize journal: {}", &[&e]))?;
+
+    // Write then rename so a crash mid-save doesn't leave a corrupt journal
+    let path = journal_path(dir, id);
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, contents)
+        .and_then(|_| std::fs::rename(&temp_path, &path))
+        .map_err(|e| trf("Failed to write journal: {}", &[&e]))
+}
+
+fn discard(dir: &Path, id: &str) {
+    let _ = std::fs::remove_file(journal_path(dir, id));
+    let _ = std::fs::remove_file(backup_path(dir, id));
+    let _ = std::fs::remove_file(lock_path(dir, id));
+}
+
+// An operation a previous run left unfinished, locked until it's resumed,
+// rolled back or dropped
+pub struct Pending {
+    dir: PathBuf,
+    id: String,
+    pub operation: Operation,
+    _lock: File,
+}
+
+impl Pending {
+    pub fn describe(&self) -> String {
+        self.operation.describe()
+    }
+
+    pub fn roll_back(self) -> Result<String, String> {
+        let summary = roll_back(&self.operation)?;
+        self.discard();
+        Ok(summary)
+    }
+
+    // Resuming starts a journal of its own, which carries on from this one
+    pub fn discard(self) {
+        discard(&self.dir, &self.id);
+    }
+}
+
+// Oldest first. Operations still running in another instance are skipped.
+pub fn pending() -> Vec<Pending> {
+    match journals_dir() {
+        Some(dir) => pending_in(&dir),
+        None => Vec::new(),
+    }
+}
+
+fn pending_in(dir: &Path) -> Vec<Pending> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+        .collect();
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let lock = lock(dir, &id).ok()??;
+            let operation = std::fs::read_to_string(journal_path(dir, &id))
+                .ok()
+                .and_then(|contents| serde_json::from_str(&contents).ok())?;
+            Some(Pending { dir: dir.to_path_buf(), id, operation, _lock: lock })
+        })
+        .collect()
+}
+
+pub struct Journal {
+    dir: PathBuf,
+    id: String,
+    _lock: File,
+    operation: Mutex<Option<Operation>>,
+    last_save: Mutex<Instant>,
+    finished: bool,
+}
+
+impl Journal {
+    // Claims a journal without writing it yet, so files it will refer to can
+    // be set aside under its id first
+    pub fn open() -> Result<Self, String> {
+        Self::open_in(journals_dir().ok_or(tr("Config directory not found"))?)
+    }
+
+    pub(crate) fn open_in(dir: PathBuf) -> Result<Self, String> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| trf("Failed to create journal directory: {}", &[&e]))?;
+
+        let id = format!("{}-{}-{}", timestamp::now(), std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+        let lock = lock(&dir, &id)?.ok_or(tr("Journal is already in use"))?;
+
+        Ok(Self {
+            dir,
+            id,
+            _lock: lock,
+            operation: Mutex::new(None),
+            last_save: Mutex::new(Instant::now()),
+            finished: false,
+        })
+    }
+
+    pub fn begin(operation: Operation) -> Result<Self, String> {
+        let journal = Self::open()?;
+        journal.start(operation)?;
+        Ok(journal)
+    }
+
+    // Written straight away, the game directory is touched right after
+    pub fn start(&self, operation: Operation) -> Result<(), String> {
+        write_operation(&self.dir, &self.id, &operation)?;
+        *self.operation.lock().unwrap() = Some(operation);
+        *self.last_save.lock().unwrap() = Instant::now();
+        Ok(())
+    }
+
+    // Where the DLL an install replaces is kept until it's done
+    pub fn backup_path(&self) -> PathBuf {
+        backup_path(&self.dir, &self.id)
+    }
+
+    // Throttled, for progress that only saves work when resuming
+    pub fn update(&self, change: impl FnOnce(&mut Operation)) {
+        let mut operation = self.operation.lock().unwrap();
+        let Some(operation) = operation.as_mut() else {
+            return;
+        };
+        change(operation);
+
+        let mut last_save = self.last_save.lock().unwrap();
+        if last_save.elapsed() >= SAVE_INTERVAL {
+            let _ = write_operation(&self.dir, &self.id, operation);
+            *last_save = Instant::now();
+        }
+    }
+
+    // Flushes throttled updates, before a step that deletes anything
+    pub fn save(&self) -> Result<(), String> {
+        let operation = self.operation.lock().unwrap();
+        let Some(operation) = operation.as_ref() else {
+            return Ok(());
+        };
+        write_operation(&self.dir, &self.id, operation)?;
+        *self.last_save.lock().unwrap() = Instant::now();
+        Ok(())
+    }
+
+    pub fn finish(mut self) {
+        self.finished = true;
+        discard(&self.dir, &self.id);
+    }
+
+    // Undo what the operation did so far, used when it's cancelled
+    pub fn roll_back(mut self) -> Result<String, String> {
+        self.finished = true;
+        let operation = self.operation.lock().unwrap().clone();
+        let summary = match operation {
+            Some(operation) => roll_back(&operation)?,
+            None => String::new(),
+        };
+        discard(&self.dir, &self.id);
+        Ok(summary)
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        // The operation failed or was abandoned, flush what got done so far
+        match self.operation.lock().unwrap().as_ref() {
+            Some(operation) => {
+                let _ = write_operation(&self.dir, &self.id, operation);
+            }
+            // Never started, nothing was touched
+            None => discard(&self.dir, &self.id),
+        }
+    }
+}
+
+fn remove_created(game_path: &Path, created: &[PathBuf]) -> usize {
+    let resources = crate::resources::resources_path(game_path);
+
+    created.iter()
+        .filter(|relative| std::fs::remove_file(resources.join(relative)).is_ok())
+        .count()
+}
+
+fn roll_back(operation: &Operation) -> Result<String, String> {
+    let summary = match operation {
+        Operation::Install { game_path, proxy_dll, backup, .. } => {
+            let dll_path = crate::install::dll_path(game_path, *proxy_dll);
+
+            match backup {
+                Some(backup) if backup.exists() => {
+                    let contents = std::fs::read(backup)
+                        .map_err(|e| trf("Failed to read previous DLL: {}", &[&e]))?;
+                    crate::install::write_dll(game_path, *proxy_dll, &contents)
+                        .map_err(|e| trf("Failed to restore previous DLL: {}", &[&e]))?;
+                    tr("Previous mod DLL restored").to_string()
+                }
+                _ => {
+                    if dll_path.exists() {
+                        std::fs::remove_file(&dll_path)
+                            .map_err(|e| trf("Failed to remove partial DLL: {}", &[&e]))?;
+                    }
+                    tr("Partially installed DLL removed").to_string()
+                }
+            }
+        }
+        Operation::Import { game_path, created, .. } => {
+            let removed = remove_created(game_path, created);
+            trf("Removed {} newly imported files", &[&removed])
+        }
+    };
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(game_path: &Path, created: Vec<PathBuf>) -> Operation {
+        Operation::Import {
+            game_path: game_path.to_path_buf(),
+            source: PathBuf::from("pack"),
+            copied: Vec::new(),
+            created,
+            remove_missing: false,
+        }
+    }
+
+    #[test]
+    fn finished_journal_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::open_in(dir.path().to_path_buf()).unwrap();
+        journal.start(import(dir.path(), Vec::new())).unwrap();
+        journal.finish();
+
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn dropped_journal_is_pending_with_its_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = Journal::open_in(dir.path().to_path_buf()).unwrap();
+        journal.start(import(dir.path(), Vec::new())).unwrap();
+        journal.update(|operation| {
+            if let Operation::Import { copied, .. } = operation {
+                copied.push(PathBuf::from("a.png"));
+            }
+        });
+
+        // Still running, so it isn't offered
+        assert!(pending_in(dir.path()).is_empty());
+        drop(journal);
+
+        let pending = pending_in(dir.path());
+        assert_eq!(pending.len(), 1);
+        assert!(matches!(&pending[0].operation, Operation::Import { copied, .. } if copied == &[PathBuf::from("a.png")]));
+        // Locked while it's offered
+        assert!(pending_in(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn import_roll_back_removes_only_created_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let game = dir.path().join("game");
+        let resources = crate::resources::resources_path(&game);
+        std::fs::create_dir_all(&resources).unwrap();
+        std::fs::write(resources.join("new.png"), "new").unwrap();
+        std::fs::write(resources.join("old.png"), "old").unwrap();
+
+        let journals = dir.path().join("journals");
+        let journal = Journal::open_in(journals.clone()).unwrap();
+        journal.start(import(&game, vec![PathBuf::from("new.png")])).unwrap();
+        drop(journal);
+
+        let pending = pending_in(&journals).pop().unwrap();
+        assert_eq!(pending.roll_back().unwrap(), "Removed 1 newly imported files");
+        assert!(!resources.join("new.png").exists());
+        assert!(resources.join("old.png").exists());
+        assert!(pending_in(&journals).is_empty());
+        assert_eq!(std::fs::read_dir(&journals).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn install_roll_back_restores_the_previous_dll() {
+        let dir = tempfile::tempdir().unwrap();
+        let proxy = ProxyDll::default();
+        let dll = crate::install::dll_path(dir.path(), proxy);
+        std::fs::write(&dll, "new").unwrap();
+
+        let journal = Journal::open_in(dir.path().join("journals")).unwrap();
+        let backup = journal.backup_path();
+        std::fs::write(&backup, "old").unwrap();
+        journal.start(Operation::Install {
+            game_path: dir.path().to_path_buf(),
+            dll_url: String::new(),
+            proxy_dll: proxy,
+            backup: Some(backup.clone()),
+        }).unwrap();
+
+        assert_eq!(journal.roll_back().unwrap(), "Previous mod DLL restored");
+        assert_eq!(std::fs::read_to_string(&dll).unwrap(), "old");
+        assert!(!backup.exists());
+    }
+}
@@ -0,0 +1,121 @@
+// Taskbar jump list tasks. Each task relaunches the exe with `--task <name>`,
+// so they go through the normal command line handling and work whether or
+// not the installer is already running.
+
+use crate::i18n::trf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JumpTask {
+    Install,
+    LaunchGame,
+    OpenLogs,
+    OpenResources,
+}
+
+impl JumpTask {
+    pub const ALL: [JumpTask; 4] = [
+        JumpTask::Install,
+        JumpTask::LaunchGame,
+        JumpTask::OpenLogs,
+        JumpTask::OpenResources,
+    ];
+
+    pub fn arg(self) -> &'static str {
+        match self {
+            JumpTask::Install => "install",
+            JumpTask::LaunchGame => "launch-game",
+            JumpTask::OpenLogs => "open-logs",
+            JumpTask::OpenResources => "open-resources",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            JumpTask::Install => "Install/Update Mod",
+            JumpTask::LaunchGame => "Launch Game",
+            JumpTask::OpenLogs => "Open Logs",
+            JumpTask::OpenResources => "Open Resources",
+        }
+    }
+
+    pub fn from_arg(arg: &str) -> Result<Self, String> {
+        Self::ALL.into_iter()
+            .find(|task| task.arg() == arg)
+            .ok_or_else(|| trf("Unknown task: {}", &[&arg]))
+    }
+}
+
+// Replaces the jump list with our tasks, pointing at this executable
+#[cfg(windows)]
+pub fn register() -> Result<(), String> {
+    use crate::i18n::tr;
+    use windows::core::{Interface, HSTRING, PROPVARIANT};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::{PropVariantChangeType, PVCHF_DEFAULT};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use windows::Win32::System::Variant::VT_LPWSTR;
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink};
+
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))?;
+    let exe = HSTRING::from(exe.as_os_str());
+    let failed = |e: windows::core::Error| trf("Failed to update jump list: {}", &[&e]);
+
+    unsafe {
+        // Already initialized on this thread is fine too
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+        let mut max_slots = 0;
+        let _removed: IObjectArray = list.BeginList(&mut max_slots).map_err(failed)?;
+
+        let tasks: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+        for task in JumpTask::ALL {
+            let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+            link.SetPath(&exe).map_err(failed)?;
+            link.SetArguments(&HSTRING::from(format!("--task {}", task.arg()))).map_err(failed)?;
+            link.SetIconLocation(&exe, 0).map_err(failed)?;
+
+            // Jump lists read the label from the link's title property, as a VT_LPWSTR
+            let mut title = PROPVARIANT::default();
+            PropVariantChangeType(&mut title, &PROPVARIANT::from(tr(task.title())), PVCHF_DEFAULT, VT_LPWSTR).map_err(failed)?;
+            let store: IPropertyStore = link.cast().map_err(failed)?;
+            store.SetValue(&PKEY_Title, &title).map_err(failed)?;
+            store.Commit().map_err(failed)?;
+
+            tasks.AddObject(&link).map_err(failed)?;
+        }
+
+        list.AddUserTasks(&tasks.cast::<IObjectArray>().map_err(failed)?).map_err(failed)?;
+        list.CommitList().map_err(failed)
+    }
+}
+
+// Removes our tasks from the jump list
+#[cfg(windows)]
+pub fn clear() -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use windows::Win32::UI::Shell::{DestinationList, ICustomDestinationList};
+
+    let failed = |e: windows::core::Error| trf("Failed to clear jump list: {}", &[&e]);
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER).map_err(failed)?;
+        list.DeleteList(PCWSTR::null()).map_err(failed)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn register() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn clear() -> Result<(), String> {
+    Ok(())
+}
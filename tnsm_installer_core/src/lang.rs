@@ -0,0 +1,182 @@
+// Community language files. Translators drop a JSON file into a `lang/`
+// folder next to the installer or in the app directory (or install one from
+// the UI, which copies it to the latter):
+//
+//   {
+//     "code": "id",
+//     "name": "Bahasa Indonesia",
+//     "fallback": "ms",
+//     "strings": { "⚡ Install Mod": "⚡ Pasang Mod", "{} / {} files": "{} / {} berkas" }
+//   }
+//
+// Keys are the English text. A translation has to use exactly the same
+// {placeholders} as its key, otherwise it's dropped and English is shown
+// instead of a string that would format wrong. Strings missing from a file
+// come from its fallback language, and in the end from English.
+//
+// Indonesian ships inside the installer. A file with the same code in a lang
+// folder replaces it, so translators can fix it without a new release.
+
+use crate::config;
+use crate::i18n::{tr, trf};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const LANG_DIR_NAME: &str = "lang";
+pub const ENGLISH_CODE: &str = "en";
+
+const BUILT_IN: &[(&str, &str)] = &[
+    ("id.json", include_str!("../lang/id.json")),
+];
+
+#[derive(Deserialize)]
+struct LanguageFile {
+    code: String,
+    name: String,
+    #[serde(default)]
+    fallback: Option<String>,
+    strings: HashMap<String, String>,
+}
+
+pub struct LanguagePack {
+    pub code: String,
+    pub name: String,
+    pub fallback: Option<String>,
+    pub strings: HashMap<String, String>,
+    // Translations that were dropped, with the reason
+    pub problems: Vec<String>,
+}
+
+pub fn dirs() -> Vec<PathBuf> {
+    let beside_exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(LANG_DIR_NAME)));
+    let app_dir = config::app_dir().map(|dir| dir.join(LANG_DIR_NAME));
+
+    beside_exe.into_iter().chain(app_dir).collect()
+}
+
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+        found.push(&rest[start..=start + length]);
+        rest = &rest[start + length + 1..];
+    }
+
+    found.sort_unstable();
+    found
+}
+
+pub fn parse(contents: &str) -> Result<LanguagePack, String> {
+    let file: LanguageFile = serde_json::from_str(contents)
+        .map_err(|e| trf("Failed to parse language file: {}", &[&e]))?;
+
+    if file.code.is_empty() || file.code == ENGLISH_CODE {
+        return Err(trf("Invalid language code \"{}\"", &[&file.code]));
+    }
+
+    let mut problems = Vec::new();
+    let strings = file.strings
+        .into_iter()
+        .filter(|(english, translated)| {
+            let matches = placeholders(english) == placeholders(translated);
+            if !matches {
+                problems.push(trf("\"{}\" doesn't keep the placeholders of \"{}\"", &[&translated, &english]));
+            }
+            matches
+        })
+        .collect();
+
+    Ok(LanguagePack {
+        code: file.code,
+        name: file.name,
+        fallback: file.fallback.filter(|code| code != ENGLISH_CODE),
+        strings,
+        problems,
+    })
+}
+
+pub fn load(path: &Path) -> Result<LanguagePack, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+    parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// The built-in languages and every language file found, plus the errors of
+// the ones that couldn't be loaded. A later folder overrides an earlier one
+// for the same code.
+pub fn discover() -> (Vec<LanguagePack>, Vec<String>) {
+    let mut packs: Vec<LanguagePack> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (file_name, contents) in BUILT_IN {
+        match parse(contents) {
+            Ok(pack) => packs.push(pack),
+            Err(e) => errors.push(trf("{} (built in): {}", &[&file_name, &e])),
+        }
+    }
+
+    for dir in dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match load(&path) {
+                Ok(pack) => {
+                    packs.retain(|existing| existing.code != pack.code);
+                    packs.push(pack);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    packs.sort_by(|a, b| a.name.cmp(&b.name));
+    (packs, errors)
+}
+
+// Validates a language file and copies it into the app directory's lang folder
+pub fn install(path: &Path) -> Result<LanguagePack, String> {
+    let pack = load(path)?;
+    let dir = config::app_dir()
+        .map(|dir| dir.join(LANG_DIR_NAME))
+        .ok_or(tr("Config directory not found"))?;
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| trf("Failed to create {}: {}", &[&dir.display(), &e]))?;
+    std::fs::copy(path, dir.join(format!("{}.json", pack.code)))
+        .map_err(|e| trf("Failed to install language file: {}", &[&e]))?;
+
+    Ok(pack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_found_and_sorted() {
+        assert_eq!(placeholders("{} / {} files"), vec!["{}", "{}"]);
+        assert_eq!(placeholders("{name} has {count}"), vec!["{count}", "{name}"]);
+        assert!(placeholders("no placeholders").is_empty());
+    }
+
+    #[test]
+    fn unclosed_brace_is_not_a_placeholder() {
+        assert_eq!(placeholders("{} and {unclosed"), vec!["{}"]);
+    }
+}
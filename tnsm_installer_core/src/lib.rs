@@ -0,0 +1,72 @@
+// Installer logic shared by the egui frontend and the command line modes
+
+pub mod action_queue;
+pub mod answer_file;
+pub mod app_files;
+pub mod arch;
+pub mod audit;
+pub mod autostart;
+pub mod benchmark;
+pub mod bundle;
+pub mod cancel;
+pub mod compat;
+pub mod config;
+pub mod config_backups;
+pub mod conflicts;
+pub mod detect;
+pub mod diagnostics;
+pub mod disk_space;
+pub mod download;
+pub mod elevated;
+pub mod events;
+pub mod game_cache;
+pub mod game_data;
+pub mod game_files;
+pub mod game_process;
+pub mod github;
+pub mod i18n;
+pub mod import_index;
+pub mod install;
+pub mod instance;
+pub mod journal;
+pub mod jumplist;
+pub mod lang;
+pub mod locked_file;
+pub mod log_tail;
+pub mod machine;
+pub mod manifest;
+pub mod mirror;
+pub mod mod_settings;
+pub mod mod_state;
+pub mod nightly;
+pub mod orphans;
+pub mod pack;
+pub mod packaging;
+pub mod pipeline;
+pub mod plugins;
+pub mod policy;
+pub mod preview;
+pub mod profiles;
+pub mod progress;
+pub mod proxy;
+pub mod registry;
+pub mod resource_link;
+pub mod resources;
+pub mod restore_point;
+pub mod scheduler;
+pub mod screenshots;
+pub mod script;
+pub mod self_update;
+pub mod settings_export;
+pub mod shell;
+pub mod status_pipe;
+pub mod steam_accounts;
+pub mod storage;
+pub mod tasks;
+pub mod timestamp;
+pub mod undo;
+pub mod vdf;
+pub mod verify;
+pub mod version_cache;
+pub mod watchdog;
+pub mod workers;
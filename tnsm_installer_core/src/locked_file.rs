@@ -0,0 +1,171 @@
+// Removing a DLL that's still loaded. Windows won't delete a file another
+// process has open, so uninstall asks the Restart Manager who holds it, can
+// have those processes closed, and as a last resort renames the DLL out of
+// the way and schedules it for deletion on the next reboot. A renamed DLL is
+// no longer loaded by the game, so the mod is off from the next launch even
+// before the file is actually gone. Scheduling the reboot deletion needs
+// admin rights; without them the leftover is removed the next time the
+// installer starts instead.
+
+#[cfg(not(windows))]
+use crate::i18n::tr;
+use crate::i18n::trf;
+use std::path::{Path, PathBuf};
+
+pub const PENDING_DELETE_EXTENSION: &str = "tnsm-pending-delete";
+
+#[derive(Clone, Debug)]
+pub struct Holder {
+    pub pid: u32,
+    pub name: String,
+}
+
+#[cfg(windows)]
+fn to_wide(path: &Path) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str().encode_wide().chain(std::iter::once(0)).collect()
+}
+
+// Runs `f` inside a Restart Manager session with `path` registered
+#[cfg(windows)]
+fn with_session<T>(path: &Path, f: impl FnOnce(u32) -> Result<T, String>) -> Result<T, String> {
+    use windows_sys::Win32::System::RestartManager::{
+        CCH_RM_SESSION_KEY, RmEndSession, RmRegisterResources, RmStartSession,
+    };
+
+    let mut session = 0;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    let error = unsafe { RmStartSession(&mut session, 0, key.as_mut_ptr()) };
+    if error != 0 {
+        return Err(trf("Failed to start Restart Manager session (error {})", &[&error]));
+    }
+
+    let wide = to_wide(path);
+    let files = [wide.as_ptr()];
+    let error = unsafe {
+        RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null())
+    };
+    let result = if error == 0 {
+        f(session)
+    } else {
+        Err(trf("Failed to register {} with the Restart Manager (error {})", &[&path.display(), &error]))
+    };
+
+    unsafe { RmEndSession(session) };
+    result
+}
+
+// Processes that have the file open
+#[cfg(windows)]
+pub fn holders(path: &Path) -> Result<Vec<Holder>, String> {
+    use windows_sys::Win32::Foundation::ERROR_MORE_DATA;
+    use windows_sys::Win32::System::RestartManager::{RM_PROCESS_INFO, RmGetList};
+
+    with_session(path, |session| {
+        let mut needed = 0;
+        let mut count;
+        let mut reasons = 0;
+        let mut processes: Vec<RM_PROCESS_INFO> = Vec::new();
+
+        // The list can grow between the two calls, so ask until it fits
+        loop {
+            count = processes.len() as u32;
+            let error = unsafe {
+                RmGetList(session, &mut needed, &mut count, processes.as_mut_ptr(), &mut reasons)
+            };
+            match error {
+                0 => break,
+                ERROR_MORE_DATA => {
+                    processes = vec![unsafe { std::mem::zeroed() }; needed as usize];
+                }
+                _ => return Err(trf("Failed to list processes using the file (error {})", &[&error])),
+            }
+        }
+
+        Ok(processes
+            .iter()
+            .take(count as usize)
+            .map(|process| {
+                let length = process.strAppName.iter().position(|c| *c == 0).unwrap_or(process.strAppName.len());
+                Holder {
+                    pid: process.Process.dwProcessId,
+                    name: String::from_utf16_lossy(&process.strAppName[..length]),
+                }
+            })
+            .collect())
+    })
+}
+
+#[cfg(not(windows))]
+pub fn holders(_path: &Path) -> Result<Vec<Holder>, String> {
+    Ok(Vec::new())
+}
+
+// Asks the processes holding the file to close, forcing them if they don't
+#[cfg(windows)]
+pub fn close_holders(path: &Path) -> Result<(), String> {
+    use windows_sys::Win32::System::RestartManager::{RmForceShutdown, RmShutdown};
+
+    with_session(path, |session| {
+        match unsafe { RmShutdown(session, RmForceShutdown as u32, None) } {
+            0 => Ok(()),
+            error => Err(trf("Failed to close the programs using the file (error {})", &[&error])),
+        }
+    })
+}
+
+#[cfg(not(windows))]
+pub fn close_holders(_path: &Path) -> Result<(), String> {
+    Err(tr("Closing programs is only supported on Windows").to_string())
+}
+
+#[cfg(windows)]
+fn delete_on_reboot(path: &Path) -> bool {
+    use windows_sys::Win32::Storage::FileSystem::{MOVEFILE_DELAY_UNTIL_REBOOT, MoveFileExW};
+
+    let wide = to_wide(path);
+    unsafe { MoveFileExW(wide.as_ptr(), std::ptr::null(), MOVEFILE_DELAY_UNTIL_REBOOT) != 0 }
+}
+
+#[cfg(not(windows))]
+fn delete_on_reboot(_path: &Path) -> bool {
+    false
+}
+
+// Moves the file aside so nothing loads it again and schedules its deletion.
+// Returns where it was moved to.
+pub fn schedule_delete(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name()
+        .ok_or_else(|| trf("Invalid path: {}", &[&path.display()]))?;
+    let pending = path.with_file_name(format!("{}.{}", file_name.to_string_lossy(), PENDING_DELETE_EXTENSION));
+
+    // Left over from an earlier attempt that never got cleaned up
+    let _ = std::fs::remove_file(&pending);
+    std::fs::rename(path, &pending)
+        .map_err(|e| trf("Failed to move {} aside: {}", &[&path.display(), &e]))?;
+
+    // Without admin rights this fails, clean_up_pending catches it on a later start
+    delete_on_reboot(&pending);
+    Ok(pending)
+}
+
+// Files moved aside by schedule_delete that are still there
+pub fn pending(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == PENDING_DELETE_EXTENSION))
+        .collect()
+}
+
+// Deletes whatever isn't held anymore, returns what's still pending
+pub fn clean_up_pending(dir: &Path) -> Vec<PathBuf> {
+    pending(dir)
+        .into_iter()
+        .filter(|path| std::fs::remove_file(path).is_err())
+        .collect()
+}
@@ -0,0 +1,103 @@
+// Follows the mod's runtime log while the game writes to it. Only the bytes
+// appended since the last poll are read, and the log is reread from the start
+// when it shrinks, which is what happens when the game starts a new session.
+
+use crate::i18n::trf;
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+// Older lines are dropped so a long session doesn't grow without bound
+const MAX_LINES: usize = 5000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warning,
+    Info,
+}
+
+pub fn level(line: &str) -> Level {
+    let upper = line.to_ascii_uppercase();
+    if upper.contains("ERROR") || upper.contains("FATAL") {
+        Level::Error
+    } else if upper.contains("WARN") {
+        Level::Warning
+    } else {
+        Level::Info
+    }
+}
+
+pub struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    // Text after the last newline, completed by the next poll
+    partial: String,
+    lines: VecDeque<String>,
+}
+
+impl LogTail {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            partial: String::new(),
+            lines: VecDeque::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    // Reads whatever was appended since the last call. Returns whether new
+    // lines arrived; a missing log just means the game hasn't written one yet.
+    pub fn poll(&mut self) -> Result<bool, String> {
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(trf("Failed to open log: {}", &[&e])),
+        };
+        let len = file.metadata()
+            .map_err(|e| trf("Failed to read log: {}", &[&e]))?
+            .len();
+
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+            self.lines.clear();
+        }
+        if len == self.offset {
+            return Ok(false);
+        }
+
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(self.offset))
+            .and_then(|_| file.take(len - self.offset).read_to_end(&mut bytes))
+            .map_err(|e| trf("Failed to read log: {}", &[&e]))?;
+        self.offset = len;
+
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+        let Some(end) = self.partial.rfind('\n') else {
+            return Ok(false);
+        };
+
+        let complete: String = self.partial.drain(..=end).collect();
+        for line in complete.lines() {
+            self.lines.push_back(line.to_string());
+        }
+        while self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,102 @@
+// State shared between the Windows accounts on one PC. Config, restore points,
+// backups and caches stay in each user's own profile; the only thing shared
+// is a record per user in ProgramData of where the game is and whether that
+// user last installed or uninstalled the mod. ProgramData lets every user
+// create files but not change other users' files, so instead of one shared
+// file each user writes their own and the newest record wins.
+
+use crate::i18n::{tr, trf};
+use crate::timestamp;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const SHARED_DIR_NAME: &str = "ThatNoobSkyApp";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InstallRecord {
+    pub user: String,
+    pub game_path: PathBuf,
+    pub mod_installed: bool,
+    pub installed_version: Option<String>,
+    // timestamp::now() when the record was written
+    pub timestamp: String,
+}
+
+impl InstallRecord {
+    pub fn is_other_user(&self) -> bool {
+        self.user != current_user()
+    }
+}
+
+pub fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+// %ProgramData%\ThatNoobSkyApp, only on Windows
+pub fn shared_root() -> Option<PathBuf> {
+    std::env::var_os("ProgramData").map(|dir| PathBuf::from(dir).join(SHARED_DIR_NAME))
+}
+
+pub fn shared_dir() -> Option<PathBuf> {
+    shared_root().map(|dir| dir.join("users"))
+}
+
+fn record_path(user: &str) -> Option<PathBuf> {
+    // User names can't contain path separators on Windows, but be safe
+    let name: String = user
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    shared_dir().map(|dir| dir.join(format!("{}.json", name)))
+}
+
+// Best effort: a missing or locked-down ProgramData only means other users
+// don't get to see this install
+pub fn record(game_path: &Path, mod_installed: bool, installed_version: Option<String>) -> Result<(), String> {
+    let user = current_user();
+    let path = record_path(&user).ok_or(tr("Shared data directory not found"))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create shared data directory: {}", &[&e]))?;
+    }
+
+    let record = InstallRecord {
+        user,
+        game_path: game_path.to_path_buf(),
+        mod_installed,
+        installed_version,
+        timestamp: timestamp::now(),
+    };
+    let contents = serde_json::to_string_pretty(&record)
+        .map_err(|e| trf("Failed to serialize install record: {}", &[&e]))?;
+
+    std::fs::write(&path, contents)
+        .map_err(|e| trf("Failed to write install record: {}", &[&e]))
+}
+
+// The most recent install or uninstall by any user on this PC
+pub fn latest() -> Option<InstallRecord> {
+    let entries = std::fs::read_dir(shared_dir()?).ok()?;
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<InstallRecord>(&contents).ok())
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
+}
+
+// Whether this account can change files in the game folder. An install made
+// by an administrator account usually can't be touched by a standard one.
+pub fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".tnsm-write-test-{}", std::process::id()));
+    match std::fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,171 @@
+// What the installer last put into a game folder, to check it's all still
+// there: the mod DLL's hash recorded when it was installed, and every file in
+// TNSM Resources from the import index. Antivirus quietly deleting the DLL
+// is the usual finding.
+//
+//   <app dir>/installed/<game folder key>.json
+
+use crate::cancel::{self, CancellationToken};
+use crate::i18n::{tr, trf};
+use crate::import_index::ImportIndex;
+use crate::resources::{self, RESOURCES_DIR_NAME};
+use crate::install::{self, ProxyDll};
+use crate::{config, verify, workers};
+use jwalk::WalkDir;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize)]
+struct InstalledDll {
+    sha256: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Problem {
+    Missing,
+    // Not put there by an install or import
+    Extra,
+    Corrupted,
+}
+
+impl Problem {
+    pub fn label(self) -> &'static str {
+        match self {
+            Problem::Missing => "missing",
+            Problem::Extra => "extra",
+            Problem::Corrupted => "corrupted or changed",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileProblem {
+    // Relative to the game folder
+    pub relative: PathBuf,
+    pub problem: Problem,
+}
+
+#[derive(Default, Debug)]
+pub struct Verification {
+    pub checked: usize,
+    pub problems: Vec<FileProblem>,
+    // Nothing to compare against, e.g. installed by an older installer
+    pub unknown_dll: bool,
+    pub unknown_resources: bool,
+}
+
+fn record_path(game_path: &Path) -> Option<PathBuf> {
+    let key = verify::sha256_hex(game_path.to_string_lossy().as_bytes());
+    config::app_dir().map(|dir| dir.join("installed").join(format!("{}.json", &key[..16])))
+}
+
+pub fn record_dll(game_path: &Path, sha256: &str) -> Result<(), String> {
+    let path = record_path(game_path).ok_or(tr("Config directory not found"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create config directory: {}", &[&e]))?;
+    }
+
+    let contents = serde_json::to_string(&InstalledDll { sha256: sha256.to_string() })
+        .map_err(|e| trf("Failed to serialize install record: {}", &[&e]))?;
+    std::fs::write(path, contents)
+        .map_err(|e| trf("Failed to write install record: {}", &[&e]))
+}
+
+pub fn forget_dll(game_path: &Path) {
+    if let Some(path) = record_path(game_path) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+// Takes the DLL as it is now as installed, after a change that didn't go
+// through an install, like reverting one
+pub fn refresh_dll(game_path: &Path, proxy: ProxyDll) {
+    let recorded = verify::sha256_file(&install::dll_path(game_path, proxy))
+        .and_then(|sha256| record_dll(game_path, &sha256));
+    if recorded.is_err() {
+        forget_dll(game_path);
+    }
+}
+
+fn recorded_dll(game_path: &Path) -> Option<InstalledDll> {
+    let contents = std::fs::read_to_string(record_path(game_path)?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn recorded_dll_sha256(game_path: &Path) -> Option<String> {
+    recorded_dll(game_path).map(|dll| dll.sha256)
+}
+
+fn check_file(path: &Path, sha256: &str) -> Option<Problem> {
+    if !path.exists() {
+        return Some(Problem::Missing);
+    }
+    match verify::sha256_file(path) {
+        Ok(actual) if actual == sha256 => None,
+        _ => Some(Problem::Corrupted),
+    }
+}
+
+// Whether the DLL is still the one installed. Antivirus quarantines a fresh
+// DLL within seconds of it being written, so this runs shortly after
+// installing and again at startup.
+pub fn check_dll(game_path: &Path, proxy: ProxyDll) -> Option<Problem> {
+    let dll = recorded_dll(game_path)?;
+    check_file(&install::dll_path(game_path, proxy), &dll.sha256)
+}
+
+pub async fn verify(game_path: PathBuf, proxy: ProxyDll, cancel: CancellationToken) -> Result<Verification, String> {
+    workers::run(move || check_files(&game_path, proxy, &cancel)).await?
+}
+
+fn check_files(game_path: &Path, proxy: ProxyDll, cancel: &CancellationToken) -> Result<Verification, String> {
+    let mut verification = Verification::default();
+
+    match recorded_dll(game_path) {
+        Some(dll) => {
+            verification.checked += 1;
+            if let Some(problem) = check_file(&install::dll_path(game_path, proxy), &dll.sha256) {
+                verification.problems.push(FileProblem { relative: PathBuf::from(proxy.file_name()), problem });
+            }
+        }
+        None => verification.unknown_dll = install::is_mod_installed(game_path, proxy),
+    }
+
+    let root = resources::resources_path(game_path);
+    let index = ImportIndex::load(&root);
+    if index.is_empty() {
+        verification.unknown_resources = root.exists();
+        return Ok(verification);
+    }
+
+    let mut expected = HashSet::new();
+    for (relative, entry) in index.entries() {
+        cancel::checkpoint(cancel)?;
+
+        verification.checked += 1;
+        expected.insert(relative.clone());
+        if let Some(problem) = check_file(&root.join(relative), &entry.sha256) {
+            verification.problems.push(FileProblem { relative: Path::new(RESOURCES_DIR_NAME).join(relative), problem });
+        }
+    }
+
+    for entry in WalkDir::new(&root).skip_hidden(false) {
+        cancel::checkpoint(cancel)?;
+
+        let entry = entry.map_err(|e| trf("Failed to read {}: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let path = entry.path();
+        if let Ok(relative) = path.strip_prefix(&root)
+            && !expected.contains(relative)
+        {
+            verification.problems.push(FileProblem { relative: Path::new(RESOURCES_DIR_NAME).join(relative), problem: Problem::Extra });
+        }
+    }
+
+    verification.problems.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(verification)
+}
@@ -0,0 +1,194 @@
+// Maintainer tooling: publishes release assets to a mirror in the layout the
+// installer expects:
+//
+//   <endpoint>/latest.json(.sig)
+//   <endpoint>/<version>/index.json(.sig)
+//   <endpoint>/<version>/checksums.sha256
+//   <endpoint>/<version>/<asset>(.sig)
+//
+// Uploads are WebDAV: MKCOL for the version folder, then a PUT per file with
+// basic auth. S3 needs signed requests, so upload to a bucket with its own
+// tools. Installs fall back to the mirrors in Config::download_mirrors when
+// GitHub downloads fail.
+
+use crate::i18n::{tr, trf};
+use crate::verify;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const INDEX_NAME: &str = "index.json";
+pub const LATEST_NAME: &str = "latest.json";
+pub const CHECKSUMS_NAME: &str = "checksums.sha256";
+
+// Where a published release asset is downloaded from
+pub fn asset_url(endpoint: &str, version: &str, name: &str) -> String {
+    format!("{}/{}/{}", endpoint.trim_end_matches('/'), version, name)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MirrorIndex {
+    pub version: String,
+    pub assets: Vec<MirrorAsset>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MirrorAsset {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+pub struct PublishOptions {
+    pub version: String,
+    pub assets: Vec<PathBuf>,
+    pub endpoint: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub signing_key: Option<PathBuf>,
+}
+
+fn load_signing_key(path: &Path) -> Result<SigningKey, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| trf("Failed to read signing key: {}", &[&e]))?;
+    let seed: [u8; 32] = hex::decode(contents.trim())
+        .map_err(|e| trf("Signing key is not valid hex: {}", &[&e]))?
+        .try_into()
+        .map_err(|_| tr("Signing key must be 32 bytes").to_string())?;
+
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+// The detached signature published next to an asset as "<asset>.sig"
+fn sign(signing_key: &SigningKey, bytes: &[u8]) -> Vec<u8> {
+    hex::encode(signing_key.sign(bytes).to_bytes()).into_bytes()
+}
+
+// Writes "<asset>.sig" next to each asset, for the ones attached to the
+// GitHub release. Bundles can only be created from a signed DLL.
+pub fn sign_assets(assets: &[PathBuf], signing_key: &Path) -> Result<Vec<PathBuf>, String> {
+    let signing_key = load_signing_key(signing_key)?;
+
+    assets.iter()
+        .map(|path| {
+            let bytes = std::fs::read(path)
+                .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+            let mut signature_path = path.clone().into_os_string();
+            signature_path.push(".sig");
+            let signature_path = PathBuf::from(signature_path);
+
+            std::fs::write(&signature_path, sign(&signing_key, &bytes))
+                .map_err(|e| trf("Failed to write {}: {}", &[&signature_path.display(), &e]))?;
+            Ok(signature_path)
+        })
+        .collect()
+}
+
+struct Uploader<'a> {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+    username: Option<String>,
+    password: Option<String>,
+    on_progress: &'a dyn Fn(&str),
+}
+
+impl Uploader<'_> {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::blocking::RequestBuilder {
+        let url = format!("{}/{}", self.endpoint.trim_end_matches('/'), path);
+        let request = self.client.request(method, url);
+
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        // An existing collection answers 405 Method Not Allowed
+        let mkcol = reqwest::Method::from_bytes(b"MKCOL").unwrap();
+        let response = self.request(mkcol, &format!("{}/", path))
+            .send()
+            .map_err(|e| trf("Failed to create {}: {}", &[&path, &e]))?;
+
+        if response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(());
+        }
+        response.error_for_status()
+            .map(|_| ())
+            .map_err(|e| trf("Failed to create {}: {}", &[&path, &e]))
+    }
+
+    fn put(&self, path: &str, bytes: Vec<u8>) -> Result<(), String> {
+        (self.on_progress)(&format!("Uploading {}", path));
+
+        self.request(reqwest::Method::PUT, path)
+            .body(bytes)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| trf("Failed to upload {}: {}", &[&path, &e]))?;
+
+        Ok(())
+    }
+}
+
+// `on_progress` is told about each upload as it starts
+pub fn publish(options: PublishOptions, on_progress: &dyn Fn(&str)) -> Result<(), String> {
+    let signing_key = options.signing_key.as_deref().map(load_signing_key).transpose()?;
+
+    let uploader = Uploader {
+        client: reqwest::blocking::Client::new(),
+        endpoint: options.endpoint,
+        username: options.username,
+        password: options.password,
+        on_progress,
+    };
+    uploader.make_dir(&options.version)?;
+
+    let mut index = MirrorIndex {
+        version: options.version.clone(),
+        assets: Vec::new(),
+    };
+    let mut checksums = String::new();
+
+    for path in &options.assets {
+        let name = path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| trf("Invalid asset path: {}", &[&path.display()]))?;
+        let bytes = std::fs::read(path)
+            .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+        let sha256 = verify::sha256_hex(&bytes);
+
+        checksums.push_str(&format!("{}  {}\n", sha256, name));
+        index.assets.push(MirrorAsset {
+            name: name.clone(),
+            size: bytes.len() as u64,
+            sha256,
+        });
+
+        if let Some(signing_key) = &signing_key {
+            uploader.put(&format!("{}/{}.sig", options.version, name), sign(signing_key, &bytes))?;
+        }
+        uploader.put(&format!("{}/{}", options.version, name), bytes)?;
+    }
+
+    uploader.put(&format!("{}/{}", options.version, CHECKSUMS_NAME), checksums.into_bytes())?;
+
+    let index_json = serde_json::to_vec_pretty(&index)
+        .map_err(|e| trf("Failed to serialize mirror index: {}", &[&e]))?;
+
+    // The versioned index goes up before latest.json so clients never see a
+    // "latest" pointing at a version that isn't fully uploaded yet
+    for path in [format!("{}/{}", options.version, INDEX_NAME), LATEST_NAME.to_string()] {
+        if let Some(signing_key) = &signing_key {
+            uploader.put(&format!("{}.sig", path), sign(signing_key, &index_json))?;
+        }
+        uploader.put(&path, index_json.clone())?;
+    }
+
+    if let Some(signing_key) = &signing_key {
+        on_progress(&trf("Signed with public key {}", &[&hex::encode(signing_key.verifying_key().to_bytes())]));
+    }
+    on_progress(&trf("Published {} to mirror", &[&options.version]));
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+// The mod's config file next to its DLL. What's in it belongs to the mod, the
+// installer only backs it up (see config_backups.rs) and puts it in
+// diagnostics, and never reads or writes its settings.
+
+use std::path::{Path, PathBuf};
+
+pub const MOD_CONFIG_NAME: &str = "TNSM.json";
+
+pub fn config_path(game_path: &Path) -> PathBuf {
+    game_path.join(MOD_CONFIG_NAME)
+}
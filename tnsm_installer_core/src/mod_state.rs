@@ -0,0 +1,41 @@
+// What the main panel shows about the mod in a game folder. Checking it reads
+// the DLL, the plugins and the resources folder, so it runs on the worker
+// pool and the frontend keeps the last result until the next one arrives.
+
+use crate::conflicts::{self, Conflict};
+use crate::install::{self, ProxyDll};
+use crate::machine::{self, InstallRecord};
+use crate::manifest::{self, Problem};
+use crate::plugins::Plugin;
+use crate::{detect, locked_file, resource_link, workers};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct ModState {
+    pub game_path: PathBuf,
+    pub is_mod_installed: bool,
+    pub game_path_writable: bool,
+    // The last install on this PC, if it was into this game folder
+    pub install_record: Option<InstallRecord>,
+    // Files still waiting to be removed, see locked_file::clean_up_pending
+    pub pending_removal: Vec<PathBuf>,
+    pub game_build_id: Option<u64>,
+    pub dll_problem: Option<Problem>,
+    pub conflicts: Vec<Conflict>,
+    pub resources_link: Option<PathBuf>,
+}
+
+pub async fn check(game_path: PathBuf, proxy: ProxyDll, plugins: Vec<Plugin>) -> Result<ModState, String> {
+    workers::run(move || ModState {
+        is_mod_installed: install::is_mod_installed(&game_path, proxy),
+        game_path_writable: machine::is_writable(&game_path),
+        install_record: machine::latest().filter(|record| record.game_path == game_path),
+        pending_removal: locked_file::clean_up_pending(&game_path),
+        game_build_id: detect::game_build_id(&game_path),
+        dll_problem: manifest::check_dll(&game_path, proxy),
+        conflicts: conflicts::scan(&game_path, proxy, &plugins),
+        resources_link: resource_link::target(&game_path),
+        game_path,
+    })
+    .await
+}
@@ -0,0 +1,196 @@
+// Nightly builds for testers: the DLLs CI uploads as GitHub Actions artifacts
+// on every successful run. Downloading artifacts needs a GitHub token, even
+// for a public repo. The artifact zip goes through the same download and
+// digest check as a release, then the DLL is taken out of it.
+
+use crate::arch::Arch;
+use crate::cancel::{self, CancellationToken};
+use crate::download::{self, DownloadedDll, ResolvedDownload};
+use crate::i18n::{tr, trf};
+use crate::{github, verify, workers};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Installed versions starting with this are nightlies
+pub const VERSION_PREFIX: &str = "nightly-";
+
+const MAX_RUNS: u32 = 20;
+const MAX_ARTIFACTS: u32 = 50;
+
+pub fn is_nightly(version: &str) -> bool {
+    version.starts_with(VERSION_PREFIX)
+}
+
+#[derive(Clone, Debug)]
+pub struct NightlyBuild {
+    pub run_number: u64,
+    pub branch: String,
+    pub commit: String,
+    // ISO 8601, as GitHub reports it
+    pub created_at: String,
+    pub artifact_name: String,
+    pub size: u64,
+    pub archive_url: String,
+    pub digest: Option<String>,
+}
+
+impl NightlyBuild {
+    pub fn version(&self) -> String {
+        format!("{}{}-{}", VERSION_PREFIX, self.run_number, self.short_commit())
+    }
+
+    pub fn short_commit(&self) -> &str {
+        self.commit.get(..7).unwrap_or(&self.commit)
+    }
+
+    pub fn label(&self) -> String {
+        format!(
+            "#{} {} ({}) · {} · {}",
+            self.run_number,
+            self.branch,
+            self.short_commit(),
+            self.created_at.replace('T', " ").trim_end_matches('Z'),
+            self.artifact_name
+        )
+    }
+
+    pub fn resolved(&self, token: &str) -> ResolvedDownload {
+        ResolvedDownload {
+            url: self.archive_url.clone(),
+            version: Some(self.version()),
+            digest: self.digest.clone(),
+            checksum_url: None,
+            signature_url: None,
+            token: Some(token.to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Runs {
+    workflow_runs: Vec<Run>,
+}
+
+#[derive(Deserialize)]
+struct Run {
+    id: u64,
+    run_number: u64,
+    #[serde(default)]
+    head_branch: Option<String>,
+    head_sha: String,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct Artifacts {
+    artifacts: Vec<Artifact>,
+}
+
+#[derive(Deserialize)]
+struct Artifact {
+    name: String,
+    size_in_bytes: u64,
+    archive_download_url: String,
+    expired: bool,
+    // Only present on artifacts uploaded after GitHub started computing digests
+    digest: Option<String>,
+    workflow_run: Option<ArtifactRun>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactRun {
+    id: u64,
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str, token: &str, cancel: &CancellationToken) -> Result<T, String> {
+    let response = github::get_authorized(&github::client()?, url, token, cancel, &|_| {})
+        .await
+        .map_err(|e| {
+            if cancel::is_cancelled(&e) {
+                e
+            } else if e.contains("401 Unauthorized") {
+                tr("GitHub didn't accept the token, check that it hasn't expired").to_string()
+            } else {
+                trf("Nightly build check failed: {}", &[&e])
+            }
+        })?;
+
+    response.json::<T>()
+        .await
+        .map_err(|e| trf("Failed to parse nightly build info: {}", &[&e]))
+}
+
+// Artifacts of recent successful runs that haven't expired yet, newest first
+pub async fn list(token: &str, cancel: &CancellationToken) -> Result<Vec<NightlyBuild>, String> {
+    let runs: Runs = get_json(
+        &format!("https://api.github.com/repos/{}/actions/runs?status=success&per_page={}", github::REPO, MAX_RUNS),
+        token,
+        cancel,
+    ).await?;
+    let artifacts: Artifacts = get_json(
+        &format!("https://api.github.com/repos/{}/actions/artifacts?per_page={}", github::REPO, MAX_ARTIFACTS),
+        token,
+        cancel,
+    ).await?;
+
+    let mut by_run: HashMap<u64, Vec<Artifact>> = HashMap::new();
+    for artifact in artifacts.artifacts.into_iter().filter(|artifact| !artifact.expired) {
+        if let Some(run) = &artifact.workflow_run {
+            by_run.entry(run.id).or_default().push(artifact);
+        }
+    }
+
+    Ok(runs.workflow_runs
+        .into_iter()
+        .flat_map(|run| {
+            by_run.remove(&run.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |artifact| NightlyBuild {
+                    run_number: run.run_number,
+                    branch: run.head_branch.clone().unwrap_or_default(),
+                    commit: run.head_sha.clone(),
+                    created_at: run.created_at.clone(),
+                    artifact_name: artifact.name,
+                    size: artifact.size_in_bytes,
+                    archive_url: artifact.archive_download_url,
+                    digest: artifact.digest,
+                })
+        })
+        .collect())
+}
+
+// Takes the DLL for `arch` out of a downloaded artifact zip
+pub async fn extract_dll(artifact: DownloadedDll, arch: Arch) -> Result<DownloadedDll, String> {
+    workers::run(move || extract_from(&artifact, arch)).await?
+}
+
+fn extract_from(artifact: &DownloadedDll, arch: Arch) -> Result<DownloadedDll, String> {
+    let file = std::fs::File::open(&artifact.path)
+        .map_err(|e| trf("Failed to open nightly build: {}", &[&e]))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| trf("Not a valid nightly build: {}", &[&e]))?;
+
+    let name = arch.dll_asset_name();
+    let index = (0..archive.len())
+        .find(|index| {
+            archive.name_for_index(*index)
+                .is_some_and(|entry| entry.rsplit('/').next() == Some(name))
+        })
+        .ok_or_else(|| trf("This nightly build doesn't contain {}", &[&name]))?;
+    let mut entry = archive.by_index(index)
+        .map_err(|e| trf("Failed to read nightly build: {}", &[&e]))?;
+
+    let mut dll = DownloadedDll {
+        path: download::temp_download_path(),
+        sha256: String::new(),
+        version: artifact.version.clone(),
+    };
+    let mut out = std::fs::File::create(&dll.path)
+        .map_err(|e| trf("Failed to create temp file: {}", &[&e]))?;
+    std::io::copy(&mut entry, &mut out)
+        .map_err(|e| trf("Failed to extract {}: {}", &[&name, &e]))?;
+
+    dll.sha256 = verify::sha256_file(&dll.path)?;
+    Ok(dll)
+}
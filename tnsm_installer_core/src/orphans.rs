@@ -0,0 +1,174 @@
+// Files in the game folder that belong neither to the game nor to the
+// installer: proxy DLLs of other or older mods, leftovers of installs that
+// didn't finish, and anything else put there by hand. Only the first two are
+// selected for cleanup by default, and cleaning up moves the files into a
+// restore point, so removing something the game list doesn't know about yet
+// can be undone.
+
+use crate::cancel::{self, CancellationToken};
+use crate::game_files::{self, GameFiles};
+use crate::i18n::{tr, trf};
+use crate::locked_file::PENDING_DELETE_EXTENSION;
+use crate::restore_point::RestorePoint;
+use crate::install::ProxyDll;
+use crate::plugins::Plugin;
+use crate::{audit, install, mod_settings, plugins, profiles, resources, workers};
+use jwalk::WalkDir;
+use std::path::{Path, PathBuf};
+
+// File names the game loads DLLs from that mod loaders commonly hijack
+pub const PROXY_DLL_NAMES: &[&str] = &[
+    "d3d11.dll",
+    "d3d12.dll",
+    "dinput8.dll",
+    "dsound.dll",
+    "dxgi.dll",
+    "version.dll",
+    "winhttp.dll",
+    "winmm.dll",
+    "xinput1_3.dll",
+    "xinput1_4.dll",
+    // Old manual installs dropped the DLL in under its own name
+    "tnsm.dll",
+];
+
+// Left behind by an installer that was interrupted
+const LEFTOVER_PREFIXES: &[&str] = &[".tnsm-write-test-"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrphanKind {
+    OldMod,
+    FailedInstall,
+    Unknown,
+}
+
+impl OrphanKind {
+    pub fn describe(self) -> &'static str {
+        match self {
+            OrphanKind::OldMod => "Another or older mod",
+            OrphanKind::FailedInstall => "Left by an interrupted install",
+            OrphanKind::Unknown => "Not part of the game",
+        }
+    }
+
+    pub fn selected_by_default(self) -> bool {
+        self != OrphanKind::Unknown
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Orphan {
+    // Relative to the game folder
+    pub relative: PathBuf,
+    pub size: u64,
+    pub kind: OrphanKind,
+}
+
+// Everything the installer itself puts into the game folder
+fn is_installer_file(game_path: &Path, relative: &Path, dll: &Path) -> bool {
+    let path = game_path.join(relative);
+
+    relative.starts_with(resources::RESOURCES_DIR_NAME)
+        || relative.starts_with(profiles::PROFILES_DIR_NAME)
+        // Still held by the game, deleted on the next restart
+        || path.extension().is_some_and(|extension| extension == PENDING_DELETE_EXTENSION)
+        || path == dll
+        || path == mod_settings::config_path(game_path)
+}
+
+fn classify(game_path: &Path, relative: &Path, dll: &Path, game_files: &GameFiles) -> Option<OrphanKind> {
+    if is_installer_file(game_path, relative, dll) || game_files.lists_exactly(relative) {
+        return None;
+    }
+
+    let name = relative.file_name()?.to_string_lossy().to_lowercase();
+    let top_level = relative.parent().is_none_or(|parent| parent.as_os_str().is_empty());
+
+    if LEFTOVER_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+        return Some(OrphanKind::FailedInstall);
+    }
+    // Only DLLs next to Sky.exe get loaded in place of the system ones
+    if top_level && PROXY_DLL_NAMES.contains(&name.as_str()) {
+        return Some(OrphanKind::OldMod);
+    }
+    if game_files.contains(relative) {
+        return None;
+    }
+
+    Some(OrphanKind::Unknown)
+}
+
+pub async fn scan(
+    game_path: PathBuf,
+    proxy: ProxyDll,
+    known_plugins: Vec<Plugin>,
+    game_files: GameFiles,
+    cancel: CancellationToken,
+) -> Result<Vec<Orphan>, String> {
+    workers::run(move || scan_folder(&game_path, proxy, &known_plugins, &game_files, &cancel)).await?
+}
+
+fn scan_folder(
+    game_path: &Path,
+    proxy: ProxyDll,
+    known_plugins: &[Plugin],
+    game_files: &GameFiles,
+    cancel: &CancellationToken,
+) -> Result<Vec<Orphan>, String> {
+    let mut orphans = Vec::new();
+    let plugin_files = plugins::installed_files(game_path, known_plugins);
+    let dll = install::dll_path(game_path, proxy);
+
+    for entry in WalkDir::new(game_path).skip_hidden(false) {
+        cancel::checkpoint(cancel)?;
+
+        let entry = entry.map_err(|e| trf("Failed to read game folder: {}", &[&e]))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        let Ok(relative) = path.strip_prefix(game_path) else {
+            continue;
+        };
+        // Companion mods often use proxy DLL names too
+        if plugin_files.contains(&path) {
+            continue;
+        }
+        if let Some(kind) = classify(game_path, relative, &dll, game_files) {
+            orphans.push(Orphan {
+                relative: relative.to_path_buf(),
+                size: entry.metadata().map_or(0, |metadata| metadata.len()),
+                kind,
+            });
+        }
+    }
+
+    orphans.sort_by_key(|orphan| game_files::normalize(&orphan.relative));
+    Ok(orphans)
+}
+
+// Returns how many files were removed and the restore point they were saved to
+pub async fn clean_up(game_path: PathBuf, orphans: Vec<Orphan>, cancel: CancellationToken) -> Result<(usize, Option<PathBuf>), String> {
+    workers::run(move || {
+        let audit = audit::begin("clean up leftover files", Some(&game_path)).detail(format!("{} files", orphans.len()));
+        let result = remove(&game_path, &orphans, &cancel);
+        audit.finish(&result);
+        result
+    }).await?
+}
+
+fn remove(game_path: &Path, orphans: &[Orphan], cancel: &CancellationToken) -> Result<(usize, Option<PathBuf>), String> {
+    let restore_point = RestorePoint::begin(game_path, tr("Before cleaning up leftover files").to_string())?;
+    for orphan in orphans {
+        cancel::checkpoint(cancel)?;
+        restore_point.preserve(&orphan.relative)?;
+    }
+    let restore_point = restore_point.finish()?;
+
+    for orphan in orphans {
+        std::fs::remove_file(game_path.join(&orphan.relative))
+            .map_err(|e| trf("Failed to remove {}: {}", &[&orphan.relative.display(), &e]))?;
+    }
+    Ok((orphans.len(), restore_point))
+}
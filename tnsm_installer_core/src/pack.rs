@@ -0,0 +1,181 @@
+// `.tnsmpack` files are zipped resource packs, and packs shared as plain
+// `.zip` files or links to one are handled the same way. They're unpacked into the app
+// directory and imported like a picked folder, so the import journal can
+// resume from the unpacked copy if it gets interrupted.
+
+use crate::cancel::{self, CancellationToken};
+use crate::i18n::{tr, trf};
+use crate::{config, disk_space};
+use crate::download::{self, ResolvedDownload};
+use crate::events::TransferProgress;
+use crate::workers;
+use crate::resources::RESOURCES_DIR_NAME;
+use std::path::{Path, PathBuf};
+
+pub const PACK_EXTENSION: &str = "tnsmpack";
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(PACK_EXTENSION) || extension.eq_ignore_ascii_case("zip"))
+}
+
+pub fn extract_root() -> Result<PathBuf, String> {
+    config::app_dir()
+        .map(|dir| dir.join("packs"))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+// Returns the pack's root folder
+pub async fn extract(path: PathBuf, cancel: CancellationToken) -> Result<PathBuf, String> {
+    let name = path.file_stem()
+        .ok_or_else(|| trf("Invalid pack path: {}", &[&path.display()]))?
+        .to_string_lossy()
+        .to_string();
+    workers::run(move || extract_as(&path, &name, &cancel)).await?
+}
+
+fn extract_as(path: &Path, name: &str, cancel: &CancellationToken) -> Result<PathBuf, String> {
+    let dest = extract_root()?.join(name);
+
+    // Start clean so files from an older copy of the pack don't get imported
+    if dest.exists() {
+        std::fs::remove_dir_all(&dest)
+            .map_err(|e| trf("Failed to clear old pack files: {}", &[&e]))?;
+    }
+
+    let file = std::fs::File::open(path)
+        .map_err(|e| trf("Failed to open pack: {}", &[&e]))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| trf("Not a valid resource pack: {}", &[&e]))?;
+    check_contents(&archive)?;
+    let size = archive.decompressed_size().unwrap_or(0).try_into().unwrap_or(u64::MAX);
+    disk_space::ensure_free(&dest, size, tr("the unpacked pack"))?;
+
+    for index in 0..archive.len() {
+        cancel::checkpoint(cancel)?;
+
+        let mut entry = archive.by_index(index)
+            .map_err(|e| trf("Failed to read pack entry: {}", &[&e]))?;
+        // Reject absolute paths and ".." so a pack can't write outside its folder
+        let relative = entry.enclosed_name()
+            .ok_or_else(|| trf("Unsafe path in pack: {}", &[&entry.name()]))?;
+        let out_path = dest.join(relative);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&out_path)
+                .map_err(|e| trf("Failed to create directory: {}", &[&e]))?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| trf("Failed to create directory: {}", &[&e]))?;
+        }
+        let mut out = std::fs::File::create(&out_path)
+            .map_err(|e| trf("Failed to create {}: {}", &[&out_path.display(), &e]))?;
+        std::io::copy(&mut entry, &mut out)
+            .map_err(|e| trf("Failed to extract {}: {}", &[&out_path.display(), &e]))?;
+    }
+
+    Ok(pack_root(dest))
+}
+
+// "https://example.com/packs/Cozy%20Pack.zip?dl=1" is "Cozy Pack"
+fn name_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let file = path.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+    let file = file.replace("%20", " ");
+    let stem = Path::new(&file).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let name: String = stem.chars()
+        .filter(|c| !matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' | '%') && !c.is_control())
+        .collect();
+    let name = name.trim().trim_matches('.').to_string();
+    if name.is_empty() {
+        tr("Downloaded pack").to_string()
+    } else {
+        name
+    }
+}
+
+// Downloads a pack shared as a link and unpacks it like a picked file.
+// Returns the pack's root folder.
+pub async fn download(
+    url: &str,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<PathBuf, String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(tr("Enter a link starting with https://").to_string());
+    }
+
+    let resolved = ResolvedDownload {
+        url: url.to_string(),
+        version: None,
+        digest: None,
+        checksum_url: None,
+        signature_url: None,
+        token: None,
+    };
+    // Removed again once it's unpacked, when this goes out of scope
+    let archive = download::fetch(&resolved, &[], cancel, &|_| {}, on_progress).await?;
+
+    let name = name_from_url(url);
+    let token = cancel.clone();
+    workers::run(move || extract_as(&archive.path, &name, &token)).await.and_then(|result| result)
+}
+
+// Checked before anything is unpacked. Resources are plain files, so an
+// archive with programs in it is the mod itself, an offline bundle or
+// something else entirely.
+fn check_contents(archive: &zip::ZipArchive<std::fs::File>) -> Result<(), String> {
+    let mut files = archive.file_names().filter(|name| !name.ends_with('/')).peekable();
+    if files.peek().is_none() {
+        return Err(tr("The resource pack is empty").to_string());
+    }
+
+    let program = files.find(|name| {
+        let name = name.to_ascii_lowercase();
+        name.ends_with(".dll") || name.ends_with(".exe")
+    });
+    match program {
+        Some(name) => Err(trf("This isn't a resource pack, it contains {}", &[&name])),
+        None => Ok(()),
+    }
+}
+
+fn single_dir(dir: &Path) -> Option<PathBuf> {
+    let entries: Vec<_> = std::fs::read_dir(dir).ok()?.filter_map(Result::ok).collect();
+
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Some(entry.path()),
+        _ => None,
+    }
+}
+
+// Packs zipped with their folder have a single directory at the top. Zips
+// of someone's whole resources folder have it once more, and would
+// otherwise end up in "TNSM Resources/TNSM Resources".
+fn pack_root(dest: PathBuf) -> PathBuf {
+    let Some(root) = single_dir(&dest) else {
+        return dest;
+    };
+
+    match single_dir(&root) {
+        Some(inner) if inner.file_name().is_some_and(|name| name == RESOURCES_DIR_NAME) => inner,
+        _ => root,
+    }
+}
+
+// Removes an unpacked pack once imported. Folders picked by the user are left alone.
+pub fn remove_extracted(source: &Path) {
+    let Ok(root) = extract_root() else {
+        return;
+    };
+
+    if let Ok(relative) = source.strip_prefix(&root)
+        && let Some(name) = relative.components().next()
+    {
+        let _ = std::fs::remove_dir_all(root.join(name));
+    }
+}
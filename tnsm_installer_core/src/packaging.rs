@@ -0,0 +1,200 @@
+// Maintainer tooling: writes winget and Scoop manifests for a release of the
+// installer app itself, so package manager listings can be updated straight
+// from the release assets:
+//
+//   <out>/winget/manifests/a/alvindimas05/ThatNoobSkyApp/<version>/*.yaml
+//   <out>/scoop/thatnoobskyapp.json
+//
+// MSI assets become winget installers, the portable exe backs the Scoop
+// manifest (and winget when no MSI was uploaded).
+
+use crate::arch::Arch;
+use crate::cancel::CancellationToken;
+use crate::github::{self, Asset, Release};
+use crate::i18n::{tr, trf};
+use crate::verify;
+use std::path::{Path, PathBuf};
+
+pub const APP_ASSET_NAME: &str = "ThatNoobSkyMod.exe";
+
+const PACKAGE_IDENTIFIER: &str = "alvindimas05.ThatNoobSkyApp";
+const PACKAGE_NAME: &str = "ThatNoobSkyApp";
+const PUBLISHER: &str = "alvindimas05";
+const SHORT_DESCRIPTION: &str = "Installer for ThatNoobSkyMod";
+const WINGET_MANIFEST_VERSION: &str = "1.6.0";
+const SCOOP_APP_NAME: &str = "thatnoobskyapp";
+
+struct PackageAsset {
+    url: String,
+    sha256: String,
+    arch: Arch,
+}
+
+async fn sha256_of(asset: &Asset, cancel: &CancellationToken) -> Result<String, String> {
+    if let Some(hex) = asset.digest.as_deref().and_then(|digest| digest.strip_prefix("sha256:")) {
+        return Ok(hex.to_ascii_lowercase());
+    }
+
+    // Assets uploaded before GitHub computed digests have to be hashed here
+    let response = github::get_with_retry(&github::client()?, &asset.browser_download_url, cancel, &|_| {}).await?;
+    let bytes = response.bytes()
+        .await
+        .map_err(|e| trf("Failed to download {}: {}", &[&asset.name, &e]))?;
+
+    Ok(verify::sha256_hex(&bytes))
+}
+
+fn asset_arch(name: &str) -> Arch {
+    let name = name.to_ascii_lowercase();
+    if name.contains("aarch64") || name.contains("arm64") {
+        Arch::Arm64
+    } else {
+        Arch::X64
+    }
+}
+
+fn winget_arch(arch: Arch) -> &'static str {
+    match arch {
+        Arch::X64 => "x64",
+        Arch::Arm64 => "arm64",
+    }
+}
+
+async fn collect(
+    release: &Release,
+    matches: impl Fn(&Asset) -> bool,
+    cancel: &CancellationToken,
+) -> Result<Vec<PackageAsset>, String> {
+    let mut assets = Vec::new();
+
+    for asset in release.assets.iter().filter(|asset| matches(asset)) {
+        assets.push(PackageAsset {
+            url: asset.browser_download_url.clone(),
+            sha256: sha256_of(asset, cancel).await?,
+            arch: asset_arch(&asset.name),
+        });
+    }
+
+    Ok(assets)
+}
+
+fn winget_manifests(version: &str, installer_type: &str, installers: &[PackageAsset]) -> Vec<(String, String)> {
+    let header = format!(
+        "PackageIdentifier: {}\nPackageVersion: {}\n",
+        PACKAGE_IDENTIFIER, version
+    );
+    let footer = |kind: &str| format!("ManifestType: {}\nManifestVersion: {}\n", kind, WINGET_MANIFEST_VERSION);
+
+    let version_manifest = format!("{}DefaultLocale: en-US\n{}", header, footer("version"));
+
+    let mut installer_manifest = format!("{}InstallerType: {}\n", header, installer_type);
+    if installer_type == "portable" {
+        installer_manifest.push_str(&format!("Commands:\n- {}\n", SCOOP_APP_NAME));
+    }
+    installer_manifest.push_str("Installers:\n");
+    for installer in installers {
+        installer_manifest.push_str(&format!(
+            "- Architecture: {}\n  InstallerUrl: {}\n  InstallerSha256: {}\n",
+            winget_arch(installer.arch),
+            installer.url,
+            installer.sha256.to_ascii_uppercase()
+        ));
+    }
+    installer_manifest.push_str(&footer("installer"));
+
+    let locale_manifest = format!(
+        "{}PackageLocale: en-US\nPublisher: {}\nPackageName: {}\nPackageUrl: https://github.com/{}\nLicense: Freeware\nShortDescription: {}\n{}",
+        header, PUBLISHER, PACKAGE_NAME, github::REPO, SHORT_DESCRIPTION, footer("defaultLocale")
+    );
+
+    vec![
+        (format!("{}.yaml", PACKAGE_IDENTIFIER), version_manifest),
+        (format!("{}.installer.yaml", PACKAGE_IDENTIFIER), installer_manifest),
+        (format!("{}.locale.en-US.yaml", PACKAGE_IDENTIFIER), locale_manifest),
+    ]
+}
+
+fn scoop_manifest(version: &str, exe: &PackageAsset) -> Result<String, String> {
+    let exe_name = format!("{}.exe", PACKAGE_NAME);
+    let download_url = format!(
+        "https://github.com/{}/releases/download/v$version/{}#/{}",
+        github::REPO, APP_ASSET_NAME, exe_name
+    );
+
+    let manifest = serde_json::json!({
+        "version": version,
+        "description": SHORT_DESCRIPTION,
+        "homepage": format!("https://github.com/{}", github::REPO),
+        "license": "Freeware",
+        "architecture": {
+            "64bit": {
+                "url": format!("{}#/{}", exe.url, exe_name),
+                "hash": exe.sha256,
+            }
+        },
+        "bin": exe_name,
+        "shortcuts": [[exe_name, PACKAGE_NAME]],
+        "checkver": "github",
+        "autoupdate": {
+            "architecture": {
+                "64bit": { "url": download_url }
+            }
+        }
+    });
+
+    serde_json::to_string_pretty(&manifest)
+        .map_err(|e| trf("Failed to serialize Scoop manifest: {}", &[&e]))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| trf("Failed to create {}: {}", &[&parent.display(), &e]))?;
+    }
+
+    std::fs::write(path, contents)
+        .map_err(|e| trf("Failed to write {}: {}", &[&path.display(), &e]))
+}
+
+// Writes the manifests for app release `tag` (or the newest one) and returns their paths
+pub async fn write_manifests(tag: Option<&str>, out_dir: &Path, cancel: &CancellationToken) -> Result<Vec<PathBuf>, String> {
+    let release = match tag {
+        Some(tag) => github::fetch_release_by_tag(tag, cancel, &|_| {}).await?,
+        None => github::fetch_app_releases(cancel, &|_| {})
+            .await?
+            .into_iter()
+            .find(|release| !release.prerelease)
+            .ok_or_else(|| tr("No installer releases found").to_string())?,
+    };
+    let version = release.app_version()
+        .ok_or_else(|| trf("{} is not an installer release", &[&release.tag_name]))?;
+
+    let msis = collect(&release, |asset| asset.name.ends_with(".msi"), cancel).await?;
+    let exe = collect(&release, |asset| asset.name == APP_ASSET_NAME, cancel).await?
+        .pop()
+        .ok_or_else(|| trf("Release {} has no {} asset", &[&release.tag_name, &APP_ASSET_NAME]))?;
+
+    let mut written = Vec::new();
+
+    let winget_dir = out_dir
+        .join("winget/manifests")
+        .join(&PACKAGE_IDENTIFIER[..1])
+        .join(PACKAGE_IDENTIFIER.replace('.', "/"))
+        .join(version);
+    let winget = if msis.is_empty() {
+        winget_manifests(version, "portable", std::slice::from_ref(&exe))
+    } else {
+        winget_manifests(version, "msi", &msis)
+    };
+    for (name, contents) in winget {
+        let path = winget_dir.join(name);
+        write(&path, &contents)?;
+        written.push(path);
+    }
+
+    let scoop_path = out_dir.join("scoop").join(format!("{}.json", SCOOP_APP_NAME));
+    write(&scoop_path, &scoop_manifest(version, &exe)?)?;
+    written.push(scoop_path);
+
+    Ok(written)
+}
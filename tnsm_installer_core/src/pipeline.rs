@@ -0,0 +1,667 @@
+// Installation as an ordered list of steps sharing one InstallContext. The
+// standard pipeline is resolve -> download -> verify -> arch-check ->
+// restore-point -> backup -> write -> post-check -> cache; extra steps (signature checks, compat gates, notifications)
+// slot in by name without touching the others. Switching to a cached version
+// replaces the first three steps with load-cached, a DLL file picked by the
+// user with load-local, and a nightly build resolves an Actions artifact and
+// extracts the DLL from it after verifying.
+
+use crate::arch::{self, Arch};
+use crate::cancel::{self, CancellationToken};
+use crate::download::{self, DownloadedDll, ResolvedDownload};
+use crate::events::TransferProgress;
+use crate::i18n::{tr, trf};
+use crate::install::{self, InstallSettings, WriteError};
+use crate::journal::{Journal, Operation};
+use crate::restore_point::RestorePoint;
+use crate::nightly::NightlyBuild;
+use crate::{disk_space, elevated, manifest, nightly, verify, version_cache, workers};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+pub type StepFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+pub trait InstallStep: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Shown while the step runs, e.g. "Downloading"
+    fn description(&self) -> &'static str;
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a>;
+}
+
+// State handed from step to step. Steps fill in the Option fields as they go
+pub struct InstallContext<'a> {
+    pub dll_url: String,
+    pub game_path: PathBuf,
+    pub settings: InstallSettings,
+    pub cancel: &'a CancellationToken,
+    pub on_rate_limited: &'a (dyn Fn(u64) + Sync),
+    pub on_progress: &'a (dyn Fn(TransferProgress) + Sync),
+    pub arch: Arch,
+    pub resolved: Option<ResolvedDownload>,
+    pub dll: Option<DownloadedDll>,
+    pub journal: Option<Journal>,
+}
+
+impl<'a> InstallContext<'a> {
+    pub fn new(
+        dll_url: &str,
+        game_path: PathBuf,
+        settings: InstallSettings,
+        cancel: &'a CancellationToken,
+        on_rate_limited: &'a (dyn Fn(u64) + Sync),
+        on_progress: &'a (dyn Fn(TransferProgress) + Sync),
+    ) -> Self {
+        Self {
+            dll_url: dll_url.to_string(),
+            settings,
+            cancel,
+            on_rate_limited,
+            on_progress,
+            arch: arch::game_arch(&game_path),
+            game_path,
+            resolved: None,
+            dll: None,
+            journal: None,
+        }
+    }
+
+    pub fn resolved(&self) -> Result<&ResolvedDownload, String> {
+        self.resolved.as_ref().ok_or_else(|| missing("resolve"))
+    }
+
+    pub fn dll(&self) -> Result<&DownloadedDll, String> {
+        self.dll.as_ref().ok_or_else(|| missing("download"))
+    }
+
+    // The release tag of the installed DLL, when it could be resolved
+    pub fn version(&self) -> Option<String> {
+        self.dll.as_ref().and_then(|dll| dll.version.clone())
+    }
+}
+
+fn missing(step: &str) -> String {
+    trf("Install pipeline is missing the {} step", &[&step])
+}
+
+type BeforeHook = Box<dyn Fn(&dyn InstallStep) + Send + Sync>;
+type AfterHook = Box<dyn Fn(&dyn InstallStep, &Result<(), String>) + Send + Sync>;
+
+pub struct Pipeline {
+    steps: Vec<Box<dyn InstallStep>>,
+    before_step: Vec<BeforeHook>,
+    after_step: Vec<AfterHook>,
+}
+
+impl Pipeline {
+    pub fn empty() -> Self {
+        Self {
+            steps: Vec::new(),
+            before_step: Vec::new(),
+            after_step: Vec::new(),
+        }
+    }
+
+    pub fn standard() -> Self {
+        Self::empty()
+            .push(Resolve)
+            .push(Download)
+            .push(Verify)
+            .push(ArchCheck)
+            .push(Snapshot)
+            .push(Backup)
+            .push(Write)
+            .push(PostCheck)
+            .push(CacheVersion)
+    }
+
+    pub fn cached(version: &str) -> Self {
+        Self::empty()
+            .push(LoadCached { version: version.to_string() })
+            .push(ArchCheck)
+            .push(Snapshot)
+            .push(Backup)
+            .push(Write)
+            .push(PostCheck)
+    }
+
+    pub fn local(path: &Path) -> Self {
+        Self::empty()
+            .push(LoadLocal { path: path.to_path_buf() })
+            .push(ArchCheck)
+            .push(Snapshot)
+            .push(Backup)
+            .push(Write)
+            .push(PostCheck)
+    }
+
+    pub fn nightly(build: &NightlyBuild, token: &str) -> Self {
+        Self::empty()
+            .push(ResolveNightly { build: build.clone(), token: token.to_string() })
+            .push(Download)
+            .push(Verify)
+            .push(ExtractArtifact)
+            .push(ArchCheck)
+            .push(Snapshot)
+            .push(Backup)
+            .push(Write)
+            .push(PostCheck)
+    }
+
+    pub fn push(mut self, step: impl InstallStep + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    pub fn insert_before(self, name: &str, step: impl InstallStep + 'static) -> Result<Self, String> {
+        self.insert_at(name, 0, step)
+    }
+
+    pub fn insert_after(self, name: &str, step: impl InstallStep + 'static) -> Result<Self, String> {
+        self.insert_at(name, 1, step)
+    }
+
+    fn insert_at(mut self, name: &str, offset: usize, step: impl InstallStep + 'static) -> Result<Self, String> {
+        let index = self.steps.iter()
+            .position(|existing| existing.name() == name)
+            .ok_or_else(|| trf("No install step named {}", &[&name]))?;
+
+        self.steps.insert(index + offset, Box::new(step));
+        Ok(self)
+    }
+
+    pub fn before_step(mut self, hook: impl Fn(&dyn InstallStep) + Send + Sync + 'static) -> Self {
+        self.before_step.push(Box::new(hook));
+        self
+    }
+
+    pub fn after_step(mut self, hook: impl Fn(&dyn InstallStep, &Result<(), String>) + Send + Sync + 'static) -> Self {
+        self.after_step.push(Box::new(hook));
+        self
+    }
+
+    pub async fn run(&self, ctx: &mut InstallContext<'_>) -> Result<(), String> {
+        let result = self.run_steps(ctx).await;
+
+        // A failed or cancelled install leaves the game directory the way it
+        // found it. If that fails too the journal stays, and the rollback is
+        // offered again on the next start.
+        if result.is_err()
+            && let Some(journal) = ctx.journal.take()
+            && let Err(e) = journal.roll_back()
+        {
+            tracing::warn!("Rolling back the failed install failed: {}", e);
+        }
+        result
+    }
+
+    async fn run_steps(&self, ctx: &mut InstallContext<'_>) -> Result<(), String> {
+        for step in &self.steps {
+            cancel::checkpoint(ctx.cancel)?;
+
+            for hook in &self.before_step {
+                hook(step.as_ref());
+            }
+
+            let result = step.run(ctx).await;
+
+            for hook in &self.after_step {
+                hook(step.as_ref(), &result);
+            }
+
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Resolve;
+
+impl InstallStep for Resolve {
+    fn name(&self) -> &'static str {
+        "resolve"
+    }
+
+    fn description(&self) -> &'static str {
+        "Looking up the latest release"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            ctx.resolved = Some(download::resolve(&ctx.dll_url, ctx.arch, ctx.cancel, ctx.on_rate_limited).await?);
+            Ok(())
+        })
+    }
+}
+
+pub struct Download;
+
+impl InstallStep for Download {
+    fn name(&self) -> &'static str {
+        "download"
+    }
+
+    fn description(&self) -> &'static str {
+        "Downloading"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let dll = download::fetch(ctx.resolved()?, &ctx.settings.mirrors, ctx.cancel, ctx.on_rate_limited, ctx.on_progress).await?;
+            ctx.dll = Some(dll);
+            Ok(())
+        })
+    }
+}
+
+pub struct Verify;
+
+impl InstallStep for Verify {
+    fn name(&self) -> &'static str {
+        "verify"
+    }
+
+    fn description(&self) -> &'static str {
+        "Verifying download"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let checksum = download::fetch_checksum(ctx.resolved()?, ctx.cancel, ctx.on_rate_limited).await?;
+            ctx.resolved()?.verify(ctx.dll()?, checksum.as_deref())
+        })
+    }
+}
+
+pub struct ArchCheck;
+
+impl InstallStep for ArchCheck {
+    fn name(&self) -> &'static str {
+        "arch-check"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checking DLL architecture"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let dll_arch = arch::pe_arch(&ctx.dll()?.path)?;
+
+            if dll_arch != ctx.arch {
+                return Err(trf(
+                    "The downloaded DLL is built for {}, but Sky is {}",
+                    &[&dll_arch.name(), &ctx.arch.name()]
+                ));
+            }
+            Ok(())
+        })
+    }
+}
+
+pub struct Snapshot;
+
+impl InstallStep for Snapshot {
+    fn name(&self) -> &'static str {
+        "restore-point"
+    }
+
+    fn description(&self) -> &'static str {
+        "Saving restore point"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let game_path = ctx.game_path.clone();
+            let dll_name = ctx.settings.proxy_dll.file_name();
+            let description = match ctx.version() {
+                Some(version) => trf("Before installing {}", &[&version]),
+                None => tr("Before installing the mod").to_string(),
+            };
+
+            workers::run(move || {
+                let point = RestorePoint::begin(&game_path, description)?;
+                point.preserve(Path::new(dll_name))?;
+                point.finish().map(|_| ())
+            }).await?
+        })
+    }
+}
+
+pub struct Backup;
+
+impl InstallStep for Backup {
+    fn name(&self) -> &'static str {
+        "backup"
+    }
+
+    fn description(&self) -> &'static str {
+        "Backing up current DLL"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let target = install::dll_path(&ctx.game_path, ctx.settings.proxy_dll);
+            let journal = Journal::open()?;
+
+            // Keep the current DLL around until the new one is fully in place
+            let backup = if target.exists() {
+                let backup = journal.backup_path();
+                tokio::fs::copy(&target, &backup)
+                    .await
+                    .map_err(|e| trf("Failed to back up current DLL: {}", &[&e]))?;
+                Some(backup)
+            } else {
+                None
+            };
+
+            // From here on the game directory is touched, so journal it
+            journal.start(Operation::Install {
+                game_path: ctx.game_path.clone(),
+                dll_url: ctx.dll_url.clone(),
+                proxy_dll: ctx.settings.proxy_dll,
+                backup,
+            })?;
+            ctx.journal = Some(journal);
+            Ok(())
+        })
+    }
+}
+
+pub struct Write;
+
+impl InstallStep for Write {
+    fn name(&self) -> &'static str {
+        "write"
+    }
+
+    fn description(&self) -> &'static str {
+        "Installing"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let source = &ctx.dll()?.path;
+            let size = tokio::fs::metadata(source).await.map_or(0, |metadata| metadata.len());
+            disk_space::ensure_free(&ctx.game_path, size, tr("the mod DLL"))?;
+            let proxy = ctx.settings.proxy_dll;
+            let result = match install::copy_dll(&ctx.game_path, proxy, source).await {
+                Ok(()) => Ok(()),
+                // Only an administrator can change this folder, ask for approval for just this file
+                Err(WriteError::AccessDenied { .. }) if elevated::AVAILABLE => {
+                    elevated::write_file(&ctx.game_path, source, Path::new(proxy.file_name())).await
+                }
+                Err(e) => Err(e.into()),
+            };
+
+            // The DLL is renamed into place, so a failed write left the old
+            // one as it was and there's nothing to roll back
+            if result.is_err()
+                && let Some(journal) = ctx.journal.take()
+            {
+                journal.finish();
+            }
+            result
+        })
+    }
+}
+
+pub struct PostCheck;
+
+impl InstallStep for PostCheck {
+    fn name(&self) -> &'static str {
+        "post-check"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checking installed DLL"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let target = install::dll_path(&ctx.game_path, ctx.settings.proxy_dll);
+            let installed = workers::run(move || verify::sha256_file(&target)).await??;
+
+            if installed != ctx.dll()?.sha256 {
+                if let Some(journal) = ctx.journal.take() {
+                    journal.roll_back()?;
+                }
+                return Err(tr("Installed DLL doesn't match the download").to_string());
+            }
+
+            if let Some(journal) = ctx.journal.take() {
+                journal.finish();
+            }
+            // Only needed to verify the install later
+            let _ = manifest::record_dll(&ctx.game_path, &installed);
+            Ok(())
+        })
+    }
+}
+
+pub struct CacheVersion;
+
+impl InstallStep for CacheVersion {
+    fn name(&self) -> &'static str {
+        "cache"
+    }
+
+    fn description(&self) -> &'static str {
+        "Keeping a copy for quick switching"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let dll = ctx.dll()?;
+            let Some(version) = dll.version.clone() else {
+                return Ok(());
+            };
+            let path = dll.path.clone();
+            let sha256 = dll.sha256.clone();
+
+            // The mod is installed either way, a missing cache entry only means downloading it again
+            let _ = workers::run(move || version_cache::store(&path, &version, &sha256)).await;
+            Ok(())
+        })
+    }
+}
+
+pub struct LoadCached {
+    pub version: String,
+}
+
+impl InstallStep for LoadCached {
+    fn name(&self) -> &'static str {
+        "load-cached"
+    }
+
+    fn description(&self) -> &'static str {
+        "Loading cached version"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            ctx.dll = Some(version_cache::load(&self.version).await?);
+            Ok(())
+        })
+    }
+}
+
+// A DLL from disk, e.g. a local build dropped onto the window. Its version
+// isn't known, so there's nothing to check compatibility or cache it by.
+pub struct LoadLocal {
+    pub path: PathBuf,
+}
+
+impl InstallStep for LoadLocal {
+    fn name(&self) -> &'static str {
+        "load-local"
+    }
+
+    fn description(&self) -> &'static str {
+        "Loading the DLL"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let path = self.path.clone();
+            ctx.dll = Some(workers::run(move || download::copy_local(&path, None)).await??);
+            Ok(())
+        })
+    }
+}
+
+pub struct ResolveNightly {
+    pub build: NightlyBuild,
+    pub token: String,
+}
+
+impl InstallStep for ResolveNightly {
+    fn name(&self) -> &'static str {
+        "resolve"
+    }
+
+    fn description(&self) -> &'static str {
+        "Looking up the nightly build"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            ctx.resolved = Some(self.build.resolved(&self.token));
+            Ok(())
+        })
+    }
+}
+
+// Swaps the downloaded artifact zip for the DLL inside it
+pub struct ExtractArtifact;
+
+impl InstallStep for ExtractArtifact {
+    fn name(&self) -> &'static str {
+        "extract"
+    }
+
+    fn description(&self) -> &'static str {
+        "Extracting the nightly build"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+        Box::pin(async move {
+            let artifact = ctx.dll.take().ok_or_else(|| missing("download"))?;
+            ctx.dll = Some(nightly::extract_dll(artifact, ctx.arch).await?);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for the download and backup steps, journaling into `journals`
+    struct Prepared {
+        dll: PathBuf,
+        journals: PathBuf,
+    }
+
+    impl InstallStep for Prepared {
+        fn name(&self) -> &'static str {
+            "prepared"
+        }
+
+        fn description(&self) -> &'static str {
+            "Preparing"
+        }
+
+        fn run<'a>(&'a self, ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+            Box::pin(async move {
+                ctx.dll = Some(DownloadedDll {
+                    path: self.dll.clone(),
+                    sha256: verify::sha256_file(&self.dll)?,
+                    version: None,
+                });
+                let journal = Journal::open_in(self.journals.clone())?;
+                journal.start(Operation::Install {
+                    game_path: ctx.game_path.clone(),
+                    dll_url: String::new(),
+                    proxy_dll: ctx.settings.proxy_dll,
+                    backup: None,
+                })?;
+                ctx.journal = Some(journal);
+                Ok(())
+            })
+        }
+    }
+
+    struct Fail;
+
+    impl InstallStep for Fail {
+        fn name(&self) -> &'static str {
+            "fail"
+        }
+
+        fn description(&self) -> &'static str {
+            "Failing"
+        }
+
+        fn run<'a>(&'a self, _ctx: &'a mut InstallContext<'_>) -> StepFuture<'a> {
+            Box::pin(async { Err("Network error".to_string()) })
+        }
+    }
+
+    fn journal_files(journals: &Path) -> usize {
+        std::fs::read_dir(journals).map_or(0, |entries| entries.count())
+    }
+
+    async fn run(pipeline: Pipeline, game_path: PathBuf) -> Result<(), String> {
+        let cancel = CancellationToken::new();
+        let mut ctx = InstallContext::new("", game_path, InstallSettings::default(), &cancel, &|_| {}, &|_| {});
+        pipeline.run(&mut ctx).await
+    }
+
+    #[tokio::test]
+    async fn failed_write_leaves_no_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let dll = dir.path().join("download.dll");
+        std::fs::write(&dll, "dll").unwrap();
+        // A file where the game folder should be, so the DLL can't be written
+        let game_path = dir.path().join("game");
+        std::fs::write(&game_path, "").unwrap();
+        let journals = dir.path().join("journals");
+
+        let pipeline = Pipeline::empty()
+            .push(Prepared { dll, journals: journals.clone() })
+            .push(Write);
+        assert!(run(pipeline, game_path).await.is_err());
+        assert_eq!(journal_files(&journals), 0);
+    }
+
+    #[tokio::test]
+    async fn failure_after_the_write_rolls_it_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let dll = dir.path().join("download.dll");
+        std::fs::write(&dll, "dll").unwrap();
+        let game_path = dir.path().join("game");
+        std::fs::create_dir(&game_path).unwrap();
+        let journals = dir.path().join("journals");
+
+        let pipeline = Pipeline::empty()
+            .push(Prepared { dll, journals: journals.clone() })
+            .push(Write)
+            .push(Fail);
+        assert_eq!(run(pipeline, game_path.clone()).await, Err("Network error".to_string()));
+        assert!(!install::dll_path(&game_path, Default::default()).exists());
+        assert_eq!(journal_files(&journals), 0);
+    }
+
+    #[test]
+    fn steps_slot_in_by_name() {
+        let pipeline = Pipeline::standard().insert_after("write", Fail).unwrap();
+        let names: Vec<_> = pipeline.steps.iter().map(|step| step.name()).collect();
+        assert_eq!(names[names.iter().position(|name| *name == "write").unwrap() + 1], "fail");
+        assert!(Pipeline::standard().insert_before("missing", Fail).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+// Companion DLL mods managed alongside TNSM. Each one is downloaded from its
+// own URL into the game folder under the file name Sky loads it by. Disabling
+// one renames it to <file name>.disabled so the game skips it but the
+// download is kept.
+
+use crate::cancel::CancellationToken;
+use crate::download::{self, ResolvedDownload};
+use crate::events::TransferProgress;
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::{audit, game_process};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const DISABLED_EXTENSION: &str = "disabled";
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Plugin {
+    pub name: String,
+    pub url: String,
+    // Relative to the game folder, e.g. version.dll
+    pub file_name: String,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PluginState {
+    NotInstalled,
+    Enabled,
+    Disabled,
+}
+
+impl Plugin {
+    fn enabled_path(&self, game_path: &Path) -> PathBuf {
+        game_path.join(&self.file_name)
+    }
+
+    fn disabled_path(&self, game_path: &Path) -> PathBuf {
+        game_path.join(format!("{}.{}", self.file_name, DISABLED_EXTENSION))
+    }
+
+    fn path(&self, game_path: &Path, enabled: bool) -> PathBuf {
+        if enabled {
+            self.enabled_path(game_path)
+        } else {
+            self.disabled_path(game_path)
+        }
+    }
+
+    pub fn state(&self, game_path: &Path) -> PluginState {
+        if self.enabled_path(game_path).is_file() {
+            PluginState::Enabled
+        } else if self.disabled_path(game_path).is_file() {
+            PluginState::Disabled
+        } else {
+            PluginState::NotInstalled
+        }
+    }
+}
+
+// Files the plugins occupy in the game folder, enabled or not
+pub fn installed_files(game_path: &Path, plugins: &[Plugin]) -> Vec<PathBuf> {
+    plugins.iter()
+        .flat_map(|plugin| [plugin.enabled_path(game_path), plugin.disabled_path(game_path)])
+        .collect()
+}
+
+// `others` are the plugins already in the list, `proxy` the mod's own DLL
+pub fn check(plugin: &Plugin, others: &[Plugin], proxy: ProxyDll) -> Result<(), String> {
+    if plugin.name.trim().is_empty() {
+        return Err(tr("Enter a name for the plugin").to_string());
+    }
+    if !plugin.url.starts_with("https://") && !plugin.url.starts_with("http://") {
+        return Err(tr("The plugin URL must start with https://").to_string());
+    }
+
+    let file_name = plugin.file_name.to_lowercase();
+    if !file_name.ends_with(".dll") || file_name.contains(['/', '\\', ':']) {
+        return Err(tr("The file name must be a DLL name like version.dll").to_string());
+    }
+    let dll_name = proxy.file_name();
+    if file_name == dll_name {
+        return Err(trf("{} is where TNSM is installed", &[&dll_name]));
+    }
+    if others.iter().any(|other| other.file_name.eq_ignore_ascii_case(&plugin.file_name)) {
+        return Err(trf("Another plugin is already installed as {}", &[&plugin.file_name]));
+    }
+    Ok(())
+}
+
+// Downloads the plugin again, which is also how it's updated
+pub async fn install(
+    game_path: &Path,
+    plugin: &Plugin,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<(), String> {
+    let audit = audit::begin("install plugin", Some(game_path)).detail(plugin.name.clone());
+    let result = download_into(game_path, plugin, cancel, on_progress).await;
+    audit.finish(&result);
+    result
+}
+
+async fn download_into(
+    game_path: &Path,
+    plugin: &Plugin,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<(), String> {
+    game_process::ensure_not_running()?;
+
+    let resolved = ResolvedDownload {
+        url: plugin.url.clone(),
+        version: None,
+        digest: None,
+        checksum_url: None,
+        signature_url: None,
+        token: None,
+    };
+    let download = download::fetch(&resolved, &[], cancel, &|_| {}, on_progress).await?;
+
+    // Copied next to the target first so a failed copy never leaves half a DLL
+    let dest = plugin.path(game_path, plugin.enabled);
+    let partial = game_path.join(format!("{}.partial", plugin.file_name));
+    std::fs::copy(&download.path, &partial)
+        .map_err(|e| trf("Failed to copy {}: {}", &[&plugin.file_name, &e]))?;
+    if let Err(e) = std::fs::rename(&partial, &dest) {
+        let _ = std::fs::remove_file(&partial);
+        return Err(trf("Failed to install {}: {}", &[&plugin.file_name, &e]));
+    }
+
+    let _ = std::fs::remove_file(plugin.path(game_path, !plugin.enabled));
+    Ok(())
+}
+
+pub fn set_enabled(game_path: &Path, plugin: &Plugin, enabled: bool) -> Result<(), String> {
+    let from = plugin.path(game_path, !enabled);
+    if !from.exists() {
+        return Ok(());
+    }
+
+    game_process::ensure_not_running()?;
+    std::fs::rename(&from, plugin.path(game_path, enabled))
+        .map_err(|e| match enabled {
+            true => trf("Failed to enable {}: {}", &[&plugin.name, &e]),
+            false => trf("Failed to disable {}: {}", &[&plugin.name, &e]),
+        })
+}
+
+pub fn remove(game_path: &Path, plugin: &Plugin) -> Result<(), String> {
+    let audit = audit::begin("remove plugin", Some(game_path)).detail(plugin.name.clone());
+    let result = game_process::ensure_not_running().and_then(|_| {
+        for path in [plugin.enabled_path(game_path), plugin.disabled_path(game_path)] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(trf("Failed to remove {}: {}", &[&plugin.file_name, &e]));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    });
+    audit.finish(&result);
+    result
+}
@@ -0,0 +1,169 @@
+// Admin-provided policy for shared machines (community events, gaming cafés).
+// policy.toml lives in %ProgramData%\ThatNoobSkyApp, which standard accounts
+// can't change, or next to the installer for a portable setup:
+//
+//   message = "Managed by the Sky Café front desk"
+//   game_path = 'D:\Games\Sky Children of the Light'
+//   mod_version = "v1.2.0"
+//   disable = ["uninstall", "import", "update_check", "storage"]
+//
+// A policy that can't be read locks everything rather than silently
+// unlocking a machine the admin meant to lock down.
+
+use crate::i18n::trf;
+use crate::{download, machine};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const POLICY_FILE_NAME: &str = "policy.toml";
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    ChangeGamePath,
+    Uninstall,
+    Import,
+    Bundles,
+    RestorePoints,
+    GameDataBackups,
+    UpdateCheck,
+    ContextMenu,
+    Language,
+    ConfigBackups,
+    GameCaches,
+    Storage,
+    Gallery,
+    Log,
+    Nightly,
+    LeftoverFiles,
+    Plugins,
+    Verify,
+    AuditLog,
+    InstallerLog,
+    Downloads,
+    SettingsTransfer,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 22] = [
+        Feature::ChangeGamePath,
+        Feature::Uninstall,
+        Feature::Import,
+        Feature::Bundles,
+        Feature::RestorePoints,
+        Feature::GameDataBackups,
+        Feature::UpdateCheck,
+        Feature::ContextMenu,
+        Feature::Language,
+        Feature::ConfigBackups,
+        Feature::GameCaches,
+        Feature::Storage,
+        Feature::Gallery,
+        Feature::Log,
+        Feature::Nightly,
+        Feature::LeftoverFiles,
+        Feature::Plugins,
+        Feature::Verify,
+        Feature::AuditLog,
+        Feature::InstallerLog,
+        Feature::Downloads,
+        Feature::SettingsTransfer,
+    ];
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    // Shown at the top of the window
+    pub message: Option<String>,
+    pub game_path: Option<PathBuf>,
+    // Release tag to install instead of the latest release
+    pub mod_version: Option<String>,
+    // Any other DLL URL, e.g. an internal mirror; mod_version wins if both are set
+    pub dll_url: Option<String>,
+    pub disable: Vec<Feature>,
+}
+
+impl Policy {
+    fn locked_down(reason: String) -> Self {
+        Self {
+            message: Some(reason),
+            disable: Feature::ALL.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn allows(&self, feature: Feature) -> bool {
+        !self.disable.contains(&feature)
+    }
+
+    pub fn pinned_dll_url(&self) -> Option<String> {
+        self.mod_version.as_deref()
+            .map(download::release_dll_url)
+            .or_else(|| self.dll_url.clone())
+    }
+
+    // None when no policy is installed
+    pub fn load() -> Option<Self> {
+        let beside_exe = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(POLICY_FILE_NAME)));
+        let path = machine::shared_root()
+            .map(|dir| dir.join(POLICY_FILE_NAME))
+            .into_iter()
+            .chain(beside_exe)
+            .find(|path| path.is_file())?;
+
+        let policy = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()));
+
+        Some(policy.unwrap_or_else(|e| {
+            Self::locked_down(trf("❌ {} could not be read, everything is locked: {}", &[&path.display(), &e]))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_listed_features_are_disabled() {
+        let policy: Policy = toml::from_str(r#"disable = ["uninstall", "audit_log", "settings_transfer"]"#).unwrap();
+
+        assert!(!policy.allows(Feature::Uninstall));
+        assert!(!policy.allows(Feature::AuditLog));
+        assert!(!policy.allows(Feature::SettingsTransfer));
+        assert!(policy.allows(Feature::Import));
+        assert!(policy.allows(Feature::Verify));
+        assert!(Policy::default().allows(Feature::Uninstall));
+    }
+
+    #[test]
+    fn unknown_settings_are_refused() {
+        assert!(toml::from_str::<Policy>(r#"disable = ["everything"]"#).is_err());
+        assert!(toml::from_str::<Policy>(r#"game_folder = 'D:\Sky'"#).is_err());
+    }
+
+    #[test]
+    fn locked_down_disables_everything() {
+        let policy = Policy::locked_down("unreadable".to_string());
+
+        assert!(Feature::ALL.iter().all(|&feature| !policy.allows(feature)));
+        assert_eq!(policy.message.as_deref(), Some("unreadable"));
+    }
+
+    #[test]
+    fn mod_version_wins_over_dll_url() {
+        let policy: Policy = toml::from_str(r#"
+            mod_version = "v1.2.0"
+            dll_url = "https://mirror.example/version.dll"
+        "#).unwrap();
+        assert_eq!(policy.pinned_dll_url(), Some(download::release_dll_url("v1.2.0")));
+
+        let policy: Policy = toml::from_str(r#"dll_url = "https://mirror.example/version.dll""#).unwrap();
+        assert_eq!(policy.pinned_dll_url().as_deref(), Some("https://mirror.example/version.dll"));
+        assert_eq!(Policy::default().pinned_dll_url(), None);
+    }
+}
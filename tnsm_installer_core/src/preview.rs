@@ -0,0 +1,109 @@
+// Dry runs of install, import and uninstall: the files each would create,
+// overwrite or delete in the game folder, worked out without writing
+// anything. Backups and restore points go outside the game folder, so they
+// aren't listed.
+
+use crate::cancel::CancellationToken;
+use crate::i18n::trf;
+use crate::install::{self, ProxyDll};
+use crate::{resources, workers};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChangeKind {
+    Create,
+    Overwrite,
+    Delete,
+}
+
+impl ChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeKind::Create => "Create",
+            ChangeKind::Overwrite => "Overwrite",
+            ChangeKind::Delete => "Delete",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileChange {
+    // Relative to the game folder
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    // Of the file written, or of the one deleted. None when it's only known
+    // once the file is downloaded.
+    pub size: Option<u64>,
+}
+
+impl FileChange {
+    // Overwrite when `path` (relative to the game folder) is already there
+    pub fn write(game_path: &Path, path: PathBuf, size: Option<u64>) -> Self {
+        let kind = if game_path.join(&path).exists() { ChangeKind::Overwrite } else { ChangeKind::Create };
+        Self { path, kind, size }
+    }
+}
+
+// Installing writes the mod DLL and nothing else. `source` is a DLL on disk,
+// None for one that's downloaded.
+pub fn install(game_path: &Path, proxy: ProxyDll, source: Option<&Path>) -> Result<Vec<FileChange>, String> {
+    let size = match source {
+        Some(source) => Some(std::fs::metadata(source)
+            .map_err(|e| trf("Failed to read {}: {}", &[&source.display(), &e]))?
+            .len()),
+        None => None,
+    };
+    Ok(vec![FileChange::write(game_path, PathBuf::from(proxy.file_name()), size)])
+}
+
+// Files already imported from the same source are skipped by the import,
+// so they aren't listed
+pub async fn import(
+    source: PathBuf,
+    game_path: PathBuf,
+    remove_missing: bool,
+    cancel: CancellationToken,
+) -> Result<Vec<FileChange>, String> {
+    workers::run(move || resources::preview_import(&source, &game_path, remove_missing, &cancel)).await?
+}
+
+pub async fn uninstall(
+    game_path: PathBuf,
+    proxy: ProxyDll,
+    remove_resources: bool,
+    cancel: CancellationToken,
+) -> Result<Vec<FileChange>, String> {
+    workers::run(move || uninstall_changes(&game_path, proxy, remove_resources, &cancel)).await?
+}
+
+fn uninstall_changes(
+    game_path: &Path,
+    proxy: ProxyDll,
+    remove_resources: bool,
+    cancel: &CancellationToken,
+) -> Result<Vec<FileChange>, String> {
+    let mut changes = Vec::new();
+    let dll = install::dll_path(game_path, proxy);
+    if let Ok(metadata) = std::fs::metadata(&dll) {
+        changes.push(FileChange {
+            path: PathBuf::from(proxy.file_name()),
+            kind: ChangeKind::Delete,
+            size: Some(metadata.len()),
+        });
+    }
+    if remove_resources && resources::resources_path(game_path).exists() {
+        changes.extend(resources::preview_remove_all(game_path, cancel)?);
+    }
+    Ok(changes)
+}
+
+// Bytes written and bytes deleted. Sizes that aren't known yet count as 0.
+pub fn totals(changes: &[FileChange]) -> (u64, u64) {
+    changes.iter().fold((0, 0), |(written, deleted), change| {
+        let size = change.size.unwrap_or(0);
+        match change.kind {
+            ChangeKind::Delete => (written, deleted + size),
+            _ => (written + size, deleted),
+        }
+    })
+}
@@ -0,0 +1,227 @@
+// Named sets of resources, e.g. "HD pack" and "Minimal", of which one at a
+// time fills TNSM Resources. The others wait next to it in the game folder,
+// so switching is two renames on the same drive rather than copying whole
+// packs, and a failed switch is renamed straight back.
+//
+//   <game>/TNSM Profiles/<name>/...
+//   <game>/TNSM Profiles/<name>/.tnsm-index.json   its import index
+//   <game>/TNSM Profiles/active.txt   name of the one in TNSM Resources
+
+use crate::i18n::{tr, trf};
+use crate::resources::{self, RESOURCES_DIR_NAME};
+use crate::{audit, game_process, import_index, resource_link};
+use std::path::{Path, PathBuf};
+
+pub const PROFILES_DIR_NAME: &str = "TNSM Profiles";
+
+const ACTIVE_FILE: &str = "active.txt";
+const INDEX_FILE: &str = ".tnsm-index.json";
+
+#[derive(Default)]
+pub struct Profiles {
+    // Sorted by name, including the active one
+    pub names: Vec<String>,
+    pub active: Option<String>,
+}
+
+pub fn profiles_path(game_path: &Path) -> PathBuf {
+    game_path.join(PROFILES_DIR_NAME)
+}
+
+// None once TNSM Resources is gone, e.g. removed when uninstalling
+fn read_active(game_path: &Path) -> Option<String> {
+    if !resources::resources_path(game_path).exists() {
+        return None;
+    }
+    std::fs::read_to_string(profiles_path(game_path).join(ACTIVE_FILE))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn write_active(game_path: &Path, name: &str) -> Result<(), String> {
+    let dir = profiles_path(game_path);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| trf("Failed to create {}: {}", &[&PROFILES_DIR_NAME, &e]))?;
+    std::fs::write(dir.join(ACTIVE_FILE), name)
+        .map_err(|e| trf("Failed to save the active profile: {}", &[&e]))
+}
+
+pub fn list(game_path: &Path) -> Profiles {
+    let active = read_active(game_path);
+    let mut names: Vec<_> = std::fs::read_dir(profiles_path(game_path))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.extend(active.clone());
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup();
+
+    Profiles { names, active }
+}
+
+// Profile names become folder names next to the active profile file
+pub fn check_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(tr("Enter a profile name").to_string());
+    }
+    if name.starts_with('.')
+        || name.ends_with('.')
+        || name.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|'])
+        || name.chars().any(char::is_control)
+        || name.eq_ignore_ascii_case(ACTIVE_FILE)
+        || is_reserved(name)
+    {
+        return Err(trf("\"{}\" can't be used as a profile name", &[&name]));
+    }
+    Ok(name.to_string())
+}
+
+// Device names Windows won't create a folder for, with or without an extension
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end().to_ascii_uppercase();
+    matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ["COM", "LPT"].iter().any(|prefix| {
+            stem.strip_prefix(prefix)
+                .is_some_and(|digit| digit.len() == 1 && matches!(digit.as_bytes()[0], b'1'..=b'9'))
+        })
+}
+
+fn exists(game_path: &Path, name: &str) -> bool {
+    profiles_path(game_path).join(name).exists() || read_active(game_path).as_deref() == Some(name)
+}
+
+// Gives the resources currently in TNSM Resources a profile name, or renames
+// the active profile
+pub fn name_current(game_path: &Path, name: &str) -> Result<String, String> {
+    let name = check_name(name)?;
+    if exists(game_path, &name) {
+        return Err(trf("There's already a profile called \"{}\"", &[&name]));
+    }
+    write_active(game_path, &name)?;
+    Ok(name)
+}
+
+// A new empty profile, filled by switching to it and importing
+pub fn create(game_path: &Path, name: &str) -> Result<String, String> {
+    let name = check_name(name)?;
+    if exists(game_path, &name) {
+        return Err(trf("There's already a profile called \"{}\"", &[&name]));
+    }
+    std::fs::create_dir_all(profiles_path(game_path).join(&name))
+        .map_err(|e| trf("Failed to create profile: {}", &[&e]))?;
+    Ok(name)
+}
+
+pub fn switch(game_path: &Path, name: &str) -> Result<(), String> {
+    let audit = audit::begin("switch resource profile", Some(game_path)).detail(name.to_string());
+    let result = swap_in(game_path, name);
+    audit.finish(&result);
+    result
+}
+
+fn swap_in(game_path: &Path, name: &str) -> Result<(), String> {
+    // Sky keeps resource files open, which would stop the renames halfway
+    game_process::ensure_not_running()?;
+
+    if resource_link::is_linked(game_path) {
+        return Err(trf("{} is linked to a folder, unlink it before switching", &[&RESOURCES_DIR_NAME]));
+    }
+
+    let active = read_active(game_path);
+    if active.as_deref() == Some(name) {
+        return Ok(());
+    }
+
+    let incoming = profiles_path(game_path).join(name);
+    if !incoming.is_dir() {
+        return Err(trf("Profile \"{}\" not found", &[&name]));
+    }
+
+    let current = resources::resources_path(game_path);
+    let stored = match (current.exists(), active) {
+        (false, _) => None,
+        (true, Some(active)) => Some(profiles_path(game_path).join(active)),
+        (true, None) => {
+            return Err(trf("Save the resources in {} as a profile before switching", &[&RESOURCES_DIR_NAME]));
+        }
+    };
+
+    if let Some(stored) = &stored {
+        std::fs::rename(&current, stored)
+            .map_err(|e| trf("Failed to move {} aside: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+    }
+
+    if let Err(e) = std::fs::rename(&incoming, &current) {
+        if let Some(stored) = &stored {
+            let _ = std::fs::rename(stored, &current);
+        }
+        return Err(trf("Failed to move profile into {}: {}", &[&RESOURCES_DIR_NAME, &e]));
+    }
+
+    // The import index goes along, it's only needed to import and verify faster
+    if let Some(stored) = &stored {
+        let _ = import_index::stash(&current, &stored.join(INDEX_FILE));
+    }
+    let _ = import_index::unstash(&current.join(INDEX_FILE), &current);
+
+    write_active(game_path, name)
+}
+
+// Moves TNSM Resources into the folder of its profile, leaving its place
+// free, e.g. for a linked folder
+pub fn put_away(game_path: &Path) -> Result<(), String> {
+    let current = resources::resources_path(game_path);
+    if !current.exists() {
+        return Ok(());
+    }
+    let Some(active) = read_active(game_path) else {
+        return Err(trf("Save the resources in {} as a profile first", &[&RESOURCES_DIR_NAME]));
+    };
+
+    let stored = profiles_path(game_path).join(active);
+    std::fs::rename(&current, &stored)
+        .map_err(|e| trf("Failed to move {} aside: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+    let _ = import_index::stash(&current, &stored.join(INDEX_FILE));
+    let _ = std::fs::remove_file(profiles_path(game_path).join(ACTIVE_FILE));
+    Ok(())
+}
+
+// Only profiles not in use, the active one is removed with TNSM Resources
+pub fn delete(game_path: &Path, name: &str) -> Result<(), String> {
+    if read_active(game_path).as_deref() == Some(name) {
+        return Err(tr("Switch to another profile before deleting this one").to_string());
+    }
+
+    let audit = audit::begin("delete resource profile", Some(game_path)).detail(name.to_string());
+    let result = std::fs::remove_dir_all(profiles_path(game_path).join(name))
+        .map_err(|e| trf("Failed to delete profile: {}", &[&e]));
+    audit.finish(&result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_names_are_trimmed() {
+        assert_eq!(check_name("  Event skins ").unwrap(), "Event skins");
+        assert_eq!(check_name("v1.2 textures").unwrap(), "v1.2 textures");
+        assert_eq!(check_name("Console").unwrap(), "Console");
+        assert_eq!(check_name("COM10").unwrap(), "COM10");
+    }
+
+    #[test]
+    fn names_windows_or_the_profiles_folder_need_are_refused() {
+        for name in ["", "  ", ".hidden", "trailing.", "a/b", "a\\b", "C:", "active.txt", "Active.TXT",
+            "con", "NUL", "prn.txt", "aux", "COM1", "lpt9.zip", "tab\there"]
+        {
+            assert!(check_name(name).is_err(), "{:?} was accepted", name);
+        }
+    }
+}
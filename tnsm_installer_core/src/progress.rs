@@ -0,0 +1,151 @@
+// Turns raw TransferProgress updates into a stable fraction, rate and ETA.
+// Files are weighted with a fixed per-file cost on top of their size, since
+// creating and closing a file takes real time no matter how small it is.
+// Without that a pack of thousands of tiny files followed by one large one
+// sits near 0% for most of the import and then jumps.
+
+use crate::events::TransferProgress;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Roughly what a copy of this much data costs compared to opening a file
+const FILE_OVERHEAD_BYTES: u64 = 64 * 1024;
+
+// Rates are averaged over this window so one slow file doesn't swing the ETA
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+// Too little data in the window to give a useful ETA yet
+const MIN_RATE_SPAN: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, Default)]
+pub struct Estimate {
+    pub fraction: f32,
+    pub bytes_per_second: Option<f64>,
+    pub eta: Option<Duration>,
+}
+
+struct Sample {
+    at: Instant,
+    work: u64,
+    bytes: u64,
+}
+
+#[derive(Default)]
+pub struct Estimator {
+    samples: VecDeque<Sample>,
+    estimate: Estimate,
+}
+
+pub fn work_done(progress: &TransferProgress) -> u64 {
+    progress.bytes_done + progress.files_done * FILE_OVERHEAD_BYTES
+}
+
+pub fn work_total(progress: &TransferProgress) -> u64 {
+    progress.bytes_total + progress.files_total * FILE_OVERHEAD_BYTES
+}
+
+impl Estimator {
+    pub fn update(&mut self, progress: TransferProgress) -> Estimate {
+        self.update_at(progress, Instant::now())
+    }
+
+    fn update_at(&mut self, progress: TransferProgress, now: Instant) -> Estimate {
+        let work = work_done(&progress);
+        let total = work_total(&progress);
+
+        self.samples.push_back(Sample { at: now, work, bytes: progress.bytes_done });
+        while self.samples.len() > 2
+            && self.samples.front().is_some_and(|sample| now - sample.at > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        let mut estimate = Estimate {
+            fraction: if total == 0 { 0.0 } else { (work as f64 / total as f64).min(1.0) as f32 },
+            ..Default::default()
+        };
+
+        if let Some(first) = self.samples.front()
+            && now - first.at >= MIN_RATE_SPAN
+        {
+            let seconds = (now - first.at).as_secs_f64();
+            let work_rate = work.saturating_sub(first.work) as f64 / seconds;
+
+            estimate.bytes_per_second = Some(progress.bytes_done.saturating_sub(first.bytes) as f64 / seconds);
+            if work_rate > 0.0 {
+                estimate.eta = Some(Duration::from_secs_f64(total.saturating_sub(work) as f64 / work_rate));
+            }
+        }
+
+        self.estimate = estimate;
+        estimate
+    }
+
+    pub fn estimate(&self) -> Estimate {
+        self.estimate
+    }
+}
+
+pub fn format_rate(bytes_per_second: f64) -> String {
+    if bytes_per_second >= 1_048_576.0 {
+        format!("{:.1} MB/s", bytes_per_second / 1_048_576.0)
+    } else {
+        format!("{:.0} KB/s", bytes_per_second / 1024.0)
+    }
+}
+
+pub fn format_eta(eta: Duration) -> String {
+    let seconds = eta.as_secs();
+    if seconds >= 60 {
+        format!("{}m {}s left", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s left", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(bytes_done: u64, files_done: u64) -> TransferProgress {
+        TransferProgress { bytes_done, bytes_total: 10 * 1024 * 1024, files_done, files_total: 100 }
+    }
+
+    #[test]
+    fn files_count_towards_the_fraction() {
+        let mut estimator = Estimator::default();
+        // Half the files but none of the bytes still moves the bar,
+        // 50 * 64 KiB of the 10 MiB + 100 * 64 KiB total
+        let estimate = estimator.update(progress(0, 50));
+        assert!((estimate.fraction - 0.192).abs() < 0.001, "{}", estimate.fraction);
+        assert_eq!(estimator.update(progress(10 * 1024 * 1024, 100)).fraction, 1.0);
+    }
+
+    #[test]
+    fn no_rate_until_enough_time_passed() {
+        let mut estimator = Estimator::default();
+        let start = Instant::now();
+        estimator.update_at(progress(0, 0), start);
+
+        let early = estimator.update_at(progress(1024 * 1024, 0), start + Duration::from_millis(100));
+        assert!(early.bytes_per_second.is_none() && early.eta.is_none());
+
+        let later = estimator.update_at(progress(2 * 1024 * 1024, 0), start + Duration::from_secs(2));
+        assert_eq!(later.bytes_per_second, Some(1024.0 * 1024.0));
+        assert!(later.eta.is_some());
+    }
+
+    #[test]
+    fn old_samples_leave_the_window() {
+        let mut estimator = Estimator::default();
+        let start = Instant::now();
+        estimator.update_at(progress(0, 0), start);
+        estimator.update_at(progress(1024, 0), start + Duration::from_secs(1));
+        estimator.update_at(progress(2048, 0), start + Duration::from_secs(4));
+
+        // The start is more than 5s old, so the rate is taken from the sample
+        // one second in
+        let estimate = estimator.update_at(progress(6144, 0), start + Duration::from_secs(6));
+        assert_eq!(estimate.bytes_per_second, Some(1024.0));
+    }
+}
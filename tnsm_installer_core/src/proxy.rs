@@ -0,0 +1,81 @@
+// How the installer's requests reach the internet. The system proxy is what
+// reqwest finds on its own: HTTP_PROXY / HTTPS_PROXY and, on Windows, the one
+// set in Internet Options. Manual is for proxies the system doesn't know about
+// or that need a username and password.
+
+use crate::i18n::{tr, trf};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ProxyMode {
+    #[default]
+    System,
+    Manual,
+    // Connect directly even when the system has a proxy set
+    Off,
+}
+
+impl ProxyMode {
+    pub const ALL: [ProxyMode; 3] = [ProxyMode::System, ProxyMode::Manual, ProxyMode::Off];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ProxyMode::System => "System",
+            ProxyMode::Manual => "Manual",
+            ProxyMode::Off => "No proxy",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Default, Debug)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    // Only used in manual mode. A host name or an http:// or https:// URL
+    pub host: String,
+    pub port: u16,
+    // Empty when the proxy doesn't ask for credentials
+    pub username: String,
+    pub password: String,
+}
+
+impl ProxySettings {
+    pub fn check(&self) -> Result<(), String> {
+        if self.mode != ProxyMode::Manual {
+            return Ok(());
+        }
+        if self.host.trim().is_empty() {
+            return Err(tr("Enter the proxy's host name").to_string());
+        }
+        if self.port == 0 {
+            return Err(tr("Enter the proxy's port").to_string());
+        }
+        reqwest::Proxy::all(self.url())
+            .map(|_| ())
+            .map_err(|e| trf("Invalid proxy address: {}", &[&e]))
+    }
+
+    fn url(&self) -> String {
+        let host = self.host.trim().trim_end_matches('/');
+        if host.contains("://") {
+            format!("{}:{}", host, self.port)
+        } else {
+            format!("http://{}:{}", host, self.port)
+        }
+    }
+}
+
+pub fn apply(builder: reqwest::ClientBuilder, settings: &ProxySettings) -> Result<reqwest::ClientBuilder, String> {
+    match settings.mode {
+        ProxyMode::System => Ok(builder),
+        ProxyMode::Off => Ok(builder.no_proxy()),
+        ProxyMode::Manual => {
+            let mut proxy = reqwest::Proxy::all(settings.url())
+                .map_err(|e| trf("Invalid proxy address: {}", &[&e]))?;
+            if !settings.username.is_empty() {
+                proxy = proxy.basic_auth(&settings.username, &settings.password);
+            }
+            Ok(builder.proxy(proxy))
+        }
+    }
+}
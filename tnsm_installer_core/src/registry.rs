@@ -0,0 +1,53 @@
+// Registry access through reg.exe, shared by the autostart, shell
+// integration and Steam detection code
+
+#[cfg(windows)]
+pub fn reg(args: &[&str]) -> Result<(), String> {
+    run(args).map(|_| ())
+}
+
+// Returns what reg printed
+#[cfg(windows)]
+fn run(args: &[&str]) -> Result<String, String> {
+    use crate::i18n::trf;
+    use std::os::windows::process::CommandExt;
+
+    // CREATE_NO_WINDOW, otherwise a console flashes up
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(0x08000000)
+        .output()
+        .map_err(|e| trf("Failed to run reg: {}", &[&e]))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(windows)]
+pub fn set_default_value(key: &str, value: &str) -> Result<(), String> {
+    reg(&["add", key, "/ve", "/t", "REG_SZ", "/d", value, "/f"])
+}
+
+#[cfg(windows)]
+pub fn key_exists(key: &str) -> bool {
+    reg(&["query", key]).is_ok()
+}
+
+// A string value, None when the key or value doesn't exist
+#[cfg(windows)]
+pub fn query_value(key: &str, name: &str) -> Option<String> {
+    let output = run(&["query", key, "/v", name]).ok()?;
+
+    // "    InstallPath    REG_SZ    C:\Program Files (x86)\Steam"
+    output.lines().find_map(|line| {
+        let line = line.trim_start();
+        let rest = line.get(..name.len())
+            .filter(|start| start.eq_ignore_ascii_case(name))
+            .map(|_| line[name.len()..].trim_start())?;
+        let (kind, value) = rest.split_once(char::is_whitespace)?;
+        kind.starts_with("REG_").then(|| value.trim().to_string())
+    })
+}
@@ -0,0 +1,171 @@
+// Instead of importing, TNSM Resources can link to a folder elsewhere, so
+// edits there are in the game the next time it loads them, without
+// importing again. Meant for resource pack authors. On Windows it's a
+// directory junction, which unlike a symlink needs neither administrator
+// rights nor developer mode.
+
+use crate::i18n::{tr, trf};
+use crate::resources::{self, RESOURCES_DIR_NAME};
+use crate::{audit, game_process, import_index, profiles};
+use std::path::{Path, PathBuf};
+
+// The folder TNSM Resources links to, None when it's a regular folder
+pub fn target(game_path: &Path) -> Option<PathBuf> {
+    let link = resources::resources_path(game_path);
+    let metadata = std::fs::symlink_metadata(&link).ok()?;
+    if !metadata.file_type().is_symlink() {
+        return None;
+    }
+
+    let target = std::fs::read_link(&link).ok()?;
+    // Junctions read back with the verbatim prefix
+    Some(match target.to_str().and_then(|path| path.strip_prefix(r"\\?\")) {
+        Some(path) => PathBuf::from(path),
+        None => target,
+    })
+}
+
+pub fn is_linked(game_path: &Path) -> bool {
+    target(game_path).is_some()
+}
+
+// A junction is an empty folder with a mount point reparse point on it,
+// which is what mklink /J makes too
+#[cfg(windows)]
+fn create(target: &Path, link: &Path) -> Result<(), String> {
+    std::fs::create_dir(link)
+        .map_err(|e| trf("Failed to link {}: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+    set_mount_point(target, link).map_err(|e| {
+        let _ = std::fs::remove_dir(link);
+        trf("Failed to link {}: {}", &[&RESOURCES_DIR_NAME, &e])
+    })
+}
+
+#[cfg(windows)]
+fn set_mount_point(target: &Path, link: &Path) -> std::io::Result<()> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{ERROR_FILENAME_EXCED_RANGE, GENERIC_WRITE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, MAXIMUM_REPARSE_DATA_BUFFER_SIZE,
+    };
+    use windows_sys::Win32::System::IO::DeviceIoControl;
+    use windows_sys::Win32::System::Ioctl::FSCTL_SET_REPARSE_POINT;
+
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+
+    // The target is canonical, so it has the verbatim prefix, which the
+    // substitute name swaps for the NT one
+    let target = target.to_string_lossy();
+    let target = target.strip_prefix(r"\\?\").unwrap_or(&*target);
+    let substitute: Vec<u16> = format!(r"\??\{}", target).encode_utf16().collect();
+    let print: Vec<u16> = target.encode_utf16().collect();
+
+    // MountPointReparseBuffer: both names, each followed by a null that
+    // their lengths leave out
+    let names_size = (substitute.len() + print.len() + 2) * 2;
+    let data_size = 8 + names_size;
+    if 8 + data_size > MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize {
+        return Err(std::io::Error::from_raw_os_error(ERROR_FILENAME_EXCED_RANGE as i32));
+    }
+
+    let mut buffer = Vec::with_capacity(8 + data_size);
+    // Tag, data length and a reserved field
+    buffer.extend(IO_REPARSE_TAG_MOUNT_POINT.to_le_bytes());
+    buffer.extend((data_size as u16).to_le_bytes());
+    buffer.extend(0u16.to_le_bytes());
+    // Offset and length in bytes of each name
+    buffer.extend(0u16.to_le_bytes());
+    buffer.extend(((substitute.len() * 2) as u16).to_le_bytes());
+    buffer.extend((((substitute.len() + 1) * 2) as u16).to_le_bytes());
+    buffer.extend(((print.len() * 2) as u16).to_le_bytes());
+    for unit in substitute.iter().chain(&[0]).chain(&print).chain(&[0]) {
+        buffer.extend(unit.to_le_bytes());
+    }
+
+    // Backup semantics to open a folder, and the reparse point flag so the
+    // handle is to the link itself
+    let folder = std::fs::OpenOptions::new()
+        .access_mode(GENERIC_WRITE)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(link)?;
+
+    let mut returned = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            folder.as_raw_handle(),
+            FSCTL_SET_REPARSE_POINT,
+            buffer.as_ptr().cast(),
+            buffer.len() as u32,
+            std::ptr::null_mut(),
+            0,
+            &mut returned,
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn create(target: &Path, link: &Path) -> Result<(), String> {
+    std::os::unix::fs::symlink(target, link)
+        .map_err(|e| trf("Failed to link {}: {}", &[&RESOURCES_DIR_NAME, &e]))
+}
+
+// Only the link goes, never what's in the folder it points to
+fn remove(link: &Path) -> Result<(), String> {
+    std::fs::remove_dir(link)
+        .or_else(|_| std::fs::remove_file(link))
+        .map_err(|e| trf("Failed to unlink {}: {}", &[&RESOURCES_DIR_NAME, &e]))
+}
+
+// Points TNSM Resources at `source`. Resources already in it are moved into
+// their profile first, see profiles::put_away.
+pub fn link(source: &Path, game_path: &Path) -> Result<(), String> {
+    let audit = audit::begin("link resources", Some(game_path)).detail(source.display().to_string());
+    let result = link_folder(source, game_path);
+    audit.finish(&result);
+    result
+}
+
+fn link_folder(source: &Path, game_path: &Path) -> Result<(), String> {
+    // Sky keeps resource files open, which would stop the folder moving
+    game_process::ensure_not_running()?;
+
+    let source = source.canonicalize()
+        .map_err(|e| trf("Failed to open {}: {}", &[&source.display(), &e]))?;
+    if !source.is_dir() {
+        return Err(trf("{} is not a folder", &[&source.display()]));
+    }
+    if source.starts_with(game_path.canonicalize().unwrap_or_else(|_| game_path.to_path_buf())) {
+        return Err(tr("Pick a folder outside the game folder").to_string());
+    }
+
+    let current = resources::resources_path(game_path);
+    if is_linked(game_path) {
+        remove(&current)?;
+    } else if std::fs::read_dir(&current).is_ok_and(|mut entries| entries.next().is_none()) {
+        std::fs::remove_dir(&current)
+            .map_err(|e| trf("Failed to remove the empty {}: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+    } else {
+        profiles::put_away(game_path)?;
+    }
+
+    // The index described the files that were there before
+    import_index::forget(&current);
+    create(&source, &current)
+}
+
+pub fn unlink(game_path: &Path) -> Result<(), String> {
+    if !is_linked(game_path) {
+        return Ok(());
+    }
+
+    let audit = audit::begin("unlink resources", Some(game_path));
+    let result = remove(&resources::resources_path(game_path));
+    audit.finish(&result);
+    result
+}
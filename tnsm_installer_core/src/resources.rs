@@ -0,0 +1,510 @@
+use crate::cancel::{self, CancellationToken};
+use crate::elevated::Helper;
+use crate::events::TransferProgress;
+use crate::i18n::{tr, trf};
+use crate::import_index::{self, ImportIndex, IndexEntry};
+use crate::journal::{Journal, Operation};
+use crate::preview::{ChangeKind, FileChange};
+use crate::restore_point::RestorePoint;
+use crate::{audit, disk_space, resource_link, verify};
+use crate::workers;
+use jwalk::WalkDir;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const RESOURCES_DIR_NAME: &str = "TNSM Resources";
+
+// Progress is reported at most this often so huge packs of tiny files don't flood the UI
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+struct SourceFile {
+    path: PathBuf,
+    relative: PathBuf,
+    size: u64,
+}
+
+// How an import went: files copied, skipped because the destination already
+// had them, and removed because the source no longer has them
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub copied: usize,
+    pub skipped: usize,
+    pub removed: usize,
+    // Of the files it replaced or removed
+    pub restore_point: Option<PathBuf>,
+}
+
+impl ImportSummary {
+    pub fn describe(&self) -> String {
+        let mut parts = vec![
+            trf("{} copied", &[&self.copied]),
+            trf("{} unchanged", &[&self.skipped]),
+        ];
+        if self.removed > 0 {
+            parts.push(trf("{} removed", &[&self.removed]));
+        }
+        parts.join(", ")
+    }
+}
+
+pub fn resources_path(game_path: &Path) -> PathBuf {
+    game_path.join(RESOURCES_DIR_NAME)
+}
+
+// Walks the tree on jwalk's thread pool: reading many directories at once
+// keeps spinning disks and network shares busy instead of waiting on each
+// read_dir in turn
+fn scan_dir(root: &Path, cancel: &CancellationToken) -> Result<Vec<SourceFile>, String> {
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(root).skip_hidden(false) {
+        cancel::checkpoint(cancel)?;
+
+        let entry = entry.map_err(|e| trf("Failed to read directory: {}", &[&e]))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        let metadata = entry.metadata()
+            .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+        let relative = path.strip_prefix(root)
+            .map_err(|e| trf("Invalid path {}: {}", &[&path.display(), &e]))?
+            .to_path_buf();
+        files.push(SourceFile { path, relative, size: metadata.len() });
+    }
+
+    Ok(files)
+}
+
+// Files in the index an earlier import of `source` wrote that it no longer has
+fn missing_from_source(index: &ImportIndex, source: &Path, files: &[SourceFile]) -> Vec<PathBuf> {
+    let present: HashSet<&Path> = files.iter().map(|file| file.relative.as_path()).collect();
+    let mut missing: Vec<PathBuf> = index.entries()
+        .filter(|(relative, entry)| entry.source.starts_with(source) && !present.contains(relative.as_path()))
+        .map(|(relative, _)| relative.clone())
+        .collect();
+    missing.sort();
+    missing
+}
+
+// Creates the folders the destination needs first
+fn copy_file(source: &Path, dest: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::copy(source, dest).map(|_| ())
+}
+
+// Copies unless the index shows the destination already holds this file.
+// Returns None when skipped, otherwise the file's new index entry.
+fn sync_file(
+    source: &Path,
+    dest: &Path,
+    relative: &Path,
+    previous: Option<IndexEntry>,
+    restore_point: &RestorePoint,
+) -> std::io::Result<Option<IndexEntry>> {
+    let source_metadata = std::fs::metadata(source)?;
+    if previous.is_some_and(|entry| entry.matches(source, &source_metadata, dest)) {
+        return Ok(None);
+    }
+
+    restore_point.preserve(&Path::new(RESOURCES_DIR_NAME).join(relative))
+        .map_err(std::io::Error::other)?;
+
+    copy_file(source, dest)?;
+
+    let dest_metadata = std::fs::metadata(dest)?;
+    let entry = IndexEntry {
+        size: dest_metadata.len(),
+        modified: import_index::modified_millis(&dest_metadata),
+        sha256: verify::sha256_file(dest).map_err(std::io::Error::other)?,
+        source_modified: import_index::modified_millis(&source_metadata),
+        source: source.to_path_buf(),
+    };
+
+    Ok(Some(entry))
+}
+
+// Copies files (relative to TNSM Resources) again from where they were
+// imported from, as long as that still holds the same file. Returns how many
+// were repaired, the ones that couldn't be and the restore point of the
+// files it replaced.
+pub async fn repair(
+    game_path: PathBuf,
+    files: Vec<PathBuf>,
+    cancel: CancellationToken,
+) -> Result<(usize, Vec<PathBuf>, Option<PathBuf>), String> {
+    workers::run(move || {
+        let audit = audit::begin("repair resources", Some(&game_path)).detail(format!("{} files", files.len()));
+        let result = repair_files(&game_path, &files, &cancel);
+        audit.finish(&result);
+        result
+    }).await?
+}
+
+fn repair_files(
+    game_path: &Path,
+    files: &[PathBuf],
+    cancel: &CancellationToken,
+) -> Result<(usize, Vec<PathBuf>, Option<PathBuf>), String> {
+    let dest_root = resources_path(game_path);
+    let mut index = ImportIndex::load(&dest_root);
+    let restore_point = RestorePoint::begin(game_path, trf("Before repairing {}", &[&RESOURCES_DIR_NAME]))?;
+    let mut repaired = 0;
+    let mut unrepairable = Vec::new();
+
+    for relative in files {
+        cancel::checkpoint(cancel)?;
+
+        let Some(entry) = index.get(relative).cloned() else {
+            unrepairable.push(relative.clone());
+            continue;
+        };
+        let source_matches = entry.source.is_file()
+            && verify::sha256_file(&entry.source).is_ok_and(|sha256| sha256 == entry.sha256);
+        if !source_matches {
+            unrepairable.push(relative.clone());
+            continue;
+        }
+
+        let dest = dest_root.join(relative);
+        let synced = sync_file(&entry.source, &dest, relative, None, &restore_point)
+            .map_err(|e| trf("Failed to copy {}: {}", &[&relative.display(), &e]))?;
+        if let Some(entry) = synced {
+            index.insert(relative.clone(), entry);
+            repaired += 1;
+        }
+    }
+
+    let _ = index.save();
+    Ok((repaired, unrepairable, restore_point.finish()?))
+}
+
+// Deletes the whole resources folder and returns how many files it held and
+// their total size
+pub async fn remove_all(game_path: PathBuf) -> Result<(usize, u64), String> {
+    workers::run(move || remove_folder(&game_path)).await?
+}
+
+fn remove_folder(game_path: &Path) -> Result<(usize, u64), String> {
+    // A linked folder isn't the installer's to delete, only the link goes
+    if resource_link::is_linked(game_path) {
+        return resource_link::unlink(game_path).map(|_| (0, 0));
+    }
+
+    let dest = resources_path(game_path);
+    let audit = audit::begin("remove resources", Some(game_path));
+    let result = scan_dir(&dest, &CancellationToken::new()).and_then(|files| {
+        std::fs::remove_dir_all(&dest)
+            .map_err(|e| trf("Failed to remove {}: {}", &[&RESOURCES_DIR_NAME, &e]))?;
+        import_index::forget(&dest);
+        Ok((files.len(), files.iter().map(|file| file.size).sum()))
+    });
+    audit.finish(&result);
+    result
+}
+
+// The files an import of `source` would write or remove, see copy_resources
+pub(crate) fn preview_import(
+    source: &Path,
+    game_path: &Path,
+    remove_missing: bool,
+    cancel: &CancellationToken,
+) -> Result<Vec<FileChange>, String> {
+    if let Some(target) = resource_link::target(game_path) {
+        return Err(trf("{} is linked to {}, unlink it to import", &[&RESOURCES_DIR_NAME, &target.display()]));
+    }
+
+    let dest = resources_path(game_path);
+    let index = ImportIndex::load(&dest);
+    let files = scan_dir(source, cancel)?;
+    let mut changes = Vec::new();
+
+    for file in &files {
+        let dest_path = dest.join(&file.relative);
+        let unchanged = index.get(&file.relative).is_some_and(|entry| {
+            std::fs::metadata(&file.path).is_ok_and(|metadata| entry.matches(&file.path, &metadata, &dest_path))
+        });
+        if !unchanged {
+            changes.push(FileChange::write(game_path, Path::new(RESOURCES_DIR_NAME).join(&file.relative), Some(file.size)));
+        }
+    }
+
+    if remove_missing {
+        for relative in missing_from_source(&index, source, &files) {
+            let Ok(metadata) = std::fs::metadata(dest.join(&relative)) else {
+                continue;
+            };
+            changes.push(FileChange {
+                path: Path::new(RESOURCES_DIR_NAME).join(relative),
+                kind: ChangeKind::Delete,
+                size: Some(metadata.len()),
+            });
+        }
+    }
+
+    Ok(changes)
+}
+
+// The files remove_all would delete
+pub(crate) fn preview_remove_all(game_path: &Path, cancel: &CancellationToken) -> Result<Vec<FileChange>, String> {
+    if resource_link::is_linked(game_path) {
+        return Ok(Vec::new());
+    }
+    let files = scan_dir(&resources_path(game_path), cancel)?;
+    Ok(files.into_iter()
+        .map(|file| FileChange {
+            path: Path::new(RESOURCES_DIR_NAME).join(file.relative),
+            kind: ChangeKind::Delete,
+            size: Some(file.size),
+        })
+        .collect())
+}
+
+pub async fn import_resources(
+    source: &Path,
+    game_path: &Path,
+    remove_missing: bool,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<ImportSummary, String> {
+    let audit = audit::begin("import resources", Some(game_path)).detail(source.display().to_string());
+    let result = copy_resources(source, game_path, Vec::new(), Vec::new(), remove_missing, cancel, on_progress).await;
+    audit.finish(&result);
+    result
+}
+
+pub async fn resume_import(
+    operation: Operation,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<ImportSummary, String> {
+    let Operation::Import { game_path, source, copied, created, remove_missing } = operation else {
+        return Err(tr("Not an import operation").to_string());
+    };
+
+    let audit = audit::begin("resume import", Some(&game_path)).detail(source.display().to_string());
+    let result = copy_resources(&source, &game_path, copied, created, remove_missing, cancel, on_progress).await;
+    audit.finish(&result);
+    result
+}
+
+// For a game folder this account can't write to: every file goes through one
+// elevated helper session instead. The helper only writes, so nothing is
+// indexed or kept in a restore point. Returns how many files were copied.
+pub async fn import_elevated(
+    source: &Path,
+    game_path: &Path,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<usize, String> {
+    let audit = audit::begin("import resources as administrator", Some(game_path)).detail(source.display().to_string());
+    let result = copy_elevated(source, game_path, cancel, on_progress).await;
+    audit.finish(&result);
+    result
+}
+
+async fn copy_elevated(
+    source: &Path,
+    game_path: &Path,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<usize, String> {
+    if let Some(target) = resource_link::target(game_path) {
+        return Err(trf("{} is linked to {}, unlink it to import", &[&RESOURCES_DIR_NAME, &target.display()]));
+    }
+
+    let root = source.to_path_buf();
+    let scan_cancel = cancel.clone();
+    let files = workers::run(move || scan_dir(&root, &scan_cancel)).await??;
+
+    let mut progress = TransferProgress {
+        bytes_total: files.iter().map(|file| file.size).sum(),
+        files_total: files.len() as u64,
+        ..Default::default()
+    };
+    on_progress(progress);
+
+    let mut helper = Helper::start(game_path).await?;
+    for file in &files {
+        cancel::checkpoint(cancel)?;
+        helper.copy_resource(&file.path, &file.relative).await?;
+
+        progress.bytes_done += file.size;
+        progress.files_done += 1;
+        on_progress(progress);
+    }
+    Ok(files.len())
+}
+
+// Takes back the files a cancelled import created and puts back the ones it
+// overwrote or removed, rather than leaving a partial pack
+fn cancel_import(journal: Journal, restore_point: Arc<RestorePoint>, index: &ImportIndex) -> Result<ImportSummary, String> {
+    journal.roll_back()?;
+    if let Ok(restore_point) = Arc::try_unwrap(restore_point) {
+        restore_point.undo()?;
+    }
+    let _ = index.save();
+    Err(cancel::CANCELLED.to_string())
+}
+
+async fn copy_resources(
+    source: &Path,
+    game_path: &Path,
+    copied: Vec<PathBuf>,
+    created: Vec<PathBuf>,
+    remove_missing: bool,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(TransferProgress) + Sync),
+) -> Result<ImportSummary, String> {
+    // It would write into the linked folder, which is where edits go already
+    if let Some(target) = resource_link::target(game_path) {
+        return Err(trf("{} is linked to {}, unlink it to import", &[&RESOURCES_DIR_NAME, &target.display()]));
+    }
+
+    let dest = resources_path(game_path);
+
+    // Scan first so progress can be reported against real totals
+    let root = source.to_path_buf();
+    let scan_cancel = cancel.clone();
+    let files = workers::run(move || scan_dir(&root, &scan_cancel)).await??;
+
+    let mut progress = TransferProgress {
+        bytes_total: files.iter().map(|file| file.size).sum(),
+        files_total: files.len() as u64,
+        ..Default::default()
+    };
+    on_progress(progress);
+
+    // Files being replaced give their space back, so only the growth counts.
+    // The ones that aren't there yet are journaled before anything is copied,
+    // so a rollback takes back every one of them however far the copy got.
+    let to_copy: Vec<_> = files.iter()
+        .filter(|file| !copied.contains(&file.relative))
+        .map(|file| (file.relative.clone(), file.size))
+        .collect();
+    let dest_root = dest.clone();
+    let (needed, new_files) = workers::run(move || {
+        let mut needed = 0;
+        let mut new_files = Vec::new();
+        for (relative, size) in to_copy {
+            match std::fs::metadata(dest_root.join(&relative)) {
+                Ok(metadata) => needed += size.saturating_sub(metadata.len()),
+                Err(_) => {
+                    needed += size;
+                    new_files.push(relative);
+                }
+            }
+        }
+        (needed, new_files)
+    })
+    .await?;
+    disk_space::ensure_free(&dest, needed, tr("the resources"))?;
+    let known: HashSet<PathBuf> = created.iter().cloned().collect();
+    let mut created = created;
+    created.extend(new_files.into_iter().filter(|relative| !known.contains(relative)));
+
+    // Create destination directory if it doesn't exist
+    let dest_root = dest.clone();
+    workers::run(move || std::fs::create_dir_all(dest_root))
+        .await?
+        .map_err(|e| trf("Failed to create destination directory: {}", &[&e]))?;
+
+    let already_copied: HashSet<PathBuf> = copied.iter().cloned().collect();
+    let mut index = ImportIndex::load(&dest);
+    // Saved when the last clone is dropped, however the import ends
+    let restore_point = Arc::new(RestorePoint::begin(
+        game_path,
+        trf("Before importing {}", &[&source.file_name().unwrap_or_default().to_string_lossy()]),
+    )?);
+    let journal = Journal::begin(Operation::Import {
+        game_path: game_path.to_path_buf(),
+        source: source.to_path_buf(),
+        copied,
+        created,
+        remove_missing,
+    })?;
+    let mut summary = ImportSummary { copied: already_copied.len(), ..Default::default() };
+
+    let mut last_report = Instant::now();
+    for file in &files {
+        if cancel.is_cancelled() {
+            return cancel_import(journal, restore_point, &index);
+        }
+
+        progress.bytes_done += file.size;
+        progress.files_done += 1;
+        // Reported up front so runs of skipped files still move the bar
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            on_progress(progress);
+            last_report = Instant::now();
+        }
+
+        if already_copied.contains(&file.relative) {
+            continue;
+        }
+
+        let source_path = file.path.clone();
+        let dest_path = dest.join(&file.relative);
+        let relative = file.relative.clone();
+        let previous = index.get(&file.relative).cloned();
+        let file_restore_point = restore_point.clone();
+
+        let synced = workers::run(move || sync_file(&source_path, &dest_path, &relative, previous, &file_restore_point))
+            .await?
+            .map_err(|e| trf("Failed to copy {}: {}", &[&file.relative.display(), &e]))?;
+        let Some(entry) = synced else {
+            summary.skipped += 1;
+            continue;
+        };
+        index.insert(file.relative.clone(), entry);
+        summary.copied += 1;
+
+        journal.update(|operation| {
+            if let Operation::Import { copied, .. } = operation {
+                copied.push(file.relative.clone());
+            }
+        });
+    }
+
+    if remove_missing {
+        journal.save()?;
+        for relative in missing_from_source(&index, source, &files) {
+            if cancel.is_cancelled() {
+                return cancel_import(journal, restore_point, &index);
+            }
+
+            let file_restore_point = restore_point.clone();
+            let path = dest.join(&relative);
+            let stale = relative.clone();
+            workers::run(move || -> Result<(), String> {
+                file_restore_point.preserve(&Path::new(RESOURCES_DIR_NAME).join(&stale))?;
+                match std::fs::remove_file(&path) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                        Err(trf("Failed to remove {}: {}", &[&stale.display(), &e]))
+                    }
+                    _ => Ok(()),
+                }
+            })
+            .await??;
+            index.remove(&relative);
+            summary.removed += 1;
+        }
+    }
+
+    journal.finish();
+    let _ = index.save();
+    on_progress(progress);
+
+    // Every copy has let go of its clone by now
+    summary.restore_point = match Arc::try_unwrap(restore_point) {
+        Ok(restore_point) => restore_point.finish()?,
+        Err(_) => None,
+    };
+    Ok(summary)
+}
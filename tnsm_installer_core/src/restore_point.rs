@@ -0,0 +1,281 @@
+// Restore points for the game directory. Before an install or import
+// changes a file, the original is copied into the restore point (or noted as
+// missing), so the whole operation can be reverted later from the UI. Unlike
+// the journal, which only covers the operation in progress, restore points
+// stay around after the operation finished.
+//
+//   <app dir>/restore-points/<timestamp>/manifest.json
+//   <app dir>/restore-points/<timestamp>/files/<path relative to the game>
+
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::{app_files, audit, config, manifest, timestamp, workers};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    description: String,
+    game_path: PathBuf,
+    files: Vec<SnapshotFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    relative: PathBuf,
+    // Files that didn't exist are removed again on revert
+    existed: bool,
+}
+
+#[derive(Clone)]
+pub struct RestorePointInfo {
+    pub dir: PathBuf,
+    pub timestamp: String,
+    pub description: String,
+    pub files: usize,
+}
+
+impl RestorePointInfo {
+    pub fn label(&self) -> String {
+        format!("{} - {}", timestamp::display(&self.timestamp), self.description)
+    }
+}
+
+pub fn root_dir() -> Result<PathBuf, String> {
+    config::app_dir()
+        .map(|dir| dir.join("restore-points"))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+pub struct RestorePoint {
+    dir: PathBuf,
+    manifest: Mutex<Manifest>,
+    preserved: Mutex<HashSet<PathBuf>>,
+    finished: bool,
+}
+
+impl RestorePoint {
+    pub fn begin(game_path: &Path, description: String) -> Result<Self, String> {
+        let root = root_dir()?;
+        let stamp = timestamp::now();
+
+        // Two operations in the same second get numbered
+        let mut dir = root.join(&stamp);
+        let mut count = 1;
+        while dir.exists() {
+            count += 1;
+            dir = root.join(format!("{}-{}", stamp, count));
+        }
+        std::fs::create_dir_all(dir.join(FILES_DIR))
+            .map_err(|e| trf("Failed to create restore point: {}", &[&e]))?;
+
+        Ok(Self {
+            dir,
+            manifest: Mutex::new(Manifest {
+                description,
+                game_path: game_path.to_path_buf(),
+                files: Vec::new(),
+            }),
+            preserved: Mutex::new(HashSet::new()),
+            finished: false,
+        })
+    }
+
+    // Call before changing `relative` (relative to the game directory).
+    // Only the first call per path copies anything.
+    pub fn preserve(&self, relative: &Path) -> Result<(), String> {
+        if !self.preserved.lock().unwrap().insert(relative.to_path_buf()) {
+            return Ok(());
+        }
+
+        let game_path = self.manifest.lock().unwrap().game_path.clone();
+        let original = game_path.join(relative);
+        let existed = original.is_file();
+
+        if existed {
+            let copy = self.dir.join(FILES_DIR).join(relative);
+            if let Some(parent) = copy.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| trf("Failed to create restore point: {}", &[&e]))?;
+            }
+            std::fs::copy(&original, &copy)
+                .map_err(|e| trf("Failed to save {} to restore point: {}", &[&relative.display(), &e]))?;
+        }
+
+        self.manifest.lock().unwrap().files.push(SnapshotFile {
+            relative: relative.to_path_buf(),
+            existed,
+        });
+        Ok(())
+    }
+
+    // Writes the manifest, or drops the restore point if nothing was changed.
+    // Returns where it was kept.
+    pub fn finish(mut self) -> Result<Option<PathBuf>, String> {
+        self.finished = true;
+        self.save()?;
+        Ok(Some(self.dir.clone()).filter(|dir| dir.exists()))
+    }
+
+    // Puts every preserved file back right away and drops the restore point,
+    // for an operation that was cancelled halfway. Returns how many files
+    // were put back.
+    pub fn undo(mut self) -> Result<usize, String> {
+        self.finished = true;
+        let manifest = self.manifest.lock().unwrap();
+        let mut restored = 0;
+
+        for file in manifest.files.iter().rev() {
+            let target = manifest.game_path.join(&file.relative);
+            if file.existed {
+                std::fs::copy(self.dir.join(FILES_DIR).join(&file.relative), &target)
+                    .map_err(|e| trf("Failed to restore {}: {}", &[&file.relative.display(), &e]))?;
+                restored += 1;
+            } else if target.exists() {
+                std::fs::remove_file(&target)
+                    .map_err(|e| trf("Failed to remove {}: {}", &[&file.relative.display(), &e]))?;
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&self.dir);
+        Ok(restored)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let manifest = self.manifest.lock().unwrap();
+        if manifest.files.is_empty() {
+            let _ = std::fs::remove_dir_all(&self.dir);
+            return Ok(());
+        }
+
+        let contents = serde_json::to_string(&*manifest)
+            .map_err(|e| trf("Failed to serialize restore point: {}", &[&e]))?;
+        std::fs::write(self.dir.join(MANIFEST_NAME), contents)
+            .map_err(|e| trf("Failed to write restore point: {}", &[&e]))
+    }
+}
+
+impl Drop for RestorePoint {
+    fn drop(&mut self) {
+        // A failed operation may still have changed files, keep what was preserved
+        if !self.finished {
+            let _ = self.save();
+        }
+    }
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let contents = std::fs::read_to_string(dir.join(MANIFEST_NAME))
+        .map_err(|e| trf("Failed to read restore point: {}", &[&e]))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| trf("Failed to parse restore point: {}", &[&e]))
+}
+
+// Newest first. Restore points without a manifest were never finished and are skipped.
+pub fn list() -> Vec<RestorePointInfo> {
+    app_files::list_newest_first(
+        root_dir().ok(),
+        |entry| {
+            let dir = entry.path();
+            let manifest = read_manifest(&dir).ok()?;
+            Some(RestorePointInfo {
+                timestamp: entry.file_name().to_string_lossy().into_owned(),
+                description: manifest.description,
+                files: manifest.files.len(),
+                dir,
+            })
+        },
+        |point| point.timestamp.clone(),
+    )
+}
+
+// Puts every file back the way it was before the operation and returns a
+// summary. The files are saved to a new restore point first, returned too,
+// so a revert can be taken back. `proxy` is the mod DLL, its install record
+// follows what's put back.
+pub async fn revert(point: RestorePointInfo, proxy: ProxyDll) -> Result<(String, Option<PathBuf>), String> {
+    workers::run(move || revert_point(&point, proxy)).await?
+}
+
+fn revert_point(point: &RestorePointInfo, proxy: ProxyDll) -> Result<(String, Option<PathBuf>), String> {
+    let manifest = read_manifest(&point.dir)?;
+    let game_path = manifest.game_path.clone();
+    let audit = audit::begin("revert", Some(&game_path)).dll(proxy).detail(point.label());
+    let description = trf("Before reverting \"{}\"", &[&manifest.description]);
+    let result = put_back(&game_path, proxy, description, &[(point.dir.clone(), manifest)]);
+    audit.finish(&result);
+    result
+}
+
+// Reverts every restore point of this game folder, newest first, which
+// leaves it the way it was before the oldest one: before the mod was
+// installed, unless that restore point was already cleaned up. Like a single
+// revert, it can be taken back.
+pub async fn revert_all(game_path: PathBuf, proxy: ProxyDll) -> Result<(String, Option<PathBuf>), String> {
+    workers::run(move || revert_folder(&game_path, proxy)).await?
+}
+
+fn revert_folder(game_path: &Path, proxy: ProxyDll) -> Result<(String, Option<PathBuf>), String> {
+    let points: Vec<(PathBuf, Manifest)> = list()
+        .into_iter()
+        .filter_map(|point| Some((point.dir.clone(), read_manifest(&point.dir).ok()?)))
+        .filter(|(_, manifest)| manifest.game_path == game_path)
+        .collect();
+    if points.is_empty() {
+        return Err(tr("There are no restore points for this game folder").to_string());
+    }
+
+    let audit = audit::begin("restore pre-install state", Some(game_path)).dll(proxy).detail(format!("{} restore points", points.len()));
+    let result = put_back(game_path, proxy, tr("Before restoring the pre-install state").to_string(), &points);
+    audit.finish(&result);
+    result
+}
+
+fn put_back(
+    game_path: &Path,
+    proxy: ProxyDll,
+    description: String,
+    points: &[(PathBuf, Manifest)],
+) -> Result<(String, Option<PathBuf>), String> {
+    let before = RestorePoint::begin(game_path, description)?;
+    let mut restored = 0;
+    let mut removed = 0;
+
+    for (dir, manifest) in points {
+        for file in manifest.files.iter().rev() {
+            let target = manifest.game_path.join(&file.relative);
+            before.preserve(&file.relative)?;
+
+            if file.existed {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| trf("Failed to create directory: {}", &[&e]))?;
+                }
+                std::fs::copy(dir.join(FILES_DIR).join(&file.relative), &target)
+                    .map_err(|e| trf("Failed to restore {}: {}", &[&file.relative.display(), &e]))?;
+                restored += 1;
+            } else if target.exists() {
+                std::fs::remove_file(&target)
+                    .map_err(|e| trf("Failed to remove {}: {}", &[&file.relative.display(), &e]))?;
+                removed += 1;
+            }
+        }
+    }
+
+    let before = before.finish()?;
+    let dll_name = Path::new(proxy.file_name());
+    if points.iter().any(|(_, manifest)| manifest.files.iter().any(|file| file.relative == dll_name)) {
+        manifest::refresh_dll(game_path, proxy);
+    }
+    Ok((trf("{} files restored, {} removed", &[&restored, &removed]), before))
+}
+
+pub fn delete(point: &RestorePointInfo) -> Result<(), String> {
+    std::fs::remove_dir_all(&point.dir)
+        .map_err(|e| trf("Failed to delete restore point: {}", &[&e]))
+}
@@ -0,0 +1,51 @@
+// Daily background update checks through the Windows Task Scheduler
+
+#[cfg(not(windows))]
+use crate::i18n::tr;
+#[cfg(windows)]
+use crate::i18n::trf;
+
+#[cfg(windows)]
+const TASK_NAME: &str = "ThatNoobSkyApp Update Check";
+
+#[cfg(windows)]
+fn schtasks(args: &[&str]) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    // CREATE_NO_WINDOW, otherwise a console flashes up
+    let output = std::process::Command::new("schtasks")
+        .args(args)
+        .creation_flags(0x08000000)
+        .output()
+        .map_err(|e| trf("Failed to run schtasks: {}", &[&e]))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(windows)]
+pub fn register_daily_check() -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))?;
+    let command = format!("\"{}\" --check-updates --quiet", exe.display());
+
+    schtasks(&["/Create", "/F", "/SC", "DAILY", "/TN", TASK_NAME, "/TR", &command])
+}
+
+#[cfg(windows)]
+pub fn unregister_daily_check() -> Result<(), String> {
+    schtasks(&["/Delete", "/F", "/TN", TASK_NAME])
+}
+
+#[cfg(not(windows))]
+pub fn register_daily_check() -> Result<(), String> {
+    Err(tr("Scheduled update checks are only supported on Windows").to_string())
+}
+
+#[cfg(not(windows))]
+pub fn unregister_daily_check() -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// Screenshots the mod captures in game. They're looked for in a folder next
+// to the game or in the user's Pictures folder, whichever exists, unless a
+// folder was picked in the gallery (Config::screenshot_dir).
+
+use crate::i18n::trf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const SCREENSHOTS_DIR_NAME: &str = "TNSM Screenshots";
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+pub struct Screenshot {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub bytes: u64,
+}
+
+impl Screenshot {
+    pub fn name(&self) -> String {
+        self.path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+    }
+}
+
+pub fn find_dir(game_path: Option<&Path>) -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+
+    if let Some(game_path) = game_path {
+        candidates.push(game_path.join(SCREENSHOTS_DIR_NAME));
+    }
+    if let Some(pictures) = dirs::picture_dir() {
+        candidates.push(pictures.join("ThatNoobSkyMod"));
+    }
+
+    candidates.into_iter().find(|dir| dir.is_dir())
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+// Newest first
+pub fn list(dir: &Path) -> Result<Vec<Screenshot>, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| trf("Failed to read screenshot folder: {}", &[&e]))?;
+
+    let mut screenshots: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| is_image(&entry.path()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some(Screenshot {
+                path: entry.path(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                bytes: metadata.len(),
+            })
+        })
+        .collect();
+
+    screenshots.sort_by_key(|screenshot| std::cmp::Reverse(screenshot.modified));
+    Ok(screenshots)
+}
+
+// Copies the screenshots into `dest`, returning how many were copied
+pub fn export(screenshots: &[PathBuf], dest: &Path) -> Result<usize, String> {
+    std::fs::create_dir_all(dest)
+        .map_err(|e| trf("Failed to create {}: {}", &[&dest.display(), &e]))?;
+
+    for path in screenshots {
+        let name = path.file_name().ok_or_else(|| trf("Invalid screenshot path: {}", &[&path.display()]))?;
+        std::fs::copy(path, dest.join(name))
+            .map_err(|e| trf("Failed to export {}: {}", &[&path.display(), &e]))?;
+    }
+
+    Ok(screenshots.len())
+}
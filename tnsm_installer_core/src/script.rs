@@ -0,0 +1,199 @@
+// Post-install scripts shipped with resource packs. A pack declares one in
+// its manifest and it only runs after the user agrees to it. Scripts run in
+// a rhai engine with no module loading and capped work, and can only touch
+// files inside the TNSM Resources folder through the functions below.
+
+use crate::i18n::trf;
+use crate::workers;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Engine, EvalAltResult};
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+
+pub const PACK_MANIFEST_NAME: &str = "tnsm-pack.json";
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PackManifest {
+    post_install: Option<PathBuf>,
+}
+
+pub struct PostInstallScript {
+    pub name: String,
+    pub source: String,
+}
+
+// Relative paths only, no way out of the scope through ".." or a drive prefix
+fn is_scoped(relative: &Path) -> bool {
+    relative.components().next().is_some()
+        && relative.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+pub fn load_post_install(pack: &Path) -> Result<Option<PostInstallScript>, String> {
+    let manifest_path = pack.join(PACK_MANIFEST_NAME);
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+
+    let manifest: PackManifest = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        .map_err(|e| trf("Failed to read pack manifest: {}", &[&e]))?;
+
+    let Some(script) = manifest.post_install else {
+        return Ok(None);
+    };
+
+    if !is_scoped(&script) {
+        return Err(trf("Post-install script must be inside the pack: {}", &[&script.display()]));
+    }
+
+    let source = std::fs::read_to_string(pack.join(&script))
+        .map_err(|e| trf("Failed to read post-install script: {}", &[&e]))?;
+
+    Ok(Some(PostInstallScript {
+        name: script.display().to_string(),
+        source,
+    }))
+}
+
+// Checking the components isn't enough on its own, a junction or symlink
+// inside the scope would lead out of it. So every part of the path that
+// already exists has to be a plain file or folder, and the deepest one has
+// to still resolve to somewhere inside the scope.
+fn resolve(scope: &Path, relative: &str) -> Result<PathBuf, Box<EvalAltResult>> {
+    let relative = Path::new(relative);
+    let outside = || trf("Path is outside the resources folder: {}", &[&relative.display()]);
+    if !is_scoped(relative) {
+        return Err(outside().into());
+    }
+
+    let scope = scope.canonicalize()
+        .map_err(|e| trf("Failed to read {}: {}", &[&scope.display(), &e]))?;
+    let mut path = scope.clone();
+    let mut deepest = scope.clone();
+    for component in relative.components() {
+        path.push(component);
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if is_link(&metadata) => return Err(outside().into()),
+            Ok(_) => deepest.clone_from(&path),
+            Err(_) => break,
+        }
+    }
+
+    let resolved = deepest.canonicalize()
+        .map_err(|e| trf("Failed to read {}: {}", &[&relative.display(), &e]))?;
+    if !resolved.starts_with(&scope) {
+        return Err(outside().into());
+    }
+    Ok(scope.join(relative))
+}
+
+// Symlinks, and on Windows junctions and other reparse points
+fn is_link(metadata: &std::fs::Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+        if metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            return true;
+        }
+    }
+    metadata.file_type().is_symlink()
+}
+
+fn sandboxed_engine(scope: &Path) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(100_000);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(64 * 1024);
+    engine.set_max_array_size(1024);
+    engine.set_max_map_size(1024);
+
+    let root = scope.to_path_buf();
+    engine.register_fn("exists", move |path: &str| -> Result<bool, Box<EvalAltResult>> {
+        Ok(resolve(&root, path)?.exists())
+    });
+
+    let root = scope.to_path_buf();
+    engine.register_fn("rename", move |from: &str, to: &str| -> Result<(), Box<EvalAltResult>> {
+        let to = resolve(&root, to)?;
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(resolve(&root, from)?, to)
+            .map_err(|e| trf("Failed to rename {}: {}", &[&from, &e]).into())
+    });
+
+    let root = scope.to_path_buf();
+    engine.register_fn("append_line", move |path: &str, line: &str| -> Result<(), Box<EvalAltResult>> {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(resolve(&root, path)?)
+            .map_err(|e| trf("Failed to open {}: {}", &[&path, &e]))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| trf("Failed to write {}: {}", &[&path, &e]).into())
+    });
+
+    engine
+}
+
+pub async fn run_post_install(script: PostInstallScript, scope: PathBuf) -> Result<(), String> {
+    workers::run(move || {
+        sandboxed_engine(&scope)
+            .run(&script.source)
+            .map_err(|e| trf("{} failed: {}", &[&script.name, &e]))
+    }).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_paths_only_go_down() {
+        assert!(is_scoped(Path::new("textures/a.png")));
+        assert!(!is_scoped(Path::new("")));
+        assert!(!is_scoped(Path::new("../powrprof.dll")));
+        assert!(!is_scoped(Path::new("a/../../b")));
+        assert!(!is_scoped(Path::new("/tmp/a")));
+    }
+
+    #[test]
+    fn scripts_work_inside_the_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+
+        sandboxed_engine(dir.path())
+            .run(r#"rename("a.txt", "sub/b.txt"); append_line("sub/b.txt", "more");"#)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(dir.path().join("sub/b.txt")).unwrap(), "amore\n");
+        assert!(sandboxed_engine(dir.path()).run(r#"append_line("../out.txt", "x")"#).is_err());
+        assert!(!dir.path().parent().unwrap().join("out.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_out_of_the_scope_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let scope = dir.path().join("scope");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&scope).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, scope.join("link")).unwrap();
+
+        assert!(resolve(&scope, "link/x.txt").is_err());
+        let result = sandboxed_engine(&scope).run(r#"append_line("link/x.txt", "x")"#);
+        assert!(result.is_err());
+        assert!(!outside.join("x.txt").exists());
+        assert!(resolve(&scope, "new/x.txt").is_ok());
+    }
+}
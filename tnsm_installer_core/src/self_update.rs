@@ -0,0 +1,215 @@
+// Updates of the installer app itself, so nobody is left on an old build
+// pointing at URLs that no longer work. App releases are published in the
+// mod's repository as app-v<version> with the portable exe attached (see
+// packaging), kept apart from the mod's own v<version> releases. The new exe is downloaded and checked like a mod DLL, then
+// swapped in: Windows won't overwrite a running exe but does let it be
+// renamed, so the running one moves aside to <exe>.old and is removed at
+// the next start.
+
+use crate::cancel::CancellationToken;
+use crate::download::{self, ResolvedDownload};
+use crate::github;
+use crate::i18n::{tr, trf};
+use crate::machine;
+use crate::packaging::APP_ASSET_NAME;
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+// Passed to the new exe, which waits for this one to close first
+pub const AFTER_UPDATE_ARG: &str = "--after-update";
+
+#[derive(Clone, Debug)]
+pub struct AppUpdate {
+    pub version: String,
+    url: String,
+    digest: Option<String>,
+    checksum_url: Option<String>,
+}
+
+// Ordered the semver way: "v1.2.9" < "v1.3.0-beta.2" < "v1.3.0". Build
+// metadata after "+" is ignored and release numbers that aren't numbers
+// count as 0.
+#[derive(PartialEq, Eq, Debug)]
+struct Version {
+    release: Vec<u64>,
+    pre_release: Vec<String>,
+}
+
+impl Version {
+    fn parse(version: &str) -> Self {
+        let version = version.trim_start_matches('v');
+        let version = version.split_once('+').map_or(version, |(version, _)| version);
+        let (release, pre_release) = version.split_once('-').unwrap_or((version, ""));
+
+        Self {
+            release: release.split('.').map(|part| part.parse().unwrap_or(0)).collect(),
+            pre_release: pre_release.split('.').filter(|part| !part.is_empty()).map(str::to_string).collect(),
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // "1.2" is "1.2.0"
+        let length = self.release.len().max(other.release.len());
+        let number = |release: &[u64], index| release.get(index).copied().unwrap_or(0);
+        let release = (0..length)
+            .map(|index| number(&self.release, index).cmp(&number(&other.release, index)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal);
+
+        release.then_with(|| match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+            // A pre-release comes before the release it leads up to
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            _ => self.pre_release.iter()
+                .zip(&other.pre_release)
+                .map(|(a, b)| compare_identifiers(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| self.pre_release.len().cmp(&other.pre_release.len())),
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Numbers compare as numbers and come before words, "beta.2" < "beta.10" < "rc"
+fn compare_identifiers(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+pub fn is_newer(version: &str, current: &str) -> bool {
+    Version::parse(version) > Version::parse(current)
+}
+
+fn old_path(exe: &Path) -> PathBuf {
+    let mut name = exe.as_os_str().to_os_string();
+    name.push(".old");
+    PathBuf::from(name)
+}
+
+// None when `current` is the newest app release with an exe
+pub async fn check(current: &str, cancel: &CancellationToken) -> Result<Option<AppUpdate>, String> {
+    let releases = github::fetch_app_releases(cancel, &|_| {}).await?;
+    let Some((release, version, asset)) = releases.iter()
+        .filter(|release| !release.prerelease)
+        .find_map(|release| Some((release, release.app_version()?, release.asset(APP_ASSET_NAME)?)))
+    else {
+        return Ok(None);
+    };
+    if !is_newer(version, current) {
+        return Ok(None);
+    }
+
+    // Never offered unless the exe can be checked before it replaces this one
+    let checksum_url = download::checksum_asset(release, APP_ASSET_NAME);
+    if asset.digest.is_none() && checksum_url.is_none() {
+        tracing::warn!("Installer release {} has no digest or checksum, not offering it", release.tag_name);
+        return Ok(None);
+    }
+
+    Ok(Some(AppUpdate {
+        version: version.to_string(),
+        url: asset.browser_download_url.clone(),
+        digest: asset.digest.clone(),
+        checksum_url,
+    }))
+}
+
+// Downloads the new exe and swaps it in for the running one. Returns the
+// path to start it from.
+pub async fn apply(update: &AppUpdate, cancel: &CancellationToken) -> Result<PathBuf, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate the installer: {}", &[&e]))?;
+    // Installed for all users from the MSI, which updates it instead
+    if !exe.parent().is_some_and(machine::is_writable) {
+        return Err(tr("This installer was installed for all users, update it with the new MSI or winget").to_string());
+    }
+    if update.digest.is_none() && update.checksum_url.is_none() {
+        return Err(tr("This update has no checksum to check it against, download it from the releases page instead").to_string());
+    }
+
+    let resolved = ResolvedDownload {
+        url: update.url.clone(),
+        version: Some(update.version.clone()),
+        digest: update.digest.clone(),
+        checksum_url: update.checksum_url.clone(),
+        signature_url: None,
+        token: None,
+    };
+    // Mirrors only carry mod releases
+    let download = download::fetch(&resolved, &[], cancel, &|_| {}, &|_| {}).await?;
+    let checksum = download::fetch_checksum(&resolved, cancel, &|_| {}).await?;
+    resolved.verify(&download, checksum.as_deref())?;
+
+    let old = old_path(&exe);
+    let _ = std::fs::remove_file(&old);
+    std::fs::rename(&exe, &old)
+        .map_err(|e| trf("Failed to move the running installer aside: {}", &[&e]))?;
+
+    if let Err(e) = std::fs::copy(&download.path, &exe) {
+        let _ = std::fs::rename(&old, &exe);
+        return Err(trf("Failed to replace the installer: {}", &[&e]));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755));
+    }
+
+    Ok(exe)
+}
+
+pub fn restart(exe: &Path) -> Result<(), String> {
+    std::process::Command::new(exe)
+        .arg(AFTER_UPDATE_ARG)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| trf("Failed to start the updated installer: {}", &[&e]))
+}
+
+// The exe replaced by the last update, free to delete once it's not running
+pub fn clean_up() {
+    if let Ok(exe) = std::env::current_exe() {
+        let _ = std::fs::remove_file(old_path(&exe));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_numbers_compare_numerically() {
+        assert!(is_newer("v1.2.10", "v1.2.9"));
+        assert!(is_newer("2.0.0", "v1.9.9"));
+        assert!(!is_newer("v1.2.0", "v1.2.0"));
+        assert!(!is_newer("v1.2", "v1.2.0"));
+    }
+
+    #[test]
+    fn pre_releases_come_before_their_release() {
+        assert!(is_newer("v1.3.0", "v1.3.0-beta.2"));
+        assert!(!is_newer("v1.3.0-beta.2", "v1.3.0"));
+        assert!(is_newer("v1.3.0-beta.2", "v1.2.9"));
+        assert!(is_newer("v1.3.0-beta.10", "v1.3.0-beta.2"));
+        assert!(is_newer("v1.3.0-rc.1", "v1.3.0-beta.2"));
+        assert!(is_newer("v1.3.0-beta.1", "v1.3.0-beta"));
+    }
+
+    #[test]
+    fn build_metadata_is_ignored() {
+        assert!(!is_newer("v1.3.0+abc", "v1.3.0"));
+        assert_eq!(Version::parse("nightly").release, vec![0]);
+    }
+}
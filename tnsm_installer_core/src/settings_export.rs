@@ -0,0 +1,82 @@
+// The installer's setup in one JSON file, to set up a second PC the same way
+// or this one again after reinstalling Windows. Only what describes the setup
+// goes in: no tokens, history or anything else tied to this PC.
+
+use crate::config::Config;
+use crate::i18n::{tr, trf};
+use crate::install::ProxyDll;
+use crate::{audit, profiles};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Bumped when a field changes meaning, newer files are refused
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ExportedSettings {
+    pub format_version: u32,
+    pub game_path: Option<PathBuf>,
+    // Release tag installs stay on, the latest when unset
+    pub pinned_version: Option<String>,
+    pub beta_channel: bool,
+    // Custom DLL URL, the release downloads when unset
+    pub dll_url: Option<String>,
+    // Resource profile names and the one in use. The resources themselves
+    // stay behind, profiles missing on import are created empty.
+    pub profiles: Vec<String>,
+    pub active_profile: Option<String>,
+    pub proxy_dll: ProxyDll,
+}
+
+impl ExportedSettings {
+    pub fn collect(config: &Config, game_path: Option<&Path>) -> Self {
+        let profiles = game_path.map(profiles::list).unwrap_or_default();
+        Self {
+            format_version: FORMAT_VERSION,
+            game_path: game_path.map(Path::to_path_buf),
+            pinned_version: config.pinned_version.clone(),
+            beta_channel: config.beta_channel,
+            dll_url: config.dll_url.clone(),
+            profiles: profiles.names,
+            active_profile: profiles.active,
+            proxy_dll: config.proxy_dll,
+        }
+    }
+}
+
+pub fn export(dest: &Path, settings: &ExportedSettings) -> Result<(), String> {
+    let audit = audit::begin("export settings", settings.game_path.as_deref()).detail(dest.display().to_string());
+    let result = serde_json::to_string_pretty(settings)
+        .map_err(|e| trf("Failed to serialize settings: {}", &[&e]))
+        .and_then(|contents| {
+            std::fs::write(dest, contents)
+                .map_err(|e| trf("Failed to write {}: {}", &[&dest.display(), &e]))
+        });
+    audit.finish(&result);
+    result
+}
+
+pub fn load(path: &Path) -> Result<ExportedSettings, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+    let settings: ExportedSettings = serde_json::from_str(&contents)
+        .map_err(|e| trf("Not a settings file: {}", &[&e]))?;
+
+    if settings.format_version > FORMAT_VERSION {
+        return Err(tr("These settings were exported by a newer installer, update this one first").to_string());
+    }
+    Ok(settings)
+}
+
+// Creates the exported profiles this game folder doesn't have yet, empty.
+// Returns how many were created.
+pub fn create_missing_profiles(settings: &ExportedSettings, game_path: &Path) -> Result<usize, String> {
+    let existing = profiles::list(game_path).names;
+    let mut created = 0;
+    for name in settings.profiles.iter().filter(|name| !existing.contains(name)) {
+        profiles::create(game_path, name)?;
+        created += 1;
+    }
+    Ok(created)
+}
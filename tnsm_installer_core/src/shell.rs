@@ -0,0 +1,189 @@
+// Windows shell integration, all opt-in: opening `.tnsmpack` files with the
+// installer, its taskbar jump list tasks and the "Install as TNSM resources"
+// folder context menu entry
+
+use crate::i18n::{tr, trf};
+use std::ffi::OsStr;
+use std::path::Path;
+
+pub const IMPORT_FLAG: &str = "--import";
+
+#[cfg(windows)]
+const CLASSES_KEY: &str = "HKCU\\Software\\Classes";
+#[cfg(windows)]
+const PACK_PROG_ID: &str = "ThatNoobSkyApp.Pack";
+// Kept on the ProgID key: what the association and jump list were last
+// written with, see registration
+#[cfg(windows)]
+const REGISTERED_VALUE: &str = "Registered";
+#[cfg(windows)]
+const FOLDER_MENU_KEY: &str = "HKCU\\Software\\Classes\\Directory\\shell\\ThatNoobSkyApp.Import";
+
+#[cfg(windows)]
+fn pack_keys() -> [String; 2] {
+    [
+        format!("{}\\.{}", CLASSES_KEY, crate::pack::PACK_EXTENSION),
+        format!("{}\\{}", CLASSES_KEY, PACK_PROG_ID),
+    ]
+}
+
+// The exe and the jump list entries as shown in the current language, both
+// written into the registrations
+#[cfg(windows)]
+fn registration() -> Result<String, String> {
+    use crate::jumplist::JumpTask;
+
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))?;
+    let tasks: Vec<_> = JumpTask::ALL.iter()
+        .map(|task| format!("{}={}", task.arg(), tr(task.title())))
+        .collect();
+    Ok(format!("{}|{}", exe.display(), tasks.join(",")))
+}
+
+// Opening .tnsmpack files with this executable and the jump list tasks, for
+// the current user. Turning it off removes both again.
+#[cfg(windows)]
+pub fn set_shell_integration(enabled: bool) -> Result<(), String> {
+    use windows_sys::Win32::UI::Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST};
+
+    let result = if enabled {
+        register_pack_association().and_then(|_| crate::jumplist::register()).and_then(|_| {
+            let [_, prog_id_key] = pack_keys();
+            crate::registry::reg(&["add", &prog_id_key, "/v", REGISTERED_VALUE, "/t", "REG_SZ", "/d", &registration()?, "/f"])
+        })
+    } else {
+        unregister_pack_association().and_then(|_| crate::jumplist::clear())
+    };
+
+    // Let Explorer pick up the change without a sign-out
+    unsafe {
+        SHChangeNotify(SHCNE_ASSOCCHANGED as i32, SHCNF_IDLIST, std::ptr::null(), std::ptr::null());
+    }
+    result
+}
+
+// For when it's turned on: writes everything again only if the exe moved or
+// the jump list entries changed since the last time
+#[cfg(windows)]
+pub fn refresh_shell_integration() -> Result<(), String> {
+    let [_, prog_id_key] = pack_keys();
+    if crate::registry::query_value(&prog_id_key, REGISTERED_VALUE) == Some(registration()?) {
+        return Ok(());
+    }
+    set_shell_integration(true)
+}
+
+#[cfg(windows)]
+fn register_pack_association() -> Result<(), String> {
+    use crate::registry::set_default_value;
+
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))?;
+    let [extension_key, prog_id_key] = pack_keys();
+
+    set_default_value(&extension_key, PACK_PROG_ID)?;
+    set_default_value(&prog_id_key, tr("ThatNoobSkyMod Resource Pack"))?;
+    set_default_value(
+        &format!("{}\\shell\\open\\command", prog_id_key),
+        &format!("\"{}\" \"%1\"", exe.display()),
+    )
+}
+
+#[cfg(windows)]
+fn unregister_pack_association() -> Result<(), String> {
+    use crate::registry::{key_exists, reg};
+
+    for key in pack_keys() {
+        if key_exists(&key) {
+            reg(&["delete", &key, "/f"])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn set_folder_context_menu(enabled: bool) -> Result<(), String> {
+    use crate::registry::{key_exists, reg, set_default_value};
+
+    if !enabled {
+        if !key_exists(FOLDER_MENU_KEY) {
+            return Ok(());
+        }
+        return reg(&["delete", FOLDER_MENU_KEY, "/f"]);
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| trf("Failed to locate installer: {}", &[&e]))?;
+
+    set_default_value(FOLDER_MENU_KEY, tr("Install as TNSM resources"))?;
+    reg(&["add", FOLDER_MENU_KEY, "/v", "Icon", "/t", "REG_SZ", "/d", &exe.display().to_string(), "/f"])?;
+    set_default_value(
+        &format!("{}\\command", FOLDER_MENU_KEY),
+        &format!("\"{}\" {} \"%1\"", exe.display(), IMPORT_FLAG),
+    )
+}
+
+// Opens a folder in the file manager, or a URL with its default handler
+pub fn open(target: impl AsRef<OsStr>) -> Result<(), String> {
+    #[cfg(windows)]
+    let opener = "explorer";
+    #[cfg(not(windows))]
+    let opener = "xdg-open";
+
+    std::process::Command::new(opener)
+        .arg(target)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| trf("Failed to run {}: {}", &[&opener, &e]))
+}
+
+// Windows Security's Protection history, where quarantined files are allowed
+// and restored
+pub fn open_protection_history() -> Result<(), String> {
+    open("windowsdefender://threat/")
+}
+
+pub fn open_folder(path: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| trf("Failed to create {}: {}", &[&path.display(), &e]))?;
+    open(path)
+}
+
+// Goes through Steam so the game starts with its usual launch options.
+// Without Steam, e.g. a copy of the game moved out of its library, Sky.exe
+// in `game_path` is started directly.
+pub fn launch_game(game_path: Option<&Path>) -> Result<(), String> {
+    use crate::detect;
+
+    let via_steam = match detect::detect_steam_path() {
+        Some(_) => open(format!("steam://rungameid/{}", detect::STEAM_APP_ID)),
+        None => Err(tr("Steam not found").to_string()),
+    };
+
+    via_steam.or_else(|e| {
+        let Some(game_path) = game_path else {
+            return Err(e);
+        };
+        std::process::Command::new(game_path.join(detect::GAME_EXE_NAME))
+            .current_dir(game_path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| trf("Failed to start {}: {}", &[&detect::GAME_EXE_NAME, &e]))
+    })
+}
+
+#[cfg(not(windows))]
+pub fn set_shell_integration(_enabled: bool) -> Result<(), String> {
+    Err(tr("Opening packs with the installer is only available on Windows").to_string())
+}
+
+#[cfg(not(windows))]
+pub fn refresh_shell_integration() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn set_folder_context_menu(_enabled: bool) -> Result<(), String> {
+    Err(tr("The folder context menu is only available on Windows").to_string())
+}
@@ -0,0 +1,94 @@
+use tokio::sync::broadcast;
+
+// The mod connects to this pipe and reads one event per line
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\ThatNoobSkyMod";
+
+#[derive(Clone, Copy, Debug)]
+pub enum PipeEvent {
+    InstallComplete,
+    ResourcesChanged,
+    Uninstalled,
+}
+
+#[cfg(windows)]
+impl PipeEvent {
+    pub fn as_line(&self) -> &'static str {
+        match self {
+            PipeEvent::InstallComplete => "install_complete\n",
+            PipeEvent::ResourcesChanged => "resources_changed\n",
+            PipeEvent::Uninstalled => "uninstalled\n",
+        }
+    }
+}
+
+pub struct StatusPipe {
+    tx: broadcast::Sender<PipeEvent>,
+}
+
+impl StatusPipe {
+    pub fn start(runtime: &tokio::runtime::Handle) -> Self {
+        let (tx, _) = broadcast::channel(16);
+
+        #[cfg(windows)]
+        runtime.spawn(serve(tx.clone()));
+        #[cfg(not(windows))]
+        let _ = runtime;
+
+        Self { tx }
+    }
+
+    pub fn send(&self, event: PipeEvent) {
+        // No subscribers just means the game isn't running
+        let _ = self.tx.send(event);
+    }
+}
+
+#[cfg(windows)]
+async fn serve(tx: broadcast::Sender<PipeEvent>) {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = match ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)
+    {
+        Ok(server) => server,
+        // Another installer instance already owns the pipe
+        Err(_) => return,
+    };
+
+    loop {
+        if server.connect().await.is_err() {
+            return;
+        }
+
+        let client = server;
+        server = match ServerOptions::new().create(PIPE_NAME) {
+            Ok(server) => server,
+            Err(_) => return,
+        };
+
+        let rx = tx.subscribe();
+        tokio::spawn(forward_events(client, rx));
+    }
+}
+
+#[cfg(windows)]
+async fn forward_events(
+    mut client: tokio::net::windows::named_pipe::NamedPipeServer,
+    mut rx: broadcast::Receiver<PipeEvent>,
+) {
+    use tokio::io::AsyncWriteExt;
+
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                if client.write_all(event.as_line().as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// Steam accounts that have signed in on this PC. Every account gets a folder
+// under Steam's userdata named after its 32-bit account ID; the names come
+// from config/loginusers.vdf, which is keyed by the 64-bit Steam ID.
+
+use crate::vdf::{self, Token};
+use std::path::Path;
+
+// Steam ID of the individual account with account ID 0
+const STEAM_ID64_BASE: u64 = 76_561_197_960_265_728;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SteamAccount {
+    pub id: u32,
+    pub name: String,
+    // Signed in most recently, most likely the one playing now
+    pub most_recent: bool,
+}
+
+// (account ID, persona name, most recent) for every user in loginusers.vdf
+fn login_users(steam_path: &Path) -> Vec<(u32, String, bool)> {
+    let Ok(contents) = std::fs::read_to_string(steam_path.join("config").join("loginusers.vdf")) else {
+        return Vec::new();
+    };
+
+    let mut users = Vec::new();
+    let mut depth = 0;
+    let mut current: Option<(u32, String, bool)> = None;
+    let mut tokens = vdf::tokens(&contents).into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => {
+                depth -= 1;
+                if depth == 1 {
+                    users.extend(current.take());
+                }
+            }
+            // A Steam ID opening a user block
+            Token::Text(id) if depth == 1 => {
+                current = id.parse::<u64>().ok()
+                    .and_then(|id| id.checked_sub(STEAM_ID64_BASE))
+                    .and_then(|id| u32::try_from(id).ok())
+                    .map(|id| (id, String::new(), false));
+            }
+            Token::Text(key) if depth == 2 => {
+                let value = match tokens.next_if(|value| matches!(value, Token::Text(_))) {
+                    Some(Token::Text(value)) => value,
+                    _ => String::new(),
+                };
+                if let Some((_, name, most_recent)) = &mut current {
+                    match key.as_str() {
+                        "PersonaName" => *name = value,
+                        "MostRecent" => *most_recent = value == "1",
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    users
+}
+
+pub fn list(steam_path: &Path) -> Vec<SteamAccount> {
+    let Ok(entries) = std::fs::read_dir(steam_path.join("userdata")) else {
+        return Vec::new();
+    };
+    let users = login_users(steam_path);
+
+    let mut accounts: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        // 0 is the anonymous account Steam uses before anyone has signed in
+        .filter(|id| *id != 0)
+        .map(|id| match users.iter().find(|(user_id, _, _)| *user_id == id) {
+            Some((_, name, most_recent)) if !name.is_empty() => SteamAccount { id, name: name.clone(), most_recent: *most_recent },
+            Some((_, _, most_recent)) => SteamAccount { id, name: id.to_string(), most_recent: *most_recent },
+            None => SteamAccount { id, name: id.to_string(), most_recent: false },
+        })
+        .collect();
+
+    accounts.sort_by(|a, b| b.most_recent.cmp(&a.most_recent).then_with(|| a.name.cmp(&b.name)));
+    accounts
+}
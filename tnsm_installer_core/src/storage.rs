@@ -0,0 +1,235 @@
+// Disk usage of what the installer keeps in its app directory, and cleanup
+// of it under per-category quotas and age limits. Each category is a folder
+// whose top-level entries are removed whole, oldest first. The newest entry
+// of a category is always kept, so cleanup never throws away the last
+// restore point or backup.
+
+use crate::i18n::trf;
+use crate::{config, game_data, pack, restore_point, version_cache, workers};
+use jwalk::WalkDir;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Category {
+    RestorePoints,
+    GameDataBackups,
+    UnpackedPacks,
+    Logs,
+    VersionCache,
+}
+
+// 0 means no limit
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct Limits {
+    pub max_mb: u64,
+    pub max_age_days: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Retention {
+    pub restore_points: Limits,
+    pub game_data_backups: Limits,
+    pub unpacked_packs: Limits,
+    pub logs: Limits,
+    pub version_cache: Limits,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            restore_points: Limits { max_mb: 2048, max_age_days: 90 },
+            game_data_backups: Limits { max_mb: 1024, max_age_days: 0 },
+            // Only kept to resume an interrupted import
+            unpacked_packs: Limits { max_mb: 0, max_age_days: 7 },
+            logs: Limits { max_mb: 100, max_age_days: 30 },
+            // Kept for switching between versions, so only trimmed when asked to
+            version_cache: Limits::default(),
+        }
+    }
+}
+
+impl Retention {
+    pub fn limits(&self, category: Category) -> Limits {
+        match category {
+            Category::RestorePoints => self.restore_points,
+            Category::GameDataBackups => self.game_data_backups,
+            Category::UnpackedPacks => self.unpacked_packs,
+            Category::Logs => self.logs,
+            Category::VersionCache => self.version_cache,
+        }
+    }
+
+    pub fn limits_mut(&mut self, category: Category) -> &mut Limits {
+        match category {
+            Category::RestorePoints => &mut self.restore_points,
+            Category::GameDataBackups => &mut self.game_data_backups,
+            Category::UnpackedPacks => &mut self.unpacked_packs,
+            Category::Logs => &mut self.logs,
+            Category::VersionCache => &mut self.version_cache,
+        }
+    }
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::RestorePoints,
+        Category::GameDataBackups,
+        Category::UnpackedPacks,
+        Category::Logs,
+        Category::VersionCache,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::RestorePoints => "Restore points",
+            Category::GameDataBackups => "Game data backups",
+            Category::UnpackedPacks => "Unpacked resource packs",
+            Category::Logs => "Logs",
+            Category::VersionCache => "Cached mod versions",
+        }
+    }
+
+    fn dir(self) -> Option<PathBuf> {
+        match self {
+            Category::RestorePoints => restore_point::root_dir().ok(),
+            Category::GameDataBackups => game_data::backups_dir().ok(),
+            Category::UnpackedPacks => pack::extract_root().ok(),
+            Category::Logs => config::log_dir(),
+            Category::VersionCache => version_cache::dir().ok(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Usage {
+    pub category: Category,
+    pub bytes: u64,
+    pub items: usize,
+}
+
+#[derive(Default)]
+pub struct CleanupReport {
+    pub items: usize,
+    pub bytes: u64,
+}
+
+struct Item {
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+fn size_of(path: &Path) -> u64 {
+    if path.is_file() {
+        return std::fs::metadata(path).map_or(0, |metadata| metadata.len());
+    }
+
+    WalkDir::new(path)
+        .skip_hidden(false)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// Newest first
+fn items(category: Category) -> Vec<Item> {
+    let Some(Ok(entries)) = category.dir().map(std::fs::read_dir) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<_> = entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let path = entry.path();
+            Item {
+                bytes: size_of(&path),
+                modified: entry.metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+            }
+        })
+        .collect();
+
+    items.sort_by_key(|item| Reverse(item.modified));
+    items
+}
+
+pub async fn usage() -> Result<Vec<Usage>, String> {
+    workers::run(measure).await
+}
+
+fn measure() -> Vec<Usage> {
+    Category::ALL
+        .into_iter()
+        .map(|category| {
+            let items = items(category);
+            Usage {
+                category,
+                bytes: items.iter().map(|item| item.bytes).sum(),
+                items: items.len(),
+            }
+        })
+        .collect()
+}
+
+fn remove(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+// Don't run it while an install or import is writing a restore point or
+// reading an unpacked pack
+pub async fn clean_up(retention: Retention) -> Result<CleanupReport, String> {
+    workers::run(move || remove_expired(&retention)).await?
+}
+
+fn remove_expired(retention: &Retention) -> Result<CleanupReport, String> {
+    let mut report = CleanupReport::default();
+
+    for category in Category::ALL {
+        let limits = retention.limits(category);
+        let max_age = Duration::from_secs(limits.max_age_days * 86_400);
+        let max_bytes = limits.max_mb * 1_048_576;
+
+        let mut kept_bytes = 0;
+        for (index, item) in items(category).into_iter().enumerate() {
+            let too_old = limits.max_age_days > 0
+                && item.modified.elapsed().is_ok_and(|age| age > max_age);
+            let over_quota = limits.max_mb > 0 && kept_bytes + item.bytes > max_bytes;
+
+            if index == 0 || !(too_old || over_quota) {
+                kept_bytes += item.bytes;
+                continue;
+            }
+
+            remove(&item.path)
+                .map_err(|e| trf("Failed to remove {}: {}", &[&item.path.display(), &e]))?;
+            report.items += 1;
+            report.bytes += item.bytes;
+        }
+    }
+
+    Ok(report)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1_073_741_824 {
+        format!("{:.1} GB", bytes as f64 / 1_073_741_824.0)
+    } else if bytes >= 1_048_576 {
+        format!("{:.1} MB", bytes as f64 / 1_048_576.0)
+    } else {
+        format!("{:.0} KB", bytes as f64 / 1024.0)
+    }
+}
@@ -0,0 +1,96 @@
+// Owns the tokio runtime and every task the frontend spawns on it, so the app
+// can cap how much runs at once and shut down without dropping the runtime
+// in the middle of a write. The runtime is only built the first time
+// something needs it.
+
+use crate::cancel::CancellationToken;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::Semaphore;
+use tokio_util::task::TaskTracker;
+
+// Installs, imports and bundles beyond this wait their turn
+const MAX_CONCURRENT_TASKS: usize = 3;
+
+// The work is network and disk bound, disk jobs go to the blocking pool anyway
+const WORKER_THREADS: usize = 2;
+
+pub struct TaskManager {
+    runtime: Mutex<Option<Runtime>>,
+    handle: OnceLock<Handle>,
+    tracker: TaskTracker,
+    permits: Arc<Semaphore>,
+    shutdown: CancellationToken,
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self {
+            runtime: Mutex::new(None),
+            handle: OnceLock::new(),
+            tracker: TaskTracker::new(),
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT_TASKS)),
+            shutdown: CancellationToken::new(),
+        }
+    }
+}
+
+impl TaskManager {
+    pub fn handle(&self) -> &Handle {
+        self.handle.get_or_init(|| {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(WORKER_THREADS)
+                .enable_all()
+                .build()
+                .expect("Failed to start async runtime");
+            let handle = runtime.handle().clone();
+
+            *self.runtime.lock().unwrap() = Some(runtime);
+            handle
+        })
+    }
+
+    // Token for a new task, cancelled along with everything else on shutdown
+    pub fn child_token(&self) -> CancellationToken {
+        self.shutdown.child_token()
+    }
+
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let permits = self.permits.clone();
+
+        self.tracker.spawn_on(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            task.await;
+        }, self.handle());
+    }
+
+    // For tasks that live as long as the app, like the tray's update checks,
+    // which would otherwise hold a permit forever. Still waited on at shutdown.
+    pub fn spawn_background<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tracker.spawn_on(task, self.handle());
+    }
+
+    // Cancels all tasks and gives them `timeout` to clean up before the runtime goes away
+    pub fn shutdown(&mut self, timeout: Duration) {
+        // Never started, so nothing can be running
+        let Some(runtime) = self.runtime.lock().unwrap().take() else {
+            return;
+        };
+
+        self.shutdown.cancel();
+        self.tracker.close();
+
+        let _ = runtime.block_on(tokio::time::timeout(timeout, self.tracker.wait()));
+        runtime.shutdown_timeout(Duration::from_secs(1));
+    }
+}
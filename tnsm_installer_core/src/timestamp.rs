@@ -0,0 +1,68 @@
+// UTC timestamps for backup names, sortable as plain strings
+// (e.g. 20261016-142530) so listing them newest first is a string sort.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn now() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    format_unix(seconds)
+}
+
+pub fn format_unix(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60
+    )
+}
+
+// Turns "20261016-142530" back into "2026-10-16 14:25:30 UTC" for display
+pub fn display(timestamp: &str) -> String {
+    match (timestamp.get(0..4), timestamp.get(4..6), timestamp.get(6..8), timestamp.get(9..11), timestamp.get(11..13), timestamp.get(13..15)) {
+        (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) => {
+            format!("{}-{}-{} {}:{}:{} UTC", year, month, day, hour, minute, second)
+        }
+        _ => timestamp.to_string(),
+    }
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_from_days_known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        // 2000 is a leap year, 2100 isn't
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        assert_eq!(civil_from_days(20_742), (2026, 10, 16));
+    }
+
+    #[test]
+    fn format_unix_round_trips_through_display() {
+        let timestamp = format_unix(1_792_160_730);
+        assert_eq!(timestamp, "20261016-142530");
+        assert_eq!(display(&timestamp), "2026-10-16 14:25:30 UTC");
+    }
+}
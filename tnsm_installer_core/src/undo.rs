@@ -0,0 +1,55 @@
+// Resource changes that can be taken back. Every undoable change saves what
+// it replaced in a restore point first, so undoing one is reverting that
+// restore point. The stack is saved in the app directory; entries whose
+// restore point was deleted in the meantime are dropped when it's loaded.
+
+use crate::i18n::tr;
+use crate::{app_files, config};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const MAX_ENTRIES: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct UndoEntry {
+    pub restore_point: PathBuf,
+    pub label: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct UndoStack {
+    pub entries: Vec<UndoEntry>,
+}
+
+fn stack_path() -> Option<PathBuf> {
+    config::app_dir().map(|dir| dir.join("undo.json"))
+}
+
+impl UndoStack {
+    pub fn load() -> Self {
+        let mut stack: Self = app_files::load_json(stack_path());
+        stack.entries.retain(|entry| entry.restore_point.exists());
+        stack
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        app_files::save_json(stack_path(), self, self.entries.is_empty(), tr("undo history"))
+    }
+
+    // The oldest entries fall off once the stack is full; their restore
+    // points stay in the list and can still be reverted from there
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn peek(&self) -> Option<&UndoEntry> {
+        self.entries.last()
+    }
+
+    pub fn pop(&mut self) -> Option<UndoEntry> {
+        self.entries.pop()
+    }
+}
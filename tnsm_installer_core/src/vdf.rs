@@ -0,0 +1,122 @@
+// Steam's KeyValues text format (loginusers.vdf, appmanifest_*.acf,
+// libraryfolders.vdf): quoted keys and values with braces for nesting.
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Token {
+    Open,
+    Close,
+    // A quoted key or value. Braces inside quotes are text, not nesting.
+    Text(String),
+}
+
+// Quoted strings and braces, which is all these files are made of
+pub fn tokens(contents: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = contents.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut token = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => token.extend(chars.next()),
+                        '"' => break,
+                        _ => token.push(c),
+                    }
+                }
+                tokens.push(Token::Text(token));
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+// Every key with a plain value, with how many sections deep it is
+pub fn values(contents: &str) -> Vec<(usize, String, String)> {
+    let tokens = tokens(contents);
+    let mut values = Vec::new();
+    let mut depth: usize = 0;
+    let mut index = 0;
+
+    while index < tokens.len() {
+        match &tokens[index] {
+            Token::Open => depth += 1,
+            Token::Close => depth = depth.saturating_sub(1),
+            // A key followed by a brace opens a section, counted on the next round
+            Token::Text(key) => {
+                if let Some(Token::Text(value)) = tokens.get(index + 1) {
+                    values.push((depth, key.clone(), value.clone()));
+                    index += 1;
+                }
+            }
+        }
+        index += 1;
+    }
+
+    values
+}
+
+// The first value stored under `key` at any depth, e.g. "buildid" in an app manifest
+pub fn find(contents: &str, key: &str) -> Option<String> {
+    let tokens = tokens(contents);
+
+    tokens.windows(2).find_map(|pair| match pair {
+        [Token::Text(found), Token::Text(value)] if found.eq_ignore_ascii_case(key) => Some(value.clone()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_braces_are_text() {
+        assert_eq!(tokens(r#""name" "{" "other" { }"#), vec![
+            Token::Text("name".to_string()),
+            Token::Text("{".to_string()),
+            Token::Text("other".to_string()),
+            Token::Open,
+            Token::Close,
+        ]);
+    }
+
+    #[test]
+    fn escapes_are_unescaped() {
+        assert_eq!(tokens(r#""path" "C:\\Games\"Sky\"""#), vec![
+            Token::Text("path".to_string()),
+            Token::Text(r#"C:\Games"Sky""#.to_string()),
+        ]);
+    }
+
+    #[test]
+    fn values_track_depth() {
+        let contents = r#"
+            "AppState"
+            {
+                "buildid"   "19283746"
+                "UserConfig"
+                {
+                    "language"  "}"
+                }
+            }
+        "#;
+
+        assert_eq!(values(contents), vec![
+            (1, "buildid".to_string(), "19283746".to_string()),
+            (2, "language".to_string(), "}".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn find_skips_sections_and_ignores_case() {
+        let contents = r#""BuildID" { } "buildid" "42""#;
+        assert_eq!(find(contents, "buildid").as_deref(), Some("42"));
+        assert_eq!(find(contents, "missing"), None);
+    }
+}
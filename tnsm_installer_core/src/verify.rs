@@ -0,0 +1,114 @@
+use crate::i18n::{tr, trf};
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| trf("Failed to open {}: {}", &[&path.display(), &e]))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)
+            .map_err(|e| trf("Failed to read {}: {}", &[&path.display(), &e]))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+// The hash listed for `name` in a sha256sum style file ("<hex>  <name>", or
+// "<hex> *<name>" for binary mode). A file holding nothing but a hash, like
+// TNSM.dll.sha256, applies to whatever it sits next to.
+pub fn checksum_for(contents: &str, name: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let listed = parts.next().map(|listed| listed.trim_start_matches('*'));
+        let is_hash = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+
+        (is_hash && listed.is_none_or(|listed| listed == name)).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+// GitHub reports asset digests as "<algorithm>:<hex>"
+pub fn check_github_digest(actual: &str, digest: &str) -> Result<(), String> {
+    let expected = match digest.split_once(':') {
+        Some(("sha256", hex)) => hex.to_ascii_lowercase(),
+        _ => return Err(trf("Unsupported digest format: {}", &[&digest])),
+    };
+
+    if actual != expected {
+        return Err(trf(
+            "Checksum mismatch (expected {}, got {})",
+            &[&expected, &actual]
+        ));
+    }
+
+    Ok(())
+}
+
+// The maintainers' release signing key. Release assets are signed with the
+// private half by `publish-mirror` and `sign-assets`, see mirror.rs.
+const RELEASE_PUBLIC_KEY: &str = "9e1555091f8ddbe748e992aa95ddd827222b476cd1dc97048361240edc34b4b0";
+
+// Checks a detached signature ("<asset>.sig", the hex-encoded ed25519
+// signature) against the release key built into the installer
+pub fn check_signature(bytes: &[u8], signature: &str) -> Result<(), String> {
+    let key: [u8; 32] = hex::decode(RELEASE_PUBLIC_KEY)
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| tr("The built-in release key is invalid").to_string())?;
+    let key = VerifyingKey::from_bytes(&key)
+        .map_err(|e| trf("The built-in release key is invalid: {}", &[&e]))?;
+
+    let signature: [u8; 64] = hex::decode(signature.trim())
+        .ok()
+        .and_then(|signature| signature.try_into().ok())
+        .ok_or_else(|| tr("Malformed signature").to_string())?;
+
+    key.verify_strict(bytes, &Signature::from_bytes(&signature))
+        .map_err(|_| tr("Signature doesn't match the ThatNoobSkyMod release key").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn checksum_for_finds_the_listed_name() {
+        let contents = format!("{}  TNSM.dll\n{}  TNSM-arm64.dll\n", HASH, "0".repeat(64));
+        assert_eq!(checksum_for(&contents, "TNSM.dll").as_deref(), Some(HASH));
+        assert_eq!(checksum_for(&contents, "TNSM-arm64.dll"), Some("0".repeat(64)));
+        assert_eq!(checksum_for(&contents, "other.dll"), None);
+    }
+
+    #[test]
+    fn checksum_for_handles_binary_mode_and_case() {
+        let contents = format!("{} *TNSM.dll", HASH.to_ascii_uppercase());
+        assert_eq!(checksum_for(&contents, "TNSM.dll").as_deref(), Some(HASH));
+    }
+
+    #[test]
+    fn checksum_for_bare_hash_applies_to_anything() {
+        assert_eq!(checksum_for(&format!("{}\n", HASH), "TNSM.dll").as_deref(), Some(HASH));
+        assert_eq!(checksum_for("not a hash  TNSM.dll", "TNSM.dll"), None);
+    }
+}
@@ -0,0 +1,86 @@
+// Copies of every released mod version installed through the installer, so
+// switching back to one doesn't need another download. Each DLL sits next to
+// its SHA-256, checked again before it's installed.
+//
+//   <app dir>/versions/<tag>/TNSM.dll
+//   <app dir>/versions/<tag>/TNSM.dll.sha256
+
+use crate::i18n::{tr, trf};
+use crate::{app_files, config, workers};
+use crate::download::{self, DownloadedDll};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Clone, Debug)]
+pub struct CachedVersion {
+    pub version: String,
+    pub path: PathBuf,
+}
+
+pub fn dir() -> Result<PathBuf, String> {
+    config::app_dir()
+        .map(|dir| dir.join("versions"))
+        .ok_or_else(|| tr("Config directory not found").to_string())
+}
+
+const DLL_FILE: &str = "TNSM.dll";
+const SHA256_FILE: &str = "TNSM.dll.sha256";
+
+fn version_dir(version: &str) -> Result<PathBuf, String> {
+    dir().map(|dir| dir.join(version.replace(['/', '\\'], "_")))
+}
+
+// Most recently cached first
+pub fn list() -> Vec<CachedVersion> {
+    app_files::list_newest_first(
+        dir().ok(),
+        |entry| {
+            let path = entry.path().join(DLL_FILE);
+            path.is_file().then(|| CachedVersion { version: entry.file_name().to_string_lossy().into_owned(), path })
+        },
+        // The folder's, the DLL keeps the time it was built on Windows
+        |cached| cached.path.parent()
+            .and_then(|dir| std::fs::metadata(dir).and_then(|metadata| metadata.modified()).ok())
+            .unwrap_or(SystemTime::UNIX_EPOCH),
+    )
+}
+
+pub fn store(source: &Path, version: &str, sha256: &str) -> Result<(), String> {
+    let dir = version_dir(version)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| trf("Failed to create version cache: {}", &[&e]))?;
+
+    std::fs::copy(source, dir.join(DLL_FILE))
+        .map_err(|e| trf("Failed to cache {}: {}", &[&version, &e]))?;
+    std::fs::write(dir.join(SHA256_FILE), sha256)
+        .map_err(|e| trf("Failed to cache {}: {}", &[&version, &e]))
+}
+
+// The cached version with this hash, if any
+pub fn find_by_sha256(sha256: &str) -> Option<String> {
+    list().into_iter()
+        .find(|cached| {
+            cached.path.parent()
+                .and_then(|dir| std::fs::read_to_string(dir.join(SHA256_FILE)).ok())
+                .is_some_and(|cached_sha256| cached_sha256.trim() == sha256)
+        })
+        .map(|cached| cached.version)
+}
+
+// A temp copy ready for the install pipeline
+pub async fn load(version: &str) -> Result<DownloadedDll, String> {
+    let version = version.to_string();
+    workers::run(move || copy_cached(&version)).await?
+}
+
+fn copy_cached(version: &str) -> Result<DownloadedDll, String> {
+    let dir = version_dir(version)?;
+    let expected = std::fs::read_to_string(dir.join(SHA256_FILE))
+        .map_err(|_| trf("{} is not in the version cache", &[&version]))?;
+
+    let dll = download::copy_local(&dir.join(DLL_FILE), Some(version.to_string()))?;
+    if dll.sha256 != expected.trim() {
+        return Err(trf("The cached copy of {} is damaged, install it again to refresh it", &[&version]));
+    }
+    Ok(dll)
+}
@@ -0,0 +1,45 @@
+// Notices background tasks that stopped reporting progress, e.g. a download
+// whose connection went quiet or a copy held up by an antivirus scan, so the
+// UI can say so instead of showing a spinner forever.
+
+use crate::events::Task;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+pub struct Watchdog {
+    last_activity: HashMap<Task, Instant>,
+}
+
+impl Watchdog {
+    pub fn start(&mut self, task: Task) {
+        self.last_activity.insert(task, Instant::now());
+    }
+
+    // Ignored for tasks that aren't being watched
+    pub fn touch(&mut self, task: Task) {
+        if let Some(last) = self.last_activity.get_mut(&task) {
+            *last = Instant::now();
+        }
+    }
+
+    pub fn stop(&mut self, task: Task) {
+        self.last_activity.remove(&task);
+    }
+
+    // How long the task has gone without progress, once that's past STALL_TIMEOUT
+    pub fn stalled_for(&self, task: Task) -> Option<Duration> {
+        self.last_activity.get(&task)
+            .map(Instant::elapsed)
+            .filter(|idle| *idle >= STALL_TIMEOUT)
+    }
+
+    // When the UI next needs to repaint for a task to become (or stay) stalled
+    pub fn next_check(&self) -> Option<Duration> {
+        self.last_activity.values()
+            .map(|last| STALL_TIMEOUT.saturating_sub(last.elapsed()).max(Duration::from_secs(1)))
+            .min()
+    }
+}
@@ -0,0 +1,34 @@
+// Bounded pool for disk-heavy work (copying, hashing, scanning). Jobs run on
+// tokio's blocking threads, but at most WORKER_COUNT at a time, so running an
+// import next to a verify can't pile up hundreds of blocking threads or stall
+// the async workers driving downloads and UI events.
+
+use crate::i18n::trf;
+use std::sync::OnceLock;
+use tokio::sync::Semaphore;
+
+pub fn worker_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|count| count.get().clamp(2, 4))
+        .unwrap_or(2)
+}
+
+fn permits() -> &'static Semaphore {
+    static PERMITS: OnceLock<Semaphore> = OnceLock::new();
+    PERMITS.get_or_init(|| Semaphore::new(worker_count()))
+}
+
+pub async fn run<T, F>(job: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _permit = permits()
+        .acquire()
+        .await
+        .map_err(|e| trf("Worker pool closed: {}", &[&e]))?;
+
+    tokio::task::spawn_blocking(job)
+        .await
+        .map_err(|e| trf("Worker task failed: {}", &[&e]))
+}